@@ -0,0 +1,137 @@
+//! Achievements unlocked by the players along their games.
+//!
+//! The progress of every account is tracked as they play: their first win,
+//! their hundredth hand and the first royal flush they show down each unlock
+//! an achievement once. Every achievement unlocked is minted as a soulbound
+//! badge of the PSP34 contract configured for the achievements, if any.
+
+use crate::psp34::Id;
+use ink_env::{
+    DefaultEnvironment,
+    Environment,
+};
+use ink_prelude::vec::Vec;
+use ink_storage::traits::{
+    PackedLayout,
+    SpreadLayout,
+};
+
+type AccountId = <DefaultEnvironment as Environment>::AccountId;
+
+/// The number of hands unlocking [`Achievement::HundredHands`].
+pub const HANDS_FOR_BADGE: u32 = 100;
+
+/// A milestone of a player.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub enum Achievement {
+    /// The first game won.
+    FirstWin,
+    /// A hundred hands played.
+    HundredHands,
+    /// A royal flush shown down.
+    RoyalFlush,
+}
+
+impl Achievement {
+    /// The id of the badge of the achievement minted to the account, unique
+    /// to both of them.
+    pub fn badge_id(&self, account: &AccountId) -> Id {
+        Id::Bytes(scale::Encode::encode(&(account, self)))
+    }
+}
+
+/// The progress of an account towards the achievements.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub struct Progress {
+    /// The number of hands played.
+    pub hands_played: u32,
+    /// The achievements unlocked, in order.
+    pub unlocked: Vec<Achievement>,
+    /// The achievements whose badge was minted.
+    pub minted: Vec<Achievement>,
+}
+
+impl Progress {
+    /// Count a hand played. Returns the achievement it unlocks, if any.
+    pub fn play_hand(&mut self) -> Option<Achievement> {
+        self.hands_played = self.hands_played.saturating_add(1);
+        match self.hands_played >= HANDS_FOR_BADGE {
+            true => self.unlock(Achievement::HundredHands),
+            false => None,
+        }
+    }
+
+    /// Unlock the achievement. Returns it unless it was already unlocked.
+    pub fn unlock(&mut self, achievement: Achievement) -> Option<Achievement> {
+        if self.unlocked.contains(&achievement) {
+            return None
+        }
+        self.unlocked.push(achievement);
+        Some(achievement)
+    }
+
+    /// The achievements unlocked whose badge is not minted yet.
+    pub fn unminted(&self) -> Vec<Achievement> {
+        self.unlocked
+            .iter()
+            .filter(|achievement| !self.minted.contains(achievement))
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn achievements_unlock_once() {
+        let mut progress = Progress::default();
+        for _ in 1..HANDS_FOR_BADGE {
+            assert_eq!(progress.play_hand(), None);
+        }
+        assert_eq!(progress.play_hand(), Some(Achievement::HundredHands));
+        assert_eq!(progress.play_hand(), None);
+        assert_eq!(progress.unlock(Achievement::FirstWin), Some(Achievement::FirstWin));
+        assert_eq!(progress.unlock(Achievement::FirstWin), None);
+        progress.minted.push(Achievement::HundredHands);
+        assert_eq!(progress.unminted(), vec![Achievement::FirstWin]);
+    }
+
+    #[test]
+    fn badges_are_unique_to_the_account_and_achievement() {
+        let alice = AccountId::from([1; 32]);
+        let bob = AccountId::from([2; 32]);
+        let first = Achievement::FirstWin.badge_id(&alice);
+        assert_ne!(Achievement::FirstWin.badge_id(&bob), first);
+        assert_ne!(Achievement::RoyalFlush.badge_id(&alice), first);
+    }
+}
@@ -0,0 +1,259 @@
+//! Punto banco baccarat against the house.
+//!
+//! Players bet on the player hand, the banker hand or a tie before the deal.
+//! Both hands are dealt from the deck shuffled by the seed of the table and
+//! draw their third card by the fixed rules of the game, so that the coup
+//! follows from the seed alone. A winning bet on the player is paid 1:1, on
+//! the banker 1:1 less a 5% commission and on a tie 8:1, while the bets on
+//! the player or the banker are returned on a tie.
+
+use crate::cards::{
+    self,
+    Card,
+    ACE,
+};
+use crate::metasino::{
+    MetasinoError,
+    Result,
+};
+use ink_env::{
+    DefaultEnvironment,
+    Environment,
+};
+use ink_prelude::vec::Vec;
+use ink_storage::traits::{
+    PackedLayout,
+    SpreadLayout,
+};
+
+type Balance = <DefaultEnvironment as Environment>::Balance;
+
+/// The commission the house takes on the winnings of a banker bet, in percent.
+const BANKER_COMMISSION: Balance = 5;
+
+/// A bet on the outcome of the coup.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub enum BaccaratBet {
+    /// The player hand wins, paying 1:1.
+    Player,
+    /// The banker hand wins, paying 1:1 less the commission.
+    Banker,
+    /// Both hands tie, paying 8:1.
+    Tie,
+}
+
+impl BaccaratBet {
+    /// The odds paid by the bet, before the commission.
+    pub fn odds(&self) -> Balance {
+        match self {
+            BaccaratBet::Tie => 8,
+            BaccaratBet::Player | BaccaratBet::Banker => 1,
+        }
+    }
+
+    /// What the bet of `amount` returns once the coup ends in `outcome`, the
+    /// amount bet included. The commission is rounded up.
+    /// error if the payout overflows.
+    pub fn payout(&self, amount: Balance, outcome: BaccaratBet) -> Result<Balance> {
+        if outcome == BaccaratBet::Tie && *self != BaccaratBet::Tie {
+            return Ok(amount)
+        }
+        if *self != outcome {
+            return Ok(0)
+        }
+        let won = amount
+            .checked_mul(self.odds())
+            .ok_or(MetasinoError::ArithmeticError)?;
+        let commission = match self {
+            BaccaratBet::Banker => won.div_ceil(100 / BANKER_COMMISSION),
+            _ => 0,
+        };
+        amount
+            .checked_add(won - commission)
+            .ok_or(MetasinoError::ArithmeticError)
+    }
+}
+
+/// A baccarat bet placed by a player along with its amount.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub struct BaccaratWager {
+    /// What the bet is on.
+    pub bet: BaccaratBet,
+    /// The amount bet.
+    pub amount: Balance,
+}
+
+/// The two hands of a coup as dealt.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+pub struct Coup {
+    /// The cards of the player hand.
+    pub player: Vec<Card>,
+    /// The cards of the banker hand.
+    pub banker: Vec<Card>,
+}
+
+impl Coup {
+    /// Which hand wins the coup, or whether they tie.
+    pub fn outcome(&self) -> BaccaratBet {
+        let (player, banker) = (total(&self.player), total(&self.banker));
+        match player.cmp(&banker) {
+            core::cmp::Ordering::Greater => BaccaratBet::Player,
+            core::cmp::Ordering::Less => BaccaratBet::Banker,
+            core::cmp::Ordering::Equal => BaccaratBet::Tie,
+        }
+    }
+}
+
+/// The points of a card: face cards and tens are worth nothing and the ace
+/// is worth one.
+fn value(card: &Card) -> u8 {
+    match card.rank {
+        ACE => 1,
+        10..=13 => 0,
+        rank => rank,
+    }
+}
+
+/// The total of a hand, the last digit of the sum of its points.
+pub fn total(hand: &[Card]) -> u8 {
+    hand.iter().map(value).sum::<u8>() % 10
+}
+
+/// Whether the banker draws a third card with the given total, once the
+/// player drew a third card worth `third` points, or stood with `None`.
+fn banker_draws(banker: u8, third: Option<u8>) -> bool {
+    match (banker, third) {
+        (0..=5, None) => true,
+        (_, None) => false,
+        (0..=2, _) => true,
+        (3, Some(third)) => third != 8,
+        (4, Some(third)) => (2..=7).contains(&third),
+        (5, Some(third)) => (4..=7).contains(&third),
+        (6, Some(third)) => (6..=7).contains(&third),
+        _ => false,
+    }
+}
+
+/// Deal the coup out of the deck shuffled by the seed: two cards to each
+/// hand in turn, then the third cards drawn by the rules. Neither hand draws
+/// when either of them holds a natural 8 or 9.
+pub fn deal(seed: &[u8; 32]) -> Coup {
+    let deck = cards::shuffled_deck(seed);
+    let mut dealt = 0;
+    let mut next = || {
+        dealt += 1;
+        deck[dealt - 1]
+    };
+    let mut coup = Coup {
+        player: Vec::new(),
+        banker: Vec::new(),
+    };
+    for _ in 0..2 {
+        coup.player.push(next());
+        coup.banker.push(next());
+    }
+    if total(&coup.player) >= 8 || total(&coup.banker) >= 8 {
+        return coup
+    }
+    let mut third = None;
+    if total(&coup.player) <= 5 {
+        let card = next();
+        third = Some(value(&card));
+        coup.player.push(card);
+    }
+    if banker_draws(total(&coup.banker), third) {
+        coup.banker.push(next());
+    }
+    coup
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::Suit;
+
+    fn hand(ranks: &[u8]) -> Vec<Card> {
+        ranks
+            .iter()
+            .map(|rank| Card { rank: *rank, suit: Suit::Hearts })
+            .collect()
+    }
+
+    #[test]
+    fn totals_keep_the_last_digit() {
+        assert_eq!(total(&hand(&[ACE, 13])), 1);
+        assert_eq!(total(&hand(&[9, 8])), 7);
+        assert_eq!(total(&hand(&[5, 5, 10])), 0);
+        let coup = Coup { player: hand(&[9, 12]), banker: hand(&[4, 4]) };
+        assert_eq!(coup.outcome(), BaccaratBet::Player);
+        let coup = Coup { player: hand(&[3, 4]), banker: hand(&[2, 5]) };
+        assert_eq!(coup.outcome(), BaccaratBet::Tie);
+    }
+
+    #[test]
+    fn banker_draws_by_the_third_card_of_the_player() {
+        assert!(banker_draws(5, None) && !banker_draws(6, None));
+        assert!(banker_draws(2, Some(8)));
+        assert!(banker_draws(3, Some(9)) && !banker_draws(3, Some(8)));
+        assert!(banker_draws(4, Some(2)) && !banker_draws(4, Some(1)));
+        assert!(banker_draws(5, Some(4)) && !banker_draws(5, Some(8)));
+        assert!(banker_draws(6, Some(7)) && !banker_draws(6, Some(5)));
+        assert!(!banker_draws(7, Some(6)));
+    }
+
+    #[test]
+    fn coups_are_dealt_by_the_rules() {
+        for byte in 0..=255 {
+            let coup = deal(&[byte; 32]);
+            let (player, banker) = (total(&coup.player[..2]), total(&coup.banker[..2]));
+            let natural = player >= 8 || banker >= 8;
+            assert_eq!(coup.player.len() == 3, !natural && player <= 5);
+            let third = coup.player.get(2).map(value);
+            assert_eq!(coup.banker.len() == 3, !natural && banker_draws(banker, third));
+        }
+        assert_eq!(deal(&[7; 32]), deal(&[7; 32]));
+    }
+
+    #[test]
+    fn payouts_follow_the_odds() {
+        assert_eq!(BaccaratBet::Player.payout(100, BaccaratBet::Player), Ok(200));
+        assert_eq!(BaccaratBet::Banker.payout(100, BaccaratBet::Banker), Ok(195));
+        assert_eq!(BaccaratBet::Banker.payout(10, BaccaratBet::Banker), Ok(19));
+        assert_eq!(BaccaratBet::Tie.payout(100, BaccaratBet::Tie), Ok(900));
+        assert_eq!(BaccaratBet::Player.payout(100, BaccaratBet::Tie), Ok(100));
+        assert_eq!(BaccaratBet::Tie.payout(100, BaccaratBet::Banker), Ok(0));
+        assert_eq!(
+            BaccaratBet::Tie.payout(Balance::MAX, BaccaratBet::Tie),
+            Err(MetasinoError::ArithmeticError)
+        );
+    }
+}
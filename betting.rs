@@ -0,0 +1,758 @@
+//! Betting rounds played on a table once its shuffle seed is derived.
+//!
+//! Seats are the indexes of the players in the table. Every street starts with
+//! the first seat still in the hand and goes around the table until every seat
+//! either folded, went all-in or matched the highest bet after having acted.
+//! A seat going all-in for less than the others gets side pots layered on top
+//! of the main pot, each layer only winnable by the seats which paid into it.
+//! When the table plays blinds, the two seats after the dealer button post
+//! them before the pre-flop, which then starts after the big blind, and the
+//! later streets start after the button.
+//!
+//! Seven-card stud is bet over five streets, from third to seventh street,
+//! the lowest up card bringing the betting in and the table choosing the
+//! seat opening every later street.
+//!
+//! The betting structure of the table limits the raises. Without limit a
+//! seat raises as much as it can put in. Under pot-limit betting a raise is
+//! at most the pot once the raiser called. Under fixed-limit betting every
+//! bet and raise takes the highest bet to the next multiple of the bet size
+//! of the street, up to `MAX_LIMIT_BETS` of them.
+
+use crate::metasino::{
+    MetasinoError,
+    Result,
+};
+use ink_env::{
+    DefaultEnvironment,
+    Environment,
+};
+use ink_prelude::vec::Vec;
+use ink_storage::traits::{
+    PackedLayout,
+    SpreadLayout,
+};
+
+type Balance = <DefaultEnvironment as Environment>::Balance;
+type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+
+/// The most bets a street of fixed-limit betting takes, counting the blind
+/// or bring-in and the raises.
+pub const MAX_LIMIT_BETS: Balance = 4;
+
+/// An action a player takes on their turn.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub enum Action {
+    Check,
+    Call,
+    Raise(Balance),
+    Fold,
+    AllIn(Balance),
+}
+
+/// The streets of a hand, each of them having its own betting round.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub enum Street {
+    PreFlop,
+    Flop,
+    Turn,
+    River,
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+}
+
+impl Street {
+    /// The street following this one, `None` after the river or seventh street.
+    pub fn next(self) -> Option<Street> {
+        match self {
+            Street::PreFlop => Some(Street::Flop),
+            Street::Flop => Some(Street::Turn),
+            Street::Turn => Some(Street::River),
+            Street::River => None,
+            Street::Third => Some(Street::Fourth),
+            Street::Fourth => Some(Street::Fifth),
+            Street::Fifth => Some(Street::Sixth),
+            Street::Sixth => Some(Street::Seventh),
+            Street::Seventh => None,
+        }
+    }
+
+    /// Whether the street is one of seven-card stud.
+    pub fn is_stud(self) -> bool {
+        matches!(
+            self,
+            Street::Third | Street::Fourth | Street::Fifth | Street::Sixth | Street::Seventh
+        )
+    }
+}
+
+/// How much the raises of a hand are limited to.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+#[allow(clippy::enum_variant_names)]
+pub enum BettingStructure {
+    /// A raise is only limited by the stack of the seat.
+    NoLimit,
+    /// A raise is at most the pot once the raiser called.
+    PotLimit,
+    /// Bets and raises are of the bet size of the street, the small bet or
+    /// twice it, and capped at `MAX_LIMIT_BETS` a street.
+    FixedLimit,
+}
+
+/// An action played in a hand, as kept in its history.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub struct Play {
+    /// The street the action was played on.
+    pub street: Street,
+    /// The seat which played the action.
+    pub seat: u8,
+    /// The action played.
+    pub action: Action,
+    /// The amount put into the pot for the action.
+    pub amount: Balance,
+}
+
+/// The betting state of a hand.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub struct BettingRound {
+    /// The street currently bet on.
+    pub street: Street,
+    /// The amount each seat has bet on the current street.
+    pub bets: Vec<Balance>,
+    /// The amount each seat has bet over the whole hand.
+    pub contributed: Vec<Balance>,
+    /// Whether each seat has folded its hand.
+    pub folded: Vec<bool>,
+    /// Whether each seat has gone all-in.
+    pub all_in: Vec<bool>,
+    /// Whether each seat has acted since the last raise.
+    pub acted: Vec<bool>,
+    /// The seat whose turn it is.
+    pub active: u8,
+    /// The seat the streets after the pre-flop start from.
+    pub first: u8,
+    /// The last block in which the active seat may act.
+    pub deadline: BlockNumber,
+    /// The highest bet of the current street.
+    pub highest_bet: Balance,
+    /// Whether the betting is over, after the river or because all but one seat folded.
+    pub finished: bool,
+    /// The number of actions played in the hand so far, the actions
+    /// themselves being kept by the contract one by one.
+    pub plays: u32,
+    /// How much the raises are limited to.
+    pub structure: BettingStructure,
+    /// The small bet of fixed-limit betting, the bets being unlimited if 0.
+    pub small_bet: Balance,
+    /// The whole pot of the hand, the stakes put in before the betting included.
+    pub pot: Balance,
+}
+
+impl BettingRound {
+    /// Open the pre-flop betting of a hand played by `seats` players, the
+    /// first seat having until the `deadline` block to act.
+    pub fn new(seats: u8, deadline: BlockNumber) -> Self {
+        let seats = seats as usize;
+        Self {
+            street: Street::PreFlop,
+            bets: ink_prelude::vec![0; seats],
+            contributed: ink_prelude::vec![0; seats],
+            folded: ink_prelude::vec![false; seats],
+            all_in: ink_prelude::vec![false; seats],
+            acted: ink_prelude::vec![false; seats],
+            active: 0,
+            first: 0,
+            deadline,
+            highest_bet: 0,
+            finished: false,
+            plays: 0,
+            structure: BettingStructure::NoLimit,
+            small_bet: 0,
+            pot: 0,
+        }
+    }
+
+    /// Open the third street betting of a seven-card stud hand played by
+    /// `seats` players.
+    pub fn stud(seats: u8, deadline: BlockNumber) -> Self {
+        Self {
+            street: Street::Third,
+            ..Self::new(seats, deadline)
+        }
+    }
+
+    /// Post the small and big blinds of the hand dealt with the dealer button
+    /// on `button`, each blind being the amount put in along with whether it
+    /// leaves the seat all-in.
+    pub fn post_blinds(&mut self, button: u8, blinds: [(Balance, bool); 2]) {
+        let seats = blind_seats(self.bets.len() as u8, button);
+        for (seat, (amount, all_in)) in seats.iter().zip(blinds.iter()) {
+            let seat = *seat as usize;
+            self.bets[seat] += amount;
+            self.contributed[seat] += amount;
+            self.pot += amount;
+            self.all_in[seat] = *all_in;
+            self.highest_bet = self.highest_bet.max(self.bets[seat]);
+        }
+        self.first = (button + 1) % self.bets.len() as u8;
+        self.active = self.next_seat(seats[1] as usize + 1);
+    }
+
+    /// Post the straddle of the seat left of the big blind once the blinds
+    /// are posted, the amount put in along with whether it leaves the seat
+    /// all-in. The pre-flop then starts after the straddling seat, which
+    /// acts last.
+    pub fn post_straddle(&mut self, seat: u8, straddle: (Balance, bool)) {
+        self.post_forced(seat, straddle);
+    }
+
+    /// Post the bring-in of the seat showing the lowest up card on third
+    /// street, the amount put in along with whether it leaves the seat
+    /// all-in. The street then starts after the bring-in.
+    pub fn post_bring_in(&mut self, seat: u8, bring_in: (Balance, bool)) {
+        self.post_forced(seat, bring_in);
+    }
+
+    /// Post a forced bet of the seat and start the street after it.
+    fn post_forced(&mut self, seat: u8, (amount, all_in): (Balance, bool)) {
+        let index = seat as usize;
+        self.bets[index] += amount;
+        self.contributed[index] += amount;
+        self.pot += amount;
+        self.all_in[index] = all_in;
+        self.highest_bet = self.highest_bet.max(self.bets[index]);
+        self.active = self.next_seat(index + 1);
+    }
+
+    /// Start the current street from the seat, or the next one able to act.
+    pub fn lead(&mut self, seat: u8) {
+        self.active = self.next_seat(seat as usize);
+    }
+
+    /// The amount the seat has to put in to match the highest bet.
+    pub fn to_call(&self, seat: u8) -> Balance {
+        self.highest_bet - self.bets[seat as usize]
+    }
+
+    /// The number of seats still in the hand.
+    pub fn remaining(&self) -> usize {
+        self.folded.iter().filter(|folded| !**folded).count()
+    }
+
+    /// Whether the seat can still take actions, being neither folded nor all-in.
+    fn can_act(&self, seat: usize) -> bool {
+        !self.folded[seat] && !self.all_in[seat]
+    }
+
+    /// Play the action of the seat and pass the turn on.
+    /// Returns the amount the seat has to put into the pot for the action.
+    pub fn act(&mut self, seat: u8, action: Action) -> Result<Balance> {
+        if self.finished || seat != self.active {
+            return Err(MetasinoError::NotYourTurn);
+        }
+        let to_call = self.to_call(seat);
+        let amount = match action {
+            Action::Check if to_call == 0 => 0,
+            Action::Call if to_call > 0 => to_call,
+            Action::Raise(by) if by > 0 => {
+                let raised = checked_add(self.highest_bet, by)?;
+                let fixed = self.structure == BettingStructure::FixedLimit;
+                let cap = self.raise_cap(to_call)?;
+                if cap.is_some_and(|cap| raised > cap || (fixed && raised != cap)) {
+                    return Err(MetasinoError::InvalidAction);
+                }
+                self.highest_bet = raised;
+                self.acted.iter_mut().for_each(|acted| *acted = false);
+                to_call + by
+            }
+            Action::Fold => {
+                self.folded[seat as usize] = true;
+                0
+            }
+            Action::AllIn(amount) if amount > 0 => {
+                let bet = checked_add(self.bets[seat as usize], amount)?;
+                if bet > self.highest_bet {
+                    if self.raise_cap(to_call)?.is_some_and(|cap| bet > cap) {
+                        return Err(MetasinoError::InvalidAction);
+                    }
+                    self.highest_bet = bet;
+                    self.acted.iter_mut().for_each(|acted| *acted = false);
+                }
+                self.all_in[seat as usize] = true;
+                amount
+            }
+            _ => return Err(MetasinoError::InvalidAction),
+        };
+        self.contributed[seat as usize] = checked_add(self.contributed[seat as usize], amount)?;
+        self.pot = self.pot.saturating_add(amount);
+        self.bets[seat as usize] += amount;
+        self.acted[seat as usize] = true;
+        self.plays += 1;
+        self.advance();
+        Ok(amount)
+    }
+
+    /// The size of the bets of the current street under fixed-limit betting:
+    /// the small bet up to the flop or fourth street, twice it later on.
+    fn bet_size(&self) -> Option<Balance> {
+        let small = Some(self.small_bet).filter(|small| *small > 0)?;
+        match self.street {
+            Street::PreFlop | Street::Flop | Street::Third | Street::Fourth => Some(small),
+            _ => small.checked_mul(2),
+        }
+    }
+
+    /// The most a seat having `to_call` left to call may take the highest
+    /// bet to, `None` if its raises are unlimited. A fixed-limit raise has
+    /// to take the highest bet exactly there.
+    /// error if a fixed-limit street already took `MAX_LIMIT_BETS` bets.
+    fn raise_cap(&self, to_call: Balance) -> Result<Option<Balance>> {
+        match self.structure {
+            BettingStructure::NoLimit => Ok(None),
+            BettingStructure::PotLimit => {
+                let pot = checked_add(self.pot, to_call)?;
+                checked_add(self.highest_bet, pot).map(Some)
+            }
+            BettingStructure::FixedLimit => {
+                let size = match self.bet_size() {
+                    Some(size) => size,
+                    None => return Ok(None),
+                };
+                let bets = self.highest_bet / size + 1;
+                if bets > MAX_LIMIT_BETS {
+                    return Err(MetasinoError::InvalidAction);
+                }
+                size.checked_mul(bets).map(Some).ok_or(MetasinoError::ArithmeticError)
+            }
+        }
+    }
+
+    /// Pass the turn to the next seat, moving on to the next street once
+    /// every seat in the hand has acted and matched the highest bet.
+    fn advance(&mut self) {
+        if self.remaining() <= 1 {
+            self.finished = true;
+            return
+        }
+        let settled = (0..self.bets.len()).all(|seat| {
+            !self.can_act(seat) || (self.acted[seat] && self.bets[seat] == self.highest_bet)
+        });
+        if !settled {
+            self.active = self.next_seat(self.active as usize + 1);
+            return
+        }
+        if (0..self.bets.len()).filter(|seat| self.can_act(*seat)).count() <= 1 {
+            // Nobody is left to bet against, the remaining streets are just run out.
+            self.finished = true;
+            return
+        }
+        match self.street.next() {
+            Some(street) => {
+                self.street = street;
+                self.bets.iter_mut().for_each(|bet| *bet = 0);
+                self.acted.iter_mut().for_each(|acted| *acted = false);
+                self.highest_bet = 0;
+                self.active = self.next_seat(self.first as usize);
+            }
+            None => self.finished = true,
+        }
+    }
+
+    /// The first seat able to act starting from `from`, going around the table.
+    fn next_seat(&self, from: usize) -> u8 {
+        let seats = self.folded.len();
+        (0..seats)
+            .map(|offset| (from + offset) % seats)
+            .find(|seat| self.can_act(*seat))
+            .unwrap_or_default() as u8
+    }
+
+    /// Split the pot into the main pot and the side pots of the all-in seats.
+    /// `base` is the part of the pot put in before the betting, such as the
+    /// start bets, which belongs to the main pot.
+    pub fn side_pots(&self, base: Balance) -> Vec<SidePot> {
+        let mut levels: Vec<Balance> = (0..self.contributed.len())
+            .filter(|seat| self.all_in[*seat])
+            .map(|seat| self.contributed[seat])
+            .collect();
+        levels.push(self.contributed.iter().copied().max().unwrap_or_default());
+        levels.sort_unstable();
+        levels.dedup();
+
+        let mut pots: Vec<SidePot> = Vec::new();
+        let mut previous = 0;
+        for level in levels {
+            let amount: Balance = self
+                .contributed
+                .iter()
+                .map(|contributed| {
+                    (*contributed).min(level) - (*contributed).min(previous)
+                })
+                .sum();
+            let eligible: Vec<u8> = (0..self.contributed.len())
+                .filter(|seat| !self.folded[*seat] && self.contributed[*seat] >= level)
+                .map(|seat| seat as u8)
+                .collect();
+            previous = level;
+            match pots.last_mut() {
+                // Chips nobody still in the hand can win go to the layer below.
+                Some(pot) if eligible.is_empty() => pot.amount += amount,
+                _ => pots.push(SidePot { amount, eligible }),
+            }
+        }
+        if let Some(main) = pots.first_mut() {
+            main.amount += base;
+        }
+        pots
+    }
+}
+
+/// `a + b`.
+/// error if the sum overflows.
+fn checked_add(a: Balance, b: Balance) -> Result<Balance> {
+    a.checked_add(b).ok_or(MetasinoError::ArithmeticError)
+}
+
+/// The seats posting the small and the big blind with the dealer button on
+/// `button`. Heads-up the button posts the small blind.
+pub fn blind_seats(seats: u8, button: u8) -> [u8; 2] {
+    match seats {
+        2 => [button, (button + 1) % 2],
+        _ => [(button + 1) % seats, (button + 2) % seats],
+    }
+}
+
+/// The seat left of the big blind, which may straddle, with the dealer
+/// button on `button`. Nobody straddles heads-up.
+pub fn straddle_seat(seats: u8, button: u8) -> Option<u8> {
+    match seats {
+        0..=2 => None,
+        _ => Some((button + 3) % seats),
+    }
+}
+
+/// A layer of the pot and the seats eligible to win it.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+pub struct SidePot {
+    /// The amount in the layer.
+    pub amount: Balance,
+    /// The seats which paid into the layer and are still in the hand.
+    pub eligible: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn street_moves_on_once_bets_are_matched() {
+        let mut round = BettingRound::new(3, 0);
+        assert_eq!(round.act(0, Action::Raise(10)), Ok(10));
+        assert_eq!(round.act(1, Action::Call), Ok(10));
+        assert_eq!(round.street, Street::PreFlop);
+        assert_eq!(round.act(2, Action::Raise(5)), Ok(15));
+        assert_eq!(round.act(0, Action::Call), Ok(5));
+        assert_eq!(round.act(1, Action::Fold), Ok(0));
+        assert_eq!(round.street, Street::Flop);
+        assert_eq!(round.highest_bet, 0);
+        assert_eq!(round.active, 0);
+        assert_eq!(round.act(0, Action::Check), Ok(0));
+        assert_eq!(round.act(2, Action::Check), Ok(0));
+        assert_eq!(round.street, Street::Turn);
+    }
+
+    #[test]
+    fn hand_finishes_after_river_or_when_all_but_one_fold() {
+        let mut round = BettingRound::new(2, 0);
+        for _ in 0..4 {
+            round.act(0, Action::Check).unwrap();
+            round.act(1, Action::Check).unwrap();
+        }
+        assert!(round.finished);
+        assert_eq!(round.act(0, Action::Check), Err(MetasinoError::NotYourTurn));
+
+        let mut round = BettingRound::new(3, 0);
+        round.act(0, Action::Fold).unwrap();
+        round.act(1, Action::Fold).unwrap();
+        assert!(round.finished);
+        assert_eq!(round.remaining(), 1);
+    }
+
+    #[test]
+    fn blinds_are_posted_after_the_button() {
+        assert_eq!(blind_seats(2, 1), [1, 0]);
+        assert_eq!(blind_seats(4, 3), [0, 1]);
+
+        let mut round = BettingRound::new(4, 0);
+        round.post_blinds(1, [(5, false), (10, false)]);
+        assert_eq!(round.bets, vec![0, 0, 5, 10]);
+        assert_eq!((round.highest_bet, round.active), (10, 0));
+        assert_eq!(round.act(0, Action::Call), Ok(10));
+        assert_eq!(round.act(1, Action::Fold), Ok(0));
+        assert_eq!(round.act(2, Action::Call), Ok(5));
+        assert_eq!(round.street, Street::PreFlop);
+        assert_eq!(round.act(3, Action::Check), Ok(0));
+        assert_eq!(round.street, Street::Flop);
+        assert_eq!(round.active, 2);
+    }
+
+    #[test]
+    fn heads_up_button_posts_small_blind_and_acts_first_pre_flop() {
+        let mut round = BettingRound::new(2, 0);
+        round.post_blinds(1, [(5, false), (10, false)]);
+        assert_eq!(round.bets, vec![10, 5]);
+        assert_eq!(round.active, 1);
+        assert_eq!(round.act(1, Action::Call), Ok(5));
+        assert_eq!(round.act(0, Action::Check), Ok(0));
+        // After the pre-flop the big blind acts first.
+        assert_eq!((round.street, round.active), (Street::Flop, 0));
+    }
+
+    #[test]
+    fn straddle_moves_the_pre_flop_on() {
+        assert_eq!(straddle_seat(2, 0), None);
+        assert_eq!(straddle_seat(4, 3), Some(2));
+
+        let mut round = BettingRound::new(4, 0);
+        round.post_blinds(0, [(5, false), (10, false)]);
+        round.post_straddle(3, (20, false));
+        assert_eq!(round.bets, vec![0, 5, 10, 20]);
+        assert_eq!((round.highest_bet, round.active), (20, 0));
+        assert_eq!(round.act(0, Action::Call), Ok(20));
+        assert_eq!(round.act(1, Action::Call), Ok(15));
+        assert_eq!(round.act(2, Action::Call), Ok(10));
+        assert_eq!(round.street, Street::PreFlop);
+        assert_eq!(round.act(3, Action::Check), Ok(0));
+        assert_eq!(round.street, Street::Flop);
+    }
+
+    #[test]
+    fn stud_is_bet_over_five_streets_from_the_bring_in() {
+        let mut round = BettingRound::stud(3, 0);
+        round.structure = BettingStructure::FixedLimit;
+        round.small_bet = 10;
+        round.post_bring_in(1, (5, false));
+        assert_eq!((round.street, round.highest_bet, round.active), (Street::Third, 5, 2));
+        assert_eq!(round.act(2, Action::Raise(10)), Err(MetasinoError::InvalidAction));
+        // The bring-in is completed to the small bet.
+        assert_eq!(round.act(2, Action::Raise(5)), Ok(10));
+        assert_eq!(round.act(0, Action::Call), Ok(10));
+        assert_eq!(round.act(1, Action::Call), Ok(5));
+        assert_eq!(round.street, Street::Fourth);
+        round.lead(2);
+        assert_eq!(round.active, 2);
+        for _ in 0..3 {
+            for seat in [2, 0, 1] {
+                round.act(seat, Action::Check).unwrap();
+            }
+            round.lead(2);
+        }
+        assert_eq!(round.street, Street::Seventh);
+        round.act(2, Action::Check).unwrap();
+        round.act(0, Action::Check).unwrap();
+        round.act(1, Action::Check).unwrap();
+        assert!(round.finished);
+    }
+
+    #[test]
+    fn fixed_limit_raises_by_the_bet_size_up_to_the_cap() {
+        let mut round = BettingRound::stud(2, 0);
+        round.structure = BettingStructure::FixedLimit;
+        round.small_bet = 10;
+        round.street = Street::Fifth;
+        assert_eq!(round.act(0, Action::Raise(10)), Err(MetasinoError::InvalidAction));
+        assert_eq!(round.act(0, Action::Raise(20)), Ok(20));
+        assert_eq!(round.act(1, Action::AllIn(50)), Err(MetasinoError::InvalidAction));
+        assert_eq!(round.act(1, Action::Raise(20)), Ok(40));
+        assert_eq!(round.act(0, Action::Raise(20)), Ok(40));
+        assert_eq!(round.act(1, Action::Raise(20)), Ok(40));
+        assert_eq!(round.highest_bet, 80);
+        assert_eq!(round.act(0, Action::Raise(20)), Err(MetasinoError::InvalidAction));
+        assert_eq!(round.act(0, Action::Call), Ok(20));
+        assert_eq!(round.street, Street::Sixth);
+    }
+
+    #[test]
+    fn pot_limit_raises_up_to_the_pot() {
+        let mut round = BettingRound::new(3, 0);
+        round.structure = BettingStructure::PotLimit;
+        round.pot = 15;
+        round.post_blinds(0, [(5, false), (10, false)]);
+        assert_eq!(round.pot, 30);
+        // Calling 10 makes the pot 40, the most the raise adds on top of the call.
+        assert_eq!(round.act(0, Action::Raise(41)), Err(MetasinoError::InvalidAction));
+        assert_eq!(round.act(0, Action::AllIn(51)), Err(MetasinoError::InvalidAction));
+        assert_eq!(round.act(0, Action::Raise(40)), Ok(50));
+        assert_eq!(round.pot, 80);
+        assert_eq!(round.act(1, Action::Raise(126)), Err(MetasinoError::InvalidAction));
+        assert_eq!(round.act(1, Action::Raise(125)), Ok(170));
+
+        let mut round = BettingRound::new(2, 0);
+        round.structure = BettingStructure::FixedLimit;
+        assert_eq!(round.act(0, Action::Raise(1_000)), Ok(1_000));
+    }
+
+    #[test]
+    fn short_blind_is_all_in() {
+        let mut round = BettingRound::new(3, 0);
+        round.post_blinds(0, [(5, false), (4, true)]);
+        assert_eq!(round.highest_bet, 5);
+        assert_eq!(round.active, 0);
+        round.act(0, Action::Call).unwrap();
+        round.act(1, Action::Check).unwrap();
+        assert_eq!(round.street, Street::Flop);
+        assert_eq!(round.active, 1);
+    }
+
+    #[test]
+    fn all_in_seats_are_skipped_and_get_side_pots() {
+        let mut round = BettingRound::new(3, 0);
+        assert_eq!(round.act(0, Action::Raise(100)), Ok(100));
+        assert_eq!(round.act(1, Action::AllIn(40)), Ok(40));
+        assert_eq!(round.act(2, Action::Call), Ok(100));
+        assert_eq!(round.street, Street::Flop);
+        assert_eq!(round.active, 0);
+        round.act(0, Action::Raise(50)).unwrap();
+        assert_eq!(round.active, 2);
+        round.act(2, Action::Call).unwrap();
+
+        assert_eq!(
+            round.side_pots(30),
+            vec![
+                SidePot { amount: 150, eligible: vec![0, 1, 2] },
+                SidePot { amount: 220, eligible: vec![0, 2] },
+            ]
+        );
+    }
+
+    #[test]
+    fn betting_finishes_when_nobody_is_left_to_bet_against() {
+        let mut round = BettingRound::new(2, 0);
+        round.act(0, Action::AllIn(70)).unwrap();
+        round.act(1, Action::Call).unwrap();
+        assert!(round.finished);
+        assert_eq!(round.side_pots(0), vec![SidePot { amount: 140, eligible: vec![0, 1] }]);
+    }
+
+    #[test]
+    fn uncalled_raise_is_only_winnable_by_the_raiser() {
+        let mut round = BettingRound::new(3, 0);
+        round.act(0, Action::AllIn(20)).unwrap();
+        round.act(1, Action::Raise(30)).unwrap();
+        round.act(2, Action::Fold).unwrap();
+        assert!(round.finished);
+        assert_eq!(
+            round.side_pots(0),
+            vec![
+                SidePot { amount: 40, eligible: vec![0, 1] },
+                SidePot { amount: 30, eligible: vec![1] },
+            ]
+        );
+    }
+
+    #[test]
+    fn actions_played_are_counted() {
+        let mut round = BettingRound::new(2, 0);
+        round.act(0, Action::Raise(10)).unwrap();
+        round.act(1, Action::Call).unwrap();
+        assert_eq!(round.act(1, Action::Check), Err(MetasinoError::NotYourTurn));
+        round.act(0, Action::Check).unwrap();
+        assert_eq!((round.street, round.plays), (Street::Flop, 3));
+    }
+
+    #[test]
+    fn invalid_actions_are_rejected() {
+        let mut round = BettingRound::new(3, 0);
+        assert_eq!(round.act(1, Action::Check), Err(MetasinoError::NotYourTurn));
+        assert_eq!(round.act(0, Action::Call), Err(MetasinoError::InvalidAction));
+        assert_eq!(round.act(0, Action::Raise(0)), Err(MetasinoError::InvalidAction));
+        round.act(0, Action::Raise(10)).unwrap();
+        assert_eq!(round.act(1, Action::Check), Err(MetasinoError::InvalidAction));
+    }
+
+    #[test]
+    fn overflowing_bets_are_rejected() {
+        let mut round = BettingRound::new(2, 0);
+        round.act(0, Action::Raise(Balance::MAX)).unwrap();
+        assert_eq!(round.act(1, Action::Raise(1)), Err(MetasinoError::ArithmeticError));
+        assert_eq!(round.act(1, Action::Call), Ok(Balance::MAX));
+
+        let mut round = BettingRound::new(2, 0);
+        round.act(0, Action::AllIn(Balance::MAX)).unwrap();
+        round.contributed[1] = 1;
+        assert_eq!(round.act(1, Action::Call), Err(MetasinoError::ArithmeticError));
+    }
+}
@@ -0,0 +1,410 @@
+//! Blackjack played by the seats of a table against the dealer of the house.
+//!
+//! The cards are dealt from the deck shuffled by the seed of the table. Every
+//! seat bets its start bet and plays its hand in turn, then the dealer draws
+//! until reaching 17. A blackjack pays 3:2, any other win pays 1:1 and a
+//! push gives the bet back.
+//!
+//! When the up-card of the dealer is an ace, the seats may insure their hand
+//! for up to half their bet during a short window after the deal. The
+//! insurance pays 2:1 if the dealer has blackjack and is lost otherwise.
+
+use crate::cards::{
+    self,
+    Card,
+    ACE,
+};
+use crate::metasino::{
+    MetasinoError,
+    Result,
+};
+use ink_env::{
+    DefaultEnvironment,
+    Environment,
+};
+use ink_prelude::vec::Vec;
+use ink_storage::traits::{
+    PackedLayout,
+    SpreadLayout,
+};
+
+type Balance = <DefaultEnvironment as Environment>::Balance;
+type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+
+/// The rank of the jack.
+const JACK: u8 = 11;
+/// The total the dealer stands on.
+const DEALER_STANDS_ON: u8 = 17;
+/// The best total of a hand.
+const BLACKJACK: u8 = 21;
+
+/// A move a seat makes on their turn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+pub enum Move {
+    Hit,
+    Stand,
+    DoubleDown,
+}
+
+/// The state of a blackjack hand dealt to the seats of a table.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub struct BlackjackRound {
+    /// The cards left in the deck, drawn from the end.
+    pub deck: Vec<Card>,
+    /// The hand of the dealer.
+    pub dealer: Vec<Card>,
+    /// The hand of each seat.
+    pub hands: Vec<Vec<Card>>,
+    /// The bet of each seat.
+    pub bets: Vec<Balance>,
+    /// Whether each seat is done playing, having stood, doubled down or busted.
+    pub done: Vec<bool>,
+    /// The seat whose turn it is.
+    pub active: u8,
+    /// The last block in which the active seat may act.
+    pub deadline: BlockNumber,
+    /// Whether every seat is done and the dealer has played.
+    pub finished: bool,
+    /// The insurance bet of each seat.
+    pub insurance: Vec<Balance>,
+    /// The last block in which the seats may insure their hand, if the
+    /// up-card of the dealer is an ace.
+    pub insurance_deadline: Option<BlockNumber>,
+}
+
+impl BlackjackRound {
+    /// Deal two cards to each of the `seats` betting `bet`, then two to the
+    /// dealer, from the deck shuffled by `seed`. The first of them is the
+    /// up-card of the dealer.
+    pub fn deal(seats: u8, bet: Balance, seed: &[u8; 32], deadline: BlockNumber) -> Self {
+        let seats = seats as usize;
+        let mut round = Self {
+            deck: cards::shuffled_deck(seed),
+            dealer: Vec::new(),
+            hands: ink_prelude::vec![Vec::new(); seats],
+            bets: ink_prelude::vec![bet; seats],
+            done: ink_prelude::vec![false; seats],
+            active: 0,
+            deadline,
+            finished: false,
+            insurance: ink_prelude::vec![0; seats],
+            insurance_deadline: None,
+        };
+        for _ in 0..2 {
+            for seat in 0..seats {
+                let card = round.draw();
+                round.hands[seat].push(card);
+            }
+            let card = round.draw();
+            round.dealer.push(card);
+        }
+        for seat in 0..seats {
+            round.done[seat] = is_blackjack(&round.hands[seat]);
+        }
+        round.advance(0);
+        round
+    }
+
+    /// Open the insurance window until `deadline` if the up-card of the
+    /// dealer is an ace and the hand is still played.
+    pub fn offer_insurance(&mut self, deadline: BlockNumber) {
+        if !self.finished && self.dealer.first().is_some_and(|card| card.rank == ACE) {
+            self.insurance_deadline = Some(deadline);
+        }
+    }
+
+    /// Insure the hand of the seat for `amount`, at most half its bet, in
+    /// block `now`. A seat insures its hand once.
+    /// error if no insurance window is open.
+    /// error if the seat already insured or the amount is out of bounds.
+    pub fn insure(&mut self, seat: u8, amount: Balance, now: BlockNumber) -> Result<()> {
+        match self.insurance_deadline {
+            Some(deadline) if !self.finished && now <= deadline => (),
+            Some(_) => return Err(MetasinoError::DeadlinePassed),
+            None => return Err(MetasinoError::WrongState),
+        }
+        let index = seat as usize;
+        if index >= self.insurance.len() || self.insurance[index] > 0 {
+            return Err(MetasinoError::InvalidAction);
+        }
+        if amount == 0 || amount > self.bets[index] / 2 {
+            return Err(MetasinoError::WrongBet);
+        }
+        self.insurance[index] = amount;
+        Ok(())
+    }
+
+    /// Play the move of the seat and pass the turn on.
+    /// Returns the amount the seat has to put into the pot for the move.
+    pub fn play(&mut self, seat: u8, action: Move) -> Result<Balance> {
+        if self.finished || seat != self.active {
+            return Err(MetasinoError::NotYourTurn);
+        }
+        let index = seat as usize;
+        let amount = match action {
+            Move::Hit => {
+                let card = self.draw();
+                self.hands[index].push(card);
+                self.done[index] = hand_value(&self.hands[index]) >= BLACKJACK;
+                0
+            }
+            Move::Stand => {
+                self.done[index] = true;
+                0
+            }
+            Move::DoubleDown if self.hands[index].len() == 2 => {
+                let card = self.draw();
+                self.hands[index].push(card);
+                self.done[index] = true;
+                let bet = self.bets[index];
+                self.bets[index] = bet.checked_mul(2).ok_or(MetasinoError::ArithmeticError)?;
+                bet
+            }
+            _ => return Err(MetasinoError::InvalidAction),
+        };
+        if self.done[index] {
+            self.advance(index + 1);
+        }
+        Ok(amount)
+    }
+
+    /// What each seat gets back out of the pot, its bet and insurance
+    /// included.
+    /// error if a payout overflows.
+    pub fn payouts(&self) -> Result<Vec<Balance>> {
+        let dealer = hand_value(&self.dealer);
+        let dealer_blackjack = is_blackjack(&self.dealer);
+        self.hands
+            .iter()
+            .zip(self.bets.iter())
+            .zip(self.insurance.iter())
+            .map(|((hand, bet), insurance)| {
+                let value = hand_value(hand);
+                let payout = if value > BLACKJACK {
+                    Some(0)
+                } else if is_blackjack(hand) && !dealer_blackjack {
+                    bet.checked_mul(2).and_then(|won| won.checked_add(bet / 2))
+                } else if dealer_blackjack && !is_blackjack(hand) {
+                    Some(0)
+                } else if dealer > BLACKJACK || value > dealer {
+                    bet.checked_mul(2)
+                } else if value == dealer {
+                    Some(*bet)
+                } else {
+                    Some(0)
+                };
+                let insured = match dealer_blackjack {
+                    true => insurance.checked_mul(3),
+                    false => Some(0),
+                };
+                payout
+                    .zip(insured)
+                    .and_then(|(payout, insured)| payout.checked_add(insured))
+                    .ok_or(MetasinoError::ArithmeticError)
+            })
+            .collect()
+    }
+
+    /// Give the turn to the first seat not done from `from` on, or let the
+    /// dealer play once every seat is done.
+    fn advance(&mut self, from: usize) {
+        match (from..self.done.len()).find(|seat| !self.done[*seat]) {
+            Some(seat) => self.active = seat as u8,
+            None => {
+                while hand_value(&self.dealer) < DEALER_STANDS_ON {
+                    let card = self.draw();
+                    self.dealer.push(card);
+                }
+                self.finished = true;
+            }
+        }
+    }
+
+    /// Take the next card of the deck.
+    fn draw(&mut self) -> Card {
+        // A hand of at most ten seats never runs through the 52 cards.
+        self.deck.pop().unwrap_or(Card {
+            rank: ACE,
+            suit: cards::Suit::Spades,
+        })
+    }
+}
+
+/// The best total of the hand, counting aces as 11 unless it busts.
+pub fn hand_value(hand: &[Card]) -> u8 {
+    let mut total: u8 = 0;
+    let mut aces = 0;
+    for card in hand {
+        total += match card.rank {
+            ACE => {
+                aces += 1;
+                11
+            }
+            rank if rank >= 10 => 10,
+            rank => rank,
+        };
+    }
+    while total > BLACKJACK && aces > 0 {
+        total -= 10;
+        aces -= 1;
+    }
+    total
+}
+
+/// Whether the hand is a natural 21 made with its first two cards.
+pub fn is_blackjack(hand: &[Card]) -> bool {
+    hand.len() == 2 && hand_value(hand) == BLACKJACK
+}
+
+/// Whether the hand is the ace and jack of spades, the rare blackjack that
+/// wins the jackpot.
+pub fn is_jackpot_hand(hand: &[Card]) -> bool {
+    let spade = |rank| {
+        hand.contains(&Card {
+            rank,
+            suit: cards::Suit::Spades,
+        })
+    };
+    hand.len() == 2 && spade(ACE) && spade(JACK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::Suit::*;
+
+    fn hand(ranks: &[u8]) -> Vec<Card> {
+        ranks.iter().map(|rank| Card { rank: *rank, suit: Hearts }).collect()
+    }
+
+    /// A round of one seat with the given hands, the deck holding `deck` to draw.
+    fn round(player: &[u8], dealer: &[u8], deck: &[u8]) -> BlackjackRound {
+        BlackjackRound {
+            deck: hand(deck),
+            dealer: hand(dealer),
+            hands: vec![hand(player)],
+            bets: vec![10],
+            done: vec![false],
+            active: 0,
+            deadline: 0,
+            finished: false,
+            insurance: vec![0],
+            insurance_deadline: None,
+        }
+    }
+
+    #[test]
+    fn aces_count_as_one_when_eleven_busts() {
+        assert_eq!(hand_value(&hand(&[ACE, 13])), 21);
+        assert_eq!(hand_value(&hand(&[ACE, ACE, 9])), 21);
+        assert_eq!(hand_value(&hand(&[ACE, 9, 5])), 15);
+        assert_eq!(hand_value(&hand(&[12, 11, 2])), 22);
+        assert!(is_blackjack(&hand(&[ACE, 10])));
+        assert!(!is_blackjack(&hand(&[7, 4, 10])));
+    }
+
+    #[test]
+    fn only_the_spade_blackjack_hits_the_jackpot() {
+        let spades = |ranks: &[u8]| -> Vec<Card> {
+            ranks.iter().map(|rank| Card { rank: *rank, suit: Spades }).collect()
+        };
+        assert!(is_jackpot_hand(&spades(&[JACK, ACE])));
+        assert!(!is_jackpot_hand(&hand(&[JACK, ACE])));
+        assert!(!is_jackpot_hand(&spades(&[13, ACE])));
+        assert!(!is_jackpot_hand(&spades(&[JACK, ACE, 2])));
+    }
+
+    #[test]
+    fn dealer_hits_on_16_and_stands_on_17() {
+        let mut sixteen = round(&[10, 8], &[10, 6], &[5, 2]);
+        sixteen.play(0, Move::Stand).unwrap();
+        assert!(sixteen.finished);
+        assert_eq!(hand_value(&sixteen.dealer), 18);
+
+        let mut seventeen = round(&[10, 8], &[10, 7], &[5]);
+        seventeen.play(0, Move::Stand).unwrap();
+        assert_eq!(seventeen.dealer.len(), 2);
+        assert_eq!(seventeen.payouts(), Ok(vec![20]));
+    }
+
+    #[test]
+    fn busting_ends_the_turn_and_loses_the_bet() {
+        let mut round = round(&[10, 6], &[10, 8], &[9]);
+        assert_eq!(round.play(0, Move::Hit), Ok(0));
+        assert!(round.finished);
+        assert_eq!(round.payouts(), Ok(vec![0]));
+        assert_eq!(round.play(0, Move::Stand), Err(MetasinoError::NotYourTurn));
+    }
+
+    #[test]
+    fn double_down_doubles_the_bet_on_two_cards_only() {
+        let mut doubled = round(&[5, 6], &[10, 7], &[10]);
+        assert_eq!(doubled.play(0, Move::DoubleDown), Ok(10));
+        assert!(doubled.finished);
+        assert_eq!(doubled.payouts(), Ok(vec![40]));
+
+        let mut late = round(&[2, 3], &[10, 7], &[10, 4]);
+        late.play(0, Move::Hit).unwrap();
+        assert_eq!(late.play(0, Move::DoubleDown), Err(MetasinoError::InvalidAction));
+    }
+
+    #[test]
+    fn payouts_follow_the_blackjack_rules() {
+        assert_eq!(round(&[ACE, 13], &[10, 9], &[]).payouts(), Ok(vec![25]));
+        assert_eq!(round(&[ACE, 13], &[ACE, 10], &[]).payouts(), Ok(vec![10]));
+        assert_eq!(round(&[10, 10], &[ACE, 10], &[]).payouts(), Ok(vec![0]));
+        assert_eq!(round(&[10, 8], &[10, 8], &[]).payouts(), Ok(vec![10]));
+        assert_eq!(round(&[10, 7], &[10, 8], &[]).payouts(), Ok(vec![0]));
+        assert_eq!(round(&[10, 7], &[10, 6, 8], &[]).payouts(), Ok(vec![20]));
+
+        let mut pushed = round(&[10, 8], &[10, 8], &[]);
+        pushed.bets = vec![Balance::MAX];
+        assert_eq!(pushed.payouts(), Ok(vec![Balance::MAX]));
+        let mut won = round(&[ACE, 13], &[10, 9], &[]);
+        won.bets = vec![Balance::MAX / 2];
+        assert_eq!(won.payouts(), Err(MetasinoError::ArithmeticError));
+    }
+
+    #[test]
+    fn insurance_pays_two_to_one_on_a_dealer_blackjack() {
+        let mut plain = round(&[10, 8], &[10, ACE], &[]);
+        plain.offer_insurance(5);
+        assert_eq!(plain.insure(0, 5, 1), Err(MetasinoError::WrongState));
+
+        let mut insured = round(&[10, 8], &[ACE, 13], &[]);
+        insured.offer_insurance(5);
+        assert_eq!(insured.insure(0, 6, 1), Err(MetasinoError::WrongBet));
+        assert_eq!(insured.insure(0, 5, 6), Err(MetasinoError::DeadlinePassed));
+        assert_eq!(insured.insure(0, 5, 5), Ok(()));
+        assert_eq!(insured.insure(0, 5, 5), Err(MetasinoError::InvalidAction));
+        assert_eq!(insured.payouts(), Ok(vec![15]));
+
+        let mut lost = round(&[10, 8], &[ACE, 6], &[]);
+        lost.offer_insurance(5);
+        lost.insure(0, 5, 5).unwrap();
+        assert_eq!(lost.payouts(), Ok(vec![20]));
+    }
+
+    #[test]
+    fn dealing_gives_two_cards_to_everyone() {
+        let round = BlackjackRound::deal(3, 10, &[1; 32], 5);
+        assert_eq!(round.hands.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 2, 2]);
+        assert_eq!(round.dealer.len(), 2);
+        assert_eq!(round.deck.len(), 44);
+        assert_eq!(round.bets, vec![10, 10, 10]);
+    }
+}
@@ -0,0 +1,157 @@
+//! Playing cards of a standard 52-card deck, or of the 36-card deck of
+//! short-deck poker.
+
+use ink_env::hash::{
+    Blake2x256,
+    HashOutput,
+};
+use ink_prelude::vec::Vec;
+use ink_storage::traits::{
+    PackedLayout,
+    SpreadLayout,
+};
+
+/// The rank of the ace, the highest card.
+pub const ACE: u8 = 14;
+
+/// The suit of a card.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+/// A playing card. The rank goes from 2 up to 14 for the ace.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub struct Card {
+    pub rank: u8,
+    pub suit: Suit,
+}
+
+/// The poker variants, dealing and scoring the cards differently.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub enum Variant {
+    /// Texas hold'em, two hole cards played with the board.
+    Holdem,
+    /// Four hole cards of which exactly two are played with three of the board.
+    Omaha,
+    /// Hold'em with the 2s to 5s stripped from the deck, a flush beating a
+    /// full house.
+    ShortDeck,
+    /// Seven-card stud, each player getting four up cards and three down
+    /// cards without any board.
+    Stud,
+}
+
+impl Variant {
+    /// The number of hole cards dealt to each player.
+    pub fn hole_cards(self) -> usize {
+        match self {
+            Variant::Omaha => 4,
+            Variant::Stud => 3,
+            Variant::Holdem | Variant::ShortDeck => 2,
+        }
+    }
+
+    /// The lowest rank of the deck.
+    pub fn lowest_rank(self) -> u8 {
+        match self {
+            Variant::ShortDeck => 6,
+            Variant::Holdem | Variant::Omaha | Variant::Stud => 2,
+        }
+    }
+}
+
+/// The 52 cards of the deck shuffled by the given seed.
+/// The same seed always gives the same order.
+pub fn shuffled_deck(seed: &[u8; 32]) -> Vec<Card> {
+    variant_deck(seed, Variant::Holdem)
+}
+
+/// The cards of the deck of the variant shuffled by the given seed.
+/// The same seed always gives the same order.
+pub fn variant_deck(seed: &[u8; 32], variant: Variant) -> Vec<Card> {
+    let suits = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+    let lowest = variant.lowest_rank();
+    let mut deck: Vec<Card> = suits
+        .iter()
+        .flat_map(|suit| (lowest..=ACE).map(move |rank| Card { rank, suit: *suit }))
+        .collect();
+    // Fisher-Yates, each swap drawing from the hash of the seed and the position.
+    for i in (1..deck.len()).rev() {
+        let mut output = <Blake2x256 as HashOutput>::Type::default();
+        ink_env::hash_encoded::<Blake2x256, _>(&(seed, i as u32), &mut output);
+        let draw = u32::from_le_bytes([output[0], output[1], output[2], output[3]]);
+        deck.swap(i, draw as usize % (i + 1));
+    }
+    deck
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shuffled_deck_holds_every_card_once() {
+        let deck = shuffled_deck(&[7; 32]);
+        assert_eq!(deck.len(), 52);
+        for (i, card) in deck.iter().enumerate() {
+            assert!((2..=ACE).contains(&card.rank));
+            assert!(!deck[i + 1..].contains(card));
+        }
+        assert_eq!(shuffled_deck(&[7; 32]), deck);
+        assert_ne!(shuffled_deck(&[8; 32]), deck);
+    }
+
+    #[test]
+    fn short_deck_strips_the_twos_to_fives() {
+        let deck = variant_deck(&[7; 32], Variant::ShortDeck);
+        assert_eq!(deck.len(), 36);
+        assert!(deck.iter().all(|card| (6..=ACE).contains(&card.rank)));
+        assert_eq!(variant_deck(&[7; 32], Variant::Omaha), shuffled_deck(&[7; 32]));
+    }
+}
@@ -0,0 +1,57 @@
+//! Randomness of the host chain read through a chain extension.
+//!
+//! With the `chain-randomness` feature the contract runs in
+//! [`RandomnessEnvironment`], whose chain extension exposes the randomness
+//! pallet of the chain (BABE or VRF based). The randomness read for each hand
+//! is mixed into the entropy revealed by the players, so that the last player
+//! to reveal cannot steer the shuffle seed by choosing whether to reveal.
+//! The chain has to implement the extension with the function id below.
+
+use ink_env::{
+    DefaultEnvironment,
+    Environment,
+};
+
+/// The chain extension reading the randomness of the chain.
+#[ink_lang::chain_extension]
+pub trait FetchRandom {
+    type ErrorCode = RandomReadErr;
+
+    /// The randomness of the chain for the given subject.
+    #[ink(extension = 1101, returns_result = false)]
+    fn fetch_random(subject: [u8; 32]) -> [u8; 32];
+}
+
+/// The errors returned by the chain extension.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+pub enum RandomReadErr {
+    /// The randomness pallet could not be read.
+    FailGetRandomSource,
+}
+
+impl ink_env::chain_extension::FromStatusCode for RandomReadErr {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            _ => Err(Self::FailGetRandomSource),
+        }
+    }
+}
+
+/// The default environment with the randomness chain extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+pub enum RandomnessEnvironment {}
+
+impl Environment for RandomnessEnvironment {
+    const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = <DefaultEnvironment as Environment>::AccountId;
+    type Balance = <DefaultEnvironment as Environment>::Balance;
+    type Hash = <DefaultEnvironment as Environment>::Hash;
+    type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+    type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+    type ChainExtension = FetchRandom;
+}
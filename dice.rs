@@ -0,0 +1,211 @@
+//! Craps-style dice with pass and don't-pass bets.
+//!
+//! The come-out roll wins for the pass bets on 7 or 11 and loses on 2, 3 or
+//! 12, where the don't-pass bets push instead of winning. Any other total
+//! becomes the point, and the dice are rolled again until they either make
+//! the point, winning for the pass bets, or roll a 7, winning for the
+//! don't-pass bets. Every roll is drawn from the seed of the table and the
+//! number of rolls so far.
+
+use crate::metasino::{
+    MetasinoError,
+    Result,
+};
+use ink_env::{
+    hash::{
+        Blake2x256,
+        HashOutput,
+    },
+    DefaultEnvironment,
+    Environment,
+};
+use ink_prelude::vec::Vec;
+use ink_storage::traits::{
+    PackedLayout,
+    SpreadLayout,
+};
+
+type Balance = <DefaultEnvironment as Environment>::Balance;
+
+/// The side a player bets on.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub enum DiceBet {
+    Pass,
+    DontPass,
+}
+
+/// How the rolls resolved the bets.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub enum DiceOutcome {
+    /// The pass bets win.
+    Pass,
+    /// The don't-pass bets win.
+    DontPass,
+    /// A 12 on the come-out roll, the pass bets lose and the don't-pass bets push.
+    Bar,
+}
+
+/// The rolls of a dice game.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub struct DiceRound {
+    /// The point to make, once set by the come-out roll.
+    pub point: Option<u8>,
+    /// The dice rolled so far.
+    pub rolls: Vec<(u8, u8)>,
+    /// How the bets are resolved, once they are.
+    pub outcome: Option<DiceOutcome>,
+}
+
+impl DiceRound {
+    /// Roll the next dice out of the seed and apply the rules to their total.
+    /// error if the bets are already resolved.
+    pub fn roll(&mut self, seed: &[u8; 32]) -> Result<(u8, u8)> {
+        if self.outcome.is_some() {
+            return Err(MetasinoError::WrongState);
+        }
+        let dice = roll(seed, self.rolls.len() as u32);
+        self.apply(dice);
+        Ok(dice)
+    }
+
+    /// Apply the rules to the dice rolled.
+    fn apply(&mut self, dice: (u8, u8)) {
+        self.rolls.push(dice);
+        let total = dice.0 + dice.1;
+        self.outcome = match (self.point, total) {
+            (None, 7) | (None, 11) => Some(DiceOutcome::Pass),
+            (None, 2) | (None, 3) => Some(DiceOutcome::DontPass),
+            (None, 12) => Some(DiceOutcome::Bar),
+            (None, point) => {
+                self.point = Some(point);
+                None
+            }
+            (Some(_), 7) => Some(DiceOutcome::DontPass),
+            (Some(point), total) if point == total => Some(DiceOutcome::Pass),
+            _ => None,
+        };
+    }
+
+    /// What the bet of `amount` returns once the bets are resolved, the
+    /// amount bet included.
+    /// error if the payout overflows.
+    pub fn payout(&self, bet: DiceBet, amount: Balance) -> Result<Balance> {
+        match (self.outcome, bet) {
+            (Some(DiceOutcome::Pass), DiceBet::Pass)
+            | (Some(DiceOutcome::DontPass), DiceBet::DontPass) => {
+                amount.checked_mul(2).ok_or(MetasinoError::ArithmeticError)
+            }
+            (Some(DiceOutcome::Bar), DiceBet::DontPass) => Ok(amount),
+            _ => Ok(0),
+        }
+    }
+}
+
+/// The two dice of the roll with the given index out of the seed.
+pub fn roll(seed: &[u8; 32], index: u32) -> (u8, u8) {
+    let mut output = <Blake2x256 as HashOutput>::Type::default();
+    ink_env::hash_encoded::<Blake2x256, _>(&(seed, index), &mut output);
+    let die = |bytes: &[u8]| {
+        (u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) % 6) as u8 + 1
+    };
+    (die(&output[..4]), die(&output[4..8]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rolled(dice: &[(u8, u8)]) -> DiceRound {
+        let mut round = DiceRound::default();
+        for roll in dice {
+            round.apply(*roll);
+        }
+        round
+    }
+
+    #[test]
+    fn come_out_roll_resolves_naturals_and_craps() {
+        assert_eq!(rolled(&[(3, 4)]).outcome, Some(DiceOutcome::Pass));
+        assert_eq!(rolled(&[(5, 6)]).outcome, Some(DiceOutcome::Pass));
+        assert_eq!(rolled(&[(1, 1)]).outcome, Some(DiceOutcome::DontPass));
+        assert_eq!(rolled(&[(1, 2)]).outcome, Some(DiceOutcome::DontPass));
+        assert_eq!(rolled(&[(6, 6)]).outcome, Some(DiceOutcome::Bar));
+    }
+
+    #[test]
+    fn point_is_made_or_sevened_out() {
+        let round = rolled(&[(2, 2)]);
+        assert_eq!((round.point, round.outcome), (Some(4), None));
+        assert_eq!(rolled(&[(2, 2), (6, 6), (1, 3)]).outcome, Some(DiceOutcome::Pass));
+        assert_eq!(rolled(&[(2, 2), (5, 6), (3, 4)]).outcome, Some(DiceOutcome::DontPass));
+    }
+
+    #[test]
+    fn payouts_follow_the_outcome() {
+        let pass = rolled(&[(3, 4)]);
+        assert_eq!(pass.payout(DiceBet::Pass, 10), Ok(20));
+        assert_eq!(pass.payout(DiceBet::DontPass, 10), Ok(0));
+        assert_eq!(
+            pass.payout(DiceBet::Pass, Balance::MAX),
+            Err(MetasinoError::ArithmeticError)
+        );
+        let bar = rolled(&[(6, 6)]);
+        assert_eq!(bar.payout(DiceBet::Pass, 10), Ok(0));
+        assert_eq!(bar.payout(DiceBet::DontPass, 10), Ok(10));
+        assert_eq!(bar.payout(DiceBet::DontPass, Balance::MAX), Ok(Balance::MAX));
+        assert_eq!(rolled(&[(2, 2)]).payout(DiceBet::Pass, 10), Ok(0));
+    }
+
+    #[test]
+    fn rolls_are_dice_until_resolved() {
+        let mut round = DiceRound::default();
+        while round.outcome.is_none() {
+            let (first, second) = round.roll(&[9; 32]).unwrap();
+            assert!((1..=6).contains(&first) && (1..=6).contains(&second));
+        }
+        assert_eq!(round.roll(&[9; 32]), Err(MetasinoError::WrongState));
+        assert_eq!(roll(&[9; 32], 0), round.rolls[0]);
+    }
+}
@@ -0,0 +1,106 @@
+//! Cross-contract calls to game engines deployed as contracts of their own.
+//!
+//! A table opened for an engine keeps its escrow, seats and seed in Metasino
+//! while the rules of its game live in the engine contract, which can be
+//! deployed and upgraded independently. The engine is dealt the players and
+//! the seed once they are revealed, receives the actions of the players and
+//! finally resolves the share of the pot won by each seat. The calls are
+//! built by hand with the selectors of [`GameEngine`].
+
+use crate::metasino::{
+    MetasinoError,
+    Result,
+    TableId,
+};
+use ink_env::{
+    call::{
+        build_call,
+        Call,
+        ExecutionInput,
+        Selector,
+    },
+    DefaultEnvironment,
+    Environment,
+};
+use ink_prelude::vec::Vec;
+
+type AccountId = <DefaultEnvironment as Environment>::AccountId;
+type Balance = <DefaultEnvironment as Environment>::Balance;
+
+/// The selector of `GameEngine::start`.
+const START: [u8; 4] = ink_lang::selector_bytes!("GameEngine::start");
+/// The selector of `GameEngine::act`.
+const ACT: [u8; 4] = ink_lang::selector_bytes!("GameEngine::act");
+/// The selector of `GameEngine::resolve`.
+const RESOLVE: [u8; 4] = ink_lang::selector_bytes!("GameEngine::resolve");
+
+/// The messages a game engine contract implements for Metasino, which is
+/// the only caller the engine should accept.
+#[ink_lang::trait_definition]
+pub trait GameEngine {
+    /// Deal a new game of the table to the players, by seat, out of the seed.
+    #[ink(message)]
+    fn start(
+        &mut self,
+        table_id: TableId,
+        players: Vec<AccountId>,
+        pot: Balance,
+        seed: [u8; 32],
+    ) -> Result<()>;
+
+    /// Play the action, encoded as the engine defines it, for the player.
+    #[ink(message)]
+    fn act(&mut self, table_id: TableId, player: AccountId, action: Vec<u8>) -> Result<()>;
+
+    /// The share of the pot won by each seat once the game is over, adding
+    /// up to the whole pot.
+    #[ink(message)]
+    fn resolve(&mut self, table_id: TableId) -> Result<Vec<Balance>>;
+}
+
+/// Deal a new game of the table to the players on the engine.
+pub fn start(
+    engine: AccountId,
+    table_id: TableId,
+    players: Vec<AccountId>,
+    pot: Balance,
+    seed: [u8; 32],
+) -> Result<()> {
+    build_call::<DefaultEnvironment>()
+        .call_type(Call::new().callee(engine))
+        .exec_input(
+            ExecutionInput::new(Selector::new(START))
+                .push_arg(table_id)
+                .push_arg(players)
+                .push_arg(pot)
+                .push_arg(seed),
+        )
+        .returns::<Result<()>>()
+        .fire()
+        .map_err(|_| MetasinoError::EngineFailed)?
+}
+
+/// Forward the action of the player to the engine.
+pub fn act(engine: AccountId, table_id: TableId, player: AccountId, action: Vec<u8>) -> Result<()> {
+    build_call::<DefaultEnvironment>()
+        .call_type(Call::new().callee(engine))
+        .exec_input(
+            ExecutionInput::new(Selector::new(ACT))
+                .push_arg(table_id)
+                .push_arg(player)
+                .push_arg(action),
+        )
+        .returns::<Result<()>>()
+        .fire()
+        .map_err(|_| MetasinoError::EngineFailed)?
+}
+
+/// Ask the engine for the share of the pot won by each seat.
+pub fn resolve(engine: AccountId, table_id: TableId) -> Result<Vec<Balance>> {
+    build_call::<DefaultEnvironment>()
+        .call_type(Call::new().callee(engine))
+        .exec_input(ExecutionInput::new(Selector::new(RESOLVE)).push_arg(table_id))
+        .returns::<Result<Vec<Balance>>>()
+        .fire()
+        .map_err(|_| MetasinoError::EngineFailed)?
+}
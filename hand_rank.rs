@@ -0,0 +1,336 @@
+//! Poker hand evaluation.
+//!
+//! A hand of 5 to 7 cards is scored by its best 5-card combination. The score
+//! holds the category of the hand in its upper bits followed by five 4-bit
+//! ranks deciding between hands of the same category, so that a better hand
+//! always gets a higher score.
+//!
+//! Omaha hands are made of exactly two hole cards and three cards of the
+//! board. Short-deck hands rank a flush above a full house, which their
+//! scores encode by swapping the two categories, and play the ace low in
+//! the A-6-7-8-9 straight.
+
+use crate::cards::{
+    Card,
+    Variant,
+    ACE,
+};
+
+/// The categories of poker hands, from the weakest to the strongest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+pub enum HandCategory {
+    HighCard,
+    Pair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+/// The number of bits holding the tie-breaking ranks of a score.
+const KICKER_BITS: u32 = 20;
+
+/// Score the best 5-card hand out of the given 5 to 7 cards.
+/// Returns `None` for any other number of cards.
+pub fn score(cards: &[Card]) -> Option<u32> {
+    best_of(cards, Variant::Holdem)
+}
+
+/// Score the best hand of the variant out of the hole cards and the board.
+/// Returns `None` if the cards cannot make a hand of the variant.
+pub fn score_variant(variant: Variant, hole: &[Card], board: &[Card]) -> Option<u32> {
+    if variant != Variant::Omaha {
+        let mut cards = board.to_vec();
+        cards.extend_from_slice(hole);
+        return best_of(&cards, variant)
+    }
+    if hole.len() != variant.hole_cards() || board.len() < 3 {
+        return None
+    }
+    let mut best = None;
+    for (a, first) in hole.iter().enumerate() {
+        for second in &hole[a + 1..] {
+            for c in 0..board.len() {
+                for d in c + 1..board.len() {
+                    for e in d + 1..board.len() {
+                        let hand = [*first, *second, board[c], board[d], board[e]];
+                        best = best.max(Some(score_five(&hand, variant)));
+                    }
+                }
+            }
+        }
+    }
+    best
+}
+
+/// Score the best 5-card hand of the variant out of the given 5 to 7 cards.
+fn best_of(cards: &[Card], variant: Variant) -> Option<u32> {
+    let n = cards.len();
+    if !(5..=7).contains(&n) {
+        return None
+    }
+    let mut best = 0;
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                for d in c + 1..n {
+                    for e in d + 1..n {
+                        let hand = [cards[a], cards[b], cards[c], cards[d], cards[e]];
+                        best = best.max(score_five(&hand, variant));
+                    }
+                }
+            }
+        }
+    }
+    Some(best)
+}
+
+/// The category of the hand with the given score.
+pub fn category(score: u32) -> HandCategory {
+    match score >> KICKER_BITS {
+        0 => HandCategory::HighCard,
+        1 => HandCategory::Pair,
+        2 => HandCategory::TwoPair,
+        3 => HandCategory::ThreeOfAKind,
+        4 => HandCategory::Straight,
+        5 => HandCategory::Flush,
+        6 => HandCategory::FullHouse,
+        7 => HandCategory::FourOfAKind,
+        _ => HandCategory::StraightFlush,
+    }
+}
+
+/// Whether the hand with the given score is a royal flush, the straight
+/// flush to the ace.
+pub fn is_royal_flush(score: u32) -> bool {
+    category(score) == HandCategory::StraightFlush && (score >> 16) & 0xf == ACE as u32
+}
+
+/// Score exactly five cards with the rankings of the variant.
+fn score_five(hand: &[Card; 5], variant: Variant) -> u32 {
+    let mut ranks = [0u8; 5];
+    for (rank, card) in ranks.iter_mut().zip(hand.iter()) {
+        *rank = card.rank;
+    }
+    ranks.sort_unstable_by(|a, b| b.cmp(a));
+
+    let flush = hand.iter().all(|card| card.suit == hand[0].suit);
+    let distinct = ranks.windows(2).all(|pair| pair[0] != pair[1]);
+    let straight_high = if distinct && ranks[0] - ranks[4] == 4 {
+        Some(ranks[0])
+    } else if distinct && ranks[0] == ACE && ranks[1] - ranks[4] == 3 {
+        // The wheel, where the ace plays low right under the lowest rank.
+        let lowest = variant.lowest_rank();
+        (ranks[4] == lowest).then_some(lowest + 3)
+    } else {
+        None
+    };
+
+    // Group the ranks by how often they appear, most frequent and highest first.
+    let mut groups: [(u8, u8); 5] = [(0, 0); 5];
+    let mut len = 0;
+    for rank in ranks {
+        match groups[..len].iter_mut().find(|(_, r)| *r == rank) {
+            Some(group) => group.0 += 1,
+            None => {
+                groups[len] = (1, rank);
+                len += 1;
+            }
+        }
+    }
+    let groups = &mut groups[..len];
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+
+    let category = match (straight_high, flush, groups[0].0, groups.get(1).map(|g| g.0)) {
+        (Some(_), true, _, _) => HandCategory::StraightFlush,
+        (_, _, 4, _) => HandCategory::FourOfAKind,
+        (_, _, 3, Some(2)) => HandCategory::FullHouse,
+        (_, true, _, _) => HandCategory::Flush,
+        (Some(_), _, _, _) => HandCategory::Straight,
+        (_, _, 3, _) => HandCategory::ThreeOfAKind,
+        (_, _, 2, Some(2)) => HandCategory::TwoPair,
+        (_, _, 2, _) => HandCategory::Pair,
+        _ => HandCategory::HighCard,
+    };
+    let category = match (variant, category) {
+        (Variant::ShortDeck, HandCategory::Flush) => HandCategory::FullHouse,
+        (Variant::ShortDeck, HandCategory::FullHouse) => HandCategory::Flush,
+        (_, category) => category,
+    };
+
+    let mut kickers = 0u32;
+    match straight_high {
+        Some(high) => kickers = (high as u32) << 16,
+        None => {
+            for (i, (_, rank)) in groups.iter().enumerate() {
+                kickers |= (*rank as u32) << (16 - 4 * i as u32);
+            }
+        }
+    }
+    ((category as u32) << KICKER_BITS) | kickers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::Suit::{
+        self,
+        *,
+    };
+
+    /// Parses a hand written as space separated cards such as `"As Td 9c"`.
+    fn hand(cards: &str) -> Vec<Card> {
+        cards
+            .split_whitespace()
+            .map(|card| {
+                let (rank, suit) = card.split_at(1);
+                let rank = match rank {
+                    "A" => 14,
+                    "K" => 13,
+                    "Q" => 12,
+                    "J" => 11,
+                    "T" => 10,
+                    digit => digit.parse().unwrap(),
+                };
+                let suit = match suit {
+                    "c" => Clubs,
+                    "d" => Diamonds,
+                    "h" => Hearts,
+                    _ => Spades,
+                };
+                Card { rank, suit }
+            })
+            .collect()
+    }
+
+    fn score_of(cards: &str) -> u32 {
+        score(&hand(cards)).unwrap()
+    }
+
+    #[test]
+    fn categories_are_recognised() {
+        let cases = [
+            ("As Kd 9c 7h 3s", HandCategory::HighCard),
+            ("As Ad 9c 7h 3s", HandCategory::Pair),
+            ("As Ad 9c 9h 3s", HandCategory::TwoPair),
+            ("As Ad Ac 9h 3s", HandCategory::ThreeOfAKind),
+            ("9s Td Jc Qh Ks", HandCategory::Straight),
+            ("As 2d 3c 4h 5s", HandCategory::Straight),
+            ("As Ts 9s 7s 3s", HandCategory::Flush),
+            ("As Ad Ac 9h 9s", HandCategory::FullHouse),
+            ("As Ad Ac Ah 9s", HandCategory::FourOfAKind),
+            ("9s Ts Js Qs Ks", HandCategory::StraightFlush),
+            ("As 2s 3s 4s 5s", HandCategory::StraightFlush),
+        ];
+        for (cards, expected) in cases {
+            assert_eq!(category(score_of(cards)), expected, "{}", cards);
+        }
+    }
+
+    #[test]
+    fn royal_flushes_are_straight_flushes_to_the_ace() {
+        assert!(is_royal_flush(score_of("As Ks Qs Js Ts 2d 3c")));
+        assert!(!is_royal_flush(score_of("9s Ts Js Qs Ks")));
+        assert!(!is_royal_flush(score_of("As Kd Qs Js Ts")));
+    }
+
+    #[test]
+    fn categories_are_ordered() {
+        let ordered = [
+            "As Kd 9c 7h 3s",
+            "2s 2d 3c 4h 6s",
+            "2s 2d 3c 3h 4s",
+            "2s 2d 2c 3h 4s",
+            "As 2d 3c 4h 5s",
+            "2s 3s 4s 5s 7s",
+            "2s 2d 2c 3h 3s",
+            "2s 2d 2c 2h 3s",
+            "As 2s 3s 4s 5s",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(score_of(pair[0]) < score_of(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn ties_are_broken_by_kickers() {
+        assert!(score_of("As Ad Kc 7h 3s") > score_of("Ah Ac Qc Jh Ts"));
+        assert!(score_of("Ks Kd 5c 5h 3s") > score_of("Kh Kc 5s 5d 2s"));
+        assert!(score_of("Qs Qd Qc 2h 2s") > score_of("Js Jd Jc Ah As"));
+        assert!(score_of("6s 2d 3c 4h 5s") > score_of("As 2d 3c 4h 5s"));
+        assert!(score_of("Ts Jd Qc Kh As") > score_of("9s Td Jc Qh Ks"));
+        assert_eq!(score_of("As Kd 9c 7h 3s"), score_of("Ad Kc 9h 7s 3c"));
+    }
+
+    #[test]
+    fn best_five_of_seven_cards_is_scored() {
+        assert_eq!(score_of("As Ks 2d 3c Qs Js Ts"), score_of("As Ks Qs Js Ts"));
+        assert_eq!(score_of("2s 2d 2c 9h 9s 9d 4c"), score_of("9h 9s 9d 2s 2d"));
+        assert_eq!(score_of("As Kd 8c 7h 3s 2d 4c"), score_of("As Kd 8c 7h 4c"));
+        assert_eq!(score_of("As 2d 3c 4h 5s 6d"), score_of("2d 3c 4h 5s 6d"));
+    }
+
+    #[test]
+    fn wrong_number_of_cards_is_not_scored() {
+        assert_eq!(score(&hand("As Kd 9c 7h")), None);
+        assert_eq!(score(&hand("As Kd 9c 7h 3s 2d 4c 5h")), None);
+    }
+
+    #[test]
+    fn omaha_plays_exactly_two_hole_cards() {
+        let board = hand("As Ks Qs 7d 2c");
+        // Four spades in hand still make no flush without three on the board.
+        assert_eq!(
+            score_variant(Variant::Omaha, &hand("Js Ts 3s 4s"), &board),
+            Some(score_of("As Ks Qs Js Ts"))
+        );
+        assert_eq!(
+            score_variant(Variant::Omaha, &hand("Ad Ac Ah 3d"), &board),
+            Some(score_of("Ad Ac As Ks Qs"))
+        );
+        assert_eq!(score_variant(Variant::Omaha, &hand("Ad Ac"), &board), None);
+        assert_eq!(
+            score_variant(Variant::Holdem, &hand("Ad Ac"), &board),
+            Some(score_of("Ad Ac As Ks Qs"))
+        );
+    }
+
+    #[test]
+    fn short_deck_ranks_a_flush_over_a_full_house() {
+        let short = |cards: &str| score_variant(Variant::ShortDeck, &hand(cards), &[]).unwrap();
+        assert!(short("As Ts 9s 7s 6s") > short("As Ad Ac 9h 9s"));
+        assert!(short("As Ad Ac 9h 9s") > short("6s 7d 8c 9h Ts"));
+        assert!(short("As 6d 7c 8h 9s") > short("As Ad Ac Kh Qs"));
+        assert!(short("6s 7d 8c 9h Ts") > short("As 6d 7c 8h 9s"));
+        assert!(score_of("As 6d 7c 8h 9s") < score_of("2s 2d 3c 4h 6s"));
+    }
+
+    #[test]
+    fn every_five_card_hand_is_categorised() {
+        let suits = [Clubs, Diamonds, Hearts, Spades];
+        let deck: Vec<Card> = (2..=ACE)
+            .flat_map(|rank| suits.iter().map(move |suit: &Suit| Card { rank, suit: *suit }))
+            .collect();
+        let mut counts = [0u32; 9];
+        for (a, first) in deck.iter().enumerate() {
+            for (b, second) in deck.iter().enumerate().skip(a + 1) {
+                for (c, third) in deck.iter().enumerate().skip(b + 1) {
+                    for (d, fourth) in deck.iter().enumerate().skip(c + 1) {
+                        for fifth in deck.iter().skip(d + 1) {
+                            let hand = [*first, *second, *third, *fourth, *fifth];
+                            counts[category(score_five(&hand, Variant::Holdem)) as usize] += 1;
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            counts,
+            [1_302_540, 1_098_240, 123_552, 54_912, 10_200, 5_108, 3_744, 624, 40]
+        );
+    }
+}
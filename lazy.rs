@@ -0,0 +1,127 @@
+//! A value of the root storage kept in a cell of its own and only loaded when
+//! read.
+//!
+//! Every message of the contract loads the fields of its root storage, and a
+//! message taking `&mut self` stores them all back, a struct field taking a
+//! cell per field of its own. A `Lazy` field takes a single cell whichever
+//! its value, loaded by [`Lazy::get`] and stored by [`Lazy::set`] alone, so
+//! that the messages never reading it do not pay for it. It plays the part
+//! of the `Lazy` of the later versions of ink!, which ink! 3 does not have,
+//! and is laid out as a `Mapping` is.
+
+use core::marker::PhantomData;
+use ink_primitives::Key;
+use ink_storage::traits::{
+    push_packed_root,
+    ExtKeyPtr,
+    KeyPtr,
+    PackedLayout,
+    SpreadAllocate,
+    SpreadLayout,
+};
+
+/// A value kept in its own storage cell, loaded on demand.
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct Lazy<T> {
+    key: Key,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for Lazy<T> {
+    fn default() -> Self {
+        Self::new(Key::default())
+    }
+}
+
+impl<T> core::fmt::Debug for Lazy<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Lazy").field("key", &self.key).finish()
+    }
+}
+
+impl<T> Lazy<T> {
+    fn new(key: Key) -> Self {
+        Self {
+            key,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: PackedLayout> Lazy<T> {
+    /// Load the value, `None` if it was never set.
+    /// Panics if the cell holds something else, as a `Mapping` does.
+    pub fn get(&self) -> Option<T> {
+        ink_env::get_contract_storage::<T>(&self.key)
+            .unwrap_or_else(|error| panic!("failed to load lazy value: {:?}", error))
+            .map(|mut value| {
+                value.pull_packed(&self.key);
+                value
+            })
+    }
+
+    /// Store the value.
+    pub fn set(&mut self, value: &T) {
+        push_packed_root(value, &self.key);
+    }
+}
+
+impl<T> SpreadLayout for Lazy<T> {
+    const FOOTPRINT: u64 = 1;
+    const REQUIRES_DEEP_CLEAN_UP: bool = false;
+
+    fn pull_spread(ptr: &mut KeyPtr) -> Self {
+        Self::new(*ExtKeyPtr::next_for::<Self>(ptr))
+    }
+
+    fn push_spread(&self, ptr: &mut KeyPtr) {
+        ptr.advance_by(Self::FOOTPRINT);
+    }
+
+    fn clear_spread(&self, ptr: &mut KeyPtr) {
+        ptr.advance_by(Self::FOOTPRINT);
+    }
+}
+
+impl<T> SpreadAllocate for Lazy<T> {
+    fn allocate_spread(ptr: &mut KeyPtr) -> Self {
+        Self::new(*ExtKeyPtr::next_for::<Self>(ptr))
+    }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+    use ink_metadata::layout::{
+        CellLayout,
+        Layout,
+        LayoutKey,
+    };
+    use ink_storage::traits::StorageLayout;
+
+    impl<T> StorageLayout for Lazy<T>
+    where
+        T: scale_info::TypeInfo + 'static,
+    {
+        fn layout(key_ptr: &mut KeyPtr) -> Layout {
+            Layout::Cell(CellLayout::new::<T>(LayoutKey::from(key_ptr.advance_by(1))))
+        }
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_loaded_from_their_own_cell() {
+        ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+            let mut first: Lazy<u32> = Lazy::new(Key::from([1; 32]));
+            let second: Lazy<u32> = Lazy::new(Key::from([2; 32]));
+            assert_eq!(first.get(), None);
+            first.set(&7);
+            assert_eq!((first.get(), second.get()), (Some(7), None));
+            Ok(())
+        })
+        .unwrap()
+    }
+}
@@ -2,307 +2,13325 @@
 
 use ink_lang as ink;
 
-#[ink::contract]
+mod achievements;
+mod baccarat;
+mod betting;
+mod blackjack;
+mod cards;
+#[cfg(feature = "chain-randomness")]
+mod chain_randomness;
+mod dice;
+pub mod engine;
+mod hand_rank;
+mod lazy;
+mod lottery;
+mod migration;
+mod parimutuel;
+mod psp22;
+mod psp34;
+mod roulette;
+mod showdown;
+mod stud;
+#[cfg(feature = "test-seed")]
+pub mod test_seed;
+mod tournament;
+pub mod yield_strategy;
+
+#[cfg_attr(
+    feature = "chain-randomness",
+    ink::contract(env = crate::chain_randomness::RandomnessEnvironment)
+)]
+#[cfg_attr(not(feature = "chain-randomness"), ink::contract)]
+// The arguments of the table constructor make its decoded call outweigh the others.
+#[allow(clippy::large_enum_variant)]
 mod metasino {
 
-    use ink_prelude::vec::Vec;
-    use ink_storage::traits::{
-        PackedLayout,
-        SpreadLayout,
-    };
+    use crate::achievements::{
+        Achievement,
+        Progress,
+    };
+    use crate::baccarat::{
+        self,
+        BaccaratBet,
+        BaccaratWager,
+        Coup,
+    };
+    use crate::betting::{
+        self,
+        Action,
+        BettingRound,
+        BettingStructure,
+        Play,
+        SidePot,
+        Street,
+    };
+    use crate::blackjack::{
+        self,
+        BlackjackRound,
+        Move,
+    };
+    use crate::cards::{
+        self,
+        Card,
+        Variant,
+    };
+    use crate::dice::{
+        DiceBet,
+        DiceRound,
+    };
+    use crate::engine;
+    use crate::hand_rank::{
+        self,
+        HandCategory,
+    };
+    use crate::lazy::Lazy;
+    use crate::lottery::Lottery;
+    use crate::migration::v1;
+    use crate::parimutuel::Market;
+    use crate::psp22;
+    use crate::psp34;
+    use crate::roulette::{
+        self,
+        RouletteBet,
+        Wager,
+    };
+    use crate::showdown;
+    use crate::stud;
+    use crate::tournament::{
+        self,
+        Chips,
+        Tournament,
+    };
+    use crate::yield_strategy;
+    use ink_lang::ToAccountId;
+    use ink_env::hash::{
+        Blake2x256,
+        HashOutput,
+    };
+    use ink_prelude::{
+        string::{
+            String,
+            ToString,
+        },
+        vec::Vec,
+    };
+    use ink_storage::{
+        traits::{
+            PackedLayout,
+            SpreadAllocate,
+            SpreadLayout,
+        },
+        Mapping,
+    };
+
+    /// The maximum players any table may be configured to seat.
+    const MAX_PLAYERS: u8 = 10;
+    /// The minimum players any table may be configured to start the game with.
+    const MIN_PLAYERS: u8 = 2;
+    /// The number of blocks players have to reveal their seed once the game started.
+    const REVEAL_PERIOD: BlockNumber = 10;
+    /// The number of blocks a player has to act once it is their turn.
+    const TURN_PERIOD: BlockNumber = 10;
+    /// The number of blocks the dealer of a table has to deal, by default.
+    const DEALER_TIMEOUT: BlockNumber = 100;
+    /// The number of blocks the seats have to insure their blackjack hand.
+    const INSURANCE_PERIOD: BlockNumber = 3;
+    /// The basis points making up a whole pot.
+    const MAX_BPS: u16 = 10_000;
+    /// The most games returned by a single history query.
+    const MAX_RECENT_GAMES: u32 = 50;
+    /// The number of the last games whose record is kept, older ones being
+    /// deleted as new games end.
+    const MAX_GAME_HISTORY: GameId = 10_000;
+    /// The most game records deleted by a single pruning.
+    const MAX_PRUNED_GAMES: GameId = 100;
+    /// The most tables returned by a single lobby query.
+    const MAX_TABLE_SUMMARIES: u32 = 50;
+    /// The reputation a player loses for timing out in a game.
+    const TIMEOUT_PENALTY: i32 = 2;
+    /// The reputation a player loses for failing to reveal their seed.
+    const REVEAL_PENALTY: i32 = 5;
+    /// The reputation a player loses for raising a dispute the ruling rejects.
+    const DISPUTE_PENALTY: i32 = 5;
+    /// The most spectators watching a single table.
+    const MAX_SPECTATORS: usize = 100;
+    /// The most accounts waiting for a seat at a single table.
+    const MAX_WAITLIST: usize = 50;
+    /// The number of players ranked on the leaderboard.
+    const LEADERBOARD_SIZE: usize = 10;
+    /// The number of blocks of an epoch of freerolls, about a day.
+    const FREEROLL_EPOCH: BlockNumber = 14_400;
+    /// The most freerolls an account may register for in an epoch.
+    const FREEROLLS_PER_EPOCH: u32 = 1;
+    /// The number of blocks over which the withdrawals of an account are
+    /// capped, about a day.
+    const WITHDRAWAL_EPOCH: BlockNumber = 14_400;
+    /// The version of the storage layout expected by this code.
+    const STORAGE_VERSION: u16 = 2;
+    /// The semantic version of this code.
+    const VERSION: &str = env!("CARGO_PKG_VERSION");
+    /// The games the tables of this code may host.
+    const GAMES: [&str; 7] =
+        ["poker", "blackjack", "roulette", "dice", "baccarat", "engine", "oracle"];
+
+    /// Errors that can occur upon calling this contract.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum MetasinoError {
+        /// The table already seats the maximum number of players.
+        TableFull,
+        /// The bet placed does not match the required start bet.
+        WrongBet,
+        /// The caller is already registered in the table.
+        AlreadyRegistered,
+        /// The message cannot be executed in the current state of the table.
+        WrongState,
+        /// The caller is not allowed to execute the message.
+        NotAuthorized,
+        /// Not enough players are registered to start the game.
+        NotEnoughPlayers,
+        /// The winners and shares given for the payout are inconsistent.
+        InvalidPayout,
+        /// Transferring funds out of the contract failed.
+        TransferFailed,
+        /// No table exists with the given id.
+        TableNotFound,
+        /// The players limits given for the table are out of bounds.
+        InvalidTableConfig,
+        /// A player has not committed to a seed before the game start.
+        MissingCommitment,
+        /// The revealed seed does not match the player's commitment.
+        InvalidReveal,
+        /// The player has already revealed their seed.
+        AlreadyRevealed,
+        /// The deadline of the current phase has passed.
+        DeadlinePassed,
+        /// The deadline of the current phase has not been reached yet.
+        DeadlineNotReached,
+        /// The rake exceeds the whole pot.
+        InvalidRake,
+        /// It is not the turn of the caller to act.
+        NotYourTurn,
+        /// The action is not allowed at this point of the betting round.
+        InvalidAction,
+        /// The contract is paused by its owner.
+        Paused,
+        /// Replacing the code of the contract failed.
+        UpgradeFailed,
+        /// No duel exists with the given id.
+        DuelNotFound,
+        /// No lottery exists with the given id.
+        LotteryNotFound,
+        /// No tournament exists with the given id.
+        TournamentNotFound,
+        /// The chips of the player do not cover the amount.
+        InsufficientChips,
+        /// The join code given does not match the one of the table.
+        InvalidJoinCode,
+        /// The account is banned from the table or the whole contract.
+        Banned,
+        /// An amount overflowed or went below zero.
+        ArithmeticError,
+        /// The account already hosts as many tables as it may.
+        TooManyTables,
+        /// Calling the game engine of the table failed.
+        EngineFailed,
+        /// Instantiating the contract of a table failed.
+        InstantiationFailed,
+        /// Reading the randomness of the chain failed.
+        RandomnessUnavailable,
+        /// The confirmations required exceed the result signers.
+        InvalidThreshold,
+        /// The reputation of the account is below the minimum of the table.
+        ReputationTooLow,
+        /// No game with the given id has ended.
+        GameNotFound,
+        /// No market exists with the given id.
+        MarketNotFound,
+        /// The account registered for every freeroll it may in the epoch.
+        FreerollLimitReached,
+        /// The account opened a table too recently to open another one.
+        CooldownActive,
+        /// The account locked too small a stake to open a public table.
+        StakeTooLow,
+        /// The signature does not recover to any account.
+        InvalidSignature,
+        /// The nonce is not the next one of the account.
+        InvalidNonce,
+        /// Calling the yield strategy failed.
+        StrategyFailed,
+        /// The treasury would be left below the buffer kept liquid for payouts.
+        YieldBufferBreached,
+        /// The withdrawals of the block or of the account reached their cap.
+        WithdrawalThrottled,
+    }
+
+    /// The result type returned by the messages of the contract.
+    pub type Result<T> = core::result::Result<T, MetasinoError>;
+
+    /// Identifier of a table hosted by the contract.
+    pub type TableId = u32;
+
+    /// Identifier of a game played on any table of the contract.
+    pub type GameId = u64;
+
+    /// Identifier of a coin-flip duel.
+    pub type DuelId = u32;
+
+    /// Identifier of a lottery.
+    pub type LotteryId = u32;
+
+    /// Identifier of a parimutuel market.
+    pub type MarketId = u32;
+
+    /// Identifier of a tournament.
+    pub type TournamentId = u32;
+
+    /// Number of a season of the leaderboards, the first one being 0.
+    pub type Season = u32;
+
+    #[derive(
+        Debug,
+        Copy,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+    )]
+    #[allow(clippy::upper_case_acronyms)]
+    pub enum STATE {
+        STAGING,
+        PLAYING,
+        ENDED,
+        /// Between two hands of a session, the players playing on chips
+        /// staying seated for the next hand.
+        INTERMISSION,
+    }
+
+    /// The game played on a table.
+    #[derive(
+        Debug,
+        Copy,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+    )]
+    pub enum GameKind {
+        /// Poker, the pot going to the winners declared by the initializer.
+        Poker,
+        /// Blackjack against the dealer of the house, settled by the contract.
+        Blackjack,
+        /// Roulette against the house, the start bets being refunded along
+        /// with the payouts of the bets placed before the spin.
+        Roulette,
+        /// Craps-style dice against the house, each player betting their start
+        /// bet on pass or don't-pass.
+        Dice,
+        /// A game whose rules live in the engine contract at the given
+        /// address, Metasino keeping the escrow and settling the pot as the
+        /// engine resolves it.
+        Engine(AccountId),
+        /// A game played off-chain, the pot going to the winners once enough
+        /// result signers confirm them.
+        Oracle,
+        /// Baccarat against the house, the start bets being refunded along
+        /// with the payouts of the bets placed on the player, the banker or a
+        /// tie before the deal.
+        Baccarat,
+    }
+
+    /// A single game table hosted by the contract.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+    )]
+    pub struct Table {
+        /// Account which opened the table.
+        pub initializer: AccountId,
+        /// The number of players seated at the table, holding the seats `0..seated`.
+        pub seated: u8,
+        /// Start betting value,
+        pub required_start_bet: Balance,
+        /// Accumulated value in the pot.
+        pub pot: Balance,
+        /// The current state of the game.
+        pub state: STATE,
+        /// The minimum players required to start the game.
+        pub min_players: u8,
+        /// The maximum players allowed in the game participation.
+        pub max_players: u8,
+        /// The last block in which players may reveal their seed.
+        pub reveal_deadline: BlockNumber,
+        /// The number of players who revealed their seed so far.
+        pub revealed: u8,
+        /// The XOR of all the seeds revealed so far.
+        pub entropy: [u8; 32],
+        /// The shuffle seed, once derived from the reveals of all players.
+        pub seed: Option<[u8; 32]>,
+        /// The share of each pot, in basis points, taken by the house.
+        pub rake_bps: u16,
+        /// The most the house takes from the pots of a game.
+        pub rake_cap: Balance,
+        /// The account the initializer has the rake paid to, less the cut of
+        /// the protocol, the house keeping all of it if `None`.
+        pub rake_recipient: Option<AccountId>,
+        /// The PSP22 token the table is played in, the native balance if `None`.
+        pub token: Option<AccountId>,
+        /// The game played on the table.
+        pub game: GameKind,
+        /// The most rebuys each player may make before the game starts.
+        pub max_rebuys: u8,
+        /// The number of blocks after registering a player may rebuy for.
+        pub rebuy_window: BlockNumber,
+        /// The position of the dealer button, moving on by one seat every hand.
+        pub button: u8,
+        /// The small blind posted at the start of each poker hand.
+        pub small_blind: Balance,
+        /// The big blind posted at the start of each poker hand, none if 0.
+        pub big_blind: Balance,
+        /// The ante every seat puts in at the start of each poker hand, none if 0.
+        pub ante: Balance,
+        /// The player straddling the next poker hand, if any.
+        pub straddler: Option<AccountId>,
+        /// The number of hands played on the table so far.
+        pub hand_number: u32,
+        /// The poker variant dealt on the table.
+        pub variant: Variant,
+        /// How much the raises of the poker hands are limited to.
+        pub structure: BettingStructure,
+        /// Whether only the accounts invited by the initializer may register.
+        pub is_private: bool,
+        /// The hash of the code players have to give to register, if any.
+        pub join_code_hash: Option<Hash>,
+        /// The block from which anyone may start the game, if scheduled.
+        pub start_at: Option<BlockNumber>,
+        /// Whether the game starts by itself once the table is full.
+        pub sit_and_go: bool,
+        /// Whether the pot is a prize of the treasury, the players registering
+        /// for free.
+        pub freeroll: bool,
+        /// The block in which the table was last staged.
+        pub staged_at: BlockNumber,
+        /// The account, typically a contract, ruling on the disputes raised
+        /// over the games of the table instead of the operators, if any.
+        pub arbiter: Option<AccountId>,
+        /// The least reputation an account needs to register, if any.
+        pub min_reputation: Option<i32>,
+        /// The bond posted by the initializer, held until the table closes.
+        pub bond: Balance,
+        /// The account nominated by the initializer to deal the table, until
+        /// it posts its dealer bond.
+        pub dealer_nominee: Option<AccountId>,
+        /// The account dealing the table in place of its players, if any.
+        pub dealer: Option<AccountId>,
+        /// The bond posted by the dealer, forfeited if the dealer stalls.
+        pub dealer_bond: Balance,
+        /// The last block in which the dealer may make the deal due on the
+        /// table before being replaced, if a deal is due.
+        pub dealer_deadline: Option<BlockNumber>,
+    }
+
+    impl Table {
+        /// Get the current number of players in the table.
+        pub fn players_count(&self) -> u8 {
+            self.seated
+        }
+
+        /// check if the table is fully occupied.
+        pub fn is_full(&self) -> bool {
+            self.players_count() >= self.max_players
+        }
+
+        /// Guarding the table from being played in a wrong state.
+        #[inline]
+        fn status_guard(&self) -> Result<()> {
+            if self.state != STATE::STAGING {
+                return Err(MetasinoError::WrongState);
+            }
+            Ok(())
+        }
+
+        /// Guarding the registration from being executed in a wrong state.
+        /// A table whose game has ended is open for the next game.
+        #[inline]
+        fn registration_guard(&self) -> Result<()> {
+            if self.state == STATE::ENDED || self.state == STATE::INTERMISSION {
+                return Ok(());
+            }
+            self.status_guard()
+        }
+
+        /// Reset the table once its game is over and its players are unseated.
+        fn reset(&mut self) {
+            self.pot = 0;
+            self.revealed = 0;
+            self.entropy = [0; 32];
+            self.seed = None;
+            self.straddler = None;
+        }
+
+        /// The deck of the variant of the table, shuffled by its seed.
+        /// error if the seed is not derived yet.
+        fn deck(&self) -> Result<Vec<Card>> {
+            let seed = self.seed.ok_or(MetasinoError::WrongState)?;
+            Ok(cards::variant_deck(&seed, self.variant))
+        }
+
+        /// Take the rake of the house out of each pot, up to the rake cap.
+        /// Returns the total rake taken.
+        fn rake_pots(&self, pots: &mut [SidePot]) -> Result<Balance> {
+            let mut rake: Balance = 0;
+            for pot in pots.iter_mut() {
+                let pot_rake =
+                    arithmetic::mul_div(pot.amount, self.rake_bps as Balance, MAX_BPS as Balance)?
+                        .min(arithmetic::sub(self.rake_cap, rake)?);
+                pot.amount = arithmetic::sub(pot.amount, pot_rake)?;
+                rake = arithmetic::add(rake, pot_rake)?;
+            }
+            Ok(rake)
+        }
+
+        /// Split the pots between the winners, given by their seats, according to
+        /// their shares in percent.
+        /// Each pot goes to the winners eligible to it in proportion to their
+        /// shares. A winner with a zero share only takes part in the pots none
+        /// of the winners with a share is eligible to. The remainder left by
+        /// the integer division goes to the first winner of the pot.
+        fn split_pots(
+            &self,
+            pots: &[SidePot],
+            seats: &[u8],
+            shares: &[u8],
+        ) -> Result<Vec<Balance>> {
+            if seats.is_empty() || seats.len() != shares.len() {
+                return Err(MetasinoError::InvalidPayout);
+            }
+            if shares.iter().map(|share| *share as u32).sum::<u32>() != 100 {
+                return Err(MetasinoError::InvalidPayout);
+            }
+            for (i, seat) in seats.iter().enumerate() {
+                if *seat >= self.seated || seats[..i].contains(seat) {
+                    return Err(MetasinoError::InvalidPayout);
+                }
+            }
+
+            let mut payouts: Vec<Balance> = ink_prelude::vec![0; seats.len()];
+            for pot in pots {
+                let eligible: Vec<usize> = (0..seats.len())
+                    .filter(|i| pot.eligible.contains(&seats[*i]))
+                    .collect();
+                let weighted: Vec<usize> = eligible
+                    .iter()
+                    .copied()
+                    .filter(|i| shares[*i] > 0)
+                    .collect();
+                let (takers, weights): (&[usize], Vec<Balance>) = if weighted.is_empty() {
+                    (&eligible, eligible.iter().map(|_| 1).collect())
+                } else {
+                    (&weighted, weighted.iter().map(|i| shares[*i] as Balance).collect())
+                };
+                let first = *takers.first().ok_or(MetasinoError::InvalidPayout)?;
+                let total = arithmetic::sum(&weights)?;
+                let mut paid = 0;
+                for (i, weight) in takers.iter().zip(weights.iter()) {
+                    let payout = arithmetic::mul_div(pot.amount, *weight, total)?;
+                    payouts[*i] = arithmetic::add(payouts[*i], payout)?;
+                    paid = arithmetic::add(paid, payout)?;
+                }
+                let remainder = arithmetic::sub(pot.amount, paid)?;
+                payouts[first] = arithmetic::add(payouts[first], remainder)?;
+            }
+            if seats
+                .iter()
+                .any(|seat| !pots.iter().any(|pot| pot.eligible.contains(seat)))
+            {
+                return Err(MetasinoError::InvalidPayout);
+            }
+            Ok(payouts)
+        }
+    }
+
+    /// A player seated at a table.
+    #[derive(
+        Debug,
+        Copy,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+    )]
+    pub struct PlayerInfo {
+        /// The seat of the player, which is their position in the betting.
+        pub seat: u8,
+        /// Whether the player revealed their seed.
+        pub revealed: bool,
+        /// The block the player registered in.
+        pub registered_at: BlockNumber,
+        /// The number of rebuys the player made.
+        pub rebuys: u8,
+        /// The hand of the table the start bet of the player is in.
+        pub hand: u32,
+        /// Whether the player timed out or failed to reveal in the hand.
+        pub penalized: bool,
+    }
+
+    /// What loyalty points are redeemed for.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum Redemption {
+        /// A credit of the treasury worth the given points, to be withdrawn.
+        Rakeback(u128),
+        /// An entry into the tournament, its buy-in paid by the treasury.
+        TournamentTicket(TournamentId),
+    }
+
+    /// The lifetime statistics of a player over every table.
+    #[derive(
+        Debug,
+        Default,
+        Copy,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+    )]
+    pub struct PlayerStats {
+        /// The number of games the player took a seat in.
+        pub games_played: u32,
+        /// The number of games the player was paid out in.
+        pub games_won: u32,
+        /// The start bets and bets put in by the player.
+        pub total_wagered: Balance,
+        /// The payouts received by the player.
+        pub total_won: Balance,
+        /// Raised by every game the player completes cleanly and lowered when
+        /// they time out, fail to reveal their seed or lose a dispute.
+        pub reputation: i32,
+    }
+
+    /// Everything a frontend needs to display a table, in a single query.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub struct TableInfo {
+        /// The id of the table.
+        pub table_id: TableId,
+        /// The current state of the game.
+        pub state: STATE,
+        /// The players by seat order.
+        pub players: Vec<AccountId>,
+        /// Accumulated value in the pot.
+        pub pot: Balance,
+        /// Start betting value.
+        pub required_start_bet: Balance,
+        /// Account which currently owns the table.
+        pub initializer: AccountId,
+        /// The minimum players required to start the game.
+        pub min_players: u8,
+        /// The maximum players allowed in the game participation.
+        pub max_players: u8,
+        /// The PSP22 token the table is played in, the native balance if `None`.
+        pub token: Option<AccountId>,
+        /// The game played on the table.
+        pub game: GameKind,
+        /// The share of each pot, in basis points, taken by the house.
+        pub rake_bps: u16,
+        /// The most the house takes from the pots of a game.
+        pub rake_cap: Balance,
+        /// The account the rake is paid to, less the cut of the protocol.
+        pub rake_recipient: Option<AccountId>,
+        /// The seat of the dealer button in the next hand.
+        pub button: u8,
+        /// The small blind posted at the start of each poker hand.
+        pub small_blind: Balance,
+        /// The big blind posted at the start of each poker hand.
+        pub big_blind: Balance,
+        /// The ante every seat puts in at the start of each poker hand.
+        pub ante: Balance,
+        /// The poker variant dealt on the table.
+        pub variant: Variant,
+        /// How much the raises of the poker hands are limited to.
+        pub structure: BettingStructure,
+        /// The number of hands played on the table so far.
+        pub hand_number: u32,
+        /// The block from which anyone may start the game, if scheduled.
+        pub start_at: Option<BlockNumber>,
+        /// Whether the game starts by itself once the table is full.
+        pub sit_and_go: bool,
+        /// Whether the pot is a prize of the treasury, the players registering
+        /// for free.
+        pub freeroll: bool,
+        /// The account dealing the table, if any.
+        pub dealer: Option<AccountId>,
+    }
+
+    /// An entry of the history of a poker hand logged for indexers.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum LoggedAction {
+        /// The seat is dealt into the hand with its start bet.
+        Deal,
+        /// The seat puts in its ante.
+        Ante,
+        /// The seat posts its blind.
+        Blind,
+        /// The seat left of the big blind posts its straddle.
+        Straddle,
+        /// The seat showing the lowest up card of a stud hand brings it in.
+        BringIn,
+        /// The seat plays a betting action.
+        Bet(Action),
+        /// The seat shows its hole cards down.
+        Show(Vec<Card>),
+    }
+
+    /// An action a player takes at a table, played through [`Metasino::play`].
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum GameAction {
+        /// Bet the amount on the spin of the roulette table.
+        Bet(RouletteBet, Balance),
+        /// Bet the amount on the coup of the baccarat table.
+        BaccaratBet(BaccaratBet, Balance),
+        /// Choose the side the start bet is on at the dice table.
+        DiceBet(DiceBet),
+        /// Reveal the seed committed to for the shuffle of the hand.
+        RevealSeed([u8; 32]),
+        /// Pass the poker turn without betting.
+        Check,
+        /// Match the highest bet of the poker street.
+        Call,
+        /// Raise the highest bet of the poker street by the amount.
+        Raise(Balance),
+        /// Give up the poker hand.
+        Fold,
+        /// Put the value transferred in the poker pot, or every chip of the
+        /// player if no value is.
+        AllIn,
+        /// Show down the hole cards at the deck positions, with the salt.
+        RevealHand(Vec<u8>, [u8; 32]),
+        /// Draw a card of the blackjack hand.
+        Hit,
+        /// Keep the blackjack hand as it is.
+        Stand,
+        /// Double the bet of the blackjack hand for a single last card.
+        DoubleDown,
+        /// Insure the blackjack hand for the amount.
+        Insure(Balance),
+        /// Roll the dice of the dice table.
+        RollDice,
+        /// Play the action encoded for the game engine.
+        Engine(Vec<u8>),
+    }
+
+    impl GameAction {
+        /// Whether the action is one of the game.
+        pub fn fits(&self, game: GameKind) -> bool {
+            match self {
+                GameAction::Bet(..) => game == GameKind::Roulette,
+                GameAction::BaccaratBet(..) => game == GameKind::Baccarat,
+                GameAction::DiceBet(_) => game == GameKind::Dice,
+                GameAction::RevealSeed(_) => true,
+                GameAction::Check
+                | GameAction::Call
+                | GameAction::Raise(_)
+                | GameAction::Fold
+                | GameAction::AllIn
+                | GameAction::RevealHand(..) => game == GameKind::Poker,
+                GameAction::Hit
+                | GameAction::Stand
+                | GameAction::DoubleDown
+                | GameAction::Insure(_) => game == GameKind::Blackjack,
+                GameAction::RollDice => game == GameKind::Dice,
+                GameAction::Engine(_) => matches!(game, GameKind::Engine(_)),
+            }
+        }
+    }
+
+    /// What spectators see of a table: the public part of the hand played
+    /// on it, without the commitments of the players.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub struct SpectatorView {
+        /// The id of the table.
+        pub table_id: TableId,
+        /// The current state of the game.
+        pub state: STATE,
+        /// The game played on the table.
+        pub game: GameKind,
+        /// The number of hands played on the table so far.
+        pub hand_number: u32,
+        /// The players by seat order.
+        pub players: Vec<AccountId>,
+        /// Accumulated value in the pot.
+        pub pot: Balance,
+        /// The number of spectators watching the table.
+        pub spectators: u32,
+        /// The community cards dealt face up so far in the poker hand.
+        pub board: Vec<Card>,
+        /// The street bet on in the poker hand, if dealt.
+        pub street: Option<Street>,
+        /// The seat whose turn it is, while the betting goes on.
+        pub active: Option<u8>,
+        /// The amount each seat has put in over the poker hand.
+        pub contributed: Vec<Balance>,
+        /// Whether each seat has folded its poker hand.
+        pub folded: Vec<bool>,
+        /// The up cards each seat shows in a stud hand.
+        pub up_cards: Vec<Vec<Card>>,
+        /// The hole cards the players have shown down.
+        pub shown_hands: Vec<(AccountId, Vec<Card>)>,
+        /// The actions played in the poker hand so far, in order.
+        pub history: Vec<Play>,
+    }
+
+    /// The native funds the contract owes, compared to its balance.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub struct InvariantReport {
+        /// The pots, bonds and chips of the tables played in the native balance.
+        pub tables: Balance,
+        /// The balances the accounts may withdraw.
+        pub claimable: Balance,
+        /// The rake collected by the house.
+        pub treasury: Balance,
+        /// The progressive jackpot.
+        pub jackpot: Balance,
+        /// The funds escrowed elsewhere: the waitlists, the payouts held, the
+        /// creator stakes, the duels, lotteries, markets and tournaments.
+        pub escrowed: Balance,
+        /// The balance of the contract.
+        pub balance: Balance,
+        /// Whether the balance covers everything above.
+        pub solvent: bool,
+    }
+
+    /// What the code deployed is built with, for the frontends to adapt to.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub struct BuildInfo {
+        /// The semantic version of the code.
+        pub version: String,
+        /// The version of the storage layout expected by the code.
+        pub storage_version: u16,
+        /// The games the tables may host.
+        pub games: Vec<String>,
+        /// Whether the randomness of the chain is mixed into the shuffle
+        /// seeds, on top of the commit-reveal of the players.
+        pub chain_randomness: bool,
+    }
+
+    /// What a lobby lists about a table.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub struct TableSummary {
+        /// The id of the table.
+        pub table_id: TableId,
+        /// The current state of the game.
+        pub state: STATE,
+        /// The game played on the table.
+        pub game: GameKind,
+        /// The PSP22 token the table is played in, the native balance if `None`.
+        pub token: Option<AccountId>,
+        /// Start betting value.
+        pub required_start_bet: Balance,
+        /// The number of players seated.
+        pub seated: u8,
+        /// The minimum players required to start the game.
+        pub min_players: u8,
+        /// The maximum players allowed in the game participation.
+        pub max_players: u8,
+        /// Whether only invited accounts or holders of the join code may register.
+        pub restricted: bool,
+    }
+
+    /// The protocol parameters the owner tunes after deployment.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+        SpreadAllocate,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+    )]
+    pub struct Config {
+        /// The rake in basis points applied to the tables opened from now on.
+        pub rake_bps: u16,
+        /// The rake cap applied to the tables opened from now on.
+        pub rake_cap: Balance,
+        /// The most tables an account may host, unlimited if 0.
+        pub max_tables_per_account: u32,
+        /// The most players a table may seat, at most `MAX_PLAYERS`.
+        pub max_players: u8,
+        /// The least start bet a table may require.
+        pub min_start_bet: Balance,
+        /// The most start bet a table may require.
+        pub max_start_bet: Balance,
+        /// The number of blocks after which a table still staging may be
+        /// expired by anyone, never if 0.
+        pub staging_timeout_blocks: BlockNumber,
+        /// The code hash of the table contracts instantiated by
+        /// [`Metasino::create_table`] in factory mode, off if `None`.
+        pub table_code_hash: Option<Hash>,
+        /// The number of result signers confirming the result of an oracle
+        /// game, none accepted if 0.
+        pub result_threshold: u32,
+        /// The number of blocks the payouts of a game ended with
+        /// [`Metasino::end_game`] are held for the players to dispute them,
+        /// paid out at once if 0.
+        pub dispute_period_blocks: BlockNumber,
+        /// The bond the initializer of a table posts along with their start
+        /// bet when opening it, none if 0.
+        pub table_bond: Balance,
+        /// The number of blocks an account waits between opening two tables,
+        /// none if 0.
+        pub table_cooldown_blocks: BlockNumber,
+        /// The stake an account locks before opening public tables, none if 0.
+        pub min_creator_stake: Balance,
+        /// The most rake, in basis points, the initializer of a table sets
+        /// for it, none if 0.
+        pub max_table_rake_bps: u16,
+        /// The share, in basis points, of the rake of the tables paying it to
+        /// a rake recipient that the house keeps.
+        pub protocol_cut_bps: u16,
+        /// The contract the idle treasury may be parked with, none if `None`.
+        pub yield_strategy: Option<AccountId>,
+        /// The part of the treasury always kept liquid for payouts.
+        pub yield_buffer: Balance,
+        /// The most withdrawn out of the contract in a block, unlimited if 0.
+        pub max_withdrawal_per_block: Balance,
+        /// The most an account withdraws in an epoch of withdrawals,
+        /// unlimited if 0.
+        pub max_withdrawal_per_account: Balance,
+        /// The largest withdrawal paid at once, the larger ones waiting for
+        /// an operator to confirm them, unlimited if 0.
+        pub payout_ceiling: Balance,
+        /// The number of blocks a withdrawal above the ceiling waits before
+        /// an operator may confirm it.
+        pub payout_timelock_blocks: BlockNumber,
+        /// The pot above which the payouts of a game ended with
+        /// [`Metasino::end_game`] are held for the payout delay, none if 0.
+        pub large_pot_threshold: Balance,
+        /// The number of blocks the payouts of a large pot are held for an
+        /// operator to veto them.
+        pub payout_delay_blocks: BlockNumber,
+        /// The bond the dealer of a table posts, none if 0.
+        pub dealer_bond: Balance,
+        /// The number of blocks the dealer of a table has to deal once a deal
+        /// is due before anyone dealing in its place replaces it.
+        pub dealer_timeout_blocks: BlockNumber,
+    }
+
+    impl Default for Config {
+        fn default() -> Self {
+            Self {
+                rake_bps: 0,
+                rake_cap: 0,
+                max_tables_per_account: 0,
+                max_players: MAX_PLAYERS,
+                min_start_bet: 1,
+                max_start_bet: Balance::MAX,
+                staging_timeout_blocks: 0,
+                table_code_hash: None,
+                result_threshold: 0,
+                dispute_period_blocks: 0,
+                table_bond: 0,
+                table_cooldown_blocks: 0,
+                min_creator_stake: 0,
+                max_table_rake_bps: 0,
+                protocol_cut_bps: 0,
+                yield_strategy: None,
+                yield_buffer: 0,
+                max_withdrawal_per_block: 0,
+                max_withdrawal_per_account: 0,
+                payout_ceiling: 0,
+                payout_timelock_blocks: 0,
+                large_pot_threshold: 0,
+                payout_delay_blocks: 0,
+                dealer_bond: 0,
+                dealer_timeout_blocks: DEALER_TIMEOUT,
+            }
+        }
+    }
+
+    /// The roles the owner grants to delegate running the contract.
+    /// The owner of the contract holds every role, and an account granted
+    /// the owner role holds every role as well.
+    #[derive(
+        Debug,
+        Copy,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+    )]
+    pub enum Role {
+        /// Tunes the contract, manages its treasury and grants the roles.
+        Owner,
+        /// Keeps the tables running: pauses the contract, forces timeouts
+        /// and terminates stuck tables.
+        Operator,
+        /// Reports the results of the games of any table or tournament.
+        Dealer,
+    }
+
+    /// The progress of a coin-flip duel.
+    #[derive(
+        Debug,
+        Copy,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+    )]
+    pub enum DuelState {
+        /// Waiting for an opponent to accept.
+        Open,
+        /// Both stakes are escrowed and the seeds are being revealed.
+        Accepted,
+        /// The pot went to the winner, or back to the players if nobody revealed.
+        Settled,
+        /// Cancelled by the challenger before anyone accepted.
+        Cancelled,
+    }
+
+    /// A coin flip between two players, each staking the same amount.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+    )]
+    pub struct Duel {
+        /// The player who opened the duel.
+        pub challenger: AccountId,
+        /// The player who accepted the duel.
+        pub opponent: Option<AccountId>,
+        /// The stake of each player.
+        pub stake: Balance,
+        /// The seed commitments of the challenger and the opponent.
+        pub commitments: [Hash; 2],
+        /// The seeds revealed by the challenger and the opponent.
+        pub seeds: [Option<[u8; 32]>; 2],
+        /// The last block in which the seeds may be revealed.
+        pub reveal_deadline: BlockNumber,
+        /// The share of the pot, in basis points, taken by the house.
+        pub rake_bps: u16,
+        /// The most the house takes from the pot.
+        pub rake_cap: Balance,
+        /// The progress of the duel.
+        pub state: DuelState,
+        /// The player who won the pot.
+        pub winner: Option<AccountId>,
+    }
+
+    /// The outcome of a game, kept once the table has moved on.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+    )]
+    pub struct GameRecord {
+        /// The id of the game.
+        pub game_id: GameId,
+        /// The table the game was played on.
+        pub table_id: TableId,
+        /// The players of the game by seat order.
+        pub players: Vec<AccountId>,
+        /// The whole pot of the game, before the rake.
+        pub pot: Balance,
+        /// The winners of the game.
+        pub winners: Vec<AccountId>,
+        /// The amount paid out to each winner.
+        pub payouts: Vec<Balance>,
+        /// The block in which the game ended.
+        pub ended_at_block: BlockNumber,
+    }
+
+    /// Who won the last game of a table and with what.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+    )]
+    pub struct GameResult {
+        /// The winners of the game.
+        pub winners: Vec<AccountId>,
+        /// The hole cards each winner showed down, empty if they won without
+        /// showing or the game deals no poker hands.
+        pub winning_hands: Vec<Vec<Card>>,
+        /// The amount of the pot won by each winner.
+        pub pot_breakdown: Vec<Balance>,
+        /// The rake the house took out of the pot.
+        pub rake_taken: Balance,
+    }
+
+    /// The payouts of a game held during the dispute period.
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+    )]
+    pub struct PendingPayout {
+        /// The table the game was played on.
+        pub table_id: TableId,
+        /// The PSP22 token the game was played in, the native balance if `None`.
+        pub token: Option<AccountId>,
+        /// The winners proposed for the game.
+        pub winners: Vec<AccountId>,
+        /// The amount proposed for each winner.
+        pub payouts: Vec<Balance>,
+        /// The last block in which the payouts may be disputed.
+        pub dispute_until: BlockNumber,
+        /// The arbiter of the table when the game ended, if any.
+        pub arbiter: Option<AccountId>,
+        /// The player who disputed the payouts and the hash of their evidence.
+        pub dispute: Option<(AccountId, Hash)>,
+    }
+
+    #[ink(event)]
+    pub struct NewTableOpened {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub initiator: AccountId,
+        #[ink(topic)]
+        pub required_start_bet: Balance,
+        /// The version of the code the table is opened by.
+        pub version: String,
+    }
+
+    #[ink(event)]
+    pub struct TableInstantiated {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub table: AccountId,
+        #[ink(topic)]
+        pub initiator: AccountId,
+        /// The version of the code the table is instantiated by.
+        pub version: String,
+    }
+
+    #[ink(event)]
+    pub struct PlayerRegistered {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub start_bet: Balance,
+    }
+
+    #[ink(event)]
+    pub struct StateChanged {
+        #[ink(topic)]
+        pub table_id: TableId,
+        pub from: STATE,
+        pub to: STATE,
+    }
+
+    #[ink(event)]
+    pub struct GameStarted {
+        #[ink(topic)]
+        pub table_id: TableId,
+        pub players: Vec<AccountId>,
+        pub reveal_deadline: BlockNumber,
+    }
+
+    #[ink(event)]
+    pub struct GameAutoStarted {
+        #[ink(topic)]
+        pub table_id: TableId,
+    }
+
+    #[ink(event)]
+    pub struct MinimumPlayerReached {
+        #[ink(topic)]
+        pub table_id: TableId,
+        /// The player whose registration brought the table to its minimum.
+        #[ink(topic)]
+        pub account_id: AccountId,
+        pub players: u8,
+    }
+
+    #[ink(event)]
+    pub struct TableFull {
+        #[ink(topic)]
+        pub table_id: TableId,
+        pub players: u8,
+    }
+
+    #[ink(event)]
+    pub struct PlayerLeft {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub refund: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PlayerKicked {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub refund: Balance,
+    }
+
+    #[ink(event)]
+    pub struct SeedRevealed {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct PlayerSlashed {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ShuffleSeedDerived {
+        #[ink(topic)]
+        pub table_id: TableId,
+        pub seed: [u8; 32],
+    }
+
+    #[ink(event)]
+    pub struct PlayerActed {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub action: Action,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ActionLogged {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub hand_number: u32,
+        pub seat: u8,
+        pub action: LoggedAction,
+        pub amount: Balance,
+        pub street: Street,
+    }
+
+    #[ink(event)]
+    pub struct BlackjackPlayed {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub action: Move,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct InsurancePlaced {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct BetPlaced {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub bet: RouletteBet,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct WheelSpun {
+        #[ink(topic)]
+        pub table_id: TableId,
+        pub pocket: u8,
+    }
+
+    #[ink(event)]
+    pub struct BaccaratBetPlaced {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub bet: BaccaratBet,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct CoupDealt {
+        #[ink(topic)]
+        pub table_id: TableId,
+        pub coup: Coup,
+        pub outcome: BaccaratBet,
+    }
+
+    #[ink(event)]
+    pub struct DiceRolled {
+        #[ink(topic)]
+        pub table_id: TableId,
+        pub dice: (u8, u8),
+        pub point: Option<u8>,
+    }
+
+    #[ink(event)]
+    pub struct DuelCreated {
+        #[ink(topic)]
+        pub duel_id: DuelId,
+        #[ink(topic)]
+        pub challenger: AccountId,
+        pub stake: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DuelAccepted {
+        #[ink(topic)]
+        pub duel_id: DuelId,
+        #[ink(topic)]
+        pub opponent: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct DuelSettled {
+        #[ink(topic)]
+        pub duel_id: DuelId,
+        pub winner: Option<AccountId>,
+        pub payout: Balance,
+        pub rake: Balance,
+    }
+
+    #[ink(event)]
+    pub struct LotteryDrawn {
+        #[ink(topic)]
+        pub lottery_id: LotteryId,
+        pub winner: Option<AccountId>,
+        pub ticket: Option<u32>,
+        pub prize: Balance,
+        pub rake: Balance,
+    }
+
+    #[ink(event)]
+    pub struct MarketStaked {
+        #[ink(topic)]
+        pub market_id: MarketId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub outcome: u8,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct MarketResolved {
+        #[ink(topic)]
+        pub market_id: MarketId,
+        pub outcome: u8,
+        pub rake: Balance,
+    }
+
+    #[ink(event)]
+    pub struct JackpotDonated {
+        #[ink(topic)]
+        pub donor: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct JackpotWon {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ReferralPaid {
+        #[ink(topic)]
+        pub referrer: AccountId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub table_id: TableId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PointsRedeemed {
+        #[ink(topic)]
+        pub player: AccountId,
+        pub redemption: Redemption,
+        pub points: u128,
+        pub value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct AchievementUnlocked {
+        #[ink(topic)]
+        pub player: AccountId,
+        pub achievement: Achievement,
+    }
+
+    #[ink(event)]
+    pub struct TournamentStarted {
+        #[ink(topic)]
+        pub tournament_id: TournamentId,
+        pub players: Vec<AccountId>,
+        pub prize_pool: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PlayerEliminated {
+        #[ink(topic)]
+        pub tournament_id: TournamentId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub place: u8,
+    }
+
+    #[ink(event)]
+    pub struct TournamentFinished {
+        #[ink(topic)]
+        pub tournament_id: TournamentId,
+        pub winners: Vec<AccountId>,
+        pub prizes: Vec<Balance>,
+        pub rake: Balance,
+    }
+
+    #[ink(event)]
+    pub struct BountyCollected {
+        #[ink(topic)]
+        pub tournament_id: TournamentId,
+        #[ink(topic)]
+        pub hunter: AccountId,
+        pub victim: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct TicketIssued {
+        #[ink(topic)]
+        pub tournament_id: TournamentId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub ticket_id: u128,
+    }
+
+    #[ink(event)]
+    pub struct ChipsBought {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct CashedOut {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PlayerRebought {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub amount: Balance,
+        pub rebuys: u8,
+    }
+
+    #[ink(event)]
+    pub struct BlindPosted {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct AntePosted {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PlayerBanned {
+        /// The table the player is banned from, every table if `None`.
+        #[ink(topic)]
+        pub table_id: Option<TableId>,
+        #[ink(topic)]
+        pub player: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct PlayerUnbanned {
+        #[ink(topic)]
+        pub table_id: Option<TableId>,
+        #[ink(topic)]
+        pub player: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct SeatedFromWaitlist {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RoleGranted {
+        pub role: Role,
+        #[ink(topic)]
+        pub account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RoleRevoked {
+        pub role: Role,
+        #[ink(topic)]
+        pub account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ConfigChanged {
+        pub config: Config,
+    }
+
+    #[ink(event)]
+    pub struct ResultSignerAdded {
+        #[ink(topic)]
+        pub account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ResultSignerRemoved {
+        #[ink(topic)]
+        pub account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ResultSubmitted {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub signer: AccountId,
+        pub result_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct ResultFinalized {
+        #[ink(topic)]
+        pub table_id: TableId,
+        pub result_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct WinningsWithdrawn {
+        #[ink(topic)]
+        pub account: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct HandRevealed {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub cards: Vec<Card>,
+    }
+
+    #[ink(event)]
+    pub struct BoardResult {
+        #[ink(topic)]
+        pub table_id: TableId,
+        pub board: u8,
+        pub cards: Vec<Card>,
+        pub payouts: Vec<Balance>,
+    }
+
+    #[ink(event)]
+    pub struct PlayerTimedOut {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub player: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct GameEnded {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub game_id: GameId,
+        pub winners: Vec<AccountId>,
+        pub payouts: Vec<Balance>,
+        pub pot: Balance,
+        pub rake: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PayoutsHeld {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub game_id: GameId,
+        pub dispute_until: BlockNumber,
+    }
+
+    #[ink(event)]
+    pub struct DisputeRaised {
+        #[ink(topic)]
+        pub game_id: GameId,
+        #[ink(topic)]
+        pub player: AccountId,
+        pub evidence_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct PayoutsFinalized {
+        #[ink(topic)]
+        pub game_id: GameId,
+        pub winners: Vec<AccountId>,
+        pub payouts: Vec<Balance>,
+    }
+
+    #[ink(event)]
+    pub struct TreasuryWithdrawn {
+        #[ink(topic)]
+        pub token: Option<AccountId>,
+        #[ink(topic)]
+        pub to: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        pub account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        pub account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Upgraded {
+        #[ink(topic)]
+        pub code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct Migrated {
+        pub from: u16,
+        pub to: u16,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipTransferStarted {
+        #[ink(topic)]
+        pub from: AccountId,
+        #[ink(topic)]
+        pub to: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        pub from: AccountId,
+        #[ink(topic)]
+        pub to: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TableOwnershipTransferred {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub from: AccountId,
+        #[ink(topic)]
+        pub to: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TableTerminated {
+        #[ink(topic)]
+        pub table_id: TableId,
+        pub players: Vec<AccountId>,
+        pub refunds: Vec<Balance>,
+    }
+
+    #[ink(event)]
+    pub struct SpectatorJoined {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub spectator: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct SpectatorLeft {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub spectator: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct BondReturned {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub initializer: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct SessionKeyApproved {
+        #[ink(topic)]
+        pub player: AccountId,
+        #[ink(topic)]
+        pub delegate: AccountId,
+        pub expiry_block: BlockNumber,
+    }
+
+    #[ink(event)]
+    pub struct CreatorStakeChanged {
+        #[ink(topic)]
+        pub account: AccountId,
+        pub stake: Balance,
+    }
+
+    #[ink(event)]
+    pub struct BondSlashed {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub initializer: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct TableExpired {
+        #[ink(topic)]
+        pub table_id: TableId,
+        pub players: Vec<AccountId>,
+        pub refunds: Vec<Balance>,
+    }
+
+    #[ink(event)]
+    pub struct SeasonClosed {
+        #[ink(topic)]
+        pub season: Season,
+        pub winners: Vec<AccountId>,
+        pub prizes: Vec<Balance>,
+    }
+
+    #[ink(event)]
+    pub struct TreasuryParked {
+        #[ink(topic)]
+        pub strategy: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct TreasuryRecalled {
+        #[ink(topic)]
+        pub strategy: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct WithdrawalQueued {
+        #[ink(topic)]
+        pub account: AccountId,
+        pub amount: Balance,
+        pub release_at: BlockNumber,
+    }
+
+    #[ink(event)]
+    pub struct WithdrawalCancelled {
+        #[ink(topic)]
+        pub account: AccountId,
+        #[ink(topic)]
+        pub operator: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PayoutVetoed {
+        #[ink(topic)]
+        pub game_id: GameId,
+        #[ink(topic)]
+        pub operator: AccountId,
+        pub evidence_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct DealerNominated {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub dealer: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct DealerSet {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub dealer: Option<AccountId>,
+        pub bond: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DealerReplaced {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub dealer: AccountId,
+        pub bond: Balance,
+    }
+
+    #[ink(event)]
+    pub struct EmergencyRefunded {
+        #[ink(topic)]
+        pub table_id: TableId,
+        pub players: Vec<AccountId>,
+        pub refunds: Vec<Balance>,
+    }
+
+    #[ink(event)]
+    pub struct RakeShared {
+        #[ink(topic)]
+        pub table_id: TableId,
+        #[ink(topic)]
+        pub recipient: AccountId,
+        pub amount: Balance,
+    }
+
+    /// Defines the storage of your contract.
+    /// Add new fields to the below struct in order
+    /// to add new static storage fields to your contract.
+    /// StorageLayout is used to define the layout of the storage.
+    /// SpreadLayout is used to define the layout of the spread.
+    #[ink(storage)]
+    #[derive(SpreadAllocate)]
+    pub struct Metasino {
+        /// Account which deployed the contract and runs the house.
+        owner: AccountId,
+        /// The account the owner handed the contract over to, until it accepts.
+        pending_owner: Option<AccountId>,
+        /// The protocol parameters tuned by the owner, loaded only by the
+        /// messages reading them.
+        config: Lazy<Config>,
+        /// The roles granted to each account on top of the owner.
+        roles: Mapping<(Role, AccountId), ()>,
+        /// The accounts confirming the results of the oracle games, kept in a
+        /// single entry so that only the messages checking a signer load them.
+        result_signers: Lazy<Vec<AccountId>>,
+        /// The result hash submitted by each signer for the game of each oracle table.
+        result_votes: Mapping<TableId, Vec<(AccountId, Hash)>>,
+        /// The result hash of the game of each oracle table, once confirmed.
+        confirmed_results: Mapping<TableId, Hash>,
+        /// The rake collected by the house.
+        treasury: Balance,
+        /// The part of the treasury parked with the yield strategy.
+        parked: Balance,
+        /// Whether opening tables, registering and starting games is suspended.
+        paused: bool,
+        /// The version of the storage layout, brought up to date by [`Metasino::migrate`].
+        storage_version: u16,
+        /// The rake collected by the house on the tables played in PSP22 tokens.
+        token_treasury: Mapping<AccountId, Balance>,
+        /// The number of PSP22 tokens the house holds some of.
+        token_treasuries: u32,
+        /// All the tables hosted by the contract.
+        tables: Mapping<TableId, Table>,
+        /// The number of tables each account hosts as their initializer.
+        hosted_tables: Mapping<AccountId, u32>,
+        /// The block in which each account last opened a table.
+        last_table_opened: Mapping<AccountId, BlockNumber>,
+        /// The stake each account locked to open public tables.
+        creator_stakes: Mapping<AccountId, Balance>,
+        /// The sum of the stakes locked to open public tables.
+        total_creator_stakes: Balance,
+        /// The player each session key plays for, and the block from which
+        /// it no longer may.
+        session_keys: Mapping<AccountId, (AccountId, BlockNumber)>,
+        /// The nonce of the next action of each account, raised by every
+        /// action it takes at the tables.
+        nonces: Mapping<AccountId, u64>,
+        /// The contract of each table instantiated in factory mode.
+        table_contracts: Mapping<TableId, AccountId>,
+        /// The id given to the next table opened.
+        next_table_id: TableId,
+        /// The players seated at each table.
+        players: Mapping<(TableId, AccountId), PlayerInfo>,
+        /// The player holding each seat of each table.
+        seats: Mapping<(TableId, u8), AccountId>,
+        /// The accounts watching each table.
+        spectators: Mapping<TableId, Vec<AccountId>>,
+        /// The seed commitment of each player per table.
+        commitments: Mapping<(TableId, AccountId), Hash>,
+        /// The commitment of each player per poker table to their hole cards.
+        hand_commitments: Mapping<(TableId, AccountId), Hash>,
+        /// The deck positions of the hole cards each player per poker table showed down.
+        revealed_hands: Mapping<(TableId, AccountId), Vec<u8>>,
+        /// The players of each poker table agreeing to run the hand twice.
+        run_twice_votes: Mapping<TableId, Vec<AccountId>>,
+        /// The betting of the hand played on each table.
+        rounds: Mapping<TableId, BettingRound>,
+        /// The actions played in the poker hand on each table by their number
+        /// in the hand, kept apart from its betting so that an action does
+        /// not load the ones before it. The next hand overwrites them.
+        hand_plays: Mapping<(TableId, u32), Play>,
+        /// The blackjack hand played on each blackjack table.
+        blackjack_rounds: Mapping<TableId, BlackjackRound>,
+        /// The roulette bets of each player per table.
+        wagers: Mapping<(TableId, AccountId), Vec<Wager>>,
+        /// The baccarat bets of each player per table.
+        baccarat_bets: Mapping<(TableId, AccountId), Vec<BaccaratWager>>,
+        /// The side of each player per dice table, pass unless chosen otherwise.
+        dice_bets: Mapping<(TableId, AccountId), DiceBet>,
+        /// The rolls of the game played on each dice table.
+        dice_rounds: Mapping<TableId, DiceRound>,
+        /// All the coin-flip duels.
+        duels: Mapping<DuelId, Duel>,
+        /// The id given to the next duel opened.
+        next_duel_id: DuelId,
+        /// All the lotteries.
+        lotteries: Mapping<LotteryId, Lottery>,
+        /// The holder of each ticket of each lottery.
+        tickets: Mapping<(LotteryId, u32), AccountId>,
+        /// The id given to the next lottery opened.
+        next_lottery_id: LotteryId,
+        /// All the parimutuel markets.
+        markets: Mapping<MarketId, Market>,
+        /// The stakes of each player on each outcome of each market, until claimed.
+        market_stakes: Mapping<(MarketId, AccountId), Vec<Balance>>,
+        /// The id given to the next market opened.
+        next_market_id: MarketId,
+        /// The share of each pot, in basis points, fed into the jackpot.
+        jackpot_bps: u16,
+        /// The progressive jackpot, in the native balance.
+        jackpot: Balance,
+        /// The share of the rake, in basis points, earned by the referrers.
+        referral_bps: u16,
+        /// The referrer of each player referred.
+        referrers: Mapping<AccountId, AccountId>,
+        /// The rake each referrer earned so far.
+        referral_earnings: Mapping<AccountId, Balance>,
+        /// The epoch of the last freeroll each account registered for, and the
+        /// number of freerolls it registered for in that epoch.
+        freeroll_entries: Mapping<AccountId, (BlockNumber, u32)>,
+        /// The loyalty points of each player, earned by the rake of their games.
+        loyalty_points: Mapping<AccountId, u128>,
+        /// The loyalty points redeemed for a unit of the native balance, 0
+        /// disabling redemptions.
+        points_per_unit: u128,
+        /// The PSP34 contract minting the badges of the achievements, if any.
+        achievements_contract: Option<AccountId>,
+        /// The progress of each account towards the achievements.
+        progress: Mapping<AccountId, Progress>,
+        /// All the tournaments.
+        tournaments: Mapping<TournamentId, Tournament>,
+        /// The id given to the next tournament opened.
+        next_tournament_id: TournamentId,
+        /// The accounts invited to each private table.
+        allowlist: Mapping<(TableId, AccountId), ()>,
+        /// The accounts banned from each table by its initializer.
+        table_bans: Mapping<(TableId, AccountId), ()>,
+        /// The accounts banned from every table by the owner.
+        bans: Mapping<AccountId, ()>,
+        /// The accounts waiting for a seat at each full table, first come first seated.
+        waitlists: Mapping<TableId, Vec<AccountId>>,
+        /// The payouts and refunds in the native balance each account may withdraw.
+        pending_withdrawals: Mapping<AccountId, Balance>,
+        /// The sum of the native balances every account may withdraw.
+        total_claimable: Balance,
+        /// The block of the last withdrawal and the amount withdrawn in it.
+        block_withdrawals: (BlockNumber, Balance),
+        /// The epoch of the last withdrawal of each account and the amount
+        /// it withdrew in it.
+        account_withdrawals: Mapping<AccountId, (BlockNumber, Balance)>,
+        /// The withdrawal above the payout ceiling each account waits for an
+        /// operator to confirm, and the block from which it may be.
+        queued_withdrawals: Mapping<AccountId, (Balance, BlockNumber)>,
+        /// The chips each player holds on each table, not yet put into the pot.
+        chips: Mapping<(TableId, AccountId), Balance>,
+        /// The record of every game ended.
+        games: Mapping<GameId, GameRecord>,
+        /// The result of the last game ended on each table.
+        last_results: Mapping<TableId, GameResult>,
+        /// The id given to the next game ended.
+        next_game_id: GameId,
+        /// The id of the oldest game whose record may still be kept.
+        history_start: GameId,
+        /// The payouts of the games ended within their dispute period.
+        pending_payouts: Mapping<GameId, PendingPayout>,
+        /// The number of games whose payouts are held.
+        held_games: u32,
+        /// The sum of the payouts held in the native balance.
+        total_held: Balance,
+        /// The lifetime statistics of every player.
+        stats: Mapping<AccountId, PlayerStats>,
+        /// The players who won the most, the best first, loaded only when a
+        /// payout is ranked.
+        leaderboard: Lazy<Vec<AccountId>>,
+        /// The season the statistics currently accrue to.
+        season: Season,
+        /// The block in which the current season started.
+        season_started_at: BlockNumber,
+        /// The number of blocks after which a season closes by itself, 0 if
+        /// only the owner closes it.
+        season_length: BlockNumber,
+        /// The prize of each rank of the leaderboard of a season, paid out of
+        /// the treasury when it closes, loaded only when the season closes.
+        season_prizes: Lazy<Vec<Balance>>,
+        /// The statistics of every player in each season.
+        season_stats: Mapping<(Season, AccountId), PlayerStats>,
+        /// The players who won the most in each season, the best first.
+        season_leaderboards: Mapping<Season, Vec<AccountId>>,
+        /// The prize each player may claim for each closed season.
+        season_awards: Mapping<(Season, AccountId), Balance>,
+        /// The next table and game moved by the migration in progress, if any.
+        migration: Option<(TableId, GameId)>,
+    }
+
+    impl Metasino {
+        /// Constructor that initializes the contract without any table.
+        /// The caller becomes the owner of the contract.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            ink_env::debug_print!("Metasino::new()");
+            ink_lang::utils::initialize_contract(|contract: &mut Self| {
+                contract.owner = Self::env().caller();
+                contract.config.set(&Config::default());
+                contract.storage_version = STORAGE_VERSION;
+            })
+        }
+
+        /// Constructor of the table contracts instantiated in factory mode.
+        /// The contract is owned by `owner`, tuned by the `config` of the
+        /// factory, and opens the table 0 for `initializer` as
+        /// [`Metasino::create_table`] does, the endowment being the start bet.
+        /// Panics if the table cannot be opened, failing the instantiation.
+        #[ink(constructor, payable)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn new_table(
+            owner: AccountId,
+            config: Config,
+            initializer: AccountId,
+            required_start_bet: Balance,
+            min_players: u8,
+            max_players: u8,
+            game: GameKind,
+            join_code_hash: Option<Hash>,
+            start_at: Option<BlockNumber>,
+        ) -> Self {
+            let mut contract: Self = ink_lang::utils::initialize_contract(|_: &mut Self| {});
+            contract.owner = owner;
+            contract.config.set(&Config {
+                table_code_hash: None,
+                ..config
+            });
+            contract.storage_version = STORAGE_VERSION;
+            contract
+                .check_table(initializer, required_start_bet, min_players, max_players, start_at)
+                .and_then(|_| {
+                    contract.open_table(
+                        initializer,
+                        required_start_bet,
+                        min_players,
+                        max_players,
+                        None,
+                        game,
+                        join_code_hash,
+                        start_at,
+                    )
+                })
+                .expect("the factory opens valid tables");
+            contract
+        }
+
+        /// Grant the role to the account.
+        /// error if the caller does not hold the owner role.
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.only_owner()?;
+            self.roles.insert((role, account), &());
+            Self::env().emit_event(RoleGranted { role, account });
+            Ok(())
+        }
+
+        /// Revoke the role granted to the account. The owner of the contract
+        /// keeps every role until the ownership is transferred.
+        /// error if the caller does not hold the owner role.
+        /// error if the role was not granted to the account.
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.only_owner()?;
+            if !self.roles.contains((role, account)) {
+                return Err(MetasinoError::WrongState);
+            }
+            self.roles.remove((role, account));
+            Self::env().emit_event(RoleRevoked { role, account });
+            Ok(())
+        }
+
+        /// Whether the account holds the role.
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: AccountId) -> bool {
+            account == self.owner
+                || self.roles.contains((role, account))
+                || self.roles.contains((Role::Owner, account))
+        }
+
+        /// Set the rake of the house for the tables opened from now on.
+        /// `rake_bps` is the share of each pot in basis points and `rake_cap`
+        /// the most taken from the pots of a single game.
+        /// error if the caller is not the owner.
+        /// error if the rake exceeds 10000 basis points.
+        #[ink(message)]
+        pub fn set_rake(&mut self, rake_bps: u16, rake_cap: Balance) -> Result<()> {
+            self.only_owner()?;
+            if rake_bps > MAX_BPS {
+                return Err(MetasinoError::InvalidRake);
+            }
+            let mut config = self.get_config();
+            config.rake_bps = rake_bps;
+            config.rake_cap = rake_cap;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Set the most rake the initializers of tables set for them, and the
+        /// share of it, both in basis points, that the house keeps out of the
+        /// tables paying their rake to a recipient of their own.
+        /// error if the caller is not the owner.
+        /// error if either exceeds 10000 basis points.
+        #[ink(message)]
+        pub fn set_table_rake_limits(
+            &mut self,
+            max_table_rake_bps: u16,
+            protocol_cut_bps: u16,
+        ) -> Result<()> {
+            self.only_owner()?;
+            if max_table_rake_bps > MAX_BPS || protocol_cut_bps > MAX_BPS {
+                return Err(MetasinoError::InvalidRake);
+            }
+            let mut config = self.get_config();
+            config.max_table_rake_bps = max_table_rake_bps;
+            config.protocol_cut_bps = protocol_cut_bps;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Set the most tables an account may host, 0 lifting the limit.
+        /// Accounts already above it keep their tables but open no more.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_max_tables_per_account(&mut self, max_tables: u32) -> Result<()> {
+            self.only_owner()?;
+            let mut config = self.get_config();
+            config.max_tables_per_account = max_tables;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Set the number of blocks an account waits between opening two
+        /// tables, 0 lifting the cooldown.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_table_cooldown(&mut self, blocks: BlockNumber) -> Result<()> {
+            self.only_owner()?;
+            let mut config = self.get_config();
+            config.table_cooldown_blocks = blocks;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Set the most players the tables opened from now on may seat.
+        /// error if the caller is not the owner.
+        /// error if the ceiling is out of the bounds of the contract.
+        #[ink(message)]
+        pub fn set_max_players_ceiling(&mut self, max_players: u8) -> Result<()> {
+            self.only_owner()?;
+            if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&max_players) {
+                return Err(MetasinoError::InvalidTableConfig);
+            }
+            let mut config = self.get_config();
+            config.max_players = max_players;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Set the bounds of the start bet the tables opened from now on may require.
+        /// error if the caller is not the owner.
+        /// error if the least start bet is 0 or above the most.
+        #[ink(message)]
+        pub fn set_start_bet_limits(
+            &mut self,
+            min_start_bet: Balance,
+            max_start_bet: Balance,
+        ) -> Result<()> {
+            self.only_owner()?;
+            if min_start_bet == 0 || min_start_bet > max_start_bet {
+                return Err(MetasinoError::InvalidTableConfig);
+            }
+            let mut config = self.get_config();
+            config.min_start_bet = min_start_bet;
+            config.max_start_bet = max_start_bet;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Set the number of blocks after which a table still staging may be
+        /// expired by anyone, 0 keeping the tables staging for good.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_staging_timeout(&mut self, blocks: BlockNumber) -> Result<()> {
+            self.only_owner()?;
+            let mut config = self.get_config();
+            config.staging_timeout_blocks = blocks;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Set the bond the initializers of the tables opened from now on post
+        /// along with their start bet, 0 requiring none.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_table_bond(&mut self, bond: Balance) -> Result<()> {
+            self.only_owner()?;
+            let mut config = self.get_config();
+            config.table_bond = bond;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Set the stake an account locks before opening public tables, 0
+        /// requiring none. Tables opened with a join code need no stake.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_min_creator_stake(&mut self, stake: Balance) -> Result<()> {
+            self.only_owner()?;
+            let mut config = self.get_config();
+            config.min_creator_stake = stake;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Add the transferred value to the stake the caller locks to open
+        /// public tables.
+        #[ink(message, payable)]
+        pub fn lock_creator_stake(&mut self) -> Result<()> {
+            let caller = Self::env().caller();
+            let locked = Self::env().transferred_value();
+            let stake = arithmetic::add(self.get_creator_stake(caller), locked)?;
+            self.total_creator_stakes = arithmetic::add(self.total_creator_stakes, locked)?;
+            self.creator_stakes.insert(caller, &stake);
+            Self::env().emit_event(CreatorStakeChanged {
+                account: caller,
+                stake,
+            });
+            Ok(())
+        }
+
+        /// Unlock `amount` of the stake of the caller to their withdrawable
+        /// balance. The stake stays locked until every table the caller hosts
+        /// has ended.
+        /// error if the caller hosts a table which has not ended.
+        /// error if the caller locked less than the amount.
+        #[ink(message)]
+        pub fn unlock_creator_stake(&mut self, amount: Balance) -> Result<()> {
+            let caller = Self::env().caller();
+            let hosting = self
+                .summaries_where(|table| table.initializer == caller && table.state != STATE::ENDED)
+                .next()
+                .is_some();
+            if hosting {
+                return Err(MetasinoError::WrongState);
+            }
+            let stake = arithmetic::sub(self.get_creator_stake(caller), amount)?;
+            self.total_creator_stakes = self.total_creator_stakes.saturating_sub(amount);
+            self.creator_stakes.insert(caller, &stake);
+            self.credit_withdrawal(caller, amount)?;
+            Self::env().emit_event(CreatorStakeChanged {
+                account: caller,
+                stake,
+            });
+            Ok(())
+        }
+
+        /// Get the stake the account locked to open public tables.
+        #[ink(message)]
+        pub fn get_creator_stake(&self, account: AccountId) -> Balance {
+            self.creator_stakes.get(account).unwrap_or_default()
+        }
+
+        /// Slash the bond of the initializer of the table for abusing their
+        /// powers over it, sharing it out between the other players seated.
+        /// error if the caller is not the owner.
+        /// error if the table holds no bond.
+        #[ink(message)]
+        pub fn slash_table_bond(&mut self, table_id: TableId) -> Result<()> {
+            self.only_owner()?;
+            let mut table = self.table(table_id)?;
+            if table.bond == 0 {
+                return Err(MetasinoError::WrongState);
+            }
+            self.slash_bond(table_id, &mut table)?;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Set the number of blocks the payouts of the games ended with
+        /// [`Metasino::end_game`] from now on are held for the players to
+        /// dispute them, 0 paying them out at once.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_dispute_period(&mut self, blocks: BlockNumber) -> Result<()> {
+            self.only_owner()?;
+            let mut config = self.get_config();
+            config.dispute_period_blocks = blocks;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Set the pot above which the payouts of the games ended with
+        /// [`Metasino::end_game`] from now on are held for `delay` blocks, for
+        /// an operator to veto them, 0 paying large pots out as any other.
+        /// Once the delay is over the payouts are executed by
+        /// [`Metasino::finalize_payouts`] with the id of the game.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_large_pot_delay(
+            &mut self,
+            threshold: Balance,
+            delay: BlockNumber,
+        ) -> Result<()> {
+            self.only_owner()?;
+            let mut config = self.get_config();
+            config.large_pot_threshold = threshold;
+            config.payout_delay_blocks = delay;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Set the bond the dealers of the tables post from now on, and the
+        /// number of blocks a dealer has to deal once a deal is due before
+        /// being replaced.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_dealer_terms(&mut self, bond: Balance, timeout: BlockNumber) -> Result<()> {
+            self.only_owner()?;
+            let mut config = self.get_config();
+            config.dealer_bond = bond;
+            config.dealer_timeout_blocks = timeout;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Switch to factory mode, [`Metasino::create_table`] instantiating
+        /// a table contract of the given code for each table, or back to
+        /// hosting the tables with `None`. The tables already opened stay
+        /// where they are.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_table_code_hash(&mut self, code_hash: Option<Hash>) -> Result<()> {
+            self.only_owner()?;
+            let mut config = self.get_config();
+            config.table_code_hash = code_hash;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Get the contract of the table instantiated in factory mode, if any.
+        #[ink(message)]
+        pub fn get_table_contract(&self, table_id: TableId) -> Option<AccountId> {
+            self.table_contracts.get(table_id)
+        }
+
+        /// Get the protocol parameters.
+        #[ink(message)]
+        pub fn get_config(&self) -> Config {
+            self.config.get().unwrap_or_default()
+        }
+
+        /// Let the account confirm the results of the oracle games.
+        /// error if the caller is not the owner.
+        /// error if the account already is a result signer.
+        #[ink(message)]
+        pub fn add_result_signer(&mut self, account: AccountId) -> Result<()> {
+            self.only_owner()?;
+            let mut signers = self.get_result_signers();
+            if signers.contains(&account) {
+                return Err(MetasinoError::WrongState);
+            }
+            signers.push(account);
+            self.result_signers.set(&signers);
+            Self::env().emit_event(ResultSignerAdded { account });
+            Ok(())
+        }
+
+        /// Stop the account from confirming the results of the oracle games,
+        /// the results it already submitted no longer counting.
+        /// error if the caller is not the owner.
+        /// error if the account is not a result signer.
+        /// error if fewer signers than the threshold would be left.
+        #[ink(message)]
+        pub fn remove_result_signer(&mut self, account: AccountId) -> Result<()> {
+            self.only_owner()?;
+            let mut signers = self.get_result_signers();
+            let position = signers
+                .iter()
+                .position(|signer| *signer == account)
+                .ok_or(MetasinoError::WrongState)?;
+            if signers.len() as u32 - 1 < self.get_config().result_threshold {
+                return Err(MetasinoError::InvalidThreshold);
+            }
+            signers.remove(position);
+            self.result_signers.set(&signers);
+            Self::env().emit_event(ResultSignerRemoved { account });
+            Ok(())
+        }
+
+        /// Set the number of result signers who have to submit the same
+        /// result for an oracle game to be paid out, 0 accepting none.
+        /// error if the caller is not the owner.
+        /// error if the threshold exceeds the number of result signers.
+        #[ink(message)]
+        pub fn set_result_threshold(&mut self, threshold: u32) -> Result<()> {
+            self.only_owner()?;
+            if threshold as usize > self.get_result_signers().len() {
+                return Err(MetasinoError::InvalidThreshold);
+            }
+            let mut config = self.get_config();
+            config.result_threshold = threshold;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Get the accounts confirming the results of the oracle games.
+        #[ink(message)]
+        pub fn get_result_signers(&self) -> Vec<AccountId> {
+            self.result_signers.get().unwrap_or_default()
+        }
+
+        /// Set the share of each pot, in basis points, fed into the jackpot.
+        /// Only the pots of tables played in the native balance feed it.
+        /// error if the caller is not the owner.
+        /// error if the share exceeds 10000 basis points.
+        #[ink(message)]
+        pub fn set_jackpot_cut(&mut self, jackpot_bps: u16) -> Result<()> {
+            self.only_owner()?;
+            if jackpot_bps > MAX_BPS {
+                return Err(MetasinoError::InvalidRake);
+            }
+            self.jackpot_bps = jackpot_bps;
+            Ok(())
+        }
+
+        /// Add the value transferred along with the call to the progressive
+        /// jackpot, the only way to send the contract funds nothing is owed for.
+        /// error if nothing is transferred.
+        #[ink(message, payable)]
+        pub fn donate_to_jackpot(&mut self) -> Result<()> {
+            let amount = Self::env().transferred_value();
+            if amount == 0 {
+                return Err(MetasinoError::WrongBet);
+            }
+            self.jackpot = arithmetic::add(self.jackpot, amount)?;
+            Self::env().emit_event(JackpotDonated {
+                donor: Self::env().caller(),
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Compare the native funds the contract owes on its tables, to the
+        /// accounts, the house, the jackpot and in escrow with its balance.
+        #[ink(message)]
+        pub fn check_invariants(&self) -> InvariantReport {
+            let tables = (0..self.next_table_id)
+                .filter_map(|table_id| Some((table_id, self.tables.get(table_id)?)))
+                .filter(|(_, table)| table.token.is_none())
+                .map(|(table_id, table)| {
+                    self.players_of(table_id, &table)
+                        .iter()
+                        .map(|player| self.get_chips(table_id, *player))
+                        .fold(
+                            table.pot.saturating_add(table.bond).saturating_add(table.dealer_bond),
+                            Balance::saturating_add,
+                        )
+                })
+                .fold(0, Balance::saturating_add);
+            let escrowed = self.escrowed();
+            let owed = tables
+                .saturating_add(self.total_claimable)
+                .saturating_add(self.treasury)
+                .saturating_add(self.jackpot)
+                .saturating_add(escrowed);
+            let balance = Self::env().balance();
+            InvariantReport {
+                tables,
+                claimable: self.total_claimable,
+                treasury: self.treasury,
+                jackpot: self.jackpot,
+                escrowed,
+                balance,
+                solvent: balance >= owed,
+            }
+        }
+
+        /// Get the progressive jackpot accrued so far.
+        #[ink(message)]
+        pub fn get_jackpot(&self) -> Balance {
+            self.jackpot
+        }
+
+        /// Set the share of the rake, in basis points, the referrer of each
+        /// player earns out of the games of the player. Only the rake of the
+        /// tables played in the native balance is shared.
+        /// error if the caller is not the owner.
+        /// error if the share exceeds 10000 basis points.
+        #[ink(message)]
+        pub fn set_referral_cut(&mut self, referral_bps: u16) -> Result<()> {
+            self.only_owner()?;
+            if referral_bps > MAX_BPS {
+                return Err(MetasinoError::InvalidRake);
+            }
+            self.referral_bps = referral_bps;
+            Ok(())
+        }
+
+        /// Set the loyalty points redeemed for a unit of the native balance,
+        /// 0 disabling redemptions. A point is earned for every unit of rake.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_loyalty_rate(&mut self, points_per_unit: u128) -> Result<()> {
+            self.only_owner()?;
+            self.points_per_unit = points_per_unit;
+            Ok(())
+        }
+
+        /// Get the loyalty points of the player.
+        #[ink(message)]
+        pub fn get_loyalty_points(&self, player: AccountId) -> u128 {
+            self.loyalty_points.get(player).unwrap_or_default()
+        }
+
+        /// Redeem loyalty points of the caller for a rakeback credit or a free
+        /// entry into a tournament, both paid out of the treasury at the
+        /// loyalty rate. Rakeback is paid in whole units, the points left over
+        /// being kept.
+        /// error if redemptions are disabled.
+        /// error if the caller holds too few points, or redeems less than a unit.
+        /// error if the treasury cannot pay for the redemption.
+        /// error if the tournament cannot be entered.
+        #[ink(message)]
+        pub fn redeem_points(&mut self, redemption: Redemption) -> Result<()> {
+            if self.points_per_unit == 0 {
+                return Err(MetasinoError::WrongState);
+            }
+            let caller = Self::env().caller();
+            let (asked, points, value) = match redemption {
+                Redemption::Rakeback(asked) => {
+                    let value = asked / self.points_per_unit;
+                    (asked, value * self.points_per_unit, value)
+                }
+                Redemption::TournamentTicket(tournament_id) => {
+                    let buy_in = self.tournament(tournament_id)?.buy_in;
+                    let points = buy_in
+                        .checked_mul(self.points_per_unit)
+                        .ok_or(MetasinoError::ArithmeticError)?;
+                    (points, points, buy_in)
+                }
+            };
+            let held = self.get_loyalty_points(caller);
+            if value == 0 || asked > held {
+                return Err(MetasinoError::InsufficientChips);
+            }
+            let treasury = arithmetic::sub(self.treasury, value)?;
+            match redemption {
+                Redemption::Rakeback(_) => self.credit_withdrawal(caller, value)?,
+                Redemption::TournamentTicket(tournament_id) => {
+                    self.when_not_paused()?;
+                    let mut tournament = self.tournament(tournament_id)?;
+                    tournament.join(caller)?;
+                    self.tournaments.insert(tournament_id, &tournament);
+                    self.record_registration(caller, value);
+                }
+            }
+            self.treasury = treasury;
+            self.loyalty_points.insert(caller, &(held - points));
+            Self::env().emit_event(PointsRedeemed {
+                player: caller,
+                redemption,
+                points,
+                value,
+            });
+            Ok(())
+        }
+
+        /// Set the PSP34 contract minting the badges of the achievements, none
+        /// to stop minting them. The contract has to let Metasino mint and
+        /// should refuse transfers so that the badges stay soulbound.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_achievements_contract(&mut self, contract: Option<AccountId>) -> Result<()> {
+            self.only_owner()?;
+            self.achievements_contract = contract;
+            Ok(())
+        }
+
+        /// Get the progress of the account towards the achievements.
+        #[ink(message)]
+        pub fn get_progress(&self, account: AccountId) -> Progress {
+            self.progress.get(account).unwrap_or_default()
+        }
+
+        /// Mint the badges of the achievements the caller unlocked whose
+        /// minting failed or happened before the contract was configured.
+        /// error if no achievements contract is configured.
+        /// error if a badge cannot be minted.
+        #[ink(message)]
+        pub fn mint_badges(&mut self) -> Result<()> {
+            let contract = self.achievements_contract.ok_or(MetasinoError::WrongState)?;
+            let caller = Self::env().caller();
+            let mut progress = self.get_progress(caller);
+            for achievement in progress.unminted() {
+                psp34::mint(contract, caller, achievement.badge_id(&caller))?;
+                progress.minted.push(achievement);
+            }
+            self.progress.insert(caller, &progress);
+            Ok(())
+        }
+
+        /// Get the referrer of the player, if any.
+        #[ink(message)]
+        pub fn get_referrer(&self, player: AccountId) -> Option<AccountId> {
+            self.referrers.get(player)
+        }
+
+        /// Get the rake the account earned so far by referring players.
+        #[ink(message)]
+        pub fn get_referral_earnings(&self, account: AccountId) -> Balance {
+            self.referral_earnings.get(account).unwrap_or_default()
+        }
+
+        /// Withdraw the payouts and refunds in the native balance credited to
+        /// the caller, as much of them as the withdrawal caps leave. A balance
+        /// above the payout ceiling is queued instead, for an operator to
+        /// confirm once its timelock is over.
+        /// error if nothing is credited to the caller.
+        /// error if the caps of the block or of the caller are used up.
+        /// error if a withdrawal of the caller is already queued.
+        #[ink(message)]
+        pub fn withdraw_winnings(&mut self) -> Result<()> {
+            let caller = Self::env().caller();
+            let claimable = self.get_claimable(caller);
+            if claimable == 0 {
+                return Err(MetasinoError::InvalidPayout);
+            }
+            let ceiling = self.get_config().payout_ceiling;
+            if ceiling > 0 && claimable > ceiling {
+                return self.queue_withdrawal(caller, claimable)
+            }
+            let amount = self.withdrawal_allowance(caller).min(claimable);
+            if amount == 0 {
+                return Err(MetasinoError::WithdrawalThrottled);
+            }
+            Self::env()
+                .transfer(caller, amount)
+                .map_err(|_| MetasinoError::TransferFailed)?;
+            match claimable - amount {
+                0 => self.pending_withdrawals.remove(caller),
+                left => self.pending_withdrawals.insert(caller, &left),
+            }
+            self.total_claimable = self.total_claimable.saturating_sub(amount);
+            self.record_withdrawal(caller, amount);
+            Self::env().emit_event(WinningsWithdrawn {
+                account: caller,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Send the withdrawal of the account queued above the payout ceiling
+        /// to it, once its timelock is over.
+        /// error if the caller is not an operator.
+        /// error if no withdrawal of the account is queued.
+        /// error if the timelock is not over.
+        #[ink(message)]
+        pub fn confirm_withdrawal(&mut self, account: AccountId) -> Result<()> {
+            self.only_role(Role::Operator)?;
+            let (amount, release_at) = self
+                .queued_withdrawals
+                .get(account)
+                .ok_or(MetasinoError::WrongState)?;
+            if Self::env().block_number() < release_at {
+                return Err(MetasinoError::DeadlineNotReached);
+            }
+            Self::env()
+                .transfer(account, amount)
+                .map_err(|_| MetasinoError::TransferFailed)?;
+            self.queued_withdrawals.remove(account);
+            self.total_claimable = self.total_claimable.saturating_sub(amount);
+            self.record_withdrawal(account, amount);
+            Self::env().emit_event(WinningsWithdrawn { account, amount });
+            Ok(())
+        }
+
+        /// Veto the withdrawal of the account queued above the payout
+        /// ceiling, crediting its amount back for the account to withdraw.
+        /// error if the caller is not an operator.
+        /// error if no withdrawal of the account is queued.
+        #[ink(message)]
+        pub fn cancel_withdrawal(&mut self, account: AccountId) -> Result<()> {
+            self.only_role(Role::Operator)?;
+            let (amount, _) = self
+                .queued_withdrawals
+                .get(account)
+                .ok_or(MetasinoError::WrongState)?;
+            let pending = arithmetic::add(self.get_claimable(account), amount)?;
+            self.queued_withdrawals.remove(account);
+            self.pending_withdrawals.insert(account, &pending);
+            Self::env().emit_event(WithdrawalCancelled {
+                account,
+                operator: Self::env().caller(),
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Get the withdrawal of the account queued above the payout ceiling
+        /// and the block from which an operator may confirm it.
+        #[ink(message)]
+        pub fn get_queued_withdrawal(&self, account: AccountId) -> Option<(Balance, BlockNumber)> {
+            self.queued_withdrawals.get(account)
+        }
+
+        /// Set the caps of the withdrawals: the most withdrawn in a block and
+        /// by an account in an epoch, 0 lifting either, and the payout
+        /// ceiling above which a withdrawal waits `timelock` blocks for an
+        /// operator to confirm it, 0 lifting it.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_withdrawal_limits(
+            &mut self,
+            per_block: Balance,
+            per_account: Balance,
+            payout_ceiling: Balance,
+            timelock: BlockNumber,
+        ) -> Result<()> {
+            self.only_owner()?;
+            let mut config = self.get_config();
+            config.max_withdrawal_per_block = per_block;
+            config.max_withdrawal_per_account = per_account;
+            config.payout_ceiling = payout_ceiling;
+            config.payout_timelock_blocks = timelock;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Get the native balance the account may withdraw.
+        #[ink(message)]
+        pub fn get_claimable(&self, account: AccountId) -> Balance {
+            self.pending_withdrawals.get(account).unwrap_or_default()
+        }
+
+        /// Send the whole treasury of the house to the given account.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn withdraw_treasury(&mut self, to: AccountId) -> Result<()> {
+            self.only_owner()?;
+            let amount = self.treasury;
+            Self::env()
+                .transfer(to, amount)
+                .map_err(|_| MetasinoError::TransferFailed)?;
+            self.treasury = 0;
+            Self::env().emit_event(TreasuryWithdrawn {
+                token: None,
+                to,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Send the whole treasury of the house in the given PSP22 token to the given account.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn withdraw_token_treasury(&mut self, token: AccountId, to: AccountId) -> Result<()> {
+            self.only_owner()?;
+            let amount = self.token_treasury.get(token).unwrap_or_default();
+            psp22::transfer(token, to, amount)?;
+            self.token_treasury.remove(token);
+            if amount > 0 {
+                self.token_treasuries = self.token_treasuries.saturating_sub(1);
+            }
+            Self::env().emit_event(TreasuryWithdrawn {
+                token: Some(token),
+                to,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Suspend the opening of tables, the registrations and the game starts.
+        /// Players can still leave and ongoing games can still be played out.
+        /// error if the caller is not an operator.
+        /// error if the contract is already paused.
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            self.only_role(Role::Operator)?;
+            self.when_not_paused()?;
+            self.paused = true;
+            Self::env().emit_event(Paused {
+                account: Self::env().caller(),
+            });
+            Ok(())
+        }
+
+        /// Pause the contract if its balance no longer covers the native funds
+        /// it owes, see [`Metasino::check_invariants`]. Returns the report.
+        /// error if the caller is not an operator.
+        #[ink(message)]
+        pub fn halt_if_insolvent(&mut self) -> Result<InvariantReport> {
+            self.only_role(Role::Operator)?;
+            let report = self.check_invariants();
+            if !report.solvent && !self.paused {
+                self.paused = true;
+                Self::env().emit_event(Paused {
+                    account: Self::env().caller(),
+                });
+            }
+            Ok(report)
+        }
+
+        /// Lift the suspension set by [`Metasino::pause`].
+        /// error if the caller is not an operator.
+        /// error if the contract is not paused.
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            self.only_role(Role::Operator)?;
+            if !self.paused {
+                return Err(MetasinoError::WrongState);
+            }
+            self.paused = false;
+            Self::env().emit_event(Unpaused {
+                account: Self::env().caller(),
+            });
+            Ok(())
+        }
+
+        /// Replace the code of the contract, keeping its storage.
+        /// The new code should be followed by a call to [`Metasino::migrate`]
+        /// if it changes the storage layout.
+        /// error if the caller is not the owner.
+        /// error if no code is deployed with the given hash.
+        #[ink(message)]
+        pub fn upgrade(&mut self, code_hash: Hash) -> Result<()> {
+            self.only_owner()?;
+            let mut hash = [0; 32];
+            hash.copy_from_slice(code_hash.as_ref());
+            ink_env::set_code_hash(&hash).map_err(|_| MetasinoError::UpgradeFailed)?;
+            Self::env().emit_event(Upgraded { code_hash });
+            Ok(())
+        }
+
+        /// Terminate the contract once it is decommissioned, sending its whole
+        /// balance, the treasury included, to the beneficiary.
+        /// error if the caller is not the owner.
+        /// error if a table is open, holds funds or has accounts waiting.
+        /// error if a duel, lottery, market or tournament is still running.
+        /// error if an account still has a balance to withdraw, queued or not,
+        /// a creator stake locked or payouts held.
+        /// error if treasury funds are parked or held in PSP22 tokens.
+        #[ink(message)]
+        pub fn destroy_contract(&mut self, beneficiary: AccountId) -> Result<()> {
+            self.only_owner()?;
+            let tables = self
+                .summaries_where(|table| table.state != STATE::ENDED || table.pot > 0)
+                .next()
+                .is_some()
+                || (0..self.next_table_id).any(|table_id| {
+                    self.waitlists.get(table_id).is_some_and(|waitlist| !waitlist.is_empty())
+                });
+            let games = (0..self.next_duel_id)
+                .filter_map(|duel_id| self.duels.get(duel_id))
+                .any(|duel| matches!(duel.state, DuelState::Open | DuelState::Accepted))
+                || (0..self.next_lottery_id)
+                    .filter_map(|lottery_id| self.lotteries.get(lottery_id))
+                    .any(|lottery| !lottery.drawn)
+                || (0..self.next_market_id)
+                    .filter_map(|market_id| self.markets.get(market_id))
+                    .any(|market| market.result.is_none())
+                || (0..self.next_tournament_id)
+                    .filter_map(|tournament_id| self.tournaments.get(tournament_id))
+                    .any(|tournament| {
+                        !tournament.is_finished()
+                            && (!tournament.players.is_empty() || tournament.tickets_issued > 0)
+                    });
+            // Queued withdrawals stay counted in the claimable balances until confirmed.
+            let funds = self.total_claimable > 0
+                || self.parked > 0
+                || self.total_creator_stakes > 0
+                || self.held_games > 0
+                || self.token_treasuries > 0;
+            if tables || games || funds {
+                return Err(MetasinoError::WrongState);
+            }
+            Self::env().terminate_contract(beneficiary)
+        }
+
+        /// Bring the storage written by a previous code up to the layout of
+        /// this code, running every migration step in between, `limit`
+        /// tables and games at most per call so that each call fits in a
+        /// block. The owner calls it until the storage version is up to date,
+        /// the contract staying paused meanwhile. The tables and the funds
+        /// escrowed on them are kept as they are.
+        ///
+        /// The root of the contract is loaded before any message runs and the
+        /// root of version 1 does not load as that of this code, so that on
+        /// chain a contract of version 1 upgraded to this code fails every
+        /// message, this one included: see the [`migration`](crate::migration)
+        /// module.
+        /// error if the caller is not the owner.
+        /// error if the storage is already up to date.
+        #[ink(message)]
+        pub fn migrate(&mut self, limit: u32) -> Result<()> {
+            self.only_owner()?;
+            let from = self.storage_version;
+            if from >= STORAGE_VERSION {
+                return Err(MetasinoError::WrongState);
+            }
+            if from < 2 && !self.migrate_from_v1(limit) {
+                return Ok(())
+            }
+            self.storage_version = STORAGE_VERSION;
+            Self::env().emit_event(Migrated {
+                from,
+                to: STORAGE_VERSION,
+            });
+            Ok(())
+        }
+
+        /// Upgrade the storage of version 1 to version 2 by `limit` tables
+        /// and games, whether it is done. The first call moves the root, the
+        /// rake of the house going into the config, and pauses the contract,
+        /// the keys of the statistics of version 1 being those this code
+        /// keeps the last table opened by each account in. Every table,
+        /// along with its players, round and token treasury, and then every
+        /// game is moved to the keys of this code, and so are the statistics
+        /// of the accounts met, each entry being removed from its old key.
+        fn migrate_from_v1(&mut self, limit: u32) -> bool {
+            let old = v1::Root::new();
+            let (mut table_id, mut game_id) = match self.migration {
+                Some(next) => next,
+                None => {
+                    let root = v1::Root::load();
+                    self.owner = root.owner;
+                    self.treasury = root.treasury;
+                    self.paused = true;
+                    self.next_table_id = root.next_table_id;
+                    self.next_game_id = root.next_game_id;
+                    self.config.set(&Config {
+                        rake_bps: root.rake_bps,
+                        rake_cap: root.rake_cap,
+                        ..Config::default()
+                    });
+                    self.result_signers.set(&Vec::new());
+                    self.leaderboard.set(&root.leaderboard);
+                    self.season_prizes.set(&Vec::new());
+                    for account in root.leaderboard {
+                        self.migrate_stats_from_v1(&old, account);
+                    }
+                    (0, 0)
+                }
+            };
+            let block = Self::env().block_number();
+            let mut left = limit;
+            while table_id < self.next_table_id && left > 0 {
+                self.migrate_table_from_v1(&old, table_id, block);
+                table_id += 1;
+                left -= 1;
+            }
+            while game_id < self.next_game_id && left > 0 {
+                if let Some(game) = old.games.get(game_id) {
+                    old.games.remove(game_id);
+                    for player in &game.players {
+                        self.migrate_stats_from_v1(&old, *player);
+                    }
+                    self.games.insert(game_id, &game);
+                }
+                game_id += 1;
+                left -= 1;
+            }
+            let done = table_id >= self.next_table_id && game_id >= self.next_game_id;
+            self.migration = if done { None } else { Some((table_id, game_id)) };
+            done
+        }
+
+        /// Move the table of version 1, its players, round and token
+        /// treasury, the table being staged anew in `block` if staging.
+        fn migrate_table_from_v1(
+            &mut self,
+            old: &v1::Root,
+            table_id: TableId,
+            block: BlockNumber,
+        ) {
+            let table = match old.tables.get(table_id) {
+                Some(table) => table,
+                None => return,
+            };
+            old.tables.remove(table_id);
+            for seat in 0..table.max_players {
+                if let Some(player) = old.seats.get((table_id, seat)) {
+                    old.seats.remove((table_id, seat));
+                    self.seats.insert((table_id, seat), &player);
+                    if let Some(info) = old.players.get((table_id, player)) {
+                        old.players.remove((table_id, player));
+                        self.players.insert((table_id, player), &info.upgrade(block));
+                    }
+                    if let Some(commitment) = old.commitments.get((table_id, player)) {
+                        old.commitments.remove((table_id, player));
+                        self.commitments.insert((table_id, player), &commitment);
+                    }
+                    self.migrate_stats_from_v1(old, player);
+                }
+            }
+            if let Some(round) = old.rounds.get(table_id) {
+                old.rounds.remove(table_id);
+                self.rounds.insert(table_id, &round.upgrade(table.pot));
+            }
+            if let Some(token) = table.token {
+                if let Some(treasury) = old.token_treasury.get(token) {
+                    old.token_treasury.remove(token);
+                    if treasury > 0 {
+                        self.token_treasuries += 1;
+                    }
+                    self.token_treasury.insert(token, &treasury);
+                }
+            }
+            let hosted = self.hosted_tables.get(table.initializer).unwrap_or_default();
+            self.hosted_tables.insert(table.initializer, &(hosted + 1));
+            self.tables.insert(table_id, &table.upgrade(block));
+        }
+
+        /// Move the statistics of version 1 of the account, if not yet moved.
+        fn migrate_stats_from_v1(&mut self, old: &v1::Root, account: AccountId) {
+            if let Some(stats) = old.stats.get(account) {
+                old.stats.remove(account);
+                self.stats.insert(account, &stats.upgrade());
+            }
+        }
+
+        /// Get the version of the storage layout.
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> u16 {
+            self.storage_version
+        }
+
+        /// Get the version of the code deployed along with the games and the
+        /// randomness it is built with.
+        #[ink(message)]
+        pub fn get_version(&self) -> BuildInfo {
+            BuildInfo {
+                version: VERSION.to_string(),
+                storage_version: STORAGE_VERSION,
+                games: GAMES.iter().map(|game| game.to_string()).collect(),
+                chain_randomness: cfg!(feature = "chain-randomness"),
+            }
+        }
+
+        /// Check if the contract is paused.
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        /// Get the rake collected by the house so far.
+        #[ink(message)]
+        pub fn get_treasury(&self) -> Balance {
+            self.treasury
+        }
+
+        /// Set the strategy the idle treasury may be parked with, and the part
+        /// of the treasury always kept liquid for payouts. The jackpot is never
+        /// parked: it is paid out whole within the hand winning it, which
+        /// cannot wait on recalling funds from another contract.
+        /// error if the caller is not the owner.
+        /// error if funds are still parked with another strategy.
+        #[ink(message)]
+        pub fn set_yield_strategy(
+            &mut self,
+            strategy: Option<AccountId>,
+            buffer: Balance,
+        ) -> Result<()> {
+            self.only_owner()?;
+            let mut config = self.get_config();
+            if self.parked > 0 && strategy != config.yield_strategy {
+                return Err(MetasinoError::WrongState);
+            }
+            config.yield_strategy = strategy;
+            config.yield_buffer = buffer;
+            self.set_config(config);
+            Ok(())
+        }
+
+        /// Park `amount` of the treasury with the yield strategy.
+        /// error if the caller is not the owner.
+        /// error if no yield strategy is set.
+        /// error if the treasury would be left below the buffer.
+        /// error if the strategy does not take the funds.
+        #[ink(message)]
+        pub fn park_treasury(&mut self, amount: Balance) -> Result<()> {
+            self.only_owner()?;
+            let config = self.get_config();
+            let strategy = config.yield_strategy.ok_or(MetasinoError::WrongState)?;
+            let treasury = arithmetic::sub(self.treasury, amount)?;
+            if treasury < config.yield_buffer {
+                return Err(MetasinoError::YieldBufferBreached);
+            }
+            yield_strategy::deposit(strategy, amount)?;
+            self.treasury = treasury;
+            self.parked = arithmetic::add(self.parked, amount)?;
+            Self::env().emit_event(TreasuryParked { strategy, amount });
+            Ok(())
+        }
+
+        /// Bring `amount` back from the yield strategy into the treasury,
+        /// what is recalled past the funds parked being their earnings. Only
+        /// the funds the strategy actually sends back are credited.
+        /// error if the caller is not an operator.
+        /// error if no yield strategy is set.
+        /// error if the strategy does not send any funds back.
+        #[ink(message)]
+        pub fn recall_treasury(&mut self, amount: Balance) -> Result<()> {
+            self.only_role(Role::Operator)?;
+            let strategy = self.get_config().yield_strategy.ok_or(MetasinoError::WrongState)?;
+            let before = Self::env().balance();
+            yield_strategy::withdraw(strategy, amount)?;
+            let received = Self::env().balance().saturating_sub(before);
+            if received == 0 {
+                return Err(MetasinoError::StrategyFailed);
+            }
+            self.treasury = arithmetic::add(self.treasury, received)?;
+            self.parked = self.parked.saturating_sub(received);
+            Self::env().emit_event(TreasuryRecalled {
+                strategy,
+                amount: received,
+            });
+            Ok(())
+        }
+
+        /// Get the part of the treasury parked with the yield strategy.
+        #[ink(message)]
+        pub fn get_parked(&self) -> Balance {
+            self.parked
+        }
+
+        /// Get the funds the yield strategy holds for the contract, earnings
+        /// included.
+        /// error if no yield strategy is set or it cannot be queried.
+        #[ink(message)]
+        pub fn get_strategy_balance(&self) -> Result<Balance> {
+            let strategy = self.get_config().yield_strategy.ok_or(MetasinoError::WrongState)?;
+            yield_strategy::balance(strategy, Self::env().account_id())
+        }
+
+        /// Get the rake collected by the house so far in the given PSP22 token.
+        #[ink(message)]
+        pub fn get_token_treasury(&self, token: AccountId) -> Balance {
+            self.token_treasury.get(token).unwrap_or_default()
+        }
+
+        /// Get the account which owns the contract.
+        #[ink(message)]
+        pub fn get_owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Hand the contract over to a new owner, who has to accept it with
+        /// [`Metasino::accept_ownership`] before taking over. Handing it over
+        /// again replaces the account pending.
+        /// error if the caller is not the owner of the contract.
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
+            let caller = Self::env().caller();
+            if caller != self.owner {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            self.pending_owner = Some(new_owner);
+            Self::env().emit_event(OwnershipTransferStarted {
+                from: caller,
+                to: new_owner,
+            });
+            Ok(())
+        }
+
+        /// Take over the contract handed over to the caller.
+        /// error if the contract is not being handed over to the caller.
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let caller = Self::env().caller();
+            if self.pending_owner != Some(caller) {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            let from = self.owner;
+            self.owner = caller;
+            self.pending_owner = None;
+            Self::env().emit_event(OwnershipTransferred { from, to: caller });
+            Ok(())
+        }
+
+        /// Get the account the contract is being handed over to, if any.
+        #[ink(message)]
+        pub fn get_pending_owner(&self) -> Option<AccountId> {
+            self.pending_owner
+        }
+
+        /// Opens a new table where the caller joins as the first player.
+        /// The caller must transfer exactly the `required_start_bet` which is
+        /// escrowed by the contract as the initial pot, plus the table bond
+        /// if one is set, returned once the table closes cleanly.
+        /// If a PSP22 `token` is given, the table is played in that token: the
+        /// start bets and the bets are pulled from the players, who have to
+        /// approve the contract beforehand, and no native value is accepted.
+        /// The `game` played on the table is fixed for its whole life.
+        /// With a `join_code_hash`, players have to give the join code hashing
+        /// to it to register, see [`Metasino::compute_join_code_hash`].
+        /// With a `start_at` block, the game is advertised to start then and
+        /// anyone may start it from that block on, but nobody before.
+        /// In factory mode the table is opened in a contract of its own,
+        /// instantiated with the start bet and bond as endowment, and only in
+        /// the native balance.
+        /// error if the required start bet and bond are not transferred.
+        /// error if the required start bet is 0.
+        /// error if `min_players` is below 2, `max_players` is above 10 or
+        /// `min_players` exceeds `max_players`.
+        /// error if `start_at` is not in the future.
+        /// error if the caller hosts too many tables or opened one too recently.
+        /// error if the table is public and the caller locked too small a stake.
+        /// error if the table contract cannot be instantiated.
+        #[ink(message, payable)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn create_table(
+            &mut self,
+            required_start_bet: Balance,
+            min_players: u8,
+            max_players: u8,
+            token: Option<AccountId>,
+            game: GameKind,
+            join_code_hash: Option<Hash>,
+            start_at: Option<BlockNumber>,
+        ) -> Result<TableId> {
+            self.when_not_paused()?;
+            let caller = Self::env().caller();
+            let hosted =
+                self.check_table(caller, required_start_bet, min_players, max_players, start_at)?;
+            let config = self.get_config();
+            if join_code_hash.is_none() && self.get_creator_stake(caller) < config.min_creator_stake
+            {
+                return Err(MetasinoError::StakeTooLow);
+            }
+            let code_hash = match config.table_code_hash {
+                Some(code_hash) => code_hash,
+                None => {
+                    return self.open_table(
+                        caller,
+                        required_start_bet,
+                        min_players,
+                        max_players,
+                        token,
+                        game,
+                        join_code_hash,
+                        start_at,
+                    )
+                }
+            };
+            if token.is_some() {
+                return Err(MetasinoError::InvalidTableConfig);
+            }
+            let table_id = self.next_table_id;
+            let table: MetasinoRef = MetasinoRef::new_table(
+                self.owner,
+                config,
+                caller,
+                required_start_bet,
+                min_players,
+                max_players,
+                game,
+                join_code_hash,
+                start_at,
+            )
+            .code_hash(code_hash)
+            .gas_limit(0)
+            .endowment(Self::env().transferred_value())
+            .salt_bytes(table_id.to_le_bytes())
+            .instantiate()
+            .map_err(|_| MetasinoError::InstantiationFailed)?;
+            let table = ToAccountId::<Environment>::to_account_id(&table);
+            self.table_contracts.insert(table_id, &table);
+            self.hosted_tables.insert(caller, &(hosted + 1));
+            self.last_table_opened.insert(caller, &Self::env().block_number());
+            self.next_table_id += 1;
+            Self::env().emit_event(TableInstantiated {
+                table_id,
+                table,
+                initiator: caller,
+                version: VERSION.to_string(),
+            });
+            Ok(table_id)
+        }
+
+        /// Check the table the account opens against the protocol parameters.
+        /// Returns the number of tables the account already hosts.
+        fn check_table(
+            &self,
+            initializer: AccountId,
+            required_start_bet: Balance,
+            min_players: u8,
+            max_players: u8,
+            start_at: Option<BlockNumber>,
+        ) -> Result<u32> {
+            let config = self.get_config();
+            if min_players < MIN_PLAYERS
+                || max_players > config.max_players
+                || min_players > max_players
+                || start_at.is_some_and(|start_at| start_at <= Self::env().block_number())
+            {
+                return Err(MetasinoError::InvalidTableConfig);
+            }
+            if required_start_bet == 0
+                || required_start_bet < config.min_start_bet
+                || required_start_bet > config.max_start_bet
+            {
+                return Err(MetasinoError::WrongBet);
+            }
+            let hosted = self.hosted_tables.get(initializer).unwrap_or_default();
+            if config.max_tables_per_account > 0 && hosted >= config.max_tables_per_account {
+                return Err(MetasinoError::TooManyTables);
+            }
+            let cooldown = config.table_cooldown_blocks;
+            let cooling = self.last_table_opened.get(initializer).is_some_and(|opened| {
+                Self::env().block_number() < opened.saturating_add(cooldown)
+            });
+            if cooldown > 0 && cooling {
+                return Err(MetasinoError::CooldownActive);
+            }
+            Ok(hosted)
+        }
+
+        /// Open a table checked by [`Metasino::check_table`] in this contract,
+        /// seating the initializer with the start bet.
+        #[allow(clippy::too_many_arguments)]
+        fn open_table(
+            &mut self,
+            initializer: AccountId,
+            required_start_bet: Balance,
+            min_players: u8,
+            max_players: u8,
+            token: Option<AccountId>,
+            game: GameKind,
+            join_code_hash: Option<Hash>,
+            start_at: Option<BlockNumber>,
+        ) -> Result<TableId> {
+            let hosted = self.hosted_tables.get(initializer).unwrap_or_default();
+            let table_id = self.next_table_id;
+            let mut table = self.new_table_record(
+                initializer,
+                required_start_bet,
+                min_players,
+                max_players,
+                token,
+                game,
+                join_code_hash,
+                start_at,
+            );
+            let deposit = arithmetic::add(required_start_bet, table.bond)?;
+            self.collect(&table, initializer, deposit)?;
+            self.seat_player(table_id, &mut table, initializer);
+            self.record_registration(initializer, required_start_bet);
+            self.tables.insert(table_id, &table);
+            self.hosted_tables.insert(initializer, &(hosted + 1));
+            self.last_table_opened.insert(initializer, &Self::env().block_number());
+            self.next_table_id += 1;
+            Self::env().emit_event(NewTableOpened {
+                table_id,
+                initiator: initializer,
+                required_start_bet,
+                version: VERSION.to_string(),
+            });
+            Ok(table_id)
+        }
+
+        /// A table staging in this contract with nobody seated yet, its pot
+        /// holding the start bet of the initializer.
+        #[allow(clippy::too_many_arguments)]
+        fn new_table_record(
+            &self,
+            initializer: AccountId,
+            required_start_bet: Balance,
+            min_players: u8,
+            max_players: u8,
+            token: Option<AccountId>,
+            game: GameKind,
+            join_code_hash: Option<Hash>,
+            start_at: Option<BlockNumber>,
+        ) -> Table {
+            let config = self.get_config();
+            Table {
+                initializer,
+                seated: 0,
+                required_start_bet,
+                pot: required_start_bet,
+                state: STATE::STAGING,
+                min_players,
+                max_players,
+                reveal_deadline: 0,
+                revealed: 0,
+                entropy: [0; 32],
+                seed: None,
+                rake_bps: config.rake_bps,
+                rake_cap: config.rake_cap,
+                rake_recipient: None,
+                token,
+                game,
+                max_rebuys: 0,
+                rebuy_window: 0,
+                button: 0,
+                small_blind: 0,
+                big_blind: 0,
+                ante: 0,
+                straddler: None,
+                hand_number: 0,
+                variant: Variant::Holdem,
+                structure: BettingStructure::NoLimit,
+                is_private: false,
+                join_code_hash,
+                start_at,
+                sit_and_go: false,
+                freeroll: false,
+                staged_at: Self::env().block_number(),
+                arbiter: None,
+                min_reputation: None,
+                bond: config.table_bond,
+                dealer_nominee: None,
+                dealer: None,
+                dealer_bond: 0,
+                dealer_deadline: None,
+            }
+        }
+
+        /// Open a poker table whose pot is a `prize` taken out of the
+        /// treasury, where up to `max_players` register for free. An account
+        /// registers for a limited number of freerolls per epoch. The table
+        /// takes no rake and gives the prize back to the treasury if it
+        /// closes before its game.
+        /// error if the caller is not the owner.
+        /// error if the prize is 0 or exceeds the treasury.
+        /// error if `max_players` is below 2 or above the most players allowed.
+        #[ink(message)]
+        pub fn create_freeroll(&mut self, prize: Balance, max_players: u8) -> Result<TableId> {
+            self.only_owner()?;
+            self.when_not_paused()?;
+            if !(MIN_PLAYERS..=self.get_config().max_players).contains(&max_players) {
+                return Err(MetasinoError::InvalidTableConfig);
+            }
+            if prize == 0 {
+                return Err(MetasinoError::WrongBet);
+            }
+            let treasury = arithmetic::sub(self.treasury, prize)?;
+            let caller = Self::env().caller();
+            let table_id = self.next_table_id;
+            let mut table = self.new_table_record(
+                caller,
+                0,
+                MIN_PLAYERS,
+                max_players,
+                None,
+                GameKind::Poker,
+                None,
+                None,
+            );
+            table.pot = prize;
+            table.rake_bps = 0;
+            table.rake_cap = 0;
+            table.bond = 0;
+            table.freeroll = true;
+            self.treasury = treasury;
+            self.tables.insert(table_id, &table);
+            self.next_table_id += 1;
+            Self::env().emit_event(NewTableOpened {
+                table_id,
+                initiator: caller,
+                required_start_bet: 0,
+                version: VERSION.to_string(),
+            });
+            Ok(table_id)
+        }
+
+        /// Count a freeroll the account registers for in the current epoch.
+        /// error if the account registered for every freeroll it may.
+        fn enter_freeroll(&mut self, account: AccountId) -> Result<()> {
+            let epoch = Self::env().block_number() / FREEROLL_EPOCH;
+            let entered = match self.freeroll_entries.get(account) {
+                Some((last, entered)) if last == epoch => entered,
+                _ => 0,
+            };
+            if entered >= FREEROLLS_PER_EPOCH {
+                return Err(MetasinoError::FreerollLimitReached);
+            }
+            self.freeroll_entries.insert(account, &(epoch, entered + 1));
+            Ok(())
+        }
+
+        /// Give back the freeroll the account entered in the current epoch
+        /// and left before its game. Does nothing if the table is no freeroll.
+        fn leave_freeroll(&mut self, table: &Table, account: AccountId) {
+            if !table.freeroll {
+                return;
+            }
+            let epoch = Self::env().block_number() / FREEROLL_EPOCH;
+            if let Some((last, entered)) = self.freeroll_entries.get(account) {
+                if last == epoch && entered > 0 {
+                    self.freeroll_entries.insert(account, &(epoch, entered - 1));
+                }
+            }
+        }
+
+        /// Give the prize of the freeroll closing before its game back to the
+        /// treasury.
+        fn return_prize(&mut self, table: &mut Table) -> Result<()> {
+            if table.freeroll {
+                self.treasury = arithmetic::add(self.treasury, table.pot)?;
+                table.pot = 0;
+            }
+            Ok(())
+        }
+
+        /// Terminate the table before the game starts.
+        /// Every player gets their start bet refunded and the table is emptied.
+        /// An operator may terminate any table stuck before its game.
+        /// error if the caller is neither the initializer nor an operator.
+        #[ink(message)]
+        pub fn terminate(&mut self, table_id: TableId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer_or(&table, Role::Operator)?;
+            table.status_guard()?;
+            let players = self.players_of(table_id, &table);
+            let mut refunds: Vec<Balance> = Vec::with_capacity(players.len());
+            for player in players.iter() {
+                let refund = self.stake_of(table_id, &table, *player)?;
+                self.pay(&table, *player, refund)?;
+                self.record_refund(*player, refund);
+                self.leave_freeroll(&table, *player);
+                refunds.push(refund);
+            }
+            Self::env().emit_event(TableTerminated {
+                table_id,
+                players,
+                refunds,
+            });
+            self.return_bond(table_id, &mut table)?;
+            self.return_prize(&mut table)?;
+
+            self.clear_players(table_id, &mut table);
+            self.spectators.remove(table_id);
+            table.reset();
+            self.set_state(table_id, &mut table, STATE::ENDED);
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Unwind a table stuck in any state while the contract is paused, as
+        /// a last resort against a bug deadlocking its game. Every player gets
+        /// back exactly what they put into the pot, along with their chips,
+        /// the accounts waiting for a seat their escrowed start bet and the
+        /// initializer their bond. What is left of the pot goes to the house.
+        /// error if the caller is not the owner.
+        /// error if the contract is not paused or the table already ended.
+        /// error if the contributions recorded exceed the pot.
+        #[ink(message)]
+        pub fn emergency_refund_all(&mut self, table_id: TableId) -> Result<()> {
+            self.only_owner()?;
+            let mut table = self.table(table_id)?;
+            if !self.paused || table.state == STATE::ENDED {
+                return Err(MetasinoError::WrongState);
+            }
+            let players = self.players_of(table_id, &table);
+            let contributions = players
+                .iter()
+                .enumerate()
+                .map(|(seat, player)| self.contribution_of(table_id, &table, seat, *player))
+                .collect::<Result<Vec<Balance>>>()?;
+            table.pot = arithmetic::sub(table.pot, arithmetic::sum(&contributions)?)?;
+            let mut refunds: Vec<Balance> = Vec::with_capacity(players.len());
+            for (player, contribution) in players.iter().zip(contributions) {
+                let refund = arithmetic::add(contribution, self.get_chips(table_id, *player))?;
+                self.chips.remove((table_id, *player));
+                self.pay(&table, *player, refund)?;
+                self.record_refund(*player, contribution);
+                if table.state == STATE::STAGING {
+                    self.leave_freeroll(&table, *player);
+                }
+                refunds.push(refund);
+            }
+            for account in self.waitlists.get(table_id).unwrap_or_default() {
+                self.pay(&table, account, table.required_start_bet)?;
+                self.leave_freeroll(&table, account);
+            }
+            self.return_bond(table_id, &mut table)?;
+            self.return_prize(&mut table)?;
+            let bank = arithmetic::add(self.house_bank(&table), table.pot)?;
+            self.set_house_bank(&table, bank);
+            Self::env().emit_event(EmergencyRefunded {
+                table_id,
+                players,
+                refunds,
+            });
+
+            self.clear_players(table_id, &mut table);
+            self.waitlists.remove(table_id);
+            self.spectators.remove(table_id);
+            self.rounds.remove(table_id);
+            self.blackjack_rounds.remove(table_id);
+            self.dice_rounds.remove(table_id);
+            self.run_twice_votes.remove(table_id);
+            table.reset();
+            self.set_state(table_id, &mut table, STATE::ENDED);
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Close a table left staging for longer than the staging timeout.
+        /// Every player gets their start bet, bets and chips refunded, the
+        /// accounts waiting for a seat their escrowed start bet, and the table
+        /// is deleted. The bond of the initializer is slashed to the other
+        /// players. Anyone may expire a stale table.
+        /// error if the table is not staging.
+        /// error if the staging timeout has not elapsed or is not set.
+        #[ink(message)]
+        pub fn expire_table(&mut self, table_id: TableId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            if table.state != STATE::STAGING {
+                return Err(MetasinoError::WrongState);
+            }
+            let timeout = self.get_config().staging_timeout_blocks;
+            let expires_at = table.staged_at.saturating_add(timeout);
+            if timeout == 0 || Self::env().block_number() < expires_at {
+                return Err(MetasinoError::DeadlineNotReached);
+            }
+            let players = self.players_of(table_id, &table);
+            let mut stakes: Vec<Balance> = Vec::with_capacity(players.len());
+            let mut refunds: Vec<Balance> = Vec::with_capacity(players.len());
+            for player in players.iter() {
+                let stake = self.stake_of(table_id, &table, *player)?;
+                let chips = self.get_chips(table_id, *player);
+                stakes.push(stake);
+                refunds.push(arithmetic::add(stake, chips)?);
+            }
+            for ((player, stake), refund) in players.iter().zip(&stakes).zip(&refunds) {
+                self.chips.remove((table_id, *player));
+                self.pay(&table, *player, *refund)?;
+                self.record_refund(*player, *stake);
+                self.leave_freeroll(&table, *player);
+            }
+            for account in self.waitlists.get(table_id).unwrap_or_default() {
+                self.pay(&table, account, table.required_start_bet)?;
+                self.leave_freeroll(&table, account);
+            }
+            self.slash_bond(table_id, &mut table)?;
+            self.return_prize(&mut table)?;
+            Self::env().emit_event(TableExpired {
+                table_id,
+                players,
+                refunds,
+            });
+
+            self.clear_players(table_id, &mut table);
+            self.waitlists.remove(table_id);
+            self.spectators.remove(table_id);
+            self.rounds.remove(table_id);
+            self.blackjack_rounds.remove(table_id);
+            self.dice_rounds.remove(table_id);
+            if !table.freeroll {
+                let hosted = self.hosted_tables.get(table.initializer).unwrap_or_default();
+                self.hosted_tables
+                    .insert(table.initializer, &hosted.saturating_sub(1));
+            }
+            self.tables.remove(table_id);
+            Ok(())
+        }
+
+        /// Register new player into the table.
+        /// The start bet is the value transferred along with the call, or
+        /// pulled from the caller if the table is played in a PSP22 token.
+        /// Any value transferred above the start bet is credited back to the
+        /// caller to withdraw.
+        /// The `join_code` is only needed by the tables opened with one.
+        /// The `referrer` earns a share of the rake of the games of the player
+        /// from then on, a player keeping the first referrer they give.
+        /// error if the player is already registered.
+        /// error if the table is full.
+        /// error if the table is private and the player is not invited.
+        /// error if the join code is missing or wrong.
+        /// error if new player transfers less than the required start bet.
+        /// error if the player refers themselves.
+        #[ink(message, payable)]
+        pub fn register_player(
+            &mut self,
+            table_id: TableId,
+            join_code: Option<Vec<u8>>,
+            referrer: Option<AccountId>,
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.when_not_paused()?;
+            table.registration_guard()?;
+            let caller = Self::env().caller();
+            if table.is_full() {
+                return Err(MetasinoError::TableFull);
+            }
+            if self.players.get((table_id, caller)).is_some() {
+                return Err(MetasinoError::AlreadyRegistered);
+            }
+            self.only_admitted(table_id, &table, join_code)?;
+            if referrer == Some(caller) {
+                return Err(MetasinoError::InvalidAction);
+            }
+            if table.freeroll {
+                self.enter_freeroll(caller)?;
+            }
+
+            let start_bet = table.required_start_bet;
+            let pot = arithmetic::add(table.pot, start_bet)?;
+            self.collect(&table, caller, start_bet)?;
+            if table.state == STATE::ENDED {
+                self.set_state(table_id, &mut table, STATE::STAGING);
+            }
+            table.pot = pot;
+            self.seat_player(table_id, &mut table, caller);
+            self.record_registration(caller, start_bet);
+            if let Some(referrer) = referrer {
+                self.record_referrer(caller, referrer);
+            }
+            self.tables.insert(table_id, &table);
+            Self::env().emit_event(PlayerRegistered {
+                table_id,
+                player: caller,
+                start_bet,
+            });
+            self.announce_seating(table_id, &table, caller);
+            self.auto_start(table_id, &mut table);
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Wait for a seat at the full table, escrowing the start bet as for
+        /// [`Metasino::register_player`]. The first account waiting is seated
+        /// as soon as a seat frees up and the table is not in play.
+        /// error if the table has a free seat to register in.
+        /// error if the caller is already registered or waiting.
+        /// error if as many accounts wait for the table as may.
+        /// error if the caller is not admitted to the table.
+        /// error if the value transferred falls short of the start bet.
+        #[ink(message, payable)]
+        pub fn join_waitlist(
+            &mut self,
+            table_id: TableId,
+            join_code: Option<Vec<u8>>,
+        ) -> Result<()> {
+            let table = self.table(table_id)?;
+            self.when_not_paused()?;
+            if !table.is_full() {
+                return Err(MetasinoError::WrongState);
+            }
+            let caller = Self::env().caller();
+            let mut waitlist = self.waitlists.get(table_id).unwrap_or_default();
+            if self.players.contains((table_id, caller)) || waitlist.contains(&caller) {
+                return Err(MetasinoError::AlreadyRegistered);
+            }
+            if waitlist.len() >= MAX_WAITLIST {
+                return Err(MetasinoError::TableFull);
+            }
+            self.only_admitted(table_id, &table, join_code)?;
+            if table.freeroll {
+                self.enter_freeroll(caller)?;
+            }
+            self.collect(&table, caller, table.required_start_bet)?;
+            waitlist.push(caller);
+            self.waitlists.insert(table_id, &waitlist);
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Stop waiting for a seat at the table and get the start bet back,
+        /// along with the freeroll entered by waiting for a freeroll.
+        /// error if the caller is not waiting for the table.
+        #[ink(message)]
+        pub fn leave_waitlist(&mut self, table_id: TableId) -> Result<()> {
+            let table = self.table(table_id)?;
+            let caller = Self::env().caller();
+            let mut waitlist = self.waitlists.get(table_id).unwrap_or_default();
+            let position = waitlist
+                .iter()
+                .position(|account| *account == caller)
+                .ok_or(MetasinoError::NotAuthorized)?;
+            waitlist.remove(position);
+            self.waitlists.insert(table_id, &waitlist);
+            self.leave_freeroll(&table, caller);
+            self.bump_nonce(caller);
+            self.pay(&table, caller, table.required_start_bet)
+        }
+
+        /// Get the accounts waiting for a seat at the table, first to be seated first.
+        #[ink(message)]
+        pub fn get_waitlist(&self, table_id: TableId) -> Vec<AccountId> {
+            self.waitlists.get(table_id).unwrap_or_default()
+        }
+
+        /// Bet `amount` on the outcome of the spin of a roulette table before its
+        /// game starts. The amount is taken as the start bets are.
+        /// error if the caller is not a player.
+        /// error if the table is not a roulette table or its game has started.
+        /// error if the bet designates no pocket or nothing is bet.
+        #[ink(message, payable)]
+        pub fn place_bet(
+            &mut self,
+            table_id: TableId,
+            bet: RouletteBet,
+            amount: Balance,
+        ) -> Result<()> {
+            self.place_bet_as(table_id, Self::env().caller(), bet, amount)
+        }
+
+        /// [`Metasino::place_bet`] played by `caller`.
+        fn place_bet_as(
+            &mut self,
+            table_id: TableId,
+            caller: AccountId,
+            bet: RouletteBet,
+            amount: Balance,
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            if self.players.get((table_id, caller)).is_none() {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            table.status_guard()?;
+            if table.game != GameKind::Roulette {
+                return Err(MetasinoError::WrongState);
+            }
+            if !bet.is_valid() {
+                return Err(MetasinoError::InvalidAction);
+            }
+            if amount == 0 {
+                return Err(MetasinoError::WrongBet);
+            }
+            // The bet could not be paid if it won.
+            if amount.checked_mul(bet.odds() + 1).is_none() {
+                return Err(MetasinoError::ArithmeticError);
+            }
+            let pot = arithmetic::add(table.pot, amount)?;
+            self.put_in(table_id, &table, caller, amount)?;
+            let mut wagers = self.wagers.get((table_id, caller)).unwrap_or_default();
+            wagers.push(Wager { bet, amount });
+            self.wagers.insert((table_id, caller), &wagers);
+            self.record_wager(caller, amount);
+            table.pot = pot;
+            self.tables.insert(table_id, &table);
+            Self::env().emit_event(BetPlaced {
+                table_id,
+                player: caller,
+                bet,
+                amount,
+            });
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Bet `amount` on the outcome of the coup of a baccarat table before
+        /// its game starts. The amount is taken as the start bets are.
+        /// error if the caller is not a player.
+        /// error if the table is not a baccarat table or its game has started.
+        /// error if nothing is bet.
+        #[ink(message, payable)]
+        pub fn place_baccarat_bet(
+            &mut self,
+            table_id: TableId,
+            bet: BaccaratBet,
+            amount: Balance,
+        ) -> Result<()> {
+            self.place_baccarat_bet_as(table_id, Self::env().caller(), bet, amount)
+        }
+
+        /// [`Metasino::place_baccarat_bet`] played by `caller`.
+        fn place_baccarat_bet_as(
+            &mut self,
+            table_id: TableId,
+            caller: AccountId,
+            bet: BaccaratBet,
+            amount: Balance,
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            if self.players.get((table_id, caller)).is_none() {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            table.status_guard()?;
+            if table.game != GameKind::Baccarat {
+                return Err(MetasinoError::WrongState);
+            }
+            if amount == 0 {
+                return Err(MetasinoError::WrongBet);
+            }
+            // The bet could not be paid if it won.
+            if amount.checked_mul(bet.odds() + 1).is_none() {
+                return Err(MetasinoError::ArithmeticError);
+            }
+            let pot = arithmetic::add(table.pot, amount)?;
+            self.put_in(table_id, &table, caller, amount)?;
+            let mut bets = self.baccarat_bets.get((table_id, caller)).unwrap_or_default();
+            bets.push(BaccaratWager { bet, amount });
+            self.baccarat_bets.insert((table_id, caller), &bets);
+            self.record_wager(caller, amount);
+            table.pot = pot;
+            self.tables.insert(table_id, &table);
+            Self::env().emit_event(BaccaratBetPlaced {
+                table_id,
+                player: caller,
+                bet,
+                amount,
+            });
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Buy chips on the table for `amount`, the value transferred along with
+        /// the call or pulled from the caller if the table is played in a PSP22
+        /// token. A chip is worth a unit of the currency of the table, and
+        /// bets are taken out of the chips instead of being paid one by one.
+        /// error if the caller is not a player.
+        /// error if the value transferred falls short of the amount.
+        #[ink(message, payable)]
+        pub fn buy_chips(&mut self, table_id: TableId, amount: Balance) -> Result<()> {
+            let table = self.table(table_id)?;
+            self.only_player(table_id)?;
+            if amount == 0 {
+                return Err(MetasinoError::WrongBet);
+            }
+            let caller = Self::env().caller();
+            self.collect(&table, caller, amount)?;
+            self.credit_chips(table_id, caller, amount)?;
+            Self::env().emit_event(ChipsBought {
+                table_id,
+                player: caller,
+                amount,
+            });
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Convert every chip the caller holds on the table back into the
+        /// currency of the table.
+        /// error if the caller is in the game being played on the table.
+        #[ink(message)]
+        pub fn cash_out(&mut self, table_id: TableId) -> Result<()> {
+            let table = self.table(table_id)?;
+            let caller = Self::env().caller();
+            if table.state == STATE::PLAYING && self.players.contains((table_id, caller)) {
+                return Err(MetasinoError::WrongState);
+            }
+            let amount = self.get_chips(table_id, caller);
+            if amount == 0 {
+                return Err(MetasinoError::InsufficientChips);
+            }
+            self.chips.remove((table_id, caller));
+            self.pay(&table, caller, amount)?;
+            Self::env().emit_event(CashedOut {
+                table_id,
+                player: caller,
+                amount,
+            });
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Let each player rebuy up to `max_rebuys` times within `rebuy_window`
+        /// blocks of registering, before the game starts.
+        /// error if the caller is not the initializer.
+        /// error if the game of the table started.
+        #[ink(message)]
+        pub fn set_rebuy_limits(
+            &mut self,
+            table_id: TableId,
+            max_rebuys: u8,
+            rebuy_window: BlockNumber,
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            table.registration_guard()?;
+            table.max_rebuys = max_rebuys;
+            table.rebuy_window = rebuy_window;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Rebuy chips for `amount`, up to the start bet of the table, paid as
+        /// for [`Metasino::buy_chips`].
+        /// error if the caller is not a player.
+        /// error if the game started, the rebuy window of the caller is over or
+        /// they made every rebuy allowed.
+        /// error if the amount is 0, above the start bet or not transferred.
+        #[ink(message, payable)]
+        pub fn rebuy(&mut self, table_id: TableId, amount: Balance) -> Result<()> {
+            let table = self.table(table_id)?;
+            let caller = Self::env().caller();
+            let mut info = self
+                .players
+                .get((table_id, caller))
+                .ok_or(MetasinoError::NotAuthorized)?;
+            table.status_guard()?;
+            let window_end = info.registered_at.saturating_add(table.rebuy_window);
+            if info.rebuys >= table.max_rebuys || Self::env().block_number() > window_end {
+                return Err(MetasinoError::WrongState);
+            }
+            if amount == 0 || amount > table.required_start_bet {
+                return Err(MetasinoError::WrongBet);
+            }
+            self.collect(&table, caller, amount)?;
+            self.credit_chips(table_id, caller, amount)?;
+            info.rebuys += 1;
+            self.players.insert((table_id, caller), &info);
+            Self::env().emit_event(PlayerRebought {
+                table_id,
+                player: caller,
+                amount,
+                rebuys: info.rebuys,
+            });
+            Ok(())
+        }
+
+        /// Set the blinds the two seats after the dealer button post out of
+        /// their chips at the start of each poker hand. A big blind of 0 plays
+        /// without blinds.
+        /// error if the caller is not the initializer.
+        /// error if the game of the table started.
+        /// error if the small blind exceeds the big blind.
+        #[ink(message)]
+        pub fn set_blinds(
+            &mut self,
+            table_id: TableId,
+            small_blind: Balance,
+            big_blind: Balance,
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            table.registration_guard()?;
+            if small_blind > big_blind {
+                return Err(MetasinoError::InvalidTableConfig);
+            }
+            table.small_blind = small_blind;
+            table.big_blind = big_blind;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Set the arbiter ruling on the disputes raised over the games of the
+        /// table, such as a DAO or a court contract, or leave them to the
+        /// operators with `None`.
+        /// error if the caller is not the initializer.
+        /// error if the game of the table started.
+        #[ink(message)]
+        pub fn set_arbiter(&mut self, table_id: TableId, arbiter: Option<AccountId>) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            table.registration_guard()?;
+            table.arbiter = arbiter;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Require the accounts registering at the table to have at least the
+        /// given reputation, or let anyone register with `None`.
+        /// error if the caller is not the initializer.
+        /// error if the game of the table started.
+        #[ink(message)]
+        pub fn set_min_reputation(
+            &mut self,
+            table_id: TableId,
+            min_reputation: Option<i32>,
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            table.registration_guard()?;
+            table.min_reputation = min_reputation;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Set the ante every seat puts into the pot out of its chips at the
+        /// start of each poker hand, before the blinds are posted. An ante of
+        /// 0 plays without antes.
+        /// error if the caller is not the initializer.
+        /// error if the game of the table started.
+        #[ink(message)]
+        pub fn set_ante(&mut self, table_id: TableId, ante: Balance) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            table.registration_guard()?;
+            table.ante = ante;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Set the rake of the table, in basis points of each pot up to
+        /// `rake_cap` a game, and the account it is paid to, less the cut of
+        /// the protocol. The house takes the rake if no recipient is given.
+        /// error if the caller is not the initializer.
+        /// error if the game of the table has started.
+        /// error if the rake exceeds the most the owner allows.
+        #[ink(message)]
+        pub fn set_table_rake(
+            &mut self,
+            table_id: TableId,
+            rake_bps: u16,
+            rake_cap: Balance,
+            rake_recipient: Option<AccountId>,
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            table.registration_guard()?;
+            if rake_bps > self.get_config().max_table_rake_bps {
+                return Err(MetasinoError::InvalidRake);
+            }
+            table.rake_bps = rake_bps;
+            table.rake_cap = rake_cap;
+            table.rake_recipient = rake_recipient;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Nominate the account, possibly a bot, dealing the table in place of
+        /// its players once it accepts with [`Metasino::accept_dealer`], or
+        /// have the players deal the table again with `None`. The bond of the
+        /// dealer replaced is returned to it.
+        /// error if the caller is not the initializer.
+        /// error if the game of the table started.
+        #[ink(message)]
+        pub fn set_dealer(&mut self, table_id: TableId, dealer: Option<AccountId>) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            table.registration_guard()?;
+            if table.dealer.is_some() {
+                self.dismiss_dealer(&mut table)?;
+                Self::env().emit_event(DealerSet {
+                    table_id,
+                    dealer: None,
+                    bond: 0,
+                });
+            }
+            table.dealer_nominee = dealer;
+            Self::env().emit_event(DealerNominated { table_id, dealer });
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Accept the nomination to deal the table, posting the dealer bond
+        /// transferred along. Only the dealer then starts the games, closes
+        /// their reveal phase, deals the next hands, rolls the dice and
+        /// settles the showdowns. The bond is returned once the table closes
+        /// or the initializer changes dealer. A dealer not dealing within the
+        /// dealer timeout of a deal falling due is replaced by the first
+        /// account dealing in its place, its bond going to the players.
+        /// error if the caller is not nominated to deal the table.
+        /// error if the game of the table started.
+        /// error if the dealer bond is not transferred.
+        #[ink(message, payable)]
+        pub fn accept_dealer(&mut self, table_id: TableId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            let caller = Self::env().caller();
+            if table.dealer_nominee != Some(caller) {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            table.registration_guard()?;
+            let bond = self.get_config().dealer_bond;
+            self.collect(&table, caller, bond)?;
+            table.dealer_nominee = None;
+            table.dealer = Some(caller);
+            table.dealer_bond = bond;
+            table.dealer_deadline = None;
+            self.await_start(table_id, &mut table);
+            Self::env().emit_event(DealerSet {
+                table_id,
+                dealer: Some(caller),
+                bond,
+            });
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Set the poker variant dealt on the table: Texas hold'em, Omaha
+        /// with four hole cards, short-deck with the 2s to 5s stripped, or
+        /// seven-card stud. A stud table brings each hand in with the small
+        /// blind.
+        /// error if the caller is not the initializer.
+        /// error if the table is not a poker table or its game has started.
+        /// error if a stud table seats more than `stud::MAX_SEATS` players.
+        #[ink(message)]
+        pub fn set_variant(&mut self, table_id: TableId, variant: Variant) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            table.registration_guard()?;
+            if table.game != GameKind::Poker {
+                return Err(MetasinoError::WrongState);
+            }
+            if variant == Variant::Stud && table.max_players > stud::MAX_SEATS {
+                return Err(MetasinoError::InvalidTableConfig);
+            }
+            table.variant = variant;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Set how much the raises of the poker hands of the table are limited
+        /// to. Fixed-limit tables bet the big blind as the small bet, and play
+        /// without limit if the big blind is 0.
+        /// error if the caller is not the initializer.
+        /// error if the table is not a poker table or its game has started.
+        #[ink(message)]
+        pub fn set_betting_structure(
+            &mut self,
+            table_id: TableId,
+            structure: BettingStructure,
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            table.registration_guard()?;
+            if table.game != GameKind::Poker {
+                return Err(MetasinoError::WrongState);
+            }
+            table.structure = structure;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Straddle the next poker hand of the table, posting twice the big
+        /// blind out of the chips of the caller along with the blinds. The
+        /// pre-flop then starts after the caller, who acts last on it.
+        /// Only the seat left of the big blind may straddle, before the cards
+        /// are dealt, and nobody heads-up.
+        /// error if the caller is not a player.
+        /// error if the table plays no blinds or the cards are dealt.
+        /// error if the caller is not left of the big blind.
+        /// error if the hand is already straddled.
+        #[ink(message)]
+        pub fn post_straddle(&mut self, table_id: TableId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            let caller = Self::env().caller();
+            let info = self
+                .players
+                .get((table_id, caller))
+                .ok_or(MetasinoError::NotAuthorized)?;
+            let dealing = matches!(
+                table.state,
+                STATE::STAGING | STATE::PLAYING | STATE::INTERMISSION
+            );
+            if table.game != GameKind::Poker
+                || table.variant == Variant::Stud
+                || table.big_blind == 0
+                || table.seed.is_some()
+                || !dealing
+                || table.straddler.is_some()
+            {
+                return Err(MetasinoError::WrongState);
+            }
+            let button = table.button % table.players_count();
+            if betting::straddle_seat(table.players_count(), button) != Some(info.seat) {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            table.straddler = Some(caller);
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Deal the next hand of a table in intermission. The players carried
+        /// over from the last hand put their start bet in again out of their
+        /// chips, and those short of it leave the table, keeping their chips to
+        /// cash out. The table ends if nobody is left.
+        /// A table with a dealer is only dealt by its dealer.
+        /// error if the caller is not a player, or the dealer of a table having one.
+        /// error if the table is not in intermission.
+        #[ink(message)]
+        pub fn next_hand(&mut self, table_id: TableId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            if !self.only_dealer(&table)? {
+                self.only_player(table_id)?;
+            }
+            if table.state != STATE::INTERMISSION {
+                return Err(MetasinoError::WrongState);
+            }
+            self.deal(table_id, &mut table)?;
+            let start_bet = table.required_start_bet;
+            for player in self.players_of(table_id, &table) {
+                let mut info = match self.players.get((table_id, player)) {
+                    Some(info) if info.hand != table.hand_number => info,
+                    _ => continue,
+                };
+                let pot = arithmetic::add(table.pot, start_bet)?;
+                if self.spend_chips(table_id, player, start_bet).is_err() {
+                    self.unseat_player(table_id, &mut table, player);
+                    continue
+                }
+                info.hand = table.hand_number;
+                self.players.insert((table_id, player), &info);
+                self.record_registration(player, start_bet);
+                table.pot = pot;
+            }
+            self.seat_from_waitlist(table_id, &mut table);
+            let next = if table.seated > 0 {
+                STATE::STAGING
+            } else {
+                STATE::ENDED
+            };
+            self.set_state(table_id, &mut table, next);
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Get the seat of the dealer button in the next hand of the table.
+        #[ink(message)]
+        pub fn get_button_position(&self, table_id: TableId) -> Result<u8> {
+            Ok(self.table(table_id)?.button)
+        }
+
+        /// Get the chips the player holds on the table.
+        #[ink(message)]
+        pub fn get_chips(&self, table_id: TableId, player: AccountId) -> Balance {
+            self.chips.get((table_id, player)).unwrap_or_default()
+        }
+
+        /// Get the roulette bets placed by the player on the table.
+        #[ink(message)]
+        pub fn get_wagers(&self, table_id: TableId, player: AccountId) -> Vec<Wager> {
+            self.wagers.get((table_id, player)).unwrap_or_default()
+        }
+
+        /// Get the baccarat bets placed by the player on the table.
+        #[ink(message)]
+        pub fn get_baccarat_bets(
+            &self,
+            table_id: TableId,
+            player: AccountId,
+        ) -> Vec<BaccaratWager> {
+            self.baccarat_bets.get((table_id, player)).unwrap_or_default()
+        }
+
+        /// Choose the side the start bet is on at a dice table before its game starts.
+        /// error if the caller is not a player.
+        /// error if the table is not a dice table or its game has started.
+        #[ink(message)]
+        pub fn choose_dice_bet(&mut self, table_id: TableId, bet: DiceBet) -> Result<()> {
+            self.choose_dice_bet_as(table_id, Self::env().caller(), bet)
+        }
+
+        /// [`Metasino::choose_dice_bet`] played by `caller`.
+        fn choose_dice_bet_as(
+            &mut self,
+            table_id: TableId,
+            caller: AccountId,
+            bet: DiceBet,
+        ) -> Result<()> {
+            let table = self.table(table_id)?;
+            if self.players.get((table_id, caller)).is_none() {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            table.status_guard()?;
+            if table.game != GameKind::Dice {
+                return Err(MetasinoError::WrongState);
+            }
+            self.dice_bets.insert((table_id, caller), &bet);
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Get the side the player bets on at the dice table.
+        #[ink(message)]
+        pub fn get_dice_bet(&self, table_id: TableId, player: AccountId) -> DiceBet {
+            self.dice_bets.get((table_id, player)).unwrap_or(DiceBet::Pass)
+        }
+
+        /// Roll the dice of the dice table, settling the bets once they are resolved.
+        /// Anyone can roll, the dice only depending on the seed of the table,
+        /// unless the table has a dealer rolling in time.
+        /// error if the dice table has no game waiting for a roll.
+        #[ink(message)]
+        pub fn roll_dice(&mut self, table_id: TableId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            let mut round = self.dice_rounds.get(table_id).ok_or(MetasinoError::WrongState)?;
+            let seed = table.seed.ok_or(MetasinoError::WrongState)?;
+            self.only_dealer(&table)?;
+            let dice = round.roll(&seed)?;
+            self.deal(table_id, &mut table)?;
+            Self::env().emit_event(DiceRolled {
+                table_id,
+                dice,
+                point: round.point,
+            });
+            if round.outcome.is_some() {
+                let owed = self
+                    .players_of(table_id, &table)
+                    .iter()
+                    .map(|player| {
+                        round.payout(self.get_dice_bet(table_id, *player), table.required_start_bet)
+                    })
+                    .collect::<Result<Vec<Balance>>>()?;
+                self.settle_with_house(table_id, &mut table, owed)?;
+            } else {
+                self.await_dealer(&mut table, Self::env().block_number());
+                self.dice_rounds.insert(table_id, &round);
+            }
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Get the rolls of the game played on the dice table.
+        #[ink(message)]
+        pub fn get_dice_round(&self, table_id: TableId) -> Option<DiceRound> {
+            self.dice_rounds.get(table_id)
+        }
+
+        /// Leave the table before the game starts and get the start bet refunded.
+        /// The initializer cannot leave and has to terminate the table instead.
+        /// error if the caller is not a player.
+        /// error if the game has already started.
+        #[ink(message)]
+        pub fn leave_table(&mut self, table_id: TableId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_player(table_id)?;
+            table.status_guard()?;
+            let caller = Self::env().caller();
+            if caller == table.initializer {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            let refund = self.refund_seat(table_id, &mut table, caller)?;
+            Self::env().emit_event(PlayerLeft {
+                table_id,
+                player: caller,
+                refund,
+            });
+            self.seat_from_waitlist(table_id, &mut table);
+            self.tables.insert(table_id, &table);
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Remove a player holding their seat hostage before the game starts,
+        /// refunding their start bet and bets.
+        /// error if the caller is not the initializer.
+        /// error if the account is not a player or is the initializer.
+        /// error if the game has already started.
+        #[ink(message)]
+        pub fn kick_player(&mut self, table_id: TableId, account: AccountId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            table.status_guard()?;
+            if account == table.initializer || !self.players.contains((table_id, account)) {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            let refund = self.refund_seat(table_id, &mut table, account)?;
+            Self::env().emit_event(PlayerKicked {
+                table_id,
+                player: account,
+                refund,
+            });
+            self.seat_from_waitlist(table_id, &mut table);
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Start the game on the table.
+        /// A scheduled game may be started by anyone once its block is reached.
+        /// A table with a dealer is only started by its dealer, see
+        /// [`Metasino::set_dealer`].
+        /// error if the caller is not a player and the game is not scheduled.
+        /// error if the caller is not the dealer of a table dealing in time.
+        /// error if the block the game is scheduled at is not reached.
+        #[ink(message)]
+        pub fn start_game(&mut self, table_id: TableId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            let dealer = self.only_dealer(&table)?;
+            match table.start_at {
+                Some(start_at) if Self::env().block_number() < start_at => {
+                    return Err(MetasinoError::DeadlineNotReached)
+                }
+                Some(_) => {}
+                None if dealer => {}
+                None => self.only_player(table_id)?,
+            }
+            self.when_not_paused()?;
+            table.status_guard()?;
+
+            if table.players_count() < table.min_players {
+                return Err(MetasinoError::NotEnoughPlayers);
+            }
+            if !self.all_committed(table_id, &table) {
+                return Err(MetasinoError::MissingCommitment);
+            }
+            self.deal(table_id, &mut table)?;
+            self.begin_game(table_id, &mut table);
+            Ok(())
+        }
+
+        /// Let the game of the table start by itself as soon as the table is
+        /// full and every player committed to their seed, without anyone
+        /// calling [`Metasino::start_game`].
+        /// error if the caller is not the initializer.
+        /// error if the game of the table started.
+        #[ink(message)]
+        pub fn set_sit_and_go(&mut self, table_id: TableId, sit_and_go: bool) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            table.registration_guard()?;
+            table.sit_and_go = sit_and_go;
+            self.tables.insert(table_id, &table);
+            self.auto_start(table_id, &mut table);
+            Ok(())
+        }
+
+        /// Approve the `delegate` as a session key of the caller until the
+        /// `expiry_block`, so that it plays for the caller at their tables:
+        /// committing, revealing, betting and the moves of the games. Taking a
+        /// seat, buying chips and withdrawing stay with the caller.
+        /// error if the delegate is the caller or the expiry block has passed.
+        /// error if the delegate is a session key of another player.
+        #[ink(message)]
+        pub fn approve_session_key(
+            &mut self,
+            delegate: AccountId,
+            expiry_block: BlockNumber,
+        ) -> Result<()> {
+            let caller = Self::env().caller();
+            if delegate == caller {
+                return Err(MetasinoError::InvalidAction);
+            }
+            if expiry_block <= Self::env().block_number() {
+                return Err(MetasinoError::DeadlinePassed);
+            }
+            let taken = self.session_keys.get(delegate).is_some_and(|(player, expiry)| {
+                player != caller && Self::env().block_number() < expiry
+            });
+            if taken {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            self.session_keys.insert(delegate, &(caller, expiry_block));
+            Self::env().emit_event(SessionKeyApproved {
+                player: caller,
+                delegate,
+                expiry_block,
+            });
+            Ok(())
+        }
+
+        /// Revoke a session key of the caller before it expires.
+        /// error if the delegate is not a session key of the caller.
+        #[ink(message)]
+        pub fn revoke_session_key(&mut self, delegate: AccountId) -> Result<()> {
+            match self.session_keys.get(delegate) {
+                Some((player, _)) if player == Self::env().caller() => {
+                    self.session_keys.remove(delegate);
+                    Ok(())
+                }
+                _ => Err(MetasinoError::NotAuthorized),
+            }
+        }
+
+        /// Get the player the session key plays for and the block from which it
+        /// no longer may.
+        #[ink(message)]
+        pub fn get_session_key(&self, delegate: AccountId) -> Option<(AccountId, BlockNumber)> {
+            self.session_keys.get(delegate)
+        }
+
+        /// Commit to a secret seed which is revealed once the game started.
+        /// The commitment is computed with [`Metasino::compute_commitment`].
+        /// error if the caller is not a player.
+        /// error if the game has already started.
+        #[ink(message)]
+        pub fn commit_seed(&mut self, table_id: TableId, commitment: Hash) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            let player = self.only_acting_player(table_id)?;
+            table.status_guard()?;
+            self.commitments.insert((table_id, player), &commitment);
+            self.auto_start(table_id, &mut table);
+            self.await_start(table_id, &mut table);
+            self.tables.insert(table_id, &table);
+            self.bump_nonce(player);
+            Ok(())
+        }
+
+        /// Reveal the seed committed to before the game started.
+        /// Once every player revealed, the shuffle seed of the table is derived.
+        /// error if the caller is not a player.
+        /// error if the table is not in its reveal phase.
+        /// error if the reveal deadline has passed.
+        /// error if the seed does not match the commitment.
+        #[ink(message)]
+        pub fn reveal_seed(&mut self, table_id: TableId, seed: [u8; 32]) -> Result<()> {
+            self.reveal_seed_as(table_id, self.acting_player(), seed)
+        }
+
+        /// [`Metasino::reveal_seed`] played by `caller`.
+        fn reveal_seed_as(
+            &mut self,
+            table_id: TableId,
+            caller: AccountId,
+            seed: [u8; 32],
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            if self.players.get((table_id, caller)).is_none() {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            if table.state != STATE::PLAYING || table.seed.is_some() {
+                return Err(MetasinoError::WrongState);
+            }
+            if Self::env().block_number() > table.reveal_deadline {
+                return Err(MetasinoError::DeadlinePassed);
+            }
+            let mut info = self
+                .players
+                .get((table_id, caller))
+                .ok_or(MetasinoError::NotAuthorized)?;
+            if info.revealed {
+                return Err(MetasinoError::AlreadyRevealed);
+            }
+            if self.commitments.get((table_id, caller)) != Some(randomness::commitment(&caller, &seed)) {
+                return Err(MetasinoError::InvalidReveal);
+            }
+
+            randomness::combine(&mut table.entropy, &seed);
+            info.revealed = true;
+            self.players.insert((table_id, caller), &info);
+            table.revealed += 1;
+            Self::env().emit_event(SeedRevealed {
+                table_id,
+                player: caller,
+            });
+            if table.revealed == table.seated {
+                self.derive_seed(table_id, &mut table)?;
+            }
+            self.tables.insert(table_id, &table);
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Close the reveal phase once its deadline has passed.
+        /// Players who did not reveal are removed from the table and their
+        /// start bet stays in the pot. If nobody revealed, the game is
+        /// cancelled and every start bet is refunded.
+        /// An operator may close the reveal phase before its deadline.
+        /// A table with a dealer dealing in time has its dealer close it.
+        /// error if the table is not in its reveal phase.
+        /// error if the reveal deadline has not been reached.
+        #[ink(message)]
+        pub fn finalize_seed(&mut self, table_id: TableId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            if table.state != STATE::PLAYING || table.seed.is_some() {
+                return Err(MetasinoError::WrongState);
+            }
+            self.only_dealer(&table)?;
+            self.after_deadline(table.reveal_deadline)?;
+            self.deal(table_id, &mut table)?;
+
+            if table.revealed == 0 {
+                let players = self.players_of(table_id, &table);
+                let mut refunds: Vec<Balance> = Vec::with_capacity(players.len());
+                for player in players.iter() {
+                    let refund = self.stake_of(table_id, &table, *player)?;
+                    self.pay(&table, *player, refund)?;
+                    self.record_refund(*player, refund);
+                    refunds.push(refund);
+                }
+                Self::env().emit_event(TableTerminated {
+                    table_id,
+                    players,
+                    refunds,
+                });
+                self.clear_players(table_id, &mut table);
+                table.reset();
+                self.set_state(table_id, &mut table, STATE::ENDED);
+            } else {
+                for player in self.unrevealed(table_id, &table) {
+                    Self::env().emit_event(PlayerSlashed {
+                        table_id,
+                        player,
+                        amount: table.required_start_bet,
+                    });
+                    self.penalize(table_id, player, REVEAL_PENALTY);
+                    self.unseat_player(table_id, &mut table, player);
+                }
+                self.derive_seed(table_id, &mut table)?;
+            }
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Match the highest bet of the street.
+        /// The amount to call has to be transferred along with the call.
+        #[ink(message, payable)]
+        pub fn call(&mut self, table_id: TableId) -> Result<()> {
+            self.act(table_id, self.acting_player(), Action::Call)
+        }
+
+        /// Raise the highest bet of the street by `amount`.
+        /// The amount to call plus the raise has to be transferred along with the call.
+        #[ink(message, payable)]
+        pub fn raise(&mut self, table_id: TableId, amount: Balance) -> Result<()> {
+            self.act(table_id, self.acting_player(), Action::Raise(amount))
+        }
+
+        /// Put every remaining fund in, which is the value transferred along with the call.
+        /// Going all-in for less than the amount to call only wins a side pot.
+        #[ink(message, payable)]
+        pub fn all_in(&mut self, table_id: TableId) -> Result<()> {
+            self.act(table_id, self.acting_player(), Action::AllIn(Self::env().transferred_value()))
+        }
+
+        /// Pass the turn without betting when there is nothing to call.
+        #[ink(message)]
+        pub fn check(&mut self, table_id: TableId) -> Result<()> {
+            self.act(table_id, self.acting_player(), Action::Check)
+        }
+
+        /// Give up the hand.
+        #[ink(message)]
+        pub fn fold(&mut self, table_id: TableId) -> Result<()> {
+            self.act(table_id, self.acting_player(), Action::Fold)
+        }
+
+        /// Play any action of the game of the table, as the message of the
+        /// action does, the value transferred being taken the same way. Given
+        /// a `nonce`, the action is only played as the next action of the
+        /// player, never twice nor after any other action they took since.
+        /// error if the nonce is not the next one of the player.
+        /// error if the action is not one of the game of the table.
+        /// error if the caller cannot play the action.
+        #[ink(message, payable)]
+        pub fn play(
+            &mut self,
+            table_id: TableId,
+            action: GameAction,
+            nonce: Option<u64>,
+        ) -> Result<()> {
+            let player = self.acting_player();
+            if let Some(nonce) = nonce {
+                self.check_nonce(player, nonce)?;
+            }
+            self.play_as(table_id, player, action)
+        }
+
+        /// Play an action signed by the player, relayed by anyone who pays for
+        /// the call. The player signs with their ECDSA key the hash given by
+        /// [`Metasino::compute_action_hash`] for their next nonce, and is the
+        /// account of the Blake2x256 hash of their compressed key. The bets
+        /// are taken out of the chips of the player, and the dice are rolled
+        /// as anyone rolls them.
+        /// error if the signature does not recover to a key.
+        /// error if the nonce is not the next one of the player.
+        /// error if the action is not one of the game of the table.
+        /// error if the player cannot play the action.
+        #[ink(message, payable)]
+        pub fn act_with_signature(
+            &mut self,
+            table_id: TableId,
+            action: GameAction,
+            nonce: u64,
+            signature: [u8; 65],
+        ) -> Result<()> {
+            let hash = self.compute_action_hash(table_id, action.clone(), nonce);
+            let player = signatures::signer(&signature, &hash)?;
+            self.check_nonce(player, nonce)?;
+            self.play_as(table_id, player, action)
+        }
+
+        /// [`Metasino::play`] played by `player`.
+        fn play_as(
+            &mut self,
+            table_id: TableId,
+            player: AccountId,
+            action: GameAction,
+        ) -> Result<()> {
+            if !action.fits(self.table(table_id)?.game) {
+                return Err(MetasinoError::InvalidAction);
+            }
+            match action {
+                GameAction::Bet(bet, amount) => self.place_bet_as(table_id, player, bet, amount),
+                GameAction::BaccaratBet(bet, amount) => {
+                    self.place_baccarat_bet_as(table_id, player, bet, amount)
+                }
+                GameAction::DiceBet(bet) => self.choose_dice_bet_as(table_id, player, bet),
+                GameAction::RevealSeed(seed) => self.reveal_seed_as(table_id, player, seed),
+                GameAction::Check => self.act(table_id, player, Action::Check),
+                GameAction::Call => self.act(table_id, player, Action::Call),
+                GameAction::Raise(amount) => self.act(table_id, player, Action::Raise(amount)),
+                GameAction::Fold => self.act(table_id, player, Action::Fold),
+                GameAction::AllIn => {
+                    let amount = match Self::env().transferred_value() {
+                        0 => self.get_chips(table_id, player),
+                        value => value,
+                    };
+                    self.act(table_id, player, Action::AllIn(amount))
+                }
+                GameAction::RevealHand(positions, salt) => {
+                    self.reveal_hand_as(table_id, player, positions, salt)
+                }
+                GameAction::Hit => self.play_blackjack(table_id, Some((player, Move::Hit))),
+                GameAction::Stand => self.play_blackjack(table_id, Some((player, Move::Stand))),
+                GameAction::DoubleDown => {
+                    self.play_blackjack(table_id, Some((player, Move::DoubleDown)))
+                }
+                GameAction::Insure(amount) => self.insure_as(table_id, player, amount),
+                GameAction::RollDice => self.roll_dice(table_id),
+                GameAction::Engine(action) => self.play_engine_as(table_id, player, action),
+            }
+        }
+
+        /// Compute the hash a player signs to have the action played for them
+        /// on the table with the given nonce. The hash commits to this
+        /// contract so that the signature cannot be replayed on another one.
+        #[ink(message)]
+        pub fn compute_action_hash(
+            &self,
+            table_id: TableId,
+            action: GameAction,
+            nonce: u64,
+        ) -> [u8; 32] {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            let payload = (Self::env().account_id(), table_id, action, nonce);
+            ink_env::hash_encoded::<Blake2x256, _>(&payload, &mut output);
+            output
+        }
+
+        /// Get the nonce of the next action the account takes. It is raised by
+        /// every action the account takes at any table, signed or not, so
+        /// that an action signed or played for a nonce is dropped once the
+        /// account took any other action in between.
+        #[ink(message)]
+        pub fn get_nonce(&self, account: AccountId) -> u64 {
+            self.nonces.get(account).unwrap_or_default()
+        }
+
+        /// Get the betting of the hand played on the table.
+        #[ink(message)]
+        pub fn get_betting_round(&self, table_id: TableId) -> Option<BettingRound> {
+            self.rounds.get(table_id)
+        }
+
+        /// Sanction a player who stalls the game past their deadline.
+        /// During the reveal phase the players who did not reveal forfeit their
+        /// start bet as in [`Metasino::finalize_seed`], during the betting the
+        /// player whose turn it is gets folded. Anyone can call this message,
+        /// and an operator may force the timeout before the deadline.
+        /// error if no phase with a deadline is ongoing on the table.
+        /// error if the deadline has not been reached.
+        #[ink(message)]
+        pub fn claim_timeout(&mut self, table_id: TableId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            if table.state != STATE::PLAYING {
+                return Err(MetasinoError::WrongState);
+            }
+            if table.seed.is_none() {
+                let stalling = self.unrevealed(table_id, &table);
+                self.finalize_seed(table_id)?;
+                for player in stalling {
+                    Self::env().emit_event(PlayerTimedOut { table_id, player });
+                }
+                return Ok(())
+            }
+            if table.game == GameKind::Blackjack {
+                return self.play_blackjack(table_id, None)
+            }
+
+            let mut round = self.rounds.get(table_id).ok_or(MetasinoError::WrongState)?;
+            if round.finished {
+                return Err(MetasinoError::WrongState);
+            }
+            self.after_deadline(round.deadline)?;
+            let seat = round.active;
+            let player = self.seats.get((table_id, seat)).ok_or(MetasinoError::WrongState)?;
+            let play = self.play_in_round(table_id, &table, &mut round, seat, Action::Fold)?;
+            round.deadline = Self::env().block_number().saturating_add(TURN_PERIOD);
+            if round.finished {
+                self.await_dealer(&mut table, Self::env().block_number());
+                self.tables.insert(table_id, &table);
+            }
+            self.rounds.insert(table_id, &round);
+            Self::log_play(table_id, &table, &play);
+            self.penalize(table_id, player, TIMEOUT_PENALTY);
+            Self::env().emit_event(PlayerTimedOut { table_id, player });
+            Ok(())
+        }
+
+        /// Draw a card on a blackjack table.
+        #[ink(message)]
+        pub fn hit(&mut self, table_id: TableId) -> Result<()> {
+            self.play_blackjack(table_id, Some((self.acting_player(), Move::Hit)))
+        }
+
+        /// Keep the blackjack hand as it is and pass the turn.
+        #[ink(message)]
+        pub fn stand(&mut self, table_id: TableId) -> Result<()> {
+            self.play_blackjack(table_id, Some((self.acting_player(), Move::Stand)))
+        }
+
+        /// Double the bet of the blackjack hand and draw a single last card.
+        /// The start bet has to be transferred again along with the call.
+        #[ink(message, payable)]
+        pub fn double_down(&mut self, table_id: TableId) -> Result<()> {
+            self.play_blackjack(table_id, Some((self.acting_player(), Move::DoubleDown)))
+        }
+
+        /// Insure the blackjack hand of the caller for `amount`, at most half
+        /// their bet, while the up-card of the dealer is an ace. The insurance
+        /// pays 2:1 if the dealer has blackjack. The amount is taken as the
+        /// start bets are.
+        /// error if the caller is not a player.
+        /// error if no insurance window is open on the table.
+        /// error if the caller already insured or the amount is out of bounds.
+        #[ink(message, payable)]
+        pub fn insure(&mut self, table_id: TableId, amount: Balance) -> Result<()> {
+            self.insure_as(table_id, self.acting_player(), amount)
+        }
+
+        /// [`Metasino::insure`] played by `caller`.
+        fn insure_as(
+            &mut self,
+            table_id: TableId,
+            caller: AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            let seat = self
+                .players
+                .get((table_id, caller))
+                .ok_or(MetasinoError::NotAuthorized)?
+                .seat;
+            let mut round = self
+                .blackjack_rounds
+                .get(table_id)
+                .ok_or(MetasinoError::WrongState)?;
+            round.insure(seat, amount, Self::env().block_number())?;
+            let pot = arithmetic::add(table.pot, amount)?;
+            self.put_in(table_id, &table, caller, amount)?;
+            self.record_wager(caller, amount);
+            table.pot = pot;
+            self.blackjack_rounds.insert(table_id, &round);
+            self.tables.insert(table_id, &table);
+            Self::env().emit_event(InsurancePlaced {
+                table_id,
+                player: caller,
+                amount,
+            });
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Get the blackjack hand played on the table.
+        #[ink(message)]
+        pub fn get_blackjack_round(&self, table_id: TableId) -> Option<BlackjackRound> {
+            self.blackjack_rounds.get(table_id)
+        }
+
+        /// Score the best poker hand out of the given 5 to 7 cards.
+        /// A better hand always gets a higher score.
+        #[ink(message)]
+        pub fn score_hand(&self, cards: Vec<Card>) -> Option<(HandCategory, u32)> {
+            hand_rank::score(&cards).map(|score| (hand_rank::category(score), score))
+        }
+
+        /// Compute the commitment of a player to the given seed.
+        #[ink(message)]
+        pub fn compute_commitment(&self, player: AccountId, seed: [u8; 32]) -> Hash {
+            randomness::commitment(&player, &seed)
+        }
+
+        /// Get the shuffle seed of the table, once every player revealed.
+        #[ink(message)]
+        pub fn get_shuffle_seed(&self, table_id: TableId) -> Result<Option<[u8; 32]>> {
+            Ok(self.table(table_id)?.seed)
+        }
+
+        /// Commit to the deck positions of the hole cards dealt to the caller
+        /// on a poker table, before the deck is shuffled, see
+        /// [`Metasino::compute_hand_commitment`].
+        /// error if the caller is not a player.
+        /// error if the table is not a poker table or its game has started.
+        #[ink(message)]
+        pub fn commit_hand(&mut self, table_id: TableId, commitment: Hash) -> Result<()> {
+            let table = self.table(table_id)?;
+            let player = self.only_acting_player(table_id)?;
+            table.status_guard()?;
+            if table.game != GameKind::Poker {
+                return Err(MetasinoError::WrongState);
+            }
+            self.hand_commitments.insert((table_id, player), &commitment);
+            self.bump_nonce(player);
+            Ok(())
+        }
+
+        /// Compute the commitment of a player to the deck positions of their
+        /// hole cards, hidden by the salt.
+        #[ink(message)]
+        pub fn compute_hand_commitment(
+            &self,
+            player: AccountId,
+            positions: Vec<u8>,
+            salt: [u8; 32],
+        ) -> Hash {
+            showdown::commitment(&player, &positions, &salt)
+        }
+
+        /// Show down the hole cards of the caller once the betting is over,
+        /// revealing their deck positions and the salt committed to.
+        /// error if the caller is not a player or has folded.
+        /// error if the betting of the poker hand is not over.
+        /// error if the showdown deadline has passed.
+        /// error if the caller already revealed or never committed.
+        /// error if the reveal does not match the commitment, or the cards
+        /// are on the board or shown by another player.
+        #[ink(message)]
+        pub fn reveal_hand(
+            &mut self,
+            table_id: TableId,
+            positions: Vec<u8>,
+            salt: [u8; 32],
+        ) -> Result<()> {
+            self.reveal_hand_as(table_id, self.acting_player(), positions, salt)
+        }
+
+        /// [`Metasino::reveal_hand`] played by `caller`.
+        fn reveal_hand_as(
+            &mut self,
+            table_id: TableId,
+            caller: AccountId,
+            positions: Vec<u8>,
+            salt: [u8; 32],
+        ) -> Result<()> {
+            let table = self.table(table_id)?;
+            let info = self
+                .players
+                .get((table_id, caller))
+                .ok_or(MetasinoError::NotAuthorized)?;
+            let round = self.showdown_round(table_id, &table)?;
+            if round.folded[info.seat as usize] {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            if Self::env().block_number() > round.deadline {
+                return Err(MetasinoError::DeadlinePassed);
+            }
+            if self.revealed_hands.contains((table_id, caller)) {
+                return Err(MetasinoError::AlreadyRevealed);
+            }
+            let commitment = self
+                .hand_commitments
+                .get((table_id, caller))
+                .ok_or(MetasinoError::MissingCommitment)?;
+            if showdown::commitment(&caller, &positions, &salt) != commitment {
+                return Err(MetasinoError::InvalidReveal);
+            }
+            let deck = table.deck()?;
+            let hole = showdown::hole_cards(&deck, &positions, table.variant)?;
+            let taken = self
+                .players_of(table_id, &table)
+                .iter()
+                .filter_map(|player| self.revealed_hands.get((table_id, *player)))
+                .any(|shown| shown.iter().any(|position| positions.contains(position)));
+            if taken {
+                return Err(MetasinoError::InvalidReveal);
+            }
+            self.revealed_hands.insert((table_id, caller), &positions);
+            Self::env().emit_event(HandRevealed {
+                table_id,
+                player: caller,
+                cards: hole.clone(),
+            });
+            let shown = LoggedAction::Show(hole);
+            Self::log_action(table_id, &table, round.street, info.seat, shown, 0);
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Agree to run the poker hand twice once its betting ended before the
+        /// river with everyone left all-in. The hand is run twice once every
+        /// player still in agrees, see [`Metasino::settle_showdown`].
+        /// error if the caller is not a player or has folded.
+        /// error if the betting of the hand did not end before the river.
+        /// error if the caller already agreed.
+        #[ink(message)]
+        pub fn run_it_twice(&mut self, table_id: TableId) -> Result<()> {
+            let table = self.table(table_id)?;
+            let caller = self.acting_player();
+            let info = self
+                .players
+                .get((table_id, caller))
+                .ok_or(MetasinoError::NotAuthorized)?;
+            let round = self.showdown_round(table_id, &table)?;
+            if round.folded[info.seat as usize] {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            if !showdown::runs_out(&round) {
+                return Err(MetasinoError::WrongState);
+            }
+            let mut votes = self.run_twice_votes.get(table_id).unwrap_or_default();
+            if votes.contains(&caller) {
+                return Err(MetasinoError::AlreadyRegistered);
+            }
+            votes.push(caller);
+            self.run_twice_votes.insert(table_id, &votes);
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Pay out the poker hand to the hands shown down, each pot going to
+        /// the best hand revealed among the seats eligible to it.
+        /// A hand every player still in agreed to run twice is played on a
+        /// second board as well, each board winning half of every pot.
+        /// Anyone may settle the hand once every player still in revealed, or
+        /// once the showdown deadline has passed, the players who did not
+        /// reveal forfeiting the hand. The last player left in the hand wins
+        /// it without revealing. A table with a dealer dealing in time has its
+        /// dealer settle the hand.
+        /// error if the betting of the poker hand is not over.
+        /// error if a player still has to reveal and the deadline is not reached.
+        /// error if nobody revealed.
+        #[ink(message)]
+        pub fn settle_showdown(&mut self, table_id: TableId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            let round = self.showdown_round(table_id, &table)?;
+            self.only_dealer(&table)?;
+            let deck = table.deck()?;
+            let players = self.players_of(table_id, &table);
+            let last_one = round.remaining() == 1;
+            let shown: Vec<Option<Vec<u8>>> = players
+                .iter()
+                .map(|player| self.revealed_hands.get((table_id, *player)))
+                .collect();
+            let scores_on = |board: &[Card]| -> Vec<Option<u32>> {
+                shown
+                    .iter()
+                    .enumerate()
+                    .map(|(seat, positions)| {
+                        if round.folded[seat] {
+                            return None
+                        }
+                        if last_one {
+                            return Some(0)
+                        }
+                        let positions = positions.as_ref()?;
+                        let hole = showdown::hole_cards(&deck, positions, table.variant).ok()?;
+                        if table.variant == Variant::Stud {
+                            let up = stud::up_cards(&deck, seat as u8, Street::Seventh);
+                            return showdown::score(table.variant, &up, &hole)
+                        }
+                        showdown::score(table.variant, board, &hole)
+                    })
+                    .collect()
+            };
+            let scores = scores_on(&deck);
+            let forfeited: Vec<AccountId> = players
+                .iter()
+                .enumerate()
+                .filter(|(seat, _)| !round.folded[*seat] && scores[*seat].is_none())
+                .map(|(_, player)| *player)
+                .collect();
+            if !forfeited.is_empty() {
+                self.after_deadline(round.deadline)?;
+            }
+            let contributed = arithmetic::sum(&round.contributed)?;
+            let mut pots = round.side_pots(arithmetic::sub(table.pot, contributed)?);
+            let rake = table.rake_pots(&mut pots)?;
+            let house_rake = self.share_rake(table_id, &table, rake)?;
+            let bank = arithmetic::add(self.house_bank(&table), house_rake)?;
+            for pot in pots.iter_mut() {
+                pot.amount = arithmetic::sub(pot.amount, self.feed_jackpot(&table, pot.amount)?)?;
+            }
+            let votes = self.run_twice_votes.get(table_id).unwrap_or_default();
+            let twice = showdown::runs_out(&round)
+                && players
+                    .iter()
+                    .enumerate()
+                    .all(|(seat, player)| round.folded[seat] || votes.contains(player));
+            let won = if twice {
+                let shown: Vec<Vec<u8>> = shown.iter().flatten().cloned().collect();
+                let second = showdown::second_board(&deck, &round, &shown);
+                let (first_pots, second_pots) = showdown::halves(&pots);
+                let first_won = showdown::award(&first_pots, &scores)?;
+                let second_won = showdown::award(&second_pots, &scores_on(&second))?;
+                let first = deck[..showdown::BOARD_CARDS].to_vec();
+                let boards = [(first, first_won.clone()), (second, second_won.clone())];
+                for (board, (cards, payouts)) in boards.into_iter().enumerate() {
+                    Self::env().emit_event(BoardResult {
+                        table_id,
+                        board: board as u8,
+                        cards,
+                        payouts,
+                    });
+                }
+                first_won
+                    .iter()
+                    .zip(second_won.iter())
+                    .map(|(first, second)| arithmetic::add(*first, *second))
+                    .collect::<Result<Vec<Balance>>>()?
+            } else {
+                showdown::award(&pots, &scores)?
+            };
+            for player in forfeited {
+                self.penalize(table_id, player, TIMEOUT_PENALTY);
+                Self::env().emit_event(PlayerTimedOut { table_id, player });
+            }
+            for (player, score) in players.iter().zip(scores.iter()) {
+                if score.is_some_and(hand_rank::is_royal_flush) {
+                    self.unlock(*player, Achievement::RoyalFlush);
+                }
+            }
+            let (winners, payouts): (Vec<AccountId>, Vec<Balance>) = players
+                .into_iter()
+                .zip(won)
+                .filter(|(_, payout)| *payout > 0)
+                .unzip();
+            for (winner, payout) in winners.iter().zip(payouts.iter()) {
+                self.pay_winnings(table_id, &table, *winner, *payout)?;
+                self.record_win(*winner, *payout);
+            }
+            self.set_house_bank(&table, bank);
+            self.deal(table_id, &mut table)?;
+            self.close_game(table_id, &mut table, winners, payouts, rake)?;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// End the ongoing game and pay out the pot to the winners.
+        /// Each winner receives their share, given in percent, of the pots they
+        /// are eligible to and the table is emptied so that a new game can be
+        /// staged on it. Side pots of all-in players only go to the winners
+        /// who paid into them.
+        /// Blackjack tables are settled by the contract instead.
+        /// Anyone may end an oracle game with the winners and shares of the
+        /// result confirmed by the result signers.
+        /// The payouts are held for the players to dispute them if a dispute
+        /// period is set, see [`Metasino::raise_dispute`], and those of a pot
+        /// above the large pot threshold for the payout delay as well, see
+        /// [`Metasino::veto_payout`].
+        /// error if the caller is neither the initializer nor a dealer.
+        /// error if the game is not an ongoing poker or oracle game.
+        /// error if the winners are not players or the shares do not add up to 100.
+        /// error if the winners and shares are not the confirmed oracle result.
+        #[ink(message)]
+        pub fn end_game(
+            &mut self,
+            table_id: TableId,
+            winners: Vec<AccountId>,
+            shares: Vec<u8>,
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            if table.state != STATE::PLAYING {
+                return Err(MetasinoError::WrongState);
+            }
+            match table.game {
+                GameKind::Poker => self.only_initializer_or(&table, Role::Dealer)?,
+                GameKind::Oracle => {
+                    let result_hash = oracle::result_hash(&winners, &shares);
+                    if self.confirmed_results.get(table_id) != Some(result_hash) {
+                        return Err(MetasinoError::InvalidPayout);
+                    }
+                }
+                _ => return Err(MetasinoError::WrongState),
+            }
+            let mut pots = match self.rounds.get(table_id) {
+                Some(round) => {
+                    let contributed = arithmetic::sum(&round.contributed)?;
+                    round.side_pots(arithmetic::sub(table.pot, contributed)?)
+                }
+                None => ink_prelude::vec![SidePot {
+                    amount: table.pot,
+                    eligible: (0..table.players_count()).collect(),
+                }],
+            };
+            let seats = winners
+                .iter()
+                .map(|winner| self.players.get((table_id, *winner)).map(|info| info.seat))
+                .collect::<Option<Vec<u8>>>()
+                .ok_or(MetasinoError::InvalidPayout)?;
+            let rake = table.rake_pots(&mut pots)?;
+            let house_rake = self.share_rake(table_id, &table, rake)?;
+            let bank = arithmetic::add(self.house_bank(&table), house_rake)?;
+            for pot in pots.iter_mut() {
+                pot.amount = arithmetic::sub(pot.amount, self.feed_jackpot(&table, pot.amount)?)?;
+            }
+            let payouts = table.split_pots(&pots, &seats, &shares)?;
+            let config = self.get_config();
+            let large = config.large_pot_threshold > 0 && table.pot > config.large_pot_threshold;
+            let hold_for = match large {
+                true => config.dispute_period_blocks.max(config.payout_delay_blocks),
+                false => config.dispute_period_blocks,
+            };
+            if hold_for > 0 {
+                self.hold_payouts(table_id, &table, &winners, &payouts, hold_for);
+            } else {
+                for (winner, payout) in winners.iter().zip(payouts.iter()) {
+                    self.pay_winnings(table_id, &table, *winner, *payout)?;
+                    self.record_win(*winner, *payout);
+                }
+            }
+            self.set_house_bank(&table, bank);
+            self.close_game(table_id, &mut table, winners, payouts, rake)?;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Dispute the payouts of the game within its dispute period, with the
+        /// hash of the evidence backing the dispute. The payouts are then held
+        /// until the arbiter of the table, or an operator if it has none,
+        /// resolves the dispute.
+        /// error if the caller did not play the game.
+        /// error if the payouts of the game are not held or already disputed.
+        /// error if the dispute period is over.
+        #[ink(message)]
+        pub fn raise_dispute(&mut self, game_id: GameId, evidence_hash: Hash) -> Result<()> {
+            let game = self.games.get(game_id).ok_or(MetasinoError::GameNotFound)?;
+            let caller = Self::env().caller();
+            if !game.players.contains(&caller) {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            let mut pending = self
+                .pending_payouts
+                .get(game_id)
+                .filter(|pending| pending.dispute.is_none())
+                .ok_or(MetasinoError::WrongState)?;
+            if Self::env().block_number() > pending.dispute_until {
+                return Err(MetasinoError::DeadlinePassed);
+            }
+            pending.dispute = Some((caller, evidence_hash));
+            self.pending_payouts.insert(game_id, &pending);
+            Self::env().emit_event(DisputeRaised {
+                game_id,
+                player: caller,
+                evidence_hash,
+            });
+            Ok(())
+        }
+
+        /// Veto the payouts of the game held for the payout delay or the
+        /// dispute period, with the hash of the evidence of fraud. The payouts
+        /// are then held until a ruling, as those disputed by a player.
+        /// error if the caller is not an operator.
+        /// error if the payouts of the game are not held or already disputed.
+        /// error if the payouts are no longer held.
+        #[ink(message)]
+        pub fn veto_payout(&mut self, game_id: GameId, evidence_hash: Hash) -> Result<()> {
+            self.only_role(Role::Operator)?;
+            let mut pending = self
+                .pending_payouts
+                .get(game_id)
+                .filter(|pending| pending.dispute.is_none())
+                .ok_or(MetasinoError::WrongState)?;
+            if Self::env().block_number() > pending.dispute_until {
+                return Err(MetasinoError::DeadlinePassed);
+            }
+            let operator = Self::env().caller();
+            pending.dispute = Some((operator, evidence_hash));
+            self.pending_payouts.insert(game_id, &pending);
+            Self::env().emit_event(PayoutVetoed {
+                game_id,
+                operator,
+                evidence_hash,
+            });
+            Ok(())
+        }
+
+        /// Pay out the payouts of the game held undisputed until the end of
+        /// its dispute period, or of its payout delay for a large pot.
+        /// Anyone may finalize the payouts.
+        /// error if the payouts of the game are not held or are disputed.
+        /// error if the dispute period is not over.
+        #[ink(message)]
+        pub fn finalize_payouts(&mut self, game_id: GameId) -> Result<()> {
+            let pending = self
+                .pending_payouts
+                .get(game_id)
+                .filter(|pending| pending.dispute.is_none())
+                .ok_or(MetasinoError::WrongState)?;
+            if Self::env().block_number() <= pending.dispute_until {
+                return Err(MetasinoError::DeadlineNotReached);
+            }
+            self.release_payouts(game_id, &pending, pending.winners.clone(), &pending.payouts)
+        }
+
+        /// Resolve the dispute over the payouts of the game with the ruling,
+        /// the amount paid out to each player of the game by seat order.
+        /// The ruling shares the same amount as the payouts disputed.
+        /// Only the arbiter of the table the game was played on may resolve
+        /// the dispute, or an operator if the table has no arbiter.
+        /// error if the caller is neither the arbiter nor an operator.
+        /// error if the payouts of the game are not disputed.
+        /// error if the ruling is not one amount per player adding up to the payouts.
+        #[ink(message)]
+        pub fn resolve_dispute(&mut self, game_id: GameId, ruling: Vec<Balance>) -> Result<()> {
+            let game = self.games.get(game_id).ok_or(MetasinoError::GameNotFound)?;
+            let pending = self
+                .pending_payouts
+                .get(game_id)
+                .filter(|pending| pending.dispute.is_some())
+                .ok_or(MetasinoError::WrongState)?;
+            match pending.arbiter {
+                Some(arbiter) if arbiter != Self::env().caller() => {
+                    return Err(MetasinoError::NotAuthorized)
+                }
+                Some(_) => (),
+                None => self.only_role(Role::Operator)?,
+            }
+            if ruling.len() != game.players.len()
+                || arithmetic::sum(&ruling)? != arithmetic::sum(&pending.payouts)?
+            {
+                return Err(MetasinoError::InvalidPayout);
+            }
+            // A veto of an operator costs them no reputation when overruled.
+            let disputer = pending
+                .dispute
+                .filter(|(player, _)| game.players.contains(player));
+            let (winners, payouts): (Vec<AccountId>, Vec<Balance>) = game
+                .players
+                .into_iter()
+                .zip(ruling)
+                .filter(|(_, payout)| *payout > 0)
+                .unzip();
+            if let Some((player, _)) = disputer {
+                if winners == pending.winners && payouts == pending.payouts {
+                    self.penalize(pending.table_id, player, DISPUTE_PENALTY);
+                }
+            }
+            self.release_payouts(game_id, &pending, winners, &payouts)
+        }
+
+        /// Get the payouts of the game held during its dispute period.
+        #[ink(message)]
+        pub fn get_pending_payout(&self, game_id: GameId) -> Option<PendingPayout> {
+            self.pending_payouts.get(game_id)
+        }
+
+        /// Play an action of the caller on the engine of the table, encoded
+        /// as the engine defines it.
+        /// error if the caller is not a player.
+        /// error if the game is not an ongoing engine game.
+        /// error if the engine rejects the action or cannot be called.
+        #[ink(message)]
+        pub fn play_engine(&mut self, table_id: TableId, action: Vec<u8>) -> Result<()> {
+            self.play_engine_as(table_id, Self::env().caller(), action)
+        }
+
+        /// [`Metasino::play_engine`] played by `caller`.
+        fn play_engine_as(
+            &mut self,
+            table_id: TableId,
+            caller: AccountId,
+            action: Vec<u8>,
+        ) -> Result<()> {
+            let table = self.table(table_id)?;
+            if self.players.get((table_id, caller)).is_none() {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            let engine = Self::running_engine(&table)?;
+            engine::act(engine, table_id, caller, action)?;
+            self.bump_nonce(caller);
+            Ok(())
+        }
+
+        /// Settle the game of an engine table with the share of the pot the
+        /// engine resolved for each seat. The rake and the cut of the jackpot
+        /// are taken out of every share in proportion, the remainder of the
+        /// division going to the seat winning the most.
+        /// Anyone may settle the game once the engine resolves it.
+        /// error if the game is not an ongoing engine game.
+        /// error if the engine has not resolved the game or cannot be called.
+        /// error if the shares are not one per seat adding up to the pot.
+        #[ink(message)]
+        pub fn resolve_engine_game(&mut self, table_id: TableId) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            let engine = Self::running_engine(&table)?;
+            let shares = engine::resolve(engine, table_id)?;
+            if shares.len() != table.players_count() as usize
+                || arithmetic::sum(&shares)? != table.pot
+            {
+                return Err(MetasinoError::InvalidPayout);
+            }
+            let mut pots = ink_prelude::vec![SidePot {
+                amount: table.pot,
+                eligible: (0..table.players_count()).collect(),
+            }];
+            let rake = table.rake_pots(&mut pots)?;
+            let house_rake = self.share_rake(table_id, &table, rake)?;
+            let bank = arithmetic::add(self.house_bank(&table), house_rake)?;
+            let net = arithmetic::sub(pots[0].amount, self.feed_jackpot(&table, pots[0].amount)?)?;
+            let mut payouts = shares
+                .iter()
+                .map(|share| arithmetic::mul_div(*share, net, table.pot))
+                .collect::<Result<Vec<Balance>>>()?;
+            let remainder = arithmetic::sub(net, arithmetic::sum(&payouts)?)?;
+            let most = shares.iter().max().copied().unwrap_or_default();
+            if let Some(top) = shares.iter().position(|share| *share == most) {
+                payouts[top] = arithmetic::add(payouts[top], remainder)?;
+            }
+            let (winners, payouts): (Vec<AccountId>, Vec<Balance>) = self
+                .players_of(table_id, &table)
+                .into_iter()
+                .zip(payouts)
+                .filter(|(_, payout)| *payout > 0)
+                .unzip();
+            for (winner, payout) in winners.iter().zip(payouts.iter()) {
+                self.pay_winnings(table_id, &table, *winner, *payout)?;
+                self.record_win(*winner, *payout);
+            }
+            self.set_house_bank(&table, bank);
+            self.close_game(table_id, &mut table, winners, payouts, rake)?;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Submit the hash of the result of the oracle game played on the
+        /// table, see [`Metasino::compute_result_hash`]. The result is
+        /// confirmed once as many result signers as the threshold submit
+        /// the same hash, and the game can then be ended with it.
+        /// error if the caller is not a result signer.
+        /// error if the game is not an ongoing oracle game.
+        /// error if the caller already submitted a result or one is confirmed.
+        #[ink(message)]
+        pub fn submit_result(&mut self, table_id: TableId, result_hash: Hash) -> Result<()> {
+            let table = self.table(table_id)?;
+            let signer = Self::env().caller();
+            if !self.get_result_signers().contains(&signer) {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            if table.state != STATE::PLAYING
+                || table.game != GameKind::Oracle
+                || self.confirmed_results.contains(table_id)
+            {
+                return Err(MetasinoError::WrongState);
+            }
+            let mut votes = self.result_votes.get(table_id).unwrap_or_default();
+            if votes.iter().any(|(voter, _)| *voter == signer) {
+                return Err(MetasinoError::WrongState);
+            }
+            votes.push((signer, result_hash));
+            Self::env().emit_event(ResultSubmitted {
+                table_id,
+                signer,
+                result_hash,
+            });
+            if self.confirms(&votes, result_hash) {
+                self.confirmed_results.insert(table_id, &result_hash);
+                self.result_votes.remove(table_id);
+                Self::env().emit_event(ResultFinalized {
+                    table_id,
+                    result_hash,
+                });
+            } else {
+                self.result_votes.insert(table_id, &votes);
+            }
+            Ok(())
+        }
+
+        /// Compute the hash of the result of an oracle game, the winners
+        /// along with their shares as given to [`Metasino::end_game`].
+        #[ink(message)]
+        pub fn compute_result_hash(&self, winners: Vec<AccountId>, shares: Vec<u8>) -> Hash {
+            oracle::result_hash(&winners, &shares)
+        }
+
+        /// Get the result hash confirmed for the oracle game of the table, if any.
+        #[ink(message)]
+        pub fn get_confirmed_result(&self, table_id: TableId) -> Option<Hash> {
+            self.confirmed_results.get(table_id)
+        }
+
+        /// Challenge anyone to a coin flip for `stake`, the value transferred
+        /// along with the call, committing to a seed as for a table.
+        /// error if nothing is staked, or so much that the pot of the duel overflows.
+        #[ink(message, payable)]
+        pub fn create_duel(&mut self, commitment: Hash) -> Result<DuelId> {
+            self.when_not_paused()?;
+            let stake = Self::env().transferred_value();
+            if stake == 0 {
+                return Err(MetasinoError::WrongBet);
+            }
+            // The pot of both stakes has to be paid out once the duel is accepted.
+            arithmetic::add(stake, stake)?;
+            let challenger = Self::env().caller();
+            let duel_id = self.next_duel_id;
+            let config = self.get_config();
+            self.duels.insert(
+                duel_id,
+                &Duel {
+                    challenger,
+                    opponent: None,
+                    stake,
+                    commitments: [commitment, Hash::default()],
+                    seeds: [None, None],
+                    reveal_deadline: 0,
+                    rake_bps: config.rake_bps,
+                    rake_cap: config.rake_cap,
+                    state: DuelState::Open,
+                    winner: None,
+                },
+            );
+            self.next_duel_id += 1;
+            self.record_registration(challenger, stake);
+            Self::env().emit_event(DuelCreated {
+                duel_id,
+                challenger,
+                stake,
+            });
+            Ok(duel_id)
+        }
+
+        /// Accept the duel by matching its stake and committing to a seed.
+        /// Both players then have to reveal their seed before the deadline.
+        /// error if the duel is not open or the caller opened it.
+        /// error if the value transferred falls short of the stake.
+        #[ink(message, payable)]
+        pub fn accept_duel(&mut self, duel_id: DuelId, commitment: Hash) -> Result<()> {
+            self.when_not_paused()?;
+            let mut duel = self.duel(duel_id)?;
+            let caller = Self::env().caller();
+            if duel.state != DuelState::Open || caller == duel.challenger {
+                return Err(MetasinoError::WrongState);
+            }
+            self.take_value(duel.stake)?;
+            duel.opponent = Some(caller);
+            duel.commitments[1] = commitment;
+            duel.reveal_deadline = Self::env().block_number().saturating_add(REVEAL_PERIOD);
+            duel.state = DuelState::Accepted;
+            self.duels.insert(duel_id, &duel);
+            self.record_registration(caller, duel.stake);
+            Self::env().emit_event(DuelAccepted {
+                duel_id,
+                opponent: caller,
+            });
+            Ok(())
+        }
+
+        /// Withdraw the duel before anyone accepted it and get the stake back.
+        /// error if the caller did not open the duel.
+        /// error if the duel is not open.
+        #[ink(message)]
+        pub fn cancel_duel(&mut self, duel_id: DuelId) -> Result<()> {
+            let mut duel = self.duel(duel_id)?;
+            if Self::env().caller() != duel.challenger {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            if duel.state != DuelState::Open {
+                return Err(MetasinoError::WrongState);
+            }
+            self.credit_withdrawal(duel.challenger, duel.stake)?;
+            self.record_refund(duel.challenger, duel.stake);
+            duel.state = DuelState::Cancelled;
+            self.duels.insert(duel_id, &duel);
+            Ok(())
+        }
+
+        /// Reveal the seed committed to in the duel. The coin is flipped once
+        /// both seeds are revealed, the challenger winning on an even flip.
+        /// error if the caller is not in the duel.
+        /// error if the duel is not accepted or the reveal deadline has passed.
+        /// error if the seed does not match the commitment.
+        #[ink(message)]
+        pub fn reveal_duel(&mut self, duel_id: DuelId, seed: [u8; 32]) -> Result<()> {
+            let mut duel = self.duel(duel_id)?;
+            let caller = Self::env().caller();
+            let side = if caller == duel.challenger {
+                0
+            } else if Some(caller) == duel.opponent {
+                1
+            } else {
+                return Err(MetasinoError::NotAuthorized)
+            };
+            if duel.state != DuelState::Accepted {
+                return Err(MetasinoError::WrongState);
+            }
+            if Self::env().block_number() > duel.reveal_deadline {
+                return Err(MetasinoError::DeadlinePassed);
+            }
+            if duel.seeds[side].is_some() {
+                return Err(MetasinoError::AlreadyRevealed);
+            }
+            if duel.commitments[side] != randomness::commitment(&caller, &seed) {
+                return Err(MetasinoError::InvalidReveal);
+            }
+            duel.seeds[side] = Some(seed);
+            if let [Some(challenger_seed), Some(opponent_seed)] = duel.seeds {
+                let mut flip = challenger_seed;
+                randomness::combine(&mut flip, &opponent_seed);
+                let winner = match flip[0] % 2 {
+                    0 => duel.challenger,
+                    _ => duel.opponent.unwrap_or(duel.challenger),
+                };
+                self.settle_duel(duel_id, &mut duel, Some(winner))?;
+            }
+            self.duels.insert(duel_id, &duel);
+            Ok(())
+        }
+
+        /// Close the duel once its reveal deadline has passed. The only player
+        /// who revealed takes the pot, and if nobody did both stakes are refunded.
+        /// error if the duel is not accepted.
+        /// error if the reveal deadline has not been reached.
+        #[ink(message)]
+        pub fn claim_duel(&mut self, duel_id: DuelId) -> Result<()> {
+            let mut duel = self.duel(duel_id)?;
+            if duel.state != DuelState::Accepted {
+                return Err(MetasinoError::WrongState);
+            }
+            if Self::env().block_number() <= duel.reveal_deadline {
+                return Err(MetasinoError::DeadlineNotReached);
+            }
+            let winner = match duel.seeds {
+                [Some(_), None] => Some(duel.challenger),
+                [None, Some(_)] => duel.opponent,
+                _ => None,
+            };
+            self.settle_duel(duel_id, &mut duel, winner)?;
+            self.duels.insert(duel_id, &duel);
+            Ok(())
+        }
+
+        /// Get the duel with the given id.
+        #[ink(message)]
+        pub fn get_duel(&self, duel_id: DuelId) -> Option<Duel> {
+            self.duels.get(duel_id)
+        }
+
+        /// Open a lottery selling tickets at `ticket_price`, drawn once the
+        /// `draw_at` block is reached.
+        /// error if the caller is not the owner.
+        /// error if the tickets are free or the draw block is already reached.
+        #[ink(message)]
+        pub fn open_lottery(
+            &mut self,
+            ticket_price: Balance,
+            draw_at: BlockNumber,
+        ) -> Result<LotteryId> {
+            self.only_owner()?;
+            if ticket_price == 0 || draw_at <= Self::env().block_number() {
+                return Err(MetasinoError::InvalidTableConfig);
+            }
+            let lottery_id = self.next_lottery_id;
+            self.lotteries.insert(lottery_id, &Lottery::new(ticket_price, draw_at));
+            self.next_lottery_id += 1;
+            Ok(lottery_id)
+        }
+
+        /// Buy the next ticket of the lottery, paying its price along with the
+        /// call. The commitment is mixed into the entropy the winner is drawn
+        /// from. Returns the number of the ticket.
+        /// error if the lottery is drawn or its draw block is reached.
+        /// error if the value transferred falls short of the ticket price.
+        #[ink(message, payable)]
+        pub fn buy_ticket(&mut self, lottery_id: LotteryId, commitment: Hash) -> Result<u32> {
+            self.when_not_paused()?;
+            let mut lottery = self.lottery(lottery_id)?;
+            if lottery.drawn || Self::env().block_number() >= lottery.draw_at {
+                return Err(MetasinoError::WrongState);
+            }
+            self.take_value(lottery.ticket_price)?;
+            let caller = Self::env().caller();
+            let ticket = lottery.add_ticket(&commitment);
+            self.lotteries.insert(lottery_id, &lottery);
+            self.tickets.insert((lottery_id, ticket), &caller);
+            self.record_wager(caller, lottery.ticket_price);
+            Ok(ticket)
+        }
+
+        /// Draw the lottery once its draw block is reached and pay the pool of
+        /// tickets minus the rake to the holder of the winning ticket.
+        /// Anyone may trigger the draw.
+        /// error if the lottery was already drawn.
+        /// error if the draw block is not reached.
+        #[ink(message)]
+        pub fn draw_lottery(&mut self, lottery_id: LotteryId) -> Result<()> {
+            let mut lottery = self.lottery(lottery_id)?;
+            let ticket = lottery.draw(Self::env().block_number())?;
+            let winner = ticket.and_then(|ticket| self.tickets.get((lottery_id, ticket)));
+            let pool = lottery.pool()?;
+            let config = self.get_config();
+            let rake = arithmetic::mul_div(pool, config.rake_bps as Balance, MAX_BPS as Balance)?
+                .min(config.rake_cap);
+            let prize = arithmetic::sub(pool, rake)?;
+            let treasury = arithmetic::add(self.treasury, rake)?;
+            if let Some(winner) = winner {
+                self.credit_withdrawal(winner, prize)?;
+                self.record_win(winner, prize);
+            }
+            self.treasury = treasury;
+            self.lotteries.insert(lottery_id, &lottery);
+            Self::env().emit_event(LotteryDrawn {
+                lottery_id,
+                winner,
+                ticket,
+                prize,
+                rake,
+            });
+            Ok(())
+        }
+
+        /// Get the lottery with the given id.
+        #[ink(message)]
+        pub fn get_lottery(&self, lottery_id: LotteryId) -> Option<Lottery> {
+            self.lotteries.get(lottery_id)
+        }
+
+        /// Get the holder of the ticket of the lottery.
+        #[ink(message)]
+        pub fn get_ticket_holder(&self, lottery_id: LotteryId, ticket: u32) -> Option<AccountId> {
+            self.tickets.get((lottery_id, ticket))
+        }
+
+        /// Open a parimutuel market on `outcomes` outcomes of an event outside
+        /// the contract, taking stakes until the `lock_at` block.
+        /// error if the caller is not an operator.
+        /// error if there are fewer than two outcomes or the lock block is reached.
+        #[ink(message)]
+        pub fn open_market(&mut self, outcomes: u8, lock_at: BlockNumber) -> Result<MarketId> {
+            self.only_role(Role::Operator)?;
+            if outcomes < 2 || lock_at <= Self::env().block_number() {
+                return Err(MetasinoError::InvalidTableConfig);
+            }
+            let market_id = self.next_market_id;
+            self.markets.insert(market_id, &Market::new(outcomes, lock_at));
+            self.next_market_id += 1;
+            Ok(market_id)
+        }
+
+        /// Stake the value transferred along with the call on the outcome of
+        /// the market.
+        /// error if the market is locked.
+        /// error if the outcome does not exist or nothing is transferred.
+        #[ink(message, payable)]
+        pub fn stake_on_market(&mut self, market_id: MarketId, outcome: u8) -> Result<()> {
+            self.when_not_paused()?;
+            let mut market = self.market(market_id)?;
+            let amount = Self::env().transferred_value();
+            market.stake(outcome, amount, Self::env().block_number())?;
+            let caller = Self::env().caller();
+            let mut stakes = self
+                .market_stakes
+                .get((market_id, caller))
+                .unwrap_or_else(|| ink_prelude::vec![0; market.stakes.len()]);
+            stakes[outcome as usize] = arithmetic::add(stakes[outcome as usize], amount)?;
+            self.market_stakes.insert((market_id, caller), &stakes);
+            self.markets.insert(market_id, &market);
+            self.record_wager(caller, amount);
+            Self::env().emit_event(MarketStaked {
+                market_id,
+                player: caller,
+                outcome,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Report the outcome of the event of the market once it is locked,
+        /// the rake of the house being taken out of the losing stakes.
+        /// error if the caller is not a result signer.
+        /// error if the market is resolved or not locked yet.
+        /// error if the outcome does not exist.
+        #[ink(message)]
+        pub fn report_market(&mut self, market_id: MarketId, outcome: u8) -> Result<()> {
+            if !self.get_result_signers().contains(&Self::env().caller()) {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            let mut market = self.market(market_id)?;
+            let config = self.get_config();
+            let rake = market.resolve(
+                outcome,
+                Self::env().block_number(),
+                config.rake_bps,
+                config.rake_cap,
+            )?;
+            self.treasury = arithmetic::add(self.treasury, rake)?;
+            self.markets.insert(market_id, &market);
+            Self::env().emit_event(MarketResolved {
+                market_id,
+                outcome,
+                rake,
+            });
+            Ok(())
+        }
+
+        /// Credit the caller with what their stakes won on the resolved
+        /// market, to be withdrawn.
+        /// error if the market is not resolved.
+        /// error if the caller has no stake left on the market.
+        #[ink(message)]
+        pub fn claim_market(&mut self, market_id: MarketId) -> Result<()> {
+            let mut market = self.market(market_id)?;
+            let caller = Self::env().caller();
+            let stakes = self
+                .market_stakes
+                .get((market_id, caller))
+                .ok_or(MetasinoError::NotAuthorized)?;
+            let payout = market.claim(&stakes)?;
+            self.market_stakes.remove((market_id, caller));
+            self.markets.insert(market_id, &market);
+            if payout > 0 {
+                self.credit_withdrawal(caller, payout)?;
+                self.record_win(caller, payout);
+            }
+            Ok(())
+        }
+
+        /// Get the market with the given id.
+        #[ink(message)]
+        pub fn get_market(&self, market_id: MarketId) -> Option<Market> {
+            self.markets.get(market_id)
+        }
+
+        /// Get the stakes of the player on each outcome of the market, until claimed.
+        #[ink(message)]
+        pub fn get_market_stakes(&self, market_id: MarketId, player: AccountId) -> Vec<Balance> {
+            self.market_stakes.get((market_id, player)).unwrap_or_default()
+        }
+
+        /// Open a tournament and enter it, paying the `buy_in` along with the
+        /// call. The caller directs the tournament, reporting its hands.
+        /// Each player starts with `starting_chips` and the blind doubles every
+        /// `blind_interval` blocks.
+        /// error if the players limit or the stacks are not valid.
+        /// error if the value transferred falls short of the buy-in.
+        #[ink(message, payable)]
+        pub fn create_tournament(
+            &mut self,
+            buy_in: Balance,
+            starting_chips: Chips,
+            max_players: u8,
+            blind_interval: BlockNumber,
+        ) -> Result<TournamentId> {
+            self.when_not_paused()?;
+            if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&max_players)
+                || starting_chips == 0
+                || blind_interval == 0
+            {
+                return Err(MetasinoError::InvalidTableConfig);
+            }
+            self.take_value(buy_in)?;
+            let caller = Self::env().caller();
+            let mut tournament =
+                Tournament::new(caller, buy_in, starting_chips, max_players, blind_interval);
+            tournament.join(caller)?;
+            let tournament_id = self.next_tournament_id;
+            self.tournaments.insert(tournament_id, &tournament);
+            self.next_tournament_id += 1;
+            self.record_registration(caller, buy_in);
+            Ok(tournament_id)
+        }
+
+        /// Enter the tournament, paying its buy-in along with the call.
+        /// error if the tournament started or is full.
+        /// error if the caller already entered.
+        /// error if the value transferred falls short of the buy-in.
+        #[ink(message, payable)]
+        pub fn join_tournament(&mut self, tournament_id: TournamentId) -> Result<()> {
+            self.when_not_paused()?;
+            let mut tournament = self.tournament(tournament_id)?;
+            self.take_value(tournament.buy_in)?;
+            let caller = Self::env().caller();
+            tournament.join(caller)?;
+            self.tournaments.insert(tournament_id, &tournament);
+            self.record_registration(caller, tournament.buy_in);
+            Ok(())
+        }
+
+        /// Enter the tournament the ticket is made out of in place of paying
+        /// its buy-in. The ticket is locked by transferring it to Metasino,
+        /// which the caller has to approve beforehand.
+        /// error if the tournament does not take tickets.
+        /// error if the ticket was not minted by Metasino.
+        /// error if the caller does not own the ticket.
+        /// error if the tournament started or is full.
+        /// error if the caller already entered.
+        #[ink(message)]
+        pub fn register_for_tournament(&mut self, ticket_id: u128) -> Result<()> {
+            self.when_not_paused()?;
+            let (tournament_id, serial) = tournament::ticket_of(ticket_id);
+            let mut tournament = self.tournament(tournament_id)?;
+            let contract = tournament.ticket.ok_or(MetasinoError::WrongState)?;
+            if serial >= tournament.tickets_issued {
+                return Err(MetasinoError::InvalidAction);
+            }
+            let caller = Self::env().caller();
+            if psp34::owner_of(contract, psp34::Id::U128(ticket_id))? != Some(caller) {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            tournament.join(caller)?;
+            tournament.tickets_redeemed += 1;
+            let locked = Self::env().account_id();
+            psp34::transfer(contract, locked, psp34::Id::U128(ticket_id))?;
+            self.tournaments.insert(tournament_id, &tournament);
+            self.record_registration(caller, tournament.buy_in);
+            Ok(())
+        }
+
+        /// Let the tournament be entered with the tickets of the PSP34
+        /// `ticket` contract, which has to let Metasino mint them, or stop
+        /// minting tickets for it with `None`.
+        /// error if the caller is not the director.
+        /// error if the tournament started.
+        #[ink(message)]
+        pub fn set_tournament_ticket(
+            &mut self,
+            tournament_id: TournamentId,
+            ticket: Option<AccountId>,
+        ) -> Result<()> {
+            let mut tournament = self.unstarted_tournament(tournament_id)?;
+            tournament.ticket = ticket;
+            self.tournaments.insert(tournament_id, &tournament);
+            Ok(())
+        }
+
+        /// Set the part of the buy-in of the tournament put on the head of
+        /// every player, paid to whoever eliminates them.
+        /// error if the caller is not the director.
+        /// error if the tournament started.
+        /// error if the bounty exceeds the buy-in.
+        #[ink(message)]
+        pub fn set_bounty(&mut self, tournament_id: TournamentId, bounty: Balance) -> Result<()> {
+            let mut tournament = self.unstarted_tournament(tournament_id)?;
+            if bounty > tournament.buy_in {
+                return Err(MetasinoError::InvalidTableConfig);
+            }
+            tournament.bounty = bounty;
+            self.tournaments.insert(tournament_id, &tournament);
+            Ok(())
+        }
+
+        /// Make the tournament a satellite of the `target` tournament, its
+        /// places being paid in tickets to the target, or a plain tournament
+        /// again with `None`.
+        /// error if the caller is not the director.
+        /// error if the tournament started.
+        /// error if the target takes no tickets or is the tournament itself.
+        #[ink(message)]
+        pub fn set_satellite(
+            &mut self,
+            tournament_id: TournamentId,
+            target: Option<TournamentId>,
+        ) -> Result<()> {
+            let mut tournament = self.unstarted_tournament(tournament_id)?;
+            if let Some(target) = target {
+                if target == tournament_id || self.tournament(target)?.ticket.is_none() {
+                    return Err(MetasinoError::InvalidTableConfig);
+                }
+            }
+            tournament.satellite_for = target;
+            self.tournaments.insert(tournament_id, &tournament);
+            Ok(())
+        }
+
+        /// Start the first blind level of the tournament.
+        /// error if the caller is not the director.
+        /// error if the tournament already started or fewer than two entered.
+        #[ink(message)]
+        pub fn start_tournament(&mut self, tournament_id: TournamentId) -> Result<()> {
+            let mut tournament = self.tournament(tournament_id)?;
+            if Self::env().caller() != tournament.director {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            tournament.start(Self::env().block_number())?;
+            self.tournaments.insert(tournament_id, &tournament);
+            Self::env().emit_event(TournamentStarted {
+                tournament_id,
+                players: tournament.players.clone(),
+                prize_pool: tournament.pool()?,
+            });
+            Ok(())
+        }
+
+        /// Report a hand of the tournament: `wagers` holds the chips each seat
+        /// put in, all of them going to the `winner` seat. Players left without
+        /// chips are eliminated, their bounty going to the winner, and the
+        /// prize pool is paid once one remains.
+        /// error if the caller is neither the director nor a dealer.
+        /// error if the tournament is not running.
+        /// error if a seat put in less than the current blind or more than its stack.
+        #[ink(message)]
+        pub fn report_hand(
+            &mut self,
+            tournament_id: TournamentId,
+            winner: u8,
+            wagers: Vec<Chips>,
+        ) -> Result<()> {
+            let mut tournament = self.tournament(tournament_id)?;
+            if Self::env().caller() != tournament.director {
+                self.only_role(Role::Dealer)?;
+            }
+            let out = tournament.play_hand(winner, &wagers, Self::env().block_number())?;
+            let remaining = tournament.players.len() - tournament.eliminated.len();
+            let mut place = (remaining + out.len()) as u8;
+            let hunter = tournament.players[winner as usize];
+            for seat in out {
+                let victim = tournament.players[seat as usize];
+                Self::env().emit_event(PlayerEliminated {
+                    tournament_id,
+                    player: victim,
+                    place,
+                });
+                place -= 1;
+                self.pay_bounty(tournament_id, &tournament, hunter, victim)?;
+            }
+            if tournament.is_finished() {
+                self.pay_bounty(tournament_id, &tournament, hunter, hunter)?;
+                self.pay_tournament(tournament_id, &tournament)?;
+            }
+            self.tournaments.insert(tournament_id, &tournament);
+            Ok(())
+        }
+
+        /// Get the tournament with the given id.
+        #[ink(message)]
+        pub fn get_tournament(&self, tournament_id: TournamentId) -> Option<Tournament> {
+            self.tournaments.get(tournament_id)
+        }
+
+        /// Get the blind every player of the tournament has to put in a hand now.
+        #[ink(message)]
+        pub fn get_blind(&self, tournament_id: TournamentId) -> Result<Chips> {
+            Ok(self.tournament(tournament_id)?.blind(Self::env().block_number()))
+        }
+
+        /// Get the record of the game with the given id.
+        #[ink(message)]
+        pub fn get_game(&self, game_id: GameId) -> Option<GameRecord> {
+            self.games.get(game_id)
+        }
+
+        /// Get the result of the last game ended on the table, if any.
+        #[ink(message)]
+        pub fn get_last_result(&self, table_id: TableId) -> Option<GameResult> {
+            self.last_results.get(table_id)
+        }
+
+        /// Delete the records of the games ended before the given one to free
+        /// their storage deposit, at most 100 of them at once. Returns the
+        /// number of records deleted. Only the last 10000 games are kept anyway.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn prune_history(&mut self, before_game_id: GameId) -> Result<GameId> {
+            self.only_owner()?;
+            let start = self.history_start;
+            let before = before_game_id
+                .min(self.next_game_id)
+                .min(start.saturating_add(MAX_PRUNED_GAMES));
+            self.prune_games(before);
+            Ok(self.history_start - start)
+        }
+
+        /// Delete the records of the games ended before the given one which
+        /// are still kept.
+        fn prune_games(&mut self, before_game_id: GameId) {
+            for game_id in self.history_start..before_game_id {
+                self.games.remove(game_id);
+            }
+            self.history_start = self.history_start.max(before_game_id);
+        }
+
+        /// Get the records of the last games ended, the most recent first.
+        /// At most 50 games are returned whatever the `limit`.
+        #[ink(message)]
+        pub fn get_recent_games(&self, limit: u32) -> Vec<GameRecord> {
+            let limit = limit.min(MAX_RECENT_GAMES) as GameId;
+            let from = self.next_game_id.saturating_sub(limit);
+            (from..self.next_game_id)
+                .rev()
+                .filter_map(|game_id| self.games.get(game_id))
+                .collect()
+        }
+
+        /// Get the lifetime statistics of the player.
+        #[ink(message)]
+        pub fn get_player_stats(&self, player: AccountId) -> PlayerStats {
+            self.stats.get(player).unwrap_or_default()
+        }
+
+        /// Get the `n` players who won the most along with their statistics,
+        /// the best first. Only the 10 best players are ranked.
+        #[ink(message)]
+        pub fn get_top_players(&self, n: u8) -> Vec<(AccountId, PlayerStats)> {
+            self.leaderboard
+                .get()
+                .unwrap_or_default()
+                .iter()
+                .take(n as usize)
+                .map(|player| (*player, self.get_player_stats(*player)))
+                .collect()
+        }
+
+        /// Get the season the statistics currently accrue to.
+        #[ink(message)]
+        pub fn get_season(&self) -> Season {
+            self.season
+        }
+
+        /// Get the statistics of the player in the season. The reputation is
+        /// only kept over the lifetime of the player.
+        #[ink(message)]
+        pub fn get_season_stats(&self, season: Season, player: AccountId) -> PlayerStats {
+            self.season_stats.get((season, player)).unwrap_or_default()
+        }
+
+        /// Get the `n` players who won the most in the season along with their
+        /// statistics in it, the best first. Only the 10 best players are ranked.
+        #[ink(message)]
+        pub fn get_season_leaderboard(
+            &self,
+            season: Season,
+            n: u8,
+        ) -> Vec<(AccountId, PlayerStats)> {
+            self.season_leaderboards
+                .get(season)
+                .unwrap_or_default()
+                .into_iter()
+                .take(n as usize)
+                .map(|player| (player, self.get_season_stats(season, player)))
+                .collect()
+        }
+
+        /// Set the number of blocks after which a season closes by itself, the
+        /// next season starting with the first game played past it. 0 leaves
+        /// closing seasons to the owner.
+        /// error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_season_length(&mut self, blocks: BlockNumber) -> Result<()> {
+            self.only_owner()?;
+            self.season_length = blocks;
+            Ok(())
+        }
+
+        /// Set the prize of each rank of the leaderboard of a season, the best
+        /// first, paid out of the treasury when the season closes.
+        /// error if the caller is not the owner.
+        /// error if more prizes than ranks are given.
+        #[ink(message)]
+        pub fn set_season_prizes(&mut self, prizes: Vec<Balance>) -> Result<()> {
+            self.only_owner()?;
+            if prizes.len() > LEADERBOARD_SIZE {
+                return Err(MetasinoError::InvalidPayout);
+            }
+            self.season_prizes.set(&prizes);
+            Ok(())
+        }
+
+        /// Close the current season, awarding its prizes to the top of its
+        /// leaderboard, and start the next one.
+        /// error if the caller is not the owner.
+        /// error if the treasury cannot pay for the prizes.
+        #[ink(message)]
+        pub fn close_season(&mut self) -> Result<()> {
+            self.only_owner()?;
+            self.end_season(true)
+        }
+
+        /// Get the prize the player may claim for the closed season.
+        #[ink(message)]
+        pub fn get_season_prize(&self, season: Season, player: AccountId) -> Balance {
+            self.season_awards.get((season, player)).unwrap_or_default()
+        }
+
+        /// Credit the prize of the caller for the closed season to their
+        /// withdrawable balance.
+        /// error if the caller has no prize to claim for the season.
+        #[ink(message)]
+        pub fn claim_season_prize(&mut self, season: Season) -> Result<()> {
+            let caller = Self::env().caller();
+            let prize = self.get_season_prize(season, caller);
+            if prize == 0 {
+                return Err(MetasinoError::InvalidPayout);
+            }
+            self.season_awards.remove((season, caller));
+            self.credit_withdrawal(caller, prize)
+        }
+
+        /// Hand over the table to a new initializer.
+        /// error if the caller is not the initializer.
+        #[ink(message)]
+        pub fn transfer_table_ownership(
+            &mut self,
+            table_id: TableId,
+            new_initializer: AccountId,
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            let hosted = self.hosted_tables.get(new_initializer).unwrap_or_default();
+            let max_tables = self.get_config().max_tables_per_account;
+            if max_tables > 0 && hosted >= max_tables {
+                return Err(MetasinoError::TooManyTables);
+            }
+            let from_hosted = self.hosted_tables.get(table.initializer).unwrap_or_default();
+            self.hosted_tables
+                .insert(table.initializer, &from_hosted.saturating_sub(1));
+            self.hosted_tables.insert(new_initializer, &(hosted + 1));
+            Self::env().emit_event(TableOwnershipTransferred {
+                table_id,
+                from: table.initializer,
+                to: new_initializer,
+            });
+            table.initializer = new_initializer;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Make the table private, only the accounts invited by the initializer
+        /// being able to register, or public again.
+        /// error if the caller is not the initializer.
+        #[ink(message)]
+        pub fn set_private(&mut self, table_id: TableId, is_private: bool) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            table.is_private = is_private;
+            self.tables.insert(table_id, &table);
+            Ok(())
+        }
+
+        /// Invite the account to register in the private table.
+        /// error if the caller is not the initializer.
+        #[ink(message)]
+        pub fn invite(&mut self, table_id: TableId, account: AccountId) -> Result<()> {
+            let table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            self.allowlist.insert((table_id, account), &());
+            Ok(())
+        }
+
+        /// Withdraw the invitation of the account to the private table.
+        /// A player already registered keeps their seat.
+        /// error if the caller is not the initializer.
+        #[ink(message)]
+        pub fn revoke_invite(&mut self, table_id: TableId, account: AccountId) -> Result<()> {
+            let table = self.table(table_id)?;
+            self.only_initializer(&table)?;
+            self.allowlist.remove((table_id, account));
+            Ok(())
+        }
+
+        /// Ban the account from registering in the table, or in every table if
+        /// `table_id` is `None`. A player already seated keeps their seat.
+        /// error if the caller is not the initializer of the table, or the
+        /// owner for a ban from every table.
+        #[ink(message)]
+        pub fn ban_player(&mut self, table_id: Option<TableId>, account: AccountId) -> Result<()> {
+            match table_id {
+                Some(table_id) => {
+                    self.only_initializer(&self.table(table_id)?)?;
+                    self.table_bans.insert((table_id, account), &());
+                }
+                None => {
+                    self.only_owner()?;
+                    self.bans.insert(account, &());
+                }
+            }
+            Self::env().emit_event(PlayerBanned {
+                table_id,
+                player: account,
+            });
+            Ok(())
+        }
+
+        /// Lift the ban of the account from the table, or from every table if
+        /// `table_id` is `None`.
+        /// error if the caller is not the initializer of the table, or the
+        /// owner for a ban from every table.
+        #[ink(message)]
+        pub fn unban_player(
+            &mut self,
+            table_id: Option<TableId>,
+            account: AccountId,
+        ) -> Result<()> {
+            match table_id {
+                Some(table_id) => {
+                    self.only_initializer(&self.table(table_id)?)?;
+                    self.table_bans.remove((table_id, account));
+                }
+                None => {
+                    self.only_owner()?;
+                    self.bans.remove(account);
+                }
+            }
+            Self::env().emit_event(PlayerUnbanned {
+                table_id,
+                player: account,
+            });
+            Ok(())
+        }
+
+        /// Whether the account is banned from the table, or from every table if
+        /// `table_id` is `None`.
+        #[ink(message)]
+        pub fn is_banned(&self, table_id: Option<TableId>, account: AccountId) -> bool {
+            match table_id {
+                Some(table_id) => self.table_bans.contains((table_id, account)),
+                None => self.bans.contains(account),
+            }
+        }
+
+        /// Compute the hash of a join code, to open a table only the players
+        /// knowing the code may register in.
+        #[ink(message)]
+        pub fn compute_join_code_hash(&self, code: Vec<u8>) -> Hash {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink_env::hash_bytes::<Blake2x256>(&code, &mut output);
+            Hash::from(output)
+        }
+
+        /// Whether the account is invited to the table.
+        #[ink(message)]
+        pub fn is_invited(&self, table_id: TableId, account: AccountId) -> bool {
+            self.allowlist.contains((table_id, account))
+        }
+
+        /// Get the table with the given id.
+        #[ink(message)]
+        pub fn get_table(&self, table_id: TableId) -> Option<Table> {
+            self.tables.get(table_id)
+        }
+
+        /// Get the state, players, pot and configuration of the table at once.
+        #[ink(message)]
+        pub fn get_table_info(&self, table_id: TableId) -> Result<TableInfo> {
+            let table = self.table(table_id)?;
+            Ok(TableInfo {
+                table_id,
+                state: table.state,
+                players: self.players_of(table_id, &table),
+                pot: table.pot,
+                required_start_bet: table.required_start_bet,
+                initializer: table.initializer,
+                min_players: table.min_players,
+                max_players: table.max_players,
+                token: table.token,
+                game: table.game,
+                rake_bps: table.rake_bps,
+                rake_cap: table.rake_cap,
+                rake_recipient: table.rake_recipient,
+                button: table.button,
+                small_blind: table.small_blind,
+                big_blind: table.big_blind,
+                ante: table.ante,
+                variant: table.variant,
+                structure: table.structure,
+                hand_number: table.hand_number,
+                start_at: table.start_at,
+                sit_and_go: table.sit_and_go,
+                freeroll: table.freeroll,
+                dealer: table.dealer,
+            })
+        }
+
+        /// Get the information of each of the tables at once, in the order of
+        /// the ids, `None` for the tables that do not exist. Only the first 50
+        /// ids are looked up whatever the number asked for.
+        #[ink(message)]
+        pub fn get_tables_info(&self, ids: Vec<TableId>) -> Vec<Option<TableInfo>> {
+            ids.into_iter()
+                .take(MAX_TABLE_SUMMARIES as usize)
+                .map(|table_id| self.get_table_info(table_id).ok())
+                .collect()
+        }
+
+        /// Watch the games of the table as a spectator, for free, following
+        /// them through [`Metasino::get_spectator_view`]. At most 100
+        /// spectators watch a table.
+        /// error if the caller already watches the table.
+        /// error if the table has as many spectators as it may.
+        #[ink(message)]
+        pub fn watch_table(&mut self, table_id: TableId) -> Result<()> {
+            self.table(table_id)?;
+            let caller = Self::env().caller();
+            let mut spectators = self.spectators.get(table_id).unwrap_or_default();
+            if spectators.contains(&caller) {
+                return Err(MetasinoError::AlreadyRegistered);
+            }
+            if spectators.len() >= MAX_SPECTATORS {
+                return Err(MetasinoError::TableFull);
+            }
+            spectators.push(caller);
+            self.spectators.insert(table_id, &spectators);
+            Self::env().emit_event(SpectatorJoined {
+                table_id,
+                spectator: caller,
+            });
+            Ok(())
+        }
+
+        /// Stop watching the games of the table.
+        /// error if the caller does not watch the table.
+        #[ink(message)]
+        pub fn stop_watching(&mut self, table_id: TableId) -> Result<()> {
+            let caller = Self::env().caller();
+            let mut spectators = self.spectators.get(table_id).unwrap_or_default();
+            let index = spectators
+                .iter()
+                .position(|spectator| *spectator == caller)
+                .ok_or(MetasinoError::NotAuthorized)?;
+            spectators.swap_remove(index);
+            self.spectators.insert(table_id, &spectators);
+            Self::env().emit_event(SpectatorLeft {
+                table_id,
+                spectator: caller,
+            });
+            Ok(())
+        }
+
+        /// Get the accounts watching the table.
+        #[ink(message)]
+        pub fn get_spectators(&self, table_id: TableId) -> Vec<AccountId> {
+            self.spectators.get(table_id).unwrap_or_default()
+        }
+
+        /// Get what spectators see of the table: its pot, the community cards
+        /// dealt so far, the actions played and the hands shown down, but
+        /// never the commitments of the players.
+        #[ink(message)]
+        pub fn get_spectator_view(&self, table_id: TableId) -> Result<SpectatorView> {
+            let table = self.table(table_id)?;
+            let players = self.players_of(table_id, &table);
+            let deck = table.deck().ok();
+            let round = self.rounds.get(table_id);
+            let shown_hands = match &deck {
+                Some(deck) => players
+                    .iter()
+                    .filter_map(|player| {
+                        let positions = self.revealed_hands.get((table_id, *player))?;
+                        let hole = showdown::hole_cards(deck, &positions, table.variant).ok()?;
+                        Some((*player, hole))
+                    })
+                    .collect(),
+                None => Vec::new(),
+            };
+            let board = match (&deck, &round) {
+                (Some(deck), Some(round)) => showdown::shown_board(deck, round),
+                _ => Vec::new(),
+            };
+            let up_cards = match (&deck, &round) {
+                (Some(deck), Some(round)) if table.variant == Variant::Stud => (0..players.len())
+                    .map(|seat| stud::up_cards(deck, seat as u8, round.street))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            Ok(SpectatorView {
+                table_id,
+                state: table.state,
+                game: table.game,
+                hand_number: table.hand_number,
+                players,
+                pot: table.pot,
+                spectators: self.get_spectators(table_id).len() as u32,
+                board,
+                street: round.as_ref().map(|round| round.street),
+                active: round
+                    .as_ref()
+                    .filter(|round| !round.finished)
+                    .map(|round| round.active),
+                contributed: round
+                    .as_ref()
+                    .map(|round| round.contributed.clone())
+                    .unwrap_or_default(),
+                folded: round
+                    .as_ref()
+                    .map(|round| round.folded.clone())
+                    .unwrap_or_default(),
+                up_cards,
+                shown_hands,
+                history: (0..round.map_or(0, |round| round.plays))
+                    .filter_map(|play| self.hand_plays.get((table_id, play)))
+                    .collect(),
+            })
+        }
+
+        /// List the tables anyone may take a seat at right now, skipping the
+        /// first `offset` of them. At most 50 tables are returned whatever the
+        /// `limit`, the oldest first.
+        #[ink(message)]
+        pub fn list_open_tables(&self, offset: u32, limit: u32) -> Vec<TableSummary> {
+            self.summaries_where(|table| {
+                table.registration_guard().is_ok()
+                    && !table.is_full()
+                    && !table.is_private
+                    && table.join_code_hash.is_none()
+            })
+            .skip(offset as usize)
+            .take(limit.min(MAX_TABLE_SUMMARIES) as usize)
+            .collect()
+        }
+
+        /// Find the tables whose start bet lies between `min` and `max`,
+        /// both included. At most 50 tables are returned, the oldest first.
+        #[ink(message)]
+        pub fn find_tables_by_bet_range(&self, min: Balance, max: Balance) -> Vec<TableSummary> {
+            self.summaries_where(|table| (min..=max).contains(&table.required_start_bet))
+                .take(MAX_TABLE_SUMMARIES as usize)
+                .collect()
+        }
+
+        /// Find the tables in the given state. At most 50 tables are returned,
+        /// the oldest first.
+        #[ink(message)]
+        pub fn find_tables_by_state(&self, state: STATE) -> Vec<TableSummary> {
+            self.summaries_where(|table| table.state == state)
+                .take(MAX_TABLE_SUMMARIES as usize)
+                .collect()
+        }
+
+        /// Get the account which currently owns the table.
+        #[ink(message)]
+        pub fn get_initializer(&self, table_id: TableId) -> Result<AccountId> {
+            Ok(self.table(table_id)?.initializer)
+        }
+
+        /// Get the current state of the table.
+        #[ink(message)]
+        pub fn get_table_state(&self, table_id: TableId) -> Result<STATE> {
+            Ok(self.table(table_id)?.state)
+        }
+
+        /// check if the table is fully occupied.
+        #[ink(message)]
+        pub fn is_table_full(&self, table_id: TableId) -> Result<bool> {
+            Ok(self.table(table_id)?.is_full())
+        }
+
+        /// Get the current number of players in the table.
+        #[ink(message)]
+        pub fn get_players_count(&self, table_id: TableId) -> Result<u8> {
+            Ok(self.table(table_id)?.players_count())
+        }
+
+        /// Get the minimum players required to start the game on the table.
+        #[ink(message)]
+        pub fn get_min_players(&self, table_id: TableId) -> Result<u8> {
+            Ok(self.table(table_id)?.min_players)
+        }
+
+        /// Get the maximum players the table can seat.
+        #[ink(message)]
+        pub fn get_max_players(&self, table_id: TableId) -> Result<u8> {
+            Ok(self.table(table_id)?.max_players)
+        }
+
+        /// Get the current accumulated pot value.
+        #[ink(message)]
+        pub fn get_accumulated_pot(&self, table_id: TableId) -> Result<Balance> {
+            Ok(self.table(table_id)?.pot)
+        }
+
+        /// Get the address of the players in the table, by seat order.
+        /// Returns at most `limit` players starting with the seat `from`.
+        #[ink(message)]
+        pub fn get_players(
+            &self,
+            table_id: TableId,
+            from: u8,
+            limit: u8,
+        ) -> Result<Vec<AccountId>> {
+            let table = self.table(table_id)?;
+            let to = table.seated.min(from.saturating_add(limit));
+            Ok((from..to)
+                .filter_map(|seat| self.seats.get((table_id, seat)))
+                .collect())
+        }
+
+        /// Get the seat of the player in the table, if they are seated.
+        #[ink(message)]
+        pub fn get_player_info(&self, table_id: TableId, player: AccountId) -> Option<PlayerInfo> {
+            self.players.get((table_id, player))
+        }
+
+        /// Get the required start bet value.
+        #[ink(message)]
+        pub fn get_required_start_bet(&self, table_id: TableId) -> Result<Balance> {
+            Ok(self.table(table_id)?.required_start_bet)
+        }
+
+        /// Get the PSP22 token the table is played in, `None` for the native balance.
+        #[ink(message)]
+        pub fn get_token(&self, table_id: TableId) -> Result<Option<AccountId>> {
+            Ok(self.table(table_id)?.token)
+        }
+
+        /// Load the table with the given id.
+        fn table(&self, table_id: TableId) -> Result<Table> {
+            self.tables.get(table_id).ok_or(MetasinoError::TableNotFound)
+        }
+
+        /// Hold the payouts of the game about to be closed on the table for
+        /// `blocks` blocks.
+        fn hold_payouts(
+            &mut self,
+            table_id: TableId,
+            table: &Table,
+            winners: &[AccountId],
+            payouts: &[Balance],
+            blocks: BlockNumber,
+        ) {
+            let game_id = self.next_game_id;
+            let dispute_until = Self::env().block_number().saturating_add(blocks);
+            self.held_games += 1;
+            if table.token.is_none() {
+                let held = payouts.iter().copied().fold(0, Balance::saturating_add);
+                self.total_held = self.total_held.saturating_add(held);
+            }
+            self.pending_payouts.insert(
+                game_id,
+                &PendingPayout {
+                    table_id,
+                    token: table.token,
+                    winners: winners.to_vec(),
+                    payouts: payouts.to_vec(),
+                    dispute_until,
+                    arbiter: table.arbiter,
+                    dispute: None,
+                },
+            );
+            Self::env().emit_event(PayoutsHeld {
+                table_id,
+                game_id,
+                dispute_until,
+            });
+        }
+
+        /// Pay out the payouts held for the game to the winners and record
+        /// them as the outcome of the game.
+        fn release_payouts(
+            &mut self,
+            game_id: GameId,
+            pending: &PendingPayout,
+            winners: Vec<AccountId>,
+            payouts: &[Balance],
+        ) -> Result<()> {
+            for (winner, payout) in winners.iter().zip(payouts.iter()) {
+                if self.chips.contains((pending.table_id, *winner)) {
+                    self.credit_chips(pending.table_id, *winner, *payout)?;
+                } else {
+                    self.pay_in(pending.token, *winner, *payout)?;
+                }
+                self.record_win(*winner, *payout);
+            }
+            if let Some(mut game) = self.games.get(game_id) {
+                game.winners = winners.clone();
+                game.payouts = payouts.to_vec();
+                self.games.insert(game_id, &game);
+            }
+            self.pending_payouts.remove(game_id);
+            self.held_games = self.held_games.saturating_sub(1);
+            if pending.token.is_none() {
+                let held = pending.payouts.iter().copied().fold(0, Balance::saturating_add);
+                self.total_held = self.total_held.saturating_sub(held);
+            }
+            Self::env().emit_event(PayoutsFinalized {
+                game_id,
+                winners,
+                payouts: payouts.to_vec(),
+            });
+            Ok(())
+        }
+
+        /// The betting round of the poker hand played on the table, once over.
+        /// error if the table is not playing a poker hand whose betting is over.
+        fn showdown_round(&self, table_id: TableId, table: &Table) -> Result<BettingRound> {
+            if table.state != STATE::PLAYING || table.game != GameKind::Poker {
+                return Err(MetasinoError::WrongState);
+            }
+            self.rounds
+                .get(table_id)
+                .filter(|round| round.finished)
+                .ok_or(MetasinoError::WrongState)
+        }
+
+        /// The summaries of the tables hosted by the contract that `keep`
+        /// selects, the oldest first.
+        fn summaries_where<'a>(
+            &'a self,
+            keep: impl Fn(&Table) -> bool + 'a,
+        ) -> impl Iterator<Item = TableSummary> + 'a {
+            (0..self.next_table_id).filter_map(move |table_id| {
+                let table = self.tables.get(table_id).filter(|table| keep(table))?;
+                Some(TableSummary {
+                    table_id,
+                    state: table.state,
+                    game: table.game,
+                    token: table.token,
+                    required_start_bet: table.required_start_bet,
+                    seated: table.seated,
+                    min_players: table.min_players,
+                    max_players: table.max_players,
+                    restricted: table.is_private || table.join_code_hash.is_some(),
+                })
+            })
+        }
+
+        /// The engine of the table, once its game is dealt.
+        /// error if the table is not playing an engine game.
+        fn running_engine(table: &Table) -> Result<AccountId> {
+            if table.state != STATE::PLAYING || table.seed.is_none() {
+                return Err(MetasinoError::WrongState);
+            }
+            match table.game {
+                GameKind::Engine(engine) => Ok(engine),
+                _ => Err(MetasinoError::WrongState),
+            }
+        }
+
+        /// Load the tournament with the given id.
+        fn tournament(&self, tournament_id: TournamentId) -> Result<Tournament> {
+            self.tournaments
+                .get(tournament_id)
+                .ok_or(MetasinoError::TournamentNotFound)
+        }
+
+        /// Load the tournament with the given id for its director to set up.
+        /// error if the caller is not the director.
+        /// error if the tournament started.
+        fn unstarted_tournament(&self, tournament_id: TournamentId) -> Result<Tournament> {
+            let tournament = self.tournament(tournament_id)?;
+            if Self::env().caller() != tournament.director {
+                return Err(MetasinoError::NotAuthorized);
+            }
+            if tournament.started_at.is_some() {
+                return Err(MetasinoError::WrongState);
+            }
+            Ok(tournament)
+        }
+
+        /// Pay the prize pool of the finished tournament minus the rake to the
+        /// paid places, in tickets to its target if it is a satellite.
+        fn pay_tournament(
+            &mut self,
+            tournament_id: TournamentId,
+            tournament: &Tournament,
+        ) -> Result<()> {
+            let pool = tournament.pool()?;
+            let config = self.get_config();
+            let mut rake =
+                arithmetic::mul_div(pool, config.rake_bps as Balance, MAX_BPS as Balance)?
+                    .min(config.rake_cap);
+            let net = arithmetic::sub(pool, rake)?;
+            let standings: Vec<AccountId> = tournament
+                .standings()
+                .iter()
+                .map(|seat| tournament.players[*seat as usize])
+                .collect();
+            let (tickets, mut prizes) = match tournament.satellite_for {
+                Some(target) => self.issue_tickets(target, tournament, &standings, net)?,
+                None => (0, tournament.prizes(net)),
+            };
+            // A satellite paying every place in tickets leaves the rest to the house.
+            if prizes.len() > standings.len() {
+                rake = arithmetic::add(rake, prizes.pop().unwrap_or_default())?;
+            }
+            let treasury = arithmetic::add(self.treasury, rake)?;
+            let winners: Vec<AccountId> = standings.into_iter().take(prizes.len()).collect();
+            for (place, (winner, prize)) in winners.iter().zip(prizes.iter()).enumerate() {
+                if place >= tickets {
+                    self.credit_withdrawal(*winner, *prize)?;
+                }
+                self.record_win(*winner, *prize);
+            }
+            self.treasury = treasury;
+            Self::env().emit_event(TournamentFinished {
+                tournament_id,
+                winners,
+                prizes,
+                rake,
+            });
+            Ok(())
+        }
+
+        /// Credit the hunter with the bounty of the victim, the last standing
+        /// being their own victim.
+        fn pay_bounty(
+            &mut self,
+            tournament_id: TournamentId,
+            tournament: &Tournament,
+            hunter: AccountId,
+            victim: AccountId,
+        ) -> Result<()> {
+            if tournament.bounty == 0 {
+                return Ok(())
+            }
+            self.credit_withdrawal(hunter, tournament.bounty)?;
+            Self::env().emit_event(BountyCollected {
+                tournament_id,
+                hunter,
+                victim,
+                amount: tournament.bounty,
+            });
+            Ok(())
+        }
+
+        /// Mint the tickets to the target tournament the pool of the satellite
+        /// pays for to its top places, each ticket worth the buy-in of the
+        /// target, which the pool keeps backing. Returns the number of
+        /// tickets and the prize of each place, the pool left once the
+        /// tickets are paid being the prize of the next place.
+        fn issue_tickets(
+            &mut self,
+            target_id: TournamentId,
+            satellite: &Tournament,
+            standings: &[AccountId],
+            pool: Balance,
+        ) -> Result<(usize, Vec<Balance>)> {
+            let mut target = self.tournament(target_id)?;
+            let contract = target.ticket.ok_or(MetasinoError::WrongState)?;
+            let (tickets, rest) = satellite.tickets(pool, target.buy_in);
+            for player in standings.iter().take(tickets as usize) {
+                let ticket_id = tournament::ticket_id(target_id, target.tickets_issued);
+                psp34::mint(contract, *player, psp34::Id::U128(ticket_id))?;
+                target.tickets_issued += 1;
+                Self::env().emit_event(TicketIssued {
+                    tournament_id: target_id,
+                    player: *player,
+                    ticket_id,
+                });
+            }
+            self.tournaments.insert(target_id, &target);
+            let mut prizes = ink_prelude::vec![target.buy_in; tickets as usize];
+            if rest > 0 {
+                prizes.push(rest);
+            }
+            Ok((tickets as usize, prizes))
+        }
+
+        /// Store the protocol parameters and let the frontends know they changed.
+        fn set_config(&mut self, config: Config) {
+            self.config.set(&config);
+            Self::env().emit_event(ConfigChanged { config });
+        }
+
+        /// Load the lottery with the given id.
+        fn lottery(&self, lottery_id: LotteryId) -> Result<Lottery> {
+            self.lotteries.get(lottery_id).ok_or(MetasinoError::LotteryNotFound)
+        }
+
+        /// Load the market with the given id.
+        fn market(&self, market_id: MarketId) -> Result<Market> {
+            self.markets.get(market_id).ok_or(MetasinoError::MarketNotFound)
+        }
+
+        /// Load the duel with the given id.
+        fn duel(&self, duel_id: DuelId) -> Result<Duel> {
+            self.duels.get(duel_id).ok_or(MetasinoError::DuelNotFound)
+        }
+
+        /// Pay the pot of the duel minus the rake to the winner, or refund both
+        /// stakes if there is none.
+        fn settle_duel(
+            &mut self,
+            duel_id: DuelId,
+            duel: &mut Duel,
+            winner: Option<AccountId>,
+        ) -> Result<()> {
+            let pot = arithmetic::add(duel.stake, duel.stake)?;
+            let (payout, rake) = match winner {
+                Some(winner) => {
+                    let rake =
+                        arithmetic::mul_div(pot, duel.rake_bps as Balance, MAX_BPS as Balance)?
+                            .min(duel.rake_cap);
+                    let payout = arithmetic::sub(pot, rake)?;
+                    self.credit_withdrawal(winner, payout)?;
+                    self.record_win(winner, payout);
+                    (payout, rake)
+                }
+                None => {
+                    for player in [Some(duel.challenger), duel.opponent].iter().flatten() {
+                        self.credit_withdrawal(*player, duel.stake)?;
+                        self.record_refund(*player, duel.stake);
+                    }
+                    (0, 0)
+                }
+            };
+            self.treasury = arithmetic::add(self.treasury, rake)?;
+            duel.state = DuelState::Settled;
+            duel.winner = winner;
+            Self::env().emit_event(DuelSettled {
+                duel_id,
+                winner,
+                payout,
+                rake,
+            });
+            Ok(())
+        }
+
+        /// Move the table to the given state.
+        fn set_state(&self, table_id: TableId, table: &mut Table, to: STATE) {
+            Self::env().emit_event(StateChanged {
+                table_id,
+                from: table.state,
+                to,
+            });
+            if to == STATE::STAGING {
+                table.staged_at = Self::env().block_number();
+            }
+            table.state = to;
+        }
+
+        /// Record the game ended on the table and clear it for the next hand.
+        /// The players playing on chips stay seated for the next hand of the
+        /// session and the table goes into intermission, unless nobody does
+        /// and the table ends, returning the bond of the initializer.
+        fn close_game(
+            &mut self,
+            table_id: TableId,
+            table: &mut Table,
+            winners: Vec<AccountId>,
+            payouts: Vec<Balance>,
+            rake: Balance,
+        ) -> Result<()> {
+            let game_id = self.next_game_id;
+            let players = self.players_of(table_id, table);
+            let house_rake = self.house_rake(table, rake)?;
+            self.pay_referrals(table_id, table, &players, house_rake)?;
+            self.accrue_points(table, &players, rake);
+            for player in players.iter() {
+                self.count_hand(*player);
+            }
+            let deck = table.deck().ok().filter(|_| table.game == GameKind::Poker);
+            let winning_hands = winners
+                .iter()
+                .map(|winner| {
+                    let positions = self.revealed_hands.get((table_id, *winner))?;
+                    showdown::hole_cards(deck.as_ref()?, &positions, table.variant).ok()
+                })
+                .map(Option::unwrap_or_default)
+                .collect();
+            self.last_results.insert(
+                table_id,
+                &GameResult {
+                    winners: winners.clone(),
+                    winning_hands,
+                    pot_breakdown: payouts.clone(),
+                    rake_taken: rake,
+                },
+            );
+            self.games.insert(
+                game_id,
+                &GameRecord {
+                    game_id,
+                    table_id,
+                    players,
+                    pot: table.pot,
+                    winners: winners.clone(),
+                    payouts: payouts.clone(),
+                    ended_at_block: Self::env().block_number(),
+                },
+            );
+            self.next_game_id += 1;
+            if self.next_game_id > MAX_GAME_HISTORY {
+                self.prune_games(self.next_game_id - MAX_GAME_HISTORY);
+            }
+            Self::env().emit_event(GameEnded {
+                table_id,
+                game_id,
+                winners,
+                payouts,
+                pot: table.pot,
+                rake,
+            });
+
+            if table.game == GameKind::Poker {
+                table.button = (table.button + 1) % table.players_count().max(1);
+            }
+            self.reward_completion(table_id, table);
+            self.carry_over_players(table_id, table);
+            self.rounds.remove(table_id);
+            self.blackjack_rounds.remove(table_id);
+            self.dice_rounds.remove(table_id);
+            self.result_votes.remove(table_id);
+            self.confirmed_results.remove(table_id);
+            self.run_twice_votes.remove(table_id);
+            table.reset();
+            table.hand_number += 1;
+            let next = if table.seated > 0 {
+                self.await_dealer(table, Self::env().block_number());
+                STATE::INTERMISSION
+            } else {
+                self.return_bond(table_id, table)?;
+                self.spectators.remove(table_id);
+                STATE::ENDED
+            };
+            self.set_state(table_id, table, next);
+            self.seat_from_waitlist(table_id, table);
+            Ok(())
+        }
+
+        /// Return the bond of the initializer of the table closing cleanly.
+        fn return_bond(&mut self, table_id: TableId, table: &mut Table) -> Result<()> {
+            self.dismiss_dealer(table)?;
+            if table.bond == 0 {
+                return Ok(())
+            }
+            self.pay(table, table.initializer, table.bond)?;
+            Self::env().emit_event(BondReturned {
+                table_id,
+                initializer: table.initializer,
+                amount: table.bond,
+            });
+            table.bond = 0;
+            Ok(())
+        }
+
+        /// Slash the bond of the initializer of the table, sharing it out
+        /// evenly between the other players seated, the remainder going to
+        /// the first of them, or to the house if nobody else is seated.
+        fn slash_bond(&mut self, table_id: TableId, table: &mut Table) -> Result<()> {
+            self.dismiss_dealer(table)?;
+            if table.bond == 0 {
+                return Ok(())
+            }
+            self.share_out(table_id, table, table.initializer, table.bond)?;
+            Self::env().emit_event(BondSlashed {
+                table_id,
+                initializer: table.initializer,
+                amount: table.bond,
+            });
+            table.bond = 0;
+            Ok(())
+        }
+
+        /// Share `amount` out evenly between the players seated other than
+        /// `except`, the remainder going to the first of them, or to the house
+        /// if nobody else is seated.
+        fn share_out(
+            &mut self,
+            table_id: TableId,
+            table: &Table,
+            except: AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            let others: Vec<AccountId> = self
+                .players_of(table_id, table)
+                .into_iter()
+                .filter(|player| *player != except)
+                .collect();
+            if others.is_empty() {
+                let bank = arithmetic::add(self.house_bank(table), amount)?;
+                self.set_house_bank(table, bank);
+                return Ok(())
+            }
+            let share = amount / others.len() as Balance;
+            let remainder = amount % others.len() as Balance;
+            for (index, player) in others.iter().enumerate() {
+                let amount = if index == 0 { share + remainder } else { share };
+                self.pay(table, *player, amount)?;
+            }
+            Ok(())
+        }
+
+        /// Return its bond to the dealer of the table, the players dealing
+        /// the table again.
+        fn dismiss_dealer(&mut self, table: &mut Table) -> Result<()> {
+            if let Some(dealer) = table.dealer.filter(|_| table.dealer_bond > 0) {
+                self.pay(table, dealer, table.dealer_bond)?;
+            }
+            table.dealer = None;
+            table.dealer_bond = 0;
+            table.dealer_deadline = None;
+            Ok(())
+        }
+
+        /// Have the dealer of the table make the deal falling due from the
+        /// block `from` within the dealer timeout.
+        fn await_dealer(&self, table: &mut Table, from: BlockNumber) {
+            if table.dealer.is_some() {
+                table.dealer_deadline =
+                    Some(from.saturating_add(self.get_config().dealer_timeout_blocks));
+            }
+        }
+
+        /// Have the dealer of the table start its game within the dealer
+        /// timeout if it is ready to start: staging with enough players, all
+        /// committed to their seed.
+        fn await_start(&self, table_id: TableId, table: &mut Table) {
+            let ready = table.state == STATE::STAGING
+                && table.players_count() >= table.min_players
+                && self.all_committed(table_id, table);
+            if ready {
+                self.await_dealer(table, Self::env().block_number());
+            }
+        }
+
+        /// Record a deal on the table: the dealer dealing has no deal due
+        /// anymore, and a dealer stalling past its deadline is replaced by the
+        /// players, its bond shared out between them.
+        fn deal(&mut self, table_id: TableId, table: &mut Table) -> Result<()> {
+            let dealer = match table.dealer {
+                Some(dealer) => dealer,
+                None => return Ok(()),
+            };
+            table.dealer_deadline = None;
+            if Self::env().caller() == dealer {
+                return Ok(())
+            }
+            self.share_out(table_id, table, dealer, table.dealer_bond)?;
+            Self::env().emit_event(DealerReplaced {
+                table_id,
+                dealer,
+                bond: table.dealer_bond,
+            });
+            table.dealer = None;
+            table.dealer_bond = 0;
+            Ok(())
+        }
+
+        /// Play the move of the player in the blackjack hand of the table, or
+        /// stand the seat whose turn it is past its deadline if `action` is `None`.
+        /// The table is settled once the dealer has played.
+        /// error if the player is not seated at the table.
+        /// error if no blackjack hand is dealt on the table.
+        /// error if the value transferred falls short of the amount due.
+        fn play_blackjack(
+            &mut self,
+            table_id: TableId,
+            action: Option<(AccountId, Move)>,
+        ) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            let mut round = self
+                .blackjack_rounds
+                .get(table_id)
+                .ok_or(MetasinoError::WrongState)?;
+            let moved = action.is_some();
+            let (player, seat, action) = match action {
+                Some((player, action)) => {
+                    let seat = self
+                        .players
+                        .get((table_id, player))
+                        .ok_or(MetasinoError::NotAuthorized)?
+                        .seat;
+                    (player, seat, action)
+                }
+                None => {
+                    self.after_deadline(round.deadline)?;
+                    let player = self
+                        .seats
+                        .get((table_id, round.active))
+                        .ok_or(MetasinoError::WrongState)?;
+                    self.penalize(table_id, player, TIMEOUT_PENALTY);
+                    Self::env().emit_event(PlayerTimedOut { table_id, player });
+                    (player, round.active, Move::Stand)
+                }
+            };
+            let amount = round.play(seat, action)?;
+            if amount > 0 {
+                let pot = arithmetic::add(table.pot, amount)?;
+                self.put_in(table_id, &table, player, amount)?;
+                self.record_wager(player, amount);
+                table.pot = pot;
+            }
+            round.deadline = Self::env().block_number().saturating_add(TURN_PERIOD);
+            Self::env().emit_event(BlackjackPlayed {
+                table_id,
+                player,
+                action,
+                amount,
+            });
+            if round.finished {
+                self.award_jackpot(table_id, &table, &round)?;
+                self.settle_with_house(table_id, &mut table, round.payouts()?)?;
+            } else {
+                self.blackjack_rounds.insert(table_id, &round);
+            }
+            self.tables.insert(table_id, &table);
+            if moved {
+                self.bump_nonce(player);
+            }
+            Ok(())
+        }
+
+        /// Everything the player put on the table before the game started.
+        fn stake_of(
+            &self,
+            table_id: TableId,
+            table: &Table,
+            player: AccountId,
+        ) -> Result<Balance> {
+            let wagers = self.wagers.get((table_id, player)).unwrap_or_default();
+            let wagered = arithmetic::sum(wagers.iter().map(|wager| &wager.amount))?;
+            let bets = self.baccarat_bets.get((table_id, player)).unwrap_or_default();
+            let bet = arithmetic::sum(bets.iter().map(|bet| &bet.amount))?;
+            arithmetic::add(table.required_start_bet, arithmetic::add(wagered, bet)?)
+        }
+
+        /// What the player at the seat put into the pot of the table so far:
+        /// their stake along with their bets on the poker hand, or their bet
+        /// and insurance on the blackjack hand. A player carried over from the
+        /// last hand of a chip session puts no start bet into the pot until
+        /// the next hand is dealt.
+        fn contribution_of(
+            &self,
+            table_id: TableId,
+            table: &Table,
+            seat: usize,
+            player: AccountId,
+        ) -> Result<Balance> {
+            if let Some(round) = self.blackjack_rounds.get(table_id) {
+                let bet = round.bets.get(seat).copied().unwrap_or_default();
+                let insurance = round.insurance.get(seat).copied().unwrap_or_default();
+                return arithmetic::add(bet, insurance)
+            }
+            let mut stake = self.stake_of(table_id, table, player)?;
+            let paid = self
+                .players
+                .get((table_id, player))
+                .is_some_and(|info| info.hand == table.hand_number);
+            if !paid {
+                stake = arithmetic::sub(stake, table.required_start_bet)?;
+            }
+            match self.rounds.get(table_id) {
+                Some(round) => {
+                    arithmetic::add(stake, round.contributed.get(seat).copied().unwrap_or_default())
+                }
+                None => Ok(stake),
+            }
+        }
+
+        /// Spin the wheel of the roulette table and pay out the bets.
+        fn settle_roulette(&mut self, table_id: TableId, table: &mut Table) -> Result<()> {
+            let pocket = roulette::spin(&table.entropy);
+            Self::env().emit_event(WheelSpun { table_id, pocket });
+            let owed = self
+                .players_of(table_id, table)
+                .iter()
+                .map(|player| {
+                    let wagers = self.wagers.get((table_id, *player)).unwrap_or_default();
+                    wagers.iter().try_fold(table.required_start_bet, |owed, wager| {
+                        arithmetic::add(owed, wager.bet.payout(wager.amount, pocket)?)
+                    })
+                })
+                .collect::<Result<Vec<Balance>>>()?;
+            self.settle_with_house(table_id, table, owed)
+        }
+
+        /// Deal the coup of the baccarat table and pay out the bets.
+        fn settle_baccarat(&mut self, table_id: TableId, table: &mut Table) -> Result<()> {
+            let coup = baccarat::deal(&table.entropy);
+            let outcome = coup.outcome();
+            Self::env().emit_event(CoupDealt {
+                table_id,
+                coup,
+                outcome,
+            });
+            let owed = self
+                .players_of(table_id, table)
+                .iter()
+                .map(|player| {
+                    let bets = self.baccarat_bets.get((table_id, *player)).unwrap_or_default();
+                    bets.iter().try_fold(table.required_start_bet, |owed, wager| {
+                        arithmetic::add(owed, wager.bet.payout(wager.amount, outcome)?)
+                    })
+                })
+                .collect::<Result<Vec<Balance>>>()?;
+            self.settle_with_house(table_id, table, owed)
+        }
+
+        /// Pay each seat of the table what it is owed by the house.
+        /// The house bank takes the pot left over or covers what the pot lacks,
+        /// and the winners are paid pro rata if even the bank falls short.
+        fn settle_with_house(
+            &mut self,
+            table_id: TableId,
+            table: &mut Table,
+            owed: Vec<Balance>,
+        ) -> Result<()> {
+            let owed_total = arithmetic::sum(&owed)?;
+            let bank = self.house_bank(table);
+            let pot = arithmetic::sub(table.pot, self.feed_jackpot(table, table.pot)?)?;
+            let available = arithmetic::add(pot, bank)?;
+            let players = self.players_of(table_id, table);
+            let mut winners: Vec<AccountId> = Vec::new();
+            let mut payouts: Vec<Balance> = Vec::new();
+            for (player, owed) in players.iter().zip(owed.iter()) {
+                let payout = if owed_total > available {
+                    arithmetic::mul_div(*owed, available, owed_total)?
+                } else {
+                    *owed
+                };
+                if payout > 0 {
+                    self.pay_winnings(table_id, table, *player, payout)?;
+                    self.record_win(*player, payout);
+                    winners.push(*player);
+                    payouts.push(payout);
+                }
+            }
+            let paid = arithmetic::sum(&payouts)?;
+            self.set_house_bank(table, arithmetic::sub(available, paid)?);
+            self.close_game(table_id, table, winners, payouts, 0)
+        }
+
+        /// The funds of the house in the currency of the table.
+        fn house_bank(&self, table: &Table) -> Balance {
+            match table.token {
+                Some(token) => self.token_treasury.get(token).unwrap_or_default(),
+                None => self.treasury,
+            }
+        }
+
+        /// Set the funds of the house in the currency of the table.
+        fn set_house_bank(&mut self, table: &Table, amount: Balance) {
+            let token = match table.token {
+                Some(token) => token,
+                None => {
+                    self.treasury = amount;
+                    return
+                }
+            };
+            let held = self.token_treasury.get(token).unwrap_or_default() > 0;
+            match (held, amount > 0) {
+                (false, true) => self.token_treasuries += 1,
+                (true, false) => self.token_treasuries -= 1,
+                _ => (),
+            }
+            match amount {
+                0 => self.token_treasury.remove(token),
+                amount => self.token_treasury.insert(token, &amount),
+            }
+        }
+
+        /// Whether every player of the table committed to their seed.
+        fn all_committed(&self, table_id: TableId, table: &Table) -> bool {
+            self.players_of(table_id, table)
+                .iter()
+                .all(|player| self.commitments.contains((table_id, *player)))
+        }
+
+        /// Start the game of the table, opening the reveal phase.
+        fn begin_game(&mut self, table_id: TableId, table: &mut Table) {
+            self.set_state(table_id, table, STATE::PLAYING);
+            // Only the advertised game is scheduled, the next ones are started by the players.
+            table.start_at = None;
+            table.reveal_deadline = Self::env().block_number().saturating_add(REVEAL_PERIOD);
+            self.await_dealer(table, table.reveal_deadline);
+            self.tables.insert(table_id, table);
+            Self::env().emit_event(GameStarted {
+                table_id,
+                players: self.players_of(table_id, table),
+                reveal_deadline: table.reveal_deadline,
+            });
+        }
+
+        /// Start the game of a sit-and-go table staged with every seat taken
+        /// and every player committed to their seed.
+        fn auto_start(&mut self, table_id: TableId, table: &mut Table) {
+            let ready = table.sit_and_go
+                && table.state == STATE::STAGING
+                && table.is_full()
+                && self.when_not_paused().is_ok()
+                && self.all_committed(table_id, table);
+            if ready {
+                self.begin_game(table_id, table);
+                Self::env().emit_event(GameAutoStarted { table_id });
+            }
+        }
+
+        /// Put the ante of every seat into the pot out of their chips. A seat
+        /// short of the ante puts in every chip it holds and is all-in.
+        fn post_antes(
+            &mut self,
+            table_id: TableId,
+            table: &mut Table,
+            round: &mut BettingRound,
+        ) -> Result<()> {
+            for (seat, player) in self.players_of(table_id, table).into_iter().enumerate() {
+                let chips = self.get_chips(table_id, player);
+                let amount = table.ante.min(chips);
+                let pot = arithmetic::add(table.pot, amount)?;
+                self.spend_chips(table_id, player, amount)?;
+                self.record_wager(player, amount);
+                table.pot = pot;
+                round.all_in[seat] = chips <= table.ante;
+                Self::env().emit_event(AntePosted {
+                    table_id,
+                    player,
+                    amount,
+                });
+                let street = round.street;
+                Self::log_action(table_id, table, street, seat as u8, LoggedAction::Ante, amount);
+            }
+            Ok(())
+        }
+
+        /// Post the blinds of the seats after the dealer button out of their
+        /// chips. A seat short of its blind posts every chip it holds and is
+        /// all-in.
+        fn post_blinds(
+            &mut self,
+            table_id: TableId,
+            table: &mut Table,
+            round: &mut BettingRound,
+        ) -> Result<()> {
+            table.button %= table.players_count();
+            let seats = betting::blind_seats(table.players_count(), table.button);
+            let mut blinds = [(0, false); 2];
+            for ((seat, blind), posted) in seats
+                .iter()
+                .zip([table.small_blind, table.big_blind])
+                .zip(blinds.iter_mut())
+            {
+                let player = self.seats.get((table_id, *seat)).ok_or(MetasinoError::WrongState)?;
+                let chips = self.get_chips(table_id, player);
+                let amount = blind.min(chips);
+                let pot = arithmetic::add(table.pot, amount)?;
+                self.spend_chips(table_id, player, amount)?;
+                self.record_wager(player, amount);
+                table.pot = pot;
+                *posted = (amount, chips <= blind);
+                Self::env().emit_event(BlindPosted {
+                    table_id,
+                    player,
+                    amount,
+                });
+                let street = Street::PreFlop;
+                Self::log_action(table_id, table, street, *seat, LoggedAction::Blind, amount);
+            }
+            round.post_blinds(table.button, blinds);
+
+            let straddle_seat = betting::straddle_seat(table.players_count(), table.button);
+            let straddler = table.straddler.zip(straddle_seat).filter(|(straddler, seat)| {
+                self.seats.get((table_id, *seat)) == Some(*straddler)
+            });
+            if let Some((player, seat)) = straddler {
+                let straddle = arithmetic::add(table.big_blind, table.big_blind)?;
+                let chips = self.get_chips(table_id, player);
+                let amount = straddle.min(chips);
+                let pot = arithmetic::add(table.pot, amount)?;
+                self.spend_chips(table_id, player, amount)?;
+                self.record_wager(player, amount);
+                table.pot = pot;
+                round.post_straddle(seat, (amount, chips <= straddle));
+                Self::env().emit_event(BlindPosted {
+                    table_id,
+                    player,
+                    amount,
+                });
+                let street = Street::PreFlop;
+                Self::log_action(table_id, table, street, seat, LoggedAction::Straddle, amount);
+            }
+            Ok(())
+        }
+
+        /// Bring the betting of a stud hand in from the seat showing the lowest
+        /// up card, which posts the small blind as the bring-in out of its
+        /// chips, or just opens third street if the small blind is 0. A seat
+        /// short of the bring-in posts every chip it holds and is all-in.
+        fn post_bring_in(
+            &mut self,
+            table_id: TableId,
+            table: &mut Table,
+            round: &mut BettingRound,
+        ) -> Result<()> {
+            let seat = stud::bring_in(&table.deck()?, table.players_count());
+            if table.small_blind == 0 {
+                round.lead(seat);
+                return Ok(())
+            }
+            let player = self.seats.get((table_id, seat)).ok_or(MetasinoError::WrongState)?;
+            let chips = self.get_chips(table_id, player);
+            let amount = table.small_blind.min(chips);
+            let pot = arithmetic::add(table.pot, amount)?;
+            self.spend_chips(table_id, player, amount)?;
+            self.record_wager(player, amount);
+            table.pot = pot;
+            round.post_bring_in(seat, (amount, chips <= table.small_blind));
+            Self::env().emit_event(BlindPosted {
+                table_id,
+                player,
+                amount,
+            });
+            Self::log_action(table_id, table, round.street, seat, LoggedAction::BringIn, amount);
+            Ok(())
+        }
+
+        /// Play the action of the seat in the betting round of the table,
+        /// the best hand showing opening each new street of a stud hand, and
+        /// keep it in the history of the hand.
+        /// Returns the action played, along with the amount the seat has to
+        /// put into the pot for it.
+        fn play_in_round(
+            &mut self,
+            table_id: TableId,
+            table: &Table,
+            round: &mut BettingRound,
+            seat: u8,
+            action: Action,
+        ) -> Result<Play> {
+            let street = round.street;
+            let amount = round.act(seat, action)?;
+            if table.variant == Variant::Stud && round.street != street {
+                round.lead(stud::leader(&table.deck()?, round));
+            }
+            let play = Play {
+                street,
+                seat,
+                action,
+                amount,
+            };
+            self.hand_plays.insert((table_id, round.plays - 1), &play);
+            Ok(play)
+        }
+
+        /// Log an entry of the history of the poker hand played on the table.
+        fn log_action(
+            table_id: TableId,
+            table: &Table,
+            street: Street,
+            seat: u8,
+            action: LoggedAction,
+            amount: Balance,
+        ) {
+            Self::env().emit_event(ActionLogged {
+                table_id,
+                hand_number: table.hand_number,
+                seat,
+                action,
+                amount,
+                street,
+            });
+        }
+
+        /// Log the action played in the betting round of the table.
+        fn log_play(table_id: TableId, table: &Table, play: &Play) {
+            let action = LoggedAction::Bet(play.action);
+            Self::log_action(table_id, table, play.street, play.seat, action, play.amount);
+        }
+
+        /// Play the action of the caller in the betting round of the table.
+        /// error if the caller is not a player.
+        /// error if no betting round is open on the table.
+        /// error if the value transferred falls short of the amount due.
+        fn act(&mut self, table_id: TableId, caller: AccountId, action: Action) -> Result<()> {
+            let mut table = self.table(table_id)?;
+            let seat = self
+                .players
+                .get((table_id, caller))
+                .ok_or(MetasinoError::NotAuthorized)?
+                .seat;
+            let mut round = self.rounds.get(table_id).ok_or(MetasinoError::WrongState)?;
+            let play = self.play_in_round(table_id, &table, &mut round, seat, action)?;
+            let amount = play.amount;
+            let pot = arithmetic::add(table.pot, amount)?;
+            self.put_in(table_id, &table, caller, amount)?;
+            self.record_wager(caller, amount);
+            round.deadline = Self::env().block_number().saturating_add(TURN_PERIOD);
+            if round.finished {
+                self.await_dealer(&mut table, Self::env().block_number());
+            }
+
+            table.pot = pot;
+            self.tables.insert(table_id, &table);
+            self.rounds.insert(table_id, &round);
+            Self::env().emit_event(PlayerActed {
+                table_id,
+                player: caller,
+                action,
+                amount,
+            });
+            Self::log_play(table_id, &table, &play);
+            self.bump_nonce(caller);
+            Ok(())
+        }
+    }
+
+    /// Arithmetic on balances failing with `ArithmeticError` instead of
+    /// wrapping around.
+    mod arithmetic {
+        use super::*;
+
+        /// `a + b`.
+        pub(super) fn add(a: Balance, b: Balance) -> Result<Balance> {
+            a.checked_add(b).ok_or(MetasinoError::ArithmeticError)
+        }
+
+        /// `a - b`.
+        pub(super) fn sub(a: Balance, b: Balance) -> Result<Balance> {
+            a.checked_sub(b).ok_or(MetasinoError::ArithmeticError)
+        }
+
+        /// `amount * numerator / denominator`, rounded down.
+        pub(super) fn mul_div(
+            amount: Balance,
+            numerator: Balance,
+            denominator: Balance,
+        ) -> Result<Balance> {
+            amount
+                .checked_mul(numerator)
+                .and_then(|product| product.checked_div(denominator))
+                .ok_or(MetasinoError::ArithmeticError)
+        }
+
+        /// The sum of the amounts.
+        pub(super) fn sum<'a>(amounts: impl IntoIterator<Item = &'a Balance>) -> Result<Balance> {
+            amounts.into_iter().try_fold(0, |total, amount| add(total, *amount))
+        }
+    }
+
+    /// Guards restricting who may execute a message.
+    mod access_control {
+        use super::*;
+        use ink_lang::codegen::StaticEnv;
+
+        impl Metasino {
+            /// Only the owner of the contract, or an account granted the
+            /// owner role, may proceed.
+            pub(super) fn only_owner(&self) -> Result<()> {
+                self.only_role(Role::Owner)
+            }
+
+            /// Only an account holding the role may proceed.
+            pub(super) fn only_role(&self, role: Role) -> Result<()> {
+                if !self.has_role(role, Self::env().caller()) {
+                    return Err(MetasinoError::NotAuthorized);
+                }
+                Ok(())
+            }
+
+            /// Only the initializer of the table, or an account holding the
+            /// role on every table, may proceed.
+            pub(super) fn only_initializer_or(&self, table: &Table, role: Role) -> Result<()> {
+                if self.has_role(role, Self::env().caller()) {
+                    return Ok(())
+                }
+                self.only_initializer(table)
+            }
+
+            /// Only proceed once the deadline has passed, unless an operator
+            /// forces the timeout.
+            pub(super) fn after_deadline(&self, deadline: BlockNumber) -> Result<()> {
+                if Self::env().block_number() <= deadline
+                    && !self.has_role(Role::Operator, Self::env().caller())
+                {
+                    return Err(MetasinoError::DeadlineNotReached);
+                }
+                Ok(())
+            }
+
+            /// Only proceed while the contract is not paused.
+            pub(super) fn when_not_paused(&self) -> Result<()> {
+                if self.paused {
+                    return Err(MetasinoError::Paused);
+                }
+                Ok(())
+            }
+
+            /// Only the initializer of the table may proceed.
+            pub(super) fn only_initializer(&self, table: &Table) -> Result<()> {
+                if Self::env().caller() != table.initializer {
+                    return Err(MetasinoError::NotAuthorized);
+                }
+                Ok(())
+            }
+
+            /// Only an account admitted to the table may proceed: not banned,
+            /// invited if the table is private, knowing the join code of the
+            /// table if it has one and reputed enough if the table requires it.
+            pub(super) fn only_admitted(
+                &self,
+                table_id: TableId,
+                table: &Table,
+                join_code: Option<Vec<u8>>,
+            ) -> Result<()> {
+                self.not_banned(table_id)?;
+                self.only_invited(table_id, table)?;
+                if let Some(join_code_hash) = table.join_code_hash {
+                    let code = join_code.ok_or(MetasinoError::InvalidJoinCode)?;
+                    if self.compute_join_code_hash(code) != join_code_hash {
+                        return Err(MetasinoError::InvalidJoinCode);
+                    }
+                }
+                let reputation = self.get_player_stats(Self::env().caller()).reputation;
+                if table.min_reputation.is_some_and(|min| reputation < min) {
+                    return Err(MetasinoError::ReputationTooLow);
+                }
+                Ok(())
+            }
+
+            /// Only an account banned neither from the table nor from the whole
+            /// contract may proceed.
+            pub(super) fn not_banned(&self, table_id: TableId) -> Result<()> {
+                let caller = Self::env().caller();
+                if self.bans.contains(caller) || self.table_bans.contains((table_id, caller)) {
+                    return Err(MetasinoError::Banned);
+                }
+                Ok(())
+            }
+
+            /// Only an account invited to the table may proceed if it is private.
+            pub(super) fn only_invited(&self, table_id: TableId, table: &Table) -> Result<()> {
+                if table.is_private && !self.allowlist.contains((table_id, Self::env().caller())) {
+                    return Err(MetasinoError::NotAuthorized);
+                }
+                Ok(())
+            }
+
+            /// Only the dealer of the table may deal it, unless the dealer let
+            /// the deadline of the deal due pass. Returns whether the caller is
+            /// the dealer, anyone else dealing the table as without a dealer.
+            pub(super) fn only_dealer(&self, table: &Table) -> Result<bool> {
+                let stalled = table
+                    .dealer_deadline
+                    .is_some_and(|deadline| Self::env().block_number() > deadline);
+                match table.dealer {
+                    Some(dealer) if dealer == Self::env().caller() => Ok(true),
+                    Some(_) if !stalled => Err(MetasinoError::NotAuthorized),
+                    _ => Ok(false),
+                }
+            }
+
+            /// Only a player registered in the table may proceed.
+            pub(super) fn only_player(&self, table_id: TableId) -> Result<()> {
+                if self.players.get((table_id, Self::env().caller())).is_none() {
+                    return Err(MetasinoError::NotAuthorized);
+                }
+                Ok(())
+            }
+
+            /// The account the caller plays for: the account which approved the
+            /// caller as its session key until a block not reached yet, or the
+            /// caller itself.
+            pub(super) fn acting_player(&self) -> AccountId {
+                let caller = Self::env().caller();
+                match self.session_keys.get(caller) {
+                    Some((player, expiry)) if Self::env().block_number() < expiry => player,
+                    _ => caller,
+                }
+            }
+
+            /// Only a player registered in the table, or their session key, may
+            /// proceed. Returns the player.
+            pub(super) fn only_acting_player(&self, table_id: TableId) -> Result<AccountId> {
+                let player = self.acting_player();
+                if self.players.get((table_id, player)).is_none() {
+                    return Err(MetasinoError::NotAuthorized);
+                }
+                Ok(player)
+            }
+        }
+    }
+
+    /// Seating of the players, each table holding its players by seat so that
+    /// membership checks do not need to load the whole table.
+    mod seating {
+        use super::*;
+        use ink_lang::codegen::{
+            EmitEvent,
+            StaticEnv,
+        };
+
+        impl Metasino {
+            /// Seat the player on the first free seat of the table.
+            pub(super) fn seat_player(
+                &mut self,
+                table_id: TableId,
+                table: &mut Table,
+                player: AccountId,
+            ) {
+                let seat = table.seated;
+                self.players.insert(
+                    (table_id, player),
+                    &PlayerInfo {
+                        seat,
+                        revealed: false,
+                        registered_at: Self::env().block_number(),
+                        rebuys: 0,
+                        hand: table.hand_number,
+                        penalized: false,
+                    },
+                );
+                self.seats.insert((table_id, seat), &player);
+                table.seated += 1;
+            }
+
+            /// Let the frontends know once the player just seated brings the
+            /// table to its minimum of players, so that the game may start,
+            /// or fills its last seat.
+            pub(super) fn announce_seating(
+                &self,
+                table_id: TableId,
+                table: &Table,
+                player: AccountId,
+            ) {
+                if table.seated == table.min_players {
+                    Self::env().emit_event(MinimumPlayerReached {
+                        table_id,
+                        account_id: player,
+                        players: table.seated,
+                    });
+                }
+                if table.is_full() {
+                    Self::env().emit_event(TableFull {
+                        table_id,
+                        players: table.seated,
+                    });
+                }
+            }
+
+            /// Refund the player everything they put on the table before the
+            /// game and free their seat.
+            /// Returns the refund.
+            pub(super) fn refund_seat(
+                &mut self,
+                table_id: TableId,
+                table: &mut Table,
+                player: AccountId,
+            ) -> Result<Balance> {
+                let refund = self.stake_of(table_id, table, player)?;
+                let pot = arithmetic::sub(table.pot, refund)?;
+                self.pay(table, player, refund)?;
+                self.record_refund(player, refund);
+                self.leave_freeroll(table, player);
+                self.unseat_player(table_id, table, player);
+                table.pot = pot;
+                Ok(refund)
+            }
+
+            /// Remove the player along with their commitment and bets from the table.
+            /// The player of the last seat takes over the freed seat.
+            pub(super) fn unseat_player(
+                &mut self,
+                table_id: TableId,
+                table: &mut Table,
+                player: AccountId,
+            ) {
+                let info = match self.players.get((table_id, player)) {
+                    Some(info) => info,
+                    None => return,
+                };
+                table.seated -= 1;
+                if info.seat != table.seated {
+                    let last = self.seats.get((table_id, table.seated));
+                    if let Some((last, mut last_info)) =
+                        last.and_then(|last| Some((last, self.players.get((table_id, last))?)))
+                    {
+                        last_info.seat = info.seat;
+                        self.players.insert((table_id, last), &last_info);
+                        self.seats.insert((table_id, info.seat), &last);
+                    }
+                }
+                self.seats.remove((table_id, table.seated));
+                self.players.remove((table_id, player));
+                self.commitments.remove((table_id, player));
+                self.hand_commitments.remove((table_id, player));
+                self.revealed_hands.remove((table_id, player));
+                self.wagers.remove((table_id, player));
+                self.baccarat_bets.remove((table_id, player));
+                self.dice_bets.remove((table_id, player));
+            }
+
+            /// Keep the players playing on chips seated for the next hand, with
+            /// their commitment and bets cleared, and remove the others.
+            pub(super) fn carry_over_players(&mut self, table_id: TableId, table: &mut Table) {
+                for player in self.players_of(table_id, table) {
+                    if !self.chips.contains((table_id, player)) {
+                        self.unseat_player(table_id, table, player);
+                        continue
+                    }
+                    if let Some(mut info) = self.players.get((table_id, player)) {
+                        info.revealed = false;
+                        info.penalized = false;
+                        self.players.insert((table_id, player), &info);
+                    }
+                    self.commitments.remove((table_id, player));
+                    self.hand_commitments.remove((table_id, player));
+                    self.revealed_hands.remove((table_id, player));
+                    self.wagers.remove((table_id, player));
+                    self.baccarat_bets.remove((table_id, player));
+                    self.dice_bets.remove((table_id, player));
+                }
+            }
+
+            /// Seat the accounts waiting for the table on its free seats, in the
+            /// order they joined the waitlist, their escrowed start bet going
+            /// into the pot. A table whose game has ended is staged again.
+            pub(super) fn seat_from_waitlist(&mut self, table_id: TableId, table: &mut Table) {
+                let mut waitlist = self.waitlists.get(table_id).unwrap_or_default();
+                if waitlist.is_empty() || table.is_full() {
+                    return
+                }
+                while !waitlist.is_empty() && !table.is_full() {
+                    // Nobody more can join a pot that would overflow.
+                    let pot = match table.pot.checked_add(table.required_start_bet) {
+                        Some(pot) => pot,
+                        None => break,
+                    };
+                    let player = waitlist.remove(0);
+                    table.pot = pot;
+                    self.seat_player(table_id, table, player);
+                    self.record_registration(player, table.required_start_bet);
+                    Self::env().emit_event(SeatedFromWaitlist { table_id, player });
+                    self.announce_seating(table_id, table, player);
+                }
+                self.waitlists.insert(table_id, &waitlist);
+                if table.state == STATE::ENDED {
+                    self.set_state(table_id, table, STATE::STAGING);
+                }
+            }
+
+            /// Remove every player along with their commitment and bets from the table.
+            pub(super) fn clear_players(&mut self, table_id: TableId, table: &mut Table) {
+                for player in self.players_of(table_id, table) {
+                    self.players.remove((table_id, player));
+                    self.commitments.remove((table_id, player));
+                    self.hand_commitments.remove((table_id, player));
+                    self.revealed_hands.remove((table_id, player));
+                    self.wagers.remove((table_id, player));
+                    self.baccarat_bets.remove((table_id, player));
+                    self.dice_bets.remove((table_id, player));
+                }
+                for seat in 0..table.seated {
+                    self.seats.remove((table_id, seat));
+                }
+                table.seated = 0;
+            }
+
+            /// The players of the table, by seat order.
+            pub(super) fn players_of(&self, table_id: TableId, table: &Table) -> Vec<AccountId> {
+                (0..table.seated)
+                    .filter_map(|seat| self.seats.get((table_id, seat)))
+                    .collect()
+            }
+
+            /// The players of the table who have not revealed their seed.
+            pub(super) fn unrevealed(&self, table_id: TableId, table: &Table) -> Vec<AccountId> {
+                self.players_of(table_id, table)
+                    .into_iter()
+                    .filter(|player| {
+                        !self
+                            .players
+                            .get((table_id, *player))
+                            .is_some_and(|info| info.revealed)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Lifetime and season statistics of the players and the leaderboards
+    /// ranking them by the payouts they received.
+    mod stats {
+        use super::*;
+        use ink_lang::codegen::{
+            EmitEvent,
+            StaticEnv,
+        };
+
+        impl Metasino {
+            /// Count a game for the player who took a seat with the start bet.
+            pub(super) fn record_registration(&mut self, player: AccountId, start_bet: Balance) {
+                self.update_stats(player, |stats| {
+                    stats.games_played = stats.games_played.saturating_add(1);
+                    stats.total_wagered = stats.total_wagered.saturating_add(start_bet);
+                });
+            }
+
+            /// Lower the reputation of the player by `penalty` and mark them
+            /// penalized in the hand of the table if they are seated at it.
+            pub(super) fn penalize(&mut self, table_id: TableId, player: AccountId, penalty: i32) {
+                let mut stats = self.get_player_stats(player);
+                stats.reputation = stats.reputation.saturating_sub(penalty);
+                self.stats.insert(player, &stats);
+                if let Some(mut info) = self.players.get((table_id, player)) {
+                    info.penalized = true;
+                    self.players.insert((table_id, player), &info);
+                }
+            }
+
+            /// Raise the reputation of every player of the game ended on the
+            /// table who was not penalized in it.
+            pub(super) fn reward_completion(&mut self, table_id: TableId, table: &Table) {
+                for player in self.players_of(table_id, table) {
+                    let clean = self
+                        .players
+                        .get((table_id, player))
+                        .is_some_and(|info| !info.penalized);
+                    if !clean {
+                        continue
+                    }
+                    let mut stats = self.get_player_stats(player);
+                    stats.reputation = stats.reputation.saturating_add(1);
+                    self.stats.insert(player, &stats);
+                }
+            }
+
+            /// Undo the registration of a player whose start bet was refunded.
+            pub(super) fn record_refund(&mut self, player: AccountId, refund: Balance) {
+                self.update_stats(player, |stats| {
+                    stats.games_played = stats.games_played.saturating_sub(1);
+                    stats.total_wagered = stats.total_wagered.saturating_sub(refund);
+                });
+            }
+
+            /// Add a bet of the player to their wagers.
+            pub(super) fn record_wager(&mut self, player: AccountId, amount: Balance) {
+                if amount == 0 {
+                    return
+                }
+                self.update_stats(player, |stats| {
+                    stats.total_wagered = stats.total_wagered.saturating_add(amount);
+                });
+            }
+
+            /// Count the payout of a winner and rank them on the leaderboards.
+            pub(super) fn record_win(&mut self, player: AccountId, payout: Balance) {
+                self.update_stats(player, |stats| {
+                    stats.games_won = stats.games_won.saturating_add(1);
+                    stats.total_won = stats.total_won.saturating_add(payout);
+                });
+                self.unlock(player, Achievement::FirstWin);
+
+                let leaderboard = self.leaderboard.get().unwrap_or_default();
+                let leaderboard =
+                    rank(leaderboard, player, |player| self.get_player_stats(player).total_won);
+                self.leaderboard.set(&leaderboard);
+                let season = self.season;
+                let leaderboard = self.season_leaderboards.get(season).unwrap_or_default();
+                let leaderboard = rank(leaderboard, player, |player| {
+                    self.get_season_stats(season, player).total_won
+                });
+                self.season_leaderboards.insert(season, &leaderboard);
+            }
+
+            /// Apply the update to the lifetime statistics of the player and to
+            /// their statistics in the current season, closing the season first
+            /// if it lasted its length.
+            fn update_stats(&mut self, player: AccountId, update: impl Fn(&mut PlayerStats)) {
+                let elapsed = Self::env().block_number().saturating_sub(self.season_started_at);
+                if self.season_length > 0 && elapsed >= self.season_length {
+                    // A treasury short of the prizes closes the season without any.
+                    let _ = self.end_season(false);
+                }
+                let mut stats = self.get_player_stats(player);
+                update(&mut stats);
+                self.stats.insert(player, &stats);
+                let mut stats = self.get_season_stats(self.season, player);
+                update(&mut stats);
+                self.season_stats.insert((self.season, player), &stats);
+            }
+
+            /// Award the prizes of the current season to the top of its
+            /// leaderboard out of the treasury, or no prize if `funded` is
+            /// false and the treasury cannot pay for them, and start the next
+            /// season.
+            /// error if the treasury cannot pay for the prizes.
+            pub(super) fn end_season(&mut self, funded: bool) -> Result<()> {
+                let season = self.season;
+                let winners = self.season_leaderboards.get(season).unwrap_or_default();
+                let mut prizes = self.season_prizes.get().unwrap_or_default();
+                prizes.truncate(winners.len());
+                match arithmetic::sub(self.treasury, arithmetic::sum(&prizes)?) {
+                    Ok(treasury) => self.treasury = treasury,
+                    Err(error) if funded => return Err(error),
+                    Err(_) => prizes.clear(),
+                }
+                for (winner, prize) in winners.iter().zip(prizes.iter()) {
+                    self.season_awards.insert((season, *winner), prize);
+                }
+                self.season = season.saturating_add(1);
+                self.season_started_at = Self::env().block_number();
+                Self::env().emit_event(SeasonClosed {
+                    season,
+                    winners,
+                    prizes,
+                });
+                Ok(())
+            }
+        }
+
+        /// Rank the player on the leaderboard by the payouts they received,
+        /// keeping the best players only.
+        fn rank(
+            mut leaderboard: Vec<AccountId>,
+            player: AccountId,
+            won: impl Fn(AccountId) -> Balance,
+        ) -> Vec<AccountId> {
+            if !leaderboard.contains(&player) {
+                leaderboard.push(player);
+            }
+            let mut ranked: Vec<(Balance, AccountId)> =
+                leaderboard.into_iter().map(|player| (won(player), player)).collect();
+            ranked.sort_by_key(|(won, _)| core::cmp::Reverse(*won));
+            ranked.truncate(LEADERBOARD_SIZE);
+            ranked.into_iter().map(|(_, player)| player).collect()
+        }
+    }
+
+    /// Results of the games played off-chain, confirmed by a threshold of
+    /// the result signers designated by the owner.
+    mod oracle {
+        use super::*;
+
+        /// The hash of the result of an oracle game.
+        pub fn result_hash(winners: &[AccountId], shares: &[u8]) -> Hash {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink_env::hash_encoded::<Blake2x256, _>(&(winners, shares), &mut output);
+            Hash::from(output)
+        }
+
+        impl Metasino {
+            /// Whether enough of the current result signers submitted the result.
+            pub(super) fn confirms(&self, votes: &[(AccountId, Hash)], result_hash: Hash) -> bool {
+                let signers = self.get_result_signers();
+                let confirmations = votes
+                    .iter()
+                    .filter(|(signer, hash)| *hash == result_hash && signers.contains(signer))
+                    .count() as u32;
+                let threshold = self.get_config().result_threshold;
+                threshold > 0 && confirmations >= threshold
+            }
+        }
+    }
+
+    /// The referrers of the players, earning a share of the rake of the games
+    /// of the players they referred.
+    mod referrals {
+        use super::*;
+        use ink_lang::codegen::{
+            EmitEvent,
+            StaticEnv,
+        };
+
+        impl Metasino {
+            /// Record the referrer of the player unless they already have one.
+            pub(super) fn record_referrer(&mut self, player: AccountId, referrer: AccountId) {
+                if !self.referrers.contains(player) {
+                    self.referrers.insert(player, &referrer);
+                }
+            }
+
+            /// Credit the referrer of each player of the game with their share
+            /// of the rake, the rake being split evenly between the players.
+            /// The shares are taken out of the treasury the rake went into.
+            pub(super) fn pay_referrals(
+                &mut self,
+                table_id: TableId,
+                table: &Table,
+                players: &[AccountId],
+                rake: Balance,
+            ) -> Result<()> {
+                if table.token.is_some() || rake == 0 || self.referral_bps == 0 {
+                    return Ok(())
+                }
+                let share = rake / players.len().max(1) as Balance;
+                let amount =
+                    arithmetic::mul_div(share, self.referral_bps as Balance, MAX_BPS as Balance)?;
+                for player in players {
+                    let referrer = match self.referrers.get(player) {
+                        Some(referrer) if amount > 0 => referrer,
+                        _ => continue,
+                    };
+                    self.treasury = arithmetic::sub(self.treasury, amount)?;
+                    self.credit_withdrawal(referrer, amount)?;
+                    let earned = arithmetic::add(self.get_referral_earnings(referrer), amount)?;
+                    self.referral_earnings.insert(referrer, &earned);
+                    Self::env().emit_event(ReferralPaid {
+                        referrer,
+                        player: *player,
+                        table_id,
+                        amount,
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// The loyalty points of the players, earned by the rake of their games
+    /// and redeemed for rakeback or tournament entries.
+    mod loyalty {
+        use super::*;
+
+        impl Metasino {
+            /// Give each player of the game a point for every unit of their
+            /// share of the rake, the rake being split evenly between them.
+            /// Only the rake of the tables played in the native balance counts.
+            pub(super) fn accrue_points(
+                &mut self,
+                table: &Table,
+                players: &[AccountId],
+                rake: Balance,
+            ) {
+                if table.token.is_some() || rake == 0 {
+                    return
+                }
+                let share = rake / players.len().max(1) as Balance;
+                for player in players {
+                    let points = self.get_loyalty_points(*player).saturating_add(share);
+                    self.loyalty_points.insert(player, &points);
+                }
+            }
+        }
+    }
+
+    /// The achievements of the players, minted as badges of the PSP34
+    /// achievements contract as they are unlocked.
+    mod badges {
+        use super::*;
+        use ink_lang::codegen::{
+            EmitEvent,
+            StaticEnv,
+        };
+
+        impl Metasino {
+            /// Count a hand played by the player towards the achievements.
+            pub(super) fn count_hand(&mut self, player: AccountId) {
+                let mut progress = self.get_progress(player);
+                let unlocked = progress.play_hand();
+                self.progress.insert(player, &progress);
+                if let Some(achievement) = unlocked {
+                    self.award_badge(player, achievement);
+                }
+            }
+
+            /// Unlock the achievement of the player unless already unlocked.
+            pub(super) fn unlock(&mut self, player: AccountId, achievement: Achievement) {
+                let mut progress = self.get_progress(player);
+                if progress.unlock(achievement).is_some() {
+                    self.progress.insert(player, &progress);
+                    self.award_badge(player, achievement);
+                }
+            }
+
+            /// Mint the badge of the achievement just unlocked by the player.
+            /// A badge failing to mint does not hold up the game and is left
+            /// for [`Metasino::mint_badges`].
+            fn award_badge(&mut self, player: AccountId, achievement: Achievement) {
+                Self::env().emit_event(AchievementUnlocked {
+                    player,
+                    achievement,
+                });
+                let contract = match self.achievements_contract {
+                    Some(contract) => contract,
+                    None => return,
+                };
+                if psp34::mint(contract, player, achievement.badge_id(&player)).is_ok() {
+                    let mut progress = self.get_progress(player);
+                    progress.minted.push(achievement);
+                    self.progress.insert(player, &progress);
+                }
+            }
+        }
+    }
+
+    /// The progressive jackpot, fed by a share of the pots of the native
+    /// tables and won by a blackjack of the ace and jack of spades.
+    mod jackpot {
+        use super::*;
+        use ink_lang::codegen::{
+            EmitEvent,
+            StaticEnv,
+        };
+
+        impl Metasino {
+            /// Move the share of `amount` due to the jackpot into it.
+            /// Returns the share taken, nothing for tables played in a token.
+            pub(super) fn feed_jackpot(
+                &mut self,
+                table: &Table,
+                amount: Balance,
+            ) -> Result<Balance> {
+                if table.token.is_some() {
+                    return Ok(0)
+                }
+                let cut =
+                    arithmetic::mul_div(amount, self.jackpot_bps as Balance, MAX_BPS as Balance)?;
+                self.jackpot = arithmetic::add(self.jackpot, cut)?;
+                Ok(cut)
+            }
+
+            /// Pay the whole jackpot to the first seat of the finished blackjack
+            /// hand holding the jackpot hand, if any.
+            pub(super) fn award_jackpot(
+                &mut self,
+                table_id: TableId,
+                table: &Table,
+                round: &BlackjackRound,
+            ) -> Result<()> {
+                if table.token.is_some() || self.jackpot == 0 {
+                    return Ok(())
+                }
+                let seat = match round
+                    .hands
+                    .iter()
+                    .position(|hand| blackjack::is_jackpot_hand(hand))
+                {
+                    Some(seat) => seat as u8,
+                    None => return Ok(()),
+                };
+                let player = self.seats.get((table_id, seat)).ok_or(MetasinoError::WrongState)?;
+                let amount = self.jackpot;
+                self.pay(table, player, amount)?;
+                self.jackpot = 0;
+                self.record_win(player, amount);
+                Self::env().emit_event(JackpotWon {
+                    table_id,
+                    player,
+                    amount,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// The chips players hold on a table, bought once and spent bet by bet.
+    mod chips {
+        use super::*;
+        use ink_lang::codegen::StaticEnv;
+
+        impl Metasino {
+            /// Add `amount` to the chips of the player on the table.
+            pub(super) fn credit_chips(
+                &mut self,
+                table_id: TableId,
+                player: AccountId,
+                amount: Balance,
+            ) -> Result<()> {
+                let chips = arithmetic::add(self.get_chips(table_id, player), amount)?;
+                self.chips.insert((table_id, player), &chips);
+                Ok(())
+            }
+
+            /// Take `amount` out of the chips of the player on the table.
+            /// error if the player holds fewer chips.
+            pub(super) fn spend_chips(
+                &mut self,
+                table_id: TableId,
+                player: AccountId,
+                amount: Balance,
+            ) -> Result<()> {
+                let chips = self.get_chips(table_id, player);
+                if chips < amount {
+                    return Err(MetasinoError::InsufficientChips);
+                }
+                self.chips.insert((table_id, player), &arithmetic::sub(chips, amount)?);
+                Ok(())
+            }
+
+            /// Pay the winnings of the player into their chips if they play on
+            /// chips, or out of the contract otherwise.
+            pub(super) fn pay_winnings(
+                &mut self,
+                table_id: TableId,
+                table: &Table,
+                player: AccountId,
+                amount: Balance,
+            ) -> Result<()> {
+                if self.chips.contains((table_id, player)) {
+                    return self.credit_chips(table_id, player, amount)
+                }
+                self.pay(table, player, amount)
+            }
+
+            /// Take the `amount` the player puts into the pot out of their chips,
+            /// or collect it from them when value is transferred along with the
+            /// call or their chips fall short.
+            pub(super) fn put_in(
+                &mut self,
+                table_id: TableId,
+                table: &Table,
+                player: AccountId,
+                amount: Balance,
+            ) -> Result<()> {
+                if amount > 0
+                    && Self::env().transferred_value() == 0
+                    && self.get_chips(table_id, player) >= amount
+                {
+                    return self.spend_chips(table_id, player, amount)
+                }
+                self.collect(table, player, amount)
+            }
+        }
+    }
+
+    /// Moving the funds of a table in and out of the contract, either in the
+    /// native balance or in the PSP22 token the table is played in.
+    mod funds {
+        use super::*;
+        use ink_lang::codegen::{
+            EmitEvent,
+            StaticEnv,
+        };
+
+        impl Metasino {
+            /// Take `amount` from the player into the contract.
+            /// Native funds are the value transferred along with the call,
+            /// tokens are pulled from the allowance given to the contract.
+            /// error if too little value is transferred, or any for a token.
+            pub(super) fn collect(
+                &mut self,
+                table: &Table,
+                from: AccountId,
+                amount: Balance,
+            ) -> Result<()> {
+                match table.token {
+                    None => self.take_value(amount),
+                    Some(token) if Self::env().transferred_value() == 0 => {
+                        if amount == 0 {
+                            return Ok(())
+                        }
+                        psp22::transfer_from(token, from, Self::env().account_id(), amount)
+                    }
+                    _ => Err(MetasinoError::WrongBet),
+                }
+            }
+
+            /// The share of the rake of the table the house keeps, all of it
+            /// unless the table pays its rake to a recipient.
+            pub(super) fn house_rake(&self, table: &Table, rake: Balance) -> Result<Balance> {
+                match table.rake_recipient {
+                    Some(_) => arithmetic::mul_div(
+                        rake,
+                        self.get_config().protocol_cut_bps as Balance,
+                        MAX_BPS as Balance,
+                    ),
+                    None => Ok(rake),
+                }
+            }
+
+            /// Pay the rake recipient of the table its share of the rake.
+            /// Returns the share the house keeps.
+            pub(super) fn share_rake(
+                &mut self,
+                table_id: TableId,
+                table: &Table,
+                rake: Balance,
+            ) -> Result<Balance> {
+                let house_rake = self.house_rake(table, rake)?;
+                let amount = arithmetic::sub(rake, house_rake)?;
+                if let (Some(recipient), true) = (table.rake_recipient, amount > 0) {
+                    self.pay(table, recipient, amount)?;
+                    Self::env().emit_event(RakeShared {
+                        table_id,
+                        recipient,
+                        amount,
+                    });
+                }
+                Ok(house_rake)
+            }
+
+            /// Take `amount` out of the native value transferred along with the
+            /// call, refunding whatever was transferred on top of it to the
+            /// caller's withdrawable balance.
+            /// error if less than the amount is transferred.
+            pub(super) fn take_value(&mut self, amount: Balance) -> Result<()> {
+                let transferred = Self::env().transferred_value();
+                let excess = transferred
+                    .checked_sub(amount)
+                    .ok_or(MetasinoError::WrongBet)?;
+                self.credit_withdrawal(Self::env().caller(), excess)
+            }
+
+            /// Pay `amount` out of the contract to the given account.
+            /// Native funds are credited for the account to withdraw, so that
+            /// an account refusing transfers cannot block a table. Tokens are
+            /// sent right away.
+            pub(super) fn pay(
+                &mut self,
+                table: &Table,
+                to: AccountId,
+                amount: Balance,
+            ) -> Result<()> {
+                self.pay_in(table.token, to, amount)
+            }
+
+            /// Pay `amount` to the account in the PSP22 token, or in the
+            /// native balance if `None`.
+            pub(super) fn pay_in(
+                &mut self,
+                token: Option<AccountId>,
+                to: AccountId,
+                amount: Balance,
+            ) -> Result<()> {
+                match token {
+                    None => self.credit_withdrawal(to, amount),
+                    Some(token) => psp22::transfer(token, to, amount),
+                }
+            }
+
+            /// The most the account may withdraw now under the caps of the
+            /// block and of the account.
+            pub(super) fn withdrawal_allowance(&self, account: AccountId) -> Balance {
+                let now = Self::env().block_number();
+                let left = |cap: Balance, (at, withdrawn): (BlockNumber, Balance), current| {
+                    match cap {
+                        0 => Balance::MAX,
+                        cap if at == current => cap.saturating_sub(withdrawn),
+                        cap => cap,
+                    }
+                };
+                let epoch = now / WITHDRAWAL_EPOCH;
+                let by_account = self.account_withdrawals.get(account).unwrap_or_default();
+                let config = self.get_config();
+                left(config.max_withdrawal_per_block, self.block_withdrawals, now)
+                    .min(left(config.max_withdrawal_per_account, by_account, epoch))
+            }
+
+            /// Count the amount withdrawn by the account against the caps.
+            pub(super) fn record_withdrawal(&mut self, account: AccountId, amount: Balance) {
+                let now = Self::env().block_number();
+                let add = |(at, withdrawn): (BlockNumber, Balance), current| {
+                    match at == current {
+                        true => (at, withdrawn.saturating_add(amount)),
+                        false => (current, amount),
+                    }
+                };
+                self.block_withdrawals = add(self.block_withdrawals, now);
+                let by_account = self.account_withdrawals.get(account).unwrap_or_default();
+                let by_account = add(by_account, now / WITHDRAWAL_EPOCH);
+                self.account_withdrawals.insert(account, &by_account);
+            }
+
+            /// Move the whole balance credited to the account into the queue of
+            /// the withdrawals waiting for an operator.
+            /// error if a withdrawal of the account is already queued.
+            pub(super) fn queue_withdrawal(
+                &mut self,
+                account: AccountId,
+                amount: Balance,
+            ) -> Result<()> {
+                if self.queued_withdrawals.contains(account) {
+                    return Err(MetasinoError::WrongState);
+                }
+                let timelock = self.get_config().payout_timelock_blocks;
+                let release_at = Self::env().block_number().saturating_add(timelock);
+                self.queued_withdrawals.insert(account, &(amount, release_at));
+                self.pending_withdrawals.remove(account);
+                Self::env().emit_event(WithdrawalQueued {
+                    account,
+                    amount,
+                    release_at,
+                });
+                Ok(())
+            }
+
+            /// The native funds escrowed outside the tables: the start bets of
+            /// the waitlists, the payouts held, the creator stakes and the
+            /// stakes of the duels, lotteries, markets and tournaments not
+            /// paid out yet.
+            pub(super) fn escrowed(&self) -> Balance {
+                let waitlists = (0..self.next_table_id)
+                    .filter_map(|table_id| Some((table_id, self.tables.get(table_id)?)))
+                    .filter(|(_, table)| table.token.is_none())
+                    .map(|(table_id, table)| {
+                        let waiting = self.waitlists.get(table_id).unwrap_or_default().len();
+                        table.required_start_bet.saturating_mul(waiting as Balance)
+                    })
+                    .fold(0, Balance::saturating_add);
+                let duels = (0..self.next_duel_id)
+                    .filter_map(|duel_id| self.duels.get(duel_id))
+                    .map(|duel| {
+                        match duel.state {
+                            DuelState::Open => duel.stake,
+                            DuelState::Accepted => duel.stake.saturating_mul(2),
+                            _ => 0,
+                        }
+                    })
+                    .fold(0, Balance::saturating_add);
+                let lotteries = (0..self.next_lottery_id)
+                    .filter_map(|lottery_id| self.lotteries.get(lottery_id))
+                    .filter(|lottery| !lottery.drawn)
+                    .map(|lottery| lottery.pool().unwrap_or(Balance::MAX))
+                    .fold(0, Balance::saturating_add);
+                let markets = (0..self.next_market_id)
+                    .filter_map(|market_id| self.markets.get(market_id))
+                    .map(|market| market.unclaimed().unwrap_or(Balance::MAX))
+                    .fold(0, Balance::saturating_add);
+                let tournaments = (0..self.next_tournament_id)
+                    .filter_map(|tournament_id| self.tournaments.get(tournament_id))
+                    .map(|tournament| tournament.escrow().unwrap_or(Balance::MAX))
+                    .fold(0, Balance::saturating_add);
+                [
+                    waitlists,
+                    self.total_held,
+                    self.total_creator_stakes,
+                    duels,
+                    lotteries,
+                    markets,
+                    tournaments,
+                ]
+                .iter()
+                .copied()
+                .fold(0, Balance::saturating_add)
+            }
+
+            /// Credit `amount` of the native balance for the account to withdraw.
+            pub(super) fn credit_withdrawal(
+                &mut self,
+                account: AccountId,
+                amount: Balance,
+            ) -> Result<()> {
+                if amount == 0 {
+                    return Ok(())
+                }
+                let pending = arithmetic::add(self.get_claimable(account), amount)?;
+                self.total_claimable = arithmetic::add(self.total_claimable, amount)?;
+                self.pending_withdrawals.insert(account, &pending);
+                Ok(())
+            }
+        }
+    }
+
+    /// Accounts of the players signing their actions with ECDSA keys, and
+    /// the nonces ordering the actions of every player.
+    mod signatures {
+        use super::*;
+
+        impl Metasino {
+            /// Only the next nonce of the player may be used.
+            pub(super) fn check_nonce(&self, player: AccountId, nonce: u64) -> Result<()> {
+                if nonce != self.get_nonce(player) {
+                    return Err(MetasinoError::InvalidNonce);
+                }
+                Ok(())
+            }
+
+            /// Move the player to their next nonce once they took an action,
+            /// so that no action they signed for the previous one is played.
+            pub(super) fn bump_nonce(&mut self, player: AccountId) {
+                let nonce = self.get_nonce(player).saturating_add(1);
+                self.nonces.insert(player, &nonce);
+            }
+        }
+
+        /// The account whose key signed the message hash: the Blake2x256 hash
+        /// of its compressed public key.
+        /// error if no key recovers from the signature.
+        pub(super) fn signer(signature: &[u8; 65], message_hash: &[u8; 32]) -> Result<AccountId> {
+            let mut key = [0; 33];
+            ink_env::ecdsa_recover(signature, message_hash, &mut key)
+                .map_err(|_| MetasinoError::InvalidSignature)?;
+            let mut account = <Blake2x256 as HashOutput>::Type::default();
+            ink_env::hash_bytes::<Blake2x256>(&key, &mut account);
+            Ok(AccountId::from(account))
+        }
+    }
+
+    /// Commit-reveal scheme deriving the shuffle seed of a table.
+    /// Players commit to the hash of a secret seed before the game starts and
+    /// reveal it afterwards, the shuffle seed is the XOR of all the reveals.
+    mod randomness {
+        use super::*;
+        use ink_env::hash::{
+            Blake2x256,
+            HashOutput,
+        };
+        use ink_lang::codegen::{
+            EmitEvent,
+            StaticEnv,
+        };
+
+        /// Compute the commitment of a player to a seed.
+        /// The player is part of the preimage so that a commitment cannot be
+        /// copied by another player to cancel out their seed.
+        pub fn commitment(player: &AccountId, seed: &[u8; 32]) -> Hash {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink_env::hash_encoded::<Blake2x256, _>(&(player, seed), &mut output);
+            Hash::from(output)
+        }
+
+        /// XOR the revealed seed into the entropy accumulated so far.
+        pub fn combine(entropy: &mut [u8; 32], seed: &[u8; 32]) {
+            for (byte, seed_byte) in entropy.iter_mut().zip(seed.iter()) {
+                *byte ^= seed_byte;
+            }
+        }
+
+        impl Metasino {
+            /// Fix the shuffle seed of the table from the entropy revealed
+            /// and open the betting of the hand, or deal the blackjack hand.
+            /// With the `chain-randomness` feature the randomness of the chain
+            /// for the hand is mixed into the entropy first. With the
+            /// `test-seed` feature a seed fixed by the test replaces it.
+            pub(super) fn derive_seed(
+                &mut self,
+                table_id: TableId,
+                table: &mut Table,
+            ) -> Result<()> {
+                #[cfg(feature = "chain-randomness")]
+                {
+                    let mut subject = <Blake2x256 as HashOutput>::Type::default();
+                    ink_env::hash_encoded::<Blake2x256, _>(
+                        &(table_id, table.hand_number),
+                        &mut subject,
+                    );
+                    let random = Self::env()
+                        .extension()
+                        .fetch_random(subject)
+                        .map_err(|_| MetasinoError::RandomnessUnavailable)?;
+                    combine(&mut table.entropy, &random);
+                }
+                #[cfg(feature = "test-seed")]
+                if let Some(seed) = crate::test_seed::fixed() {
+                    table.entropy = seed;
+                }
+                table.seed = Some(table.entropy);
+                table.dealer_deadline = None;
+                let deadline = Self::env().block_number().saturating_add(TURN_PERIOD);
+                Self::env().emit_event(ShuffleSeedDerived {
+                    table_id,
+                    seed: table.entropy,
+                });
+                match table.game {
+                    GameKind::Poker => {
+                        let seats = table.players_count();
+                        let mut round = match table.variant {
+                            Variant::Stud => BettingRound::stud(seats, deadline),
+                            _ => BettingRound::new(seats, deadline),
+                        };
+                        round.structure = table.structure;
+                        round.small_bet = table.big_blind;
+                        let (street, stake) = (round.street, table.required_start_bet);
+                        for seat in 0..seats {
+                            let deal = LoggedAction::Deal;
+                            Self::log_action(table_id, table, street, seat, deal, stake);
+                        }
+                        if table.ante > 0 {
+                            self.post_antes(table_id, table, &mut round)?;
+                        }
+                        if table.variant == Variant::Stud {
+                            self.post_bring_in(table_id, table, &mut round)?;
+                        } else if table.big_blind > 0 {
+                            self.post_blinds(table_id, table, &mut round)?;
+                        }
+                        round.pot = table.pot;
+                        if round.finished {
+                            self.await_dealer(table, Self::env().block_number());
+                        }
+                        self.rounds.insert(table_id, &round);
+                    }
+                    GameKind::Blackjack => {
+                        let mut round = BlackjackRound::deal(
+                            table.players_count(),
+                            table.required_start_bet,
+                            &table.entropy,
+                            deadline,
+                        );
+                        let insurance = Self::env().block_number().saturating_add(INSURANCE_PERIOD);
+                        round.offer_insurance(insurance);
+                        if round.finished {
+                            self.award_jackpot(table_id, table, &round)?;
+                            self.settle_with_house(table_id, table, round.payouts()?)?;
+                        } else {
+                            self.blackjack_rounds.insert(table_id, &round);
+                        }
+                    }
+                    GameKind::Roulette => self.settle_roulette(table_id, table)?,
+                    GameKind::Baccarat => self.settle_baccarat(table_id, table)?,
+                    GameKind::Dice => {
+                        self.dice_rounds.insert(table_id, &DiceRound::default());
+                        self.await_dealer(table, Self::env().block_number());
+                    }
+                    GameKind::Engine(engine) => {
+                        let players = self.players_of(table_id, table);
+                        engine::start(engine, table_id, players, table.pot, table.entropy)?;
+                    }
+                    GameKind::Oracle => (),
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
+    /// module and test functions are marked with a `#[test]` attribute.
+    /// The below code is technically just normal Rust code.
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+
+        /// Imports `ink_lang` so we can use `#[ink::test]`.
+        use ink_lang as ink;
+
+        /// The events emitted by the contract.
+        type Event = <Metasino as ::ink_lang::reflect::ContractEventBase>::Type;
+
+        /// Sets the caller and the value transferred along with the next call.
+        /// With the `chain-randomness` feature the chain is mocked to return
+        /// no randomness, leaving the seeds to the reveals.
+        fn call_as(caller: AccountId, value: Balance) {
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(caller);
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(value);
+            #[cfg(feature = "chain-randomness")]
+            ink_env::test::register_chain_extension(MockedRandomness([0; 32]));
+        }
+
+        /// The randomness pallet of the chain, always returning the same randomness.
+        #[cfg(feature = "chain-randomness")]
+        struct MockedRandomness([u8; 32]);
+
+        #[cfg(feature = "chain-randomness")]
+        impl ink_env::test::ChainExtension for MockedRandomness {
+            fn func_id(&self) -> u32 {
+                1101
+            }
+
+            fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
+                scale::Encode::encode_to(&self.0, output);
+                0
+            }
+        }
+
+        /// The secret seed used by the given player in the tests.
+        fn seed_of(player: AccountId) -> [u8; 32] {
+            let mut seed = [0; 32];
+            seed.copy_from_slice(player.as_ref());
+            seed
+        }
+
+        /// Commits the player to their seed.
+        fn commit_as(metasino: &mut Metasino, table_id: TableId, player: AccountId) {
+            call_as(player, 0);
+            let commitment = metasino.compute_commitment(player, seed_of(player));
+            metasino.commit_seed(table_id, commitment).unwrap();
+        }
+
+        /// Opens a table of alice with bob and charlie registered and committed
+        /// to their seed, and the contract account holding the escrowed pot.
+        fn staged_table() -> (Metasino, TableId) {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 300);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 3, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            call_as(accounts.charlie, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                commit_as(&mut metasino, table_id, player);
+            }
+            call_as(accounts.alice, 0);
+            (metasino, table_id)
+        }
+
+        /// Same as [`staged_table`] with the game started by alice.
+        fn started_table() -> (Metasino, TableId) {
+            let (mut metasino, table_id) = staged_table();
+            metasino.start_game(table_id).unwrap();
+            (metasino, table_id)
+        }
+
+        /// Same as [`started_table`] with every player having revealed their seed.
+        fn revealed_table() -> (Metasino, TableId) {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            (metasino, table_id)
+        }
+
+        fn balance_of(account: AccountId) -> Balance {
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(account)
+                .expect("account has a balance")
+        }
+
+        /// Has every account with something credited withdraw it.
+        fn withdraw_all(metasino: &mut Metasino) {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            for account in [
+                accounts.alice,
+                accounts.bob,
+                accounts.charlie,
+                accounts.django,
+                accounts.eve,
+            ] {
+                if metasino.get_claimable(account) > 0 {
+                    call_as(account, 0);
+                    metasino.withdraw_winnings().unwrap();
+                }
+            }
+            call_as(accounts.alice, 0);
+        }
+
+        /// Test table creation works as per expected.
+        /// - Test the required start bet value is as per the initialized.
+        /// - Test the initializer is the caller who created the table.
+        /// - Test the total number of player is 1 when created.
+        /// - Test the total accumulated pot value is as start bet value put by the initializer caller.
+        /// - Test the state is STAGING when created.
+        #[ink::test]
+        fn create_table_with_player_count_equal_one() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 3, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            assert_eq!(metasino.get_required_start_bet(table_id), Ok(100));
+            assert_eq!(metasino.get_initializer(table_id), Ok(accounts.alice));
+            assert_eq!(metasino.get_players_count(table_id), Ok(1));
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(100));
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::STAGING));
+        }
+
+        #[ink::test]
+        fn create_table_requires_escrowed_start_bet() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.create_table(0, 3, 10, None, GameKind::Poker, None, None),
+                Err(MetasinoError::WrongBet)
+            );
+            call_as(accounts.alice, 50);
+            assert_eq!(
+                metasino.create_table(100, 3, 10, None, GameKind::Poker, None, None),
+                Err(MetasinoError::WrongBet)
+            );
+            assert_eq!(metasino.get_table(0), None);
+        }
+
+        #[ink::test]
+        fn token_tables_reject_native_value() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let token = AccountId::from([0x99; 32]);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            assert_eq!(
+                metasino.create_table(100, 3, 10, Some(token), GameKind::Poker, None, None),
+                Err(MetasinoError::WrongBet)
+            );
+            assert_eq!(metasino.get_table(0), None);
+            assert_eq!(metasino.get_token_treasury(token), 0);
+
+            let table_id = metasino
+                .create_table(100, 3, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            assert_eq!(metasino.get_token(table_id), Ok(None));
+        }
+
+        #[ink::test]
+        fn tables_are_played_independently() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, first) = staged_table();
+            call_as(accounts.django, 200);
+            let second = metasino
+                .create_table(200, 3, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            assert_ne!(first, second);
+            call_as(accounts.bob, 200);
+            assert_eq!(metasino.register_player(second, None, None), Ok(()));
+
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.start_game(first), Ok(()));
+            assert_eq!(metasino.get_table_state(first), Ok(STATE::PLAYING));
+            assert_eq!(metasino.get_table_state(second), Ok(STATE::STAGING));
+            assert_eq!(metasino.get_accumulated_pot(second), Ok(400));
+            assert_eq!(
+                metasino.get_players(second, 0, MAX_PLAYERS),
+                Ok(vec![accounts.django, accounts.bob])
+            );
+        }
+
+        #[ink::test]
+        fn unknown_table_is_not_found() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            assert_eq!(metasino.register_player(7, None, None), Err(MetasinoError::TableNotFound));
+            assert_eq!(metasino.get_table_state(7), Err(MetasinoError::TableNotFound));
+        }
+
+        #[ink::test]
+        fn register_same_player_will_fail() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 3, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            assert_eq!(
+                metasino.register_player(table_id, None, None),
+                Err(MetasinoError::AlreadyRegistered)
+            );
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(100));
+        }
+
+        #[ink::test]
+        fn adding_new_player() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 3, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            assert_eq!(metasino.register_player(table_id, None, None), Ok(()));
+            assert_eq!(metasino.get_players_count(table_id), Ok(2));
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(200));
+            assert_eq!(
+                metasino.get_players(table_id, 0, MAX_PLAYERS),
+                Ok(vec![accounts.alice, accounts.bob])
+            );
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::STAGING));
+            assert_eq!(metasino.get_required_start_bet(table_id), Ok(100));
+        }
+
+        #[ink::test]
+        fn register_with_wrong_bet_will_fail() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 3, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 50);
+            assert_eq!(
+                metasino.register_player(table_id, None, None),
+                Err(MetasinoError::WrongBet)
+            );
+            assert_eq!(metasino.get_players_count(table_id), Ok(1));
+        }
+
+        #[ink::test]
+        fn create_table_validates_players_limits() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            assert_eq!(
+                metasino.create_table(100, 1, 10, None, GameKind::Poker, None, None),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            assert_eq!(
+                metasino.create_table(100, 3, 11, None, GameKind::Poker, None, None),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            assert_eq!(
+                metasino.create_table(100, 5, 4, None, GameKind::Poker, None, None),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            let table_id = metasino
+                .create_table(100, 2, 4, None, GameKind::Poker, None, None)
+                .unwrap();
+            assert_eq!(metasino.get_min_players(table_id), Ok(2));
+            assert_eq!(metasino.get_max_players(table_id), Ok(4));
+        }
+
+        #[ink::test]
+        fn seating_announces_the_minimum_and_a_full_table() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 3, None, GameKind::Poker, None, None)
+                .unwrap();
+            let announced = || -> Vec<Event> {
+                ink_env::test::recorded_events()
+                    .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                    .filter(|event| {
+                        matches!(event, Event::MinimumPlayerReached(_) | Event::TableFull(_))
+                    })
+                    .collect()
+            };
+            assert!(announced().is_empty());
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            assert!(matches!(
+                announced()[..],
+                [Event::MinimumPlayerReached(MinimumPlayerReached { players: 2, account_id, .. })]
+                    if account_id == accounts.bob
+            ));
+            call_as(accounts.charlie, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            assert_eq!(announced().len(), 2);
+            assert!(matches!(
+                announced()[1],
+                Event::TableFull(TableFull { players: 3, .. })
+            ));
+        }
+
+        #[ink::test]
+        fn table_starts_with_configured_minimum() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 2, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            call_as(accounts.charlie, 100);
+            assert_eq!(
+                metasino.register_player(table_id, None, None),
+                Err(MetasinoError::TableFull)
+            );
+            commit_as(&mut metasino, table_id, accounts.alice);
+            commit_as(&mut metasino, table_id, accounts.bob);
+            assert_eq!(metasino.start_game(table_id), Ok(()));
+        }
+
+        #[ink::test]
+        fn register_into_full_table_will_fail() {
+            let mut metasino = Metasino::new();
+            call_as(AccountId::from([0x01; 32]), 100);
+            let table_id = metasino
+                .create_table(100, 3, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            for seat in 2..=MAX_PLAYERS {
+                call_as(AccountId::from([seat; 32]), 100);
+                assert_eq!(metasino.register_player(table_id, None, None), Ok(()));
+            }
+            assert_eq!(metasino.is_table_full(table_id), Ok(true));
+            call_as(AccountId::from([0xff; 32]), 100);
+            assert_eq!(
+                metasino.register_player(table_id, None, None),
+                Err(MetasinoError::TableFull)
+            );
+        }
+
+        #[ink::test]
+        fn less_than_minimum_player_unable_to_start_game(){
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 3, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            assert_eq!(metasino.start_game(table_id), Err(MetasinoError::NotEnoughPlayers));
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::STAGING));
+        }
+
+        #[ink::test]
+        fn able_to_start_game(){
+            let (mut metasino, table_id) = staged_table();
+            assert_eq!(metasino.start_game(table_id), Ok(()));
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::PLAYING));
+        }
+
+        #[ink::test]
+        fn fail_to_add_player_when_game_status_started(){
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            call_as(accounts.django, 100);
+            assert_eq!(
+                metasino.register_player(table_id, None, None),
+                Err(MetasinoError::WrongState)
+            );
+        }
+
+        #[ink::test]
+        fn should_not_allow_termination_if_table_game_in_started_state(){
+            let (mut metasino, table_id) = started_table();
+            assert_eq!(metasino.terminate(table_id), Err(MetasinoError::WrongState));
+        }
+
+        #[ink::test]
+        fn terminate_refunds_every_player() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            let alice_balance = metasino.get_claimable(accounts.alice);
+            let bob_balance = metasino.get_claimable(accounts.bob);
+
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.terminate(table_id), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.terminate(table_id), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.terminate(table_id), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.alice), alice_balance + 100);
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 100);
+            assert_eq!(metasino.get_players_count(table_id), Ok(0));
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(0));
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::ENDED));
+            assert_eq!(ink_env::test::recorded_events().count(), 6);
+            withdraw_all(&mut metasino);
+            assert_eq!(balance_of(AccountId::from([0x42; 32])), 0);
+        }
+
+        #[ink::test]
+        fn stale_tables_expire_and_refund() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.expire_table(table_id), Err(MetasinoError::DeadlineNotReached));
+            assert_eq!(metasino.set_staging_timeout(5), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_staging_timeout(5), Ok(()));
+            assert_eq!(metasino.get_config().staging_timeout_blocks, 5);
+            for _ in 0..4 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.expire_table(table_id), Err(MetasinoError::DeadlineNotReached));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            assert_eq!(metasino.expire_table(table_id), Ok(()));
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                assert_eq!(metasino.get_claimable(player), 100);
+                assert_eq!(metasino.get_player_stats(player).games_played, 0);
+            }
+            assert_eq!(metasino.get_table_info(table_id), Err(MetasinoError::TableNotFound));
+            assert_eq!(metasino.expire_table(table_id), Err(MetasinoError::TableNotFound));
+            let event = ink_env::test::recorded_events().last().unwrap();
+            let decoded = <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+            assert!(matches!(
+                decoded,
+                Event::TableExpired(TableExpired { refunds, .. }) if refunds == vec![100; 3]
+            ));
+            withdraw_all(&mut metasino);
+            assert_eq!(balance_of(AccountId::from([0x42; 32])), 0);
+        }
+
+        #[ink::test]
+        fn initializer_bond_is_returned_or_slashed() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 500);
+            call_as(accounts.eve, 0);
+            let mut metasino = Metasino::new();
+            metasino.set_staging_timeout(5).unwrap();
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_table_bond(50), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.set_table_bond(50), Ok(()));
+
+            // Terminating the table cleanly returns the bond.
+            call_as(accounts.alice, 100);
+            assert_eq!(
+                metasino.create_table(100, 2, 10, None, GameKind::Poker, None, None),
+                Err(MetasinoError::WrongBet)
+            );
+            call_as(accounts.alice, 150);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            assert_eq!(metasino.terminate(table_id), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.alice), 150);
+
+            // Abandoning it to expire shares the bond out to the other players.
+            call_as(accounts.alice, 150);
+            let table_id = metasino
+                .create_table(100, 3, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            for player in [accounts.bob, accounts.charlie] {
+                call_as(player, 100);
+                metasino.register_player(table_id, None, None).unwrap();
+            }
+            for _ in 0..5 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            assert_eq!(metasino.expire_table(table_id), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.alice), 250);
+            assert_eq!(metasino.get_claimable(accounts.bob), 125);
+            assert_eq!(metasino.get_claimable(accounts.charlie), 125);
+            assert_eq!(metasino.slash_table_bond(table_id), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.slash_table_bond(table_id), Err(MetasinoError::TableNotFound));
+            withdraw_all(&mut metasino);
+            assert_eq!(balance_of(contract), 0);
+        }
+
+        #[ink::test]
+        fn engine_games_are_only_played_on_engine_tables() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, poker) = revealed_table();
+            assert_eq!(metasino.play_engine(poker, vec![1]), Err(MetasinoError::WrongState));
+            assert_eq!(metasino.resolve_engine_game(poker), Err(MetasinoError::WrongState));
+
+            let engine = AccountId::from([0x07; 32]);
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Engine(engine), None, None)
+                .unwrap();
+            assert_eq!(
+                metasino.get_table_info(table_id).unwrap().game,
+                GameKind::Engine(engine)
+            );
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.play_engine(table_id, vec![1]), Err(MetasinoError::WrongState));
+            assert_eq!(metasino.resolve_engine_game(table_id), Err(MetasinoError::WrongState));
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.play_engine(table_id, vec![1]), Err(MetasinoError::NotAuthorized));
+        }
+
+        #[ink::test]
+        fn oracle_results_are_confirmed_by_the_signers() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 2, None, GameKind::Oracle, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            for player in [accounts.alice, accounts.bob] {
+                commit_as(&mut metasino, table_id, player);
+            }
+            metasino.start_game(table_id).unwrap();
+            for player in [accounts.alice, accounts.bob] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_result_threshold(1), Err(MetasinoError::InvalidThreshold));
+            for signer in [accounts.charlie, accounts.django, accounts.eve] {
+                assert_eq!(metasino.add_result_signer(signer), Ok(()));
+            }
+            assert_eq!(metasino.add_result_signer(accounts.eve), Err(MetasinoError::WrongState));
+            assert_eq!(metasino.set_result_threshold(2), Ok(()));
+            assert_eq!(metasino.remove_result_signer(accounts.eve), Ok(()));
+            assert_eq!(
+                metasino.remove_result_signer(accounts.django),
+                Err(MetasinoError::InvalidThreshold)
+            );
+            assert_eq!(metasino.add_result_signer(accounts.eve), Ok(()));
+
+            let result_hash = metasino.compute_result_hash(vec![accounts.bob], vec![100]);
+            assert_eq!(
+                metasino.end_game(table_id, vec![accounts.bob], vec![100]),
+                Err(MetasinoError::InvalidPayout)
+            );
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.submit_result(table_id, result_hash),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.charlie, 0);
+            assert_eq!(metasino.submit_result(table_id, result_hash), Ok(()));
+            assert_eq!(
+                metasino.submit_result(table_id, result_hash),
+                Err(MetasinoError::WrongState)
+            );
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.submit_result(table_id, Hash::from([1; 32])), Ok(()));
+            assert_eq!(metasino.get_confirmed_result(table_id), None);
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.submit_result(table_id, result_hash), Ok(()));
+            assert_eq!(metasino.get_confirmed_result(table_id), Some(result_hash));
+            let event = ink_env::test::recorded_events().last().unwrap();
+            let decoded = <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+            assert!(matches!(decoded, Event::ResultFinalized(ResultFinalized { .. })));
+
+            call_as(accounts.django, 0);
+            assert_eq!(
+                metasino.end_game(table_id, vec![accounts.alice], vec![100]),
+                Err(MetasinoError::InvalidPayout)
+            );
+            assert_eq!(metasino.end_game(table_id, vec![accounts.bob], vec![100]), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.bob), 200);
+            assert_eq!(metasino.get_confirmed_result(table_id), None);
+        }
+
+        #[ink::test]
+        fn winnings_are_withdrawn_once() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.withdraw_winnings(), Err(MetasinoError::InvalidPayout));
+            call_as(accounts.alice, 0);
+            metasino.terminate(table_id).unwrap();
+            let bob_balance = balance_of(accounts.bob);
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.withdraw_winnings(), Ok(()));
+            assert_eq!(balance_of(accounts.bob), bob_balance + 100);
+            assert_eq!(metasino.get_claimable(accounts.bob), 0);
+            assert_eq!(metasino.withdraw_winnings(), Err(MetasinoError::InvalidPayout));
+            let event = ink_env::test::recorded_events().last().unwrap();
+            let decoded = <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+            assert!(matches!(
+                decoded,
+                Event::WinningsWithdrawn(WinningsWithdrawn { amount: 100, .. })
+            ));
+        }
+
+        #[ink::test]
+        fn table_info_gathers_the_table_at_once() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (metasino, table_id) = staged_table();
+            assert_eq!(
+                metasino.get_table_info(table_id),
+                Ok(TableInfo {
+                    table_id,
+                    state: STATE::STAGING,
+                    players: vec![accounts.alice, accounts.bob, accounts.charlie],
+                    pot: 300,
+                    required_start_bet: 100,
+                    initializer: accounts.alice,
+                    min_players: 3,
+                    max_players: 10,
+                    token: None,
+                    game: GameKind::Poker,
+                    rake_bps: 0,
+                    rake_cap: 0,
+                    rake_recipient: None,
+                    button: 0,
+                    small_blind: 0,
+                    big_blind: 0,
+                    ante: 0,
+                    variant: Variant::Holdem,
+                    structure: BettingStructure::NoLimit,
+                    hand_number: 0,
+                    start_at: None,
+                    sit_and_go: false,
+                    freeroll: false,
+                    dealer: None,
+                })
+            );
+            assert_eq!(metasino.get_table_info(table_id + 1), Err(MetasinoError::TableNotFound));
+            assert_eq!(
+                metasino.get_tables_info(vec![table_id + 1, table_id]),
+                vec![None, metasino.get_table_info(table_id).ok()]
+            );
+        }
+
+        #[ink::test]
+        fn tables_info_is_capped() {
+            let (metasino, table_id) = staged_table();
+            let ids = vec![table_id; MAX_TABLE_SUMMARIES as usize + 10];
+            let infos = metasino.get_tables_info(ids);
+            assert_eq!(infos.len(), MAX_TABLE_SUMMARIES as usize);
+            assert!(infos.iter().all(Option::is_some));
+        }
+
+        #[ink::test]
+        fn players_are_paginated_by_seat() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (metasino, table_id) = staged_table();
+            assert_eq!(
+                metasino.get_players(table_id, 0, 2),
+                Ok(vec![accounts.alice, accounts.bob])
+            );
+            assert_eq!(metasino.get_players(table_id, 2, 2), Ok(vec![accounts.charlie]));
+            assert_eq!(metasino.get_players(table_id, 3, 2), Ok(vec![]));
+            assert_eq!(
+                metasino.get_players(table_id, 1, u8::MAX).map(|players| players.len()),
+                Ok(2)
+            );
+        }
+
+        #[ink::test]
+        fn state_transitions_are_emitted() {
+            let (mut metasino, table_id) = staged_table();
+            assert_eq!(metasino.start_game(table_id), Ok(()));
+            let events: Vec<Event> = ink_env::test::recorded_events()
+                .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            assert!(matches!(
+                events[1],
+                Event::PlayerRegistered(PlayerRegistered { start_bet: 100, .. })
+            ));
+            assert!(matches!(
+                events[events.len() - 2],
+                Event::StateChanged(StateChanged {
+                    from: STATE::STAGING,
+                    to: STATE::PLAYING,
+                    ..
+                })
+            ));
+            match &events[events.len() - 1] {
+                Event::GameStarted(started) => {
+                    assert_eq!(started.players.len(), 3);
+                    assert_eq!(started.reveal_deadline, REVEAL_PERIOD);
+                }
+                _ => panic!("expected GameStarted"),
+            }
+        }
+
+        #[ink::test]
+        fn pause_suspends_new_games() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            assert_eq!(metasino.pause(), Ok(()));
+            assert!(metasino.is_paused());
+            assert_eq!(metasino.pause(), Err(MetasinoError::Paused));
+            assert_eq!(metasino.start_game(table_id), Err(MetasinoError::Paused));
+            call_as(accounts.django, 100);
+            assert_eq!(metasino.register_player(table_id, None, None), Err(MetasinoError::Paused));
+            assert_eq!(
+                metasino.create_table(100, 3, 10, None, GameKind::Poker, None, None),
+                Err(MetasinoError::Paused)
+            );
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.unpause(), Err(MetasinoError::NotAuthorized));
+            assert_eq!(metasino.leave_table(table_id), Ok(()));
+
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.unpause(), Ok(()));
+            assert_eq!(metasino.unpause(), Err(MetasinoError::WrongState));
+            assert_eq!(metasino.start_game(table_id), Err(MetasinoError::NotEnoughPlayers));
+        }
+
+        #[ink::test]
+        fn lobby_lists_and_finds_tables() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let full = metasino
+                .create_table(100, 2, 2, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(full, None, None).unwrap();
+            call_as(accounts.alice, 200);
+            let locked = metasino
+                .create_table(200, 2, 4, None, GameKind::Dice, Some(Hash::from([1; 32])), None)
+                .unwrap();
+            call_as(accounts.alice, 300);
+            let open = metasino
+                .create_table(300, 2, 4, None, GameKind::Roulette, None, None)
+                .unwrap();
+
+            let ids = |summaries: Vec<TableSummary>| -> Vec<TableId> {
+                summaries.iter().map(|summary| summary.table_id).collect()
+            };
+            let lobby = metasino.list_open_tables(0, 10);
+            assert_eq!(ids(lobby.clone()), vec![open]);
+            assert_eq!(
+                lobby[0],
+                TableSummary {
+                    table_id: open,
+                    state: STATE::STAGING,
+                    game: GameKind::Roulette,
+                    token: None,
+                    required_start_bet: 300,
+                    seated: 1,
+                    min_players: 2,
+                    max_players: 4,
+                    restricted: false,
+                }
+            );
+            assert!(metasino.list_open_tables(1, 10).is_empty());
+            assert!(metasino.list_open_tables(0, 0).is_empty());
+            assert_eq!(ids(metasino.find_tables_by_bet_range(150, 300)), vec![locked, open]);
+            assert_eq!(ids(metasino.find_tables_by_bet_range(0, 100)), vec![full]);
+            assert_eq!(
+                ids(metasino.find_tables_by_state(STATE::STAGING)),
+                vec![full, locked, open]
+            );
+            assert!(metasino.find_tables_by_state(STATE::PLAYING).is_empty());
+        }
+
+        #[ink::test]
+        fn factory_mode_is_switched_by_the_owner() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            let code_hash = Some(Hash::from([0x0c; 32]));
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.set_table_code_hash(code_hash), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_table_code_hash(code_hash), Ok(()));
+            assert_eq!(metasino.get_config().table_code_hash, code_hash);
+
+            let token = AccountId::from([0x07; 32]);
+            assert_eq!(
+                metasino.create_table(100, 2, 4, Some(token), GameKind::Poker, None, None),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            assert_eq!(metasino.get_table_contract(0), None);
+
+            assert_eq!(metasino.set_table_code_hash(None), Ok(()));
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 4, None, GameKind::Poker, None, None)
+                .unwrap();
+            assert_eq!(metasino.get_table_contract(table_id), None);
+            assert_eq!(metasino.get_players_count(table_id), Ok(1));
+        }
+
+        #[ink::test]
+        fn config_bounds_the_tables_opened() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            assert_eq!(metasino.get_config(), Config::default());
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.set_max_tables_per_account(1),
+                Err(MetasinoError::NotAuthorized)
+            );
+            assert_eq!(metasino.set_max_players_ceiling(4), Err(MetasinoError::NotAuthorized));
+            assert_eq!(
+                metasino.set_start_bet_limits(10, 100),
+                Err(MetasinoError::NotAuthorized)
+            );
+
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.set_max_players_ceiling(MAX_PLAYERS + 1),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            assert_eq!(
+                metasino.set_start_bet_limits(0, 100),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            assert_eq!(
+                metasino.set_start_bet_limits(100, 10),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            assert_eq!(metasino.set_max_tables_per_account(1), Ok(()));
+            assert_eq!(metasino.set_max_players_ceiling(4), Ok(()));
+            assert_eq!(metasino.set_start_bet_limits(10, 100), Ok(()));
+            assert_eq!(
+                metasino.get_config(),
+                Config {
+                    max_tables_per_account: 1,
+                    max_players: 4,
+                    min_start_bet: 10,
+                    max_start_bet: 100,
+                    ..Config::default()
+                }
+            );
+            let event = ink_env::test::recorded_events().last().unwrap();
+            let decoded = <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+            assert!(matches!(
+                decoded,
+                Event::ConfigChanged(ConfigChanged { config }) if config.min_start_bet == 10
+            ));
+
+            call_as(accounts.bob, 5);
+            assert_eq!(
+                metasino.create_table(5, 2, 4, None, GameKind::Poker, None, None),
+                Err(MetasinoError::WrongBet)
+            );
+            call_as(accounts.bob, 101);
+            assert_eq!(
+                metasino.create_table(101, 2, 4, None, GameKind::Poker, None, None),
+                Err(MetasinoError::WrongBet)
+            );
+            call_as(accounts.bob, 100);
+            assert_eq!(
+                metasino.create_table(100, 2, 5, None, GameKind::Poker, None, None),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            let table_id = metasino
+                .create_table(100, 2, 4, None, GameKind::Poker, None, None)
+                .unwrap();
+            assert_eq!(
+                metasino.create_table(100, 2, 4, None, GameKind::Poker, None, None),
+                Err(MetasinoError::TooManyTables)
+            );
+            call_as(accounts.charlie, 100);
+            let other_id = metasino
+                .create_table(100, 2, 4, None, GameKind::Poker, None, None)
+                .unwrap();
+            assert_eq!(
+                metasino.transfer_table_ownership(other_id, accounts.bob),
+                Err(MetasinoError::TooManyTables)
+            );
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.transfer_table_ownership(table_id, accounts.django), Ok(()));
+            call_as(accounts.bob, 100);
+            assert!(metasino.create_table(100, 2, 4, None, GameKind::Poker, None, None).is_ok());
+        }
+
+        #[ink::test]
+        fn public_tables_require_a_creator_stake() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            call_as(accounts.eve, 0);
+            let mut metasino = Metasino::new();
+            assert_eq!(metasino.set_min_creator_stake(50), Ok(()));
+
+            call_as(accounts.bob, 100);
+            assert_eq!(
+                metasino.create_table(100, 2, 4, None, GameKind::Poker, None, None),
+                Err(MetasinoError::StakeTooLow)
+            );
+            let code_hash = Some(metasino.compute_join_code_hash(b"secret".to_vec()));
+            assert!(metasino
+                .create_table(100, 2, 4, None, GameKind::Poker, code_hash, None)
+                .is_ok());
+            call_as(accounts.charlie, 50);
+            assert_eq!(metasino.lock_creator_stake(), Ok(()));
+            assert_eq!(metasino.get_creator_stake(accounts.charlie), 50);
+            call_as(accounts.charlie, 100);
+            let table_id = metasino
+                .create_table(100, 2, 4, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.charlie, 0);
+            assert_eq!(metasino.unlock_creator_stake(50), Err(MetasinoError::WrongState));
+            assert_eq!(metasino.terminate(table_id), Ok(()));
+            assert_eq!(
+                metasino.unlock_creator_stake(51),
+                Err(MetasinoError::ArithmeticError)
+            );
+            let claimable = metasino.get_claimable(accounts.charlie);
+            assert_eq!(metasino.unlock_creator_stake(50), Ok(()));
+            assert_eq!(metasino.get_creator_stake(accounts.charlie), 0);
+            assert_eq!(metasino.get_claimable(accounts.charlie), claimable + 50);
+        }
+
+        #[ink::test]
+        fn session_keys_play_for_their_player() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.approve_session_key(accounts.bob, 2),
+                Err(MetasinoError::InvalidAction)
+            );
+            assert_eq!(
+                metasino.approve_session_key(accounts.django, 0),
+                Err(MetasinoError::DeadlinePassed)
+            );
+            assert_eq!(metasino.approve_session_key(accounts.django, 2), Ok(()));
+            assert_eq!(metasino.get_session_key(accounts.django), Some((accounts.bob, 2)));
+            call_as(accounts.charlie, 0);
+            assert_eq!(
+                metasino.approve_session_key(accounts.django, 5),
+                Err(MetasinoError::NotAuthorized)
+            );
+
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.reveal_seed(table_id, seed_of(accounts.bob)), Ok(()));
+            let event = ink_env::test::recorded_events().last().unwrap();
+            let decoded = <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+            assert!(matches!(
+                decoded,
+                Event::SeedRevealed(SeedRevealed { player, .. }) if player == accounts.bob
+            ));
+            assert_eq!(
+                metasino.reveal_seed(table_id, seed_of(accounts.bob)),
+                Err(MetasinoError::AlreadyRevealed)
+            );
+            for _ in 0..2 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            assert_eq!(
+                metasino.reveal_seed(table_id, seed_of(accounts.charlie)),
+                Err(MetasinoError::NotAuthorized)
+            );
+
+            call_as(accounts.charlie, 0);
+            assert_eq!(
+                metasino.revoke_session_key(accounts.django),
+                Err(MetasinoError::NotAuthorized)
+            );
+            assert_eq!(metasino.approve_session_key(accounts.django, 5), Ok(()));
+            assert_eq!(metasino.revoke_session_key(accounts.django), Ok(()));
+            assert_eq!(metasino.get_session_key(accounts.django), None);
+        }
+
+        #[ink::test]
+        fn signed_actions_are_played_for_their_signer() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let key = secp256k1::SecretKey::from_slice(&[7; 32]).unwrap();
+            let public = secp256k1::PublicKey::from_secret_key(secp256k1::SECP256K1, &key);
+            let mut player = [0; 32];
+            ink_env::hash_bytes::<Blake2x256>(&public.serialize(), &mut player);
+            let player = AccountId::from(player);
+            let sign = |hash: [u8; 32]| {
+                let message = secp256k1::Message::from_slice(&hash).unwrap();
+                let (id, compact) = secp256k1::SECP256K1
+                    .sign_ecdsa_recoverable(&message, &key)
+                    .serialize_compact();
+                let mut signature = [0; 65];
+                signature[..64].copy_from_slice(&compact);
+                signature[64] = id.to_i32() as u8;
+                signature
+            };
+
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 200);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(player, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            for account in [accounts.alice, player] {
+                commit_as(&mut metasino, table_id, account);
+            }
+            metasino.start_game(table_id).unwrap();
+            for account in [accounts.alice, player] {
+                call_as(account, 0);
+                metasino.reveal_seed(table_id, seed_of(account)).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            metasino.check(table_id).unwrap();
+
+            // Registering, committing and revealing each took a nonce.
+            assert_eq!(metasino.get_nonce(player), 3);
+            call_as(accounts.eve, 0);
+            let early = sign(metasino.compute_action_hash(table_id, GameAction::Check, 4));
+            assert_eq!(
+                metasino.act_with_signature(table_id, GameAction::Check, 4, early),
+                Err(MetasinoError::InvalidNonce)
+            );
+            let signature = sign(metasino.compute_action_hash(table_id, GameAction::Check, 3));
+            assert_eq!(
+                metasino.act_with_signature(table_id, GameAction::Check, 3, signature),
+                Ok(())
+            );
+            assert_eq!(metasino.get_nonce(player), 4);
+            let acted = ink_env::test::recorded_events()
+                .filter_map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).ok())
+                .any(|event| {
+                    matches!(event, Event::PlayerActed(acted) if acted.player == player)
+                });
+            assert!(acted);
+            assert_eq!(
+                metasino.act_with_signature(table_id, GameAction::Check, 3, signature),
+                Err(MetasinoError::InvalidNonce)
+            );
+            // Any action is relayed, as long as it is one of the game.
+            let hit = sign(metasino.compute_action_hash(table_id, GameAction::Hit, 4));
+            assert_eq!(
+                metasino.act_with_signature(table_id, GameAction::Hit, 4, hit),
+                Err(MetasinoError::InvalidAction)
+            );
+
+            // An action taken by the player at any table, even a plain one,
+            // drops what they signed for the nonce it took.
+            let fold = sign(metasino.compute_action_hash(table_id, GameAction::Fold, 4));
+            call_as(accounts.alice, 100);
+            let other_table = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(player, 100);
+            metasino.register_player(other_table, None, None).unwrap();
+            assert_eq!(metasino.get_nonce(player), 5);
+            call_as(accounts.eve, 0);
+            assert_eq!(
+                metasino.act_with_signature(table_id, GameAction::Fold, 4, fold),
+                Err(MetasinoError::InvalidNonce)
+            );
+        }
+
+        #[ink::test]
+        fn actions_played_for_a_nonce_are_played_once() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            call_as(accounts.bob, 0);
+            let nonce = metasino.get_nonce(accounts.bob);
+            assert_eq!(metasino.reveal_seed(table_id, seed_of(accounts.bob)), Ok(()));
+            assert_eq!(metasino.get_nonce(accounts.bob), nonce + 1);
+
+            call_as(accounts.alice, 0);
+            let nonce = metasino.get_nonce(accounts.alice);
+            let reveal = GameAction::RevealSeed(seed_of(accounts.alice));
+            assert_eq!(
+                metasino.play(table_id, reveal.clone(), Some(nonce + 1)),
+                Err(MetasinoError::InvalidNonce)
+            );
+            assert_eq!(metasino.play(table_id, reveal.clone(), Some(nonce)), Ok(()));
+            assert_eq!(metasino.get_nonce(accounts.alice), nonce + 1);
+            assert_eq!(
+                metasino.play(table_id, reveal, Some(nonce)),
+                Err(MetasinoError::InvalidNonce)
+            );
+        }
+
+        #[ink::test]
+        fn overpayments_are_refunded_and_donations_feed_the_jackpot() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            call_as(accounts.eve, 0);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 99);
+            assert_eq!(
+                metasino.register_player(table_id, None, None),
+                Err(MetasinoError::WrongBet)
+            );
+            call_as(accounts.bob, 150);
+            assert_eq!(metasino.register_player(table_id, None, None), Ok(()));
+            assert_eq!(metasino.get_table(table_id).unwrap().pot, 200);
+            assert_eq!(metasino.get_claimable(accounts.bob), 50);
+
+            call_as(accounts.charlie, 0);
+            assert_eq!(metasino.donate_to_jackpot(), Err(MetasinoError::WrongBet));
+            call_as(accounts.charlie, 30);
+            assert_eq!(metasino.donate_to_jackpot(), Ok(()));
+            assert_eq!(metasino.get_jackpot(), 30);
+        }
+
+        #[ink::test]
+        fn contract_is_destroyed_once_everything_is_paid_out() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 100);
+            call_as(accounts.eve, 0);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.destroy_contract(accounts.eve), Err(MetasinoError::WrongState));
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.destroy_contract(accounts.alice),
+                Err(MetasinoError::NotAuthorized)
+            );
+            metasino.terminate(table_id).unwrap();
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.destroy_contract(accounts.eve), Err(MetasinoError::WrongState));
+            call_as(accounts.alice, 0);
+            metasino.withdraw_winnings().unwrap();
+
+            call_as(accounts.eve, 0);
+            let balance = balance_of(contract);
+            ink_env::test::assert_contract_termination::<ink_env::DefaultEnvironment, _>(
+                move || {
+                    metasino.destroy_contract(accounts.django).unwrap();
+                },
+                accounts.django,
+                balance,
+            );
+        }
+
+        #[ink::test]
+        fn contract_is_not_destroyed_while_funds_are_escrowed() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 1_000);
+            call_as(accounts.alice, 0);
+            let mut metasino = Metasino::new();
+            let refused = |metasino: &mut Metasino| {
+                call_as(accounts.alice, 0);
+                metasino.destroy_contract(accounts.alice) == Err(MetasinoError::WrongState)
+            };
+
+            call_as(accounts.bob, 10);
+            let duel_id = metasino.create_duel(Hash::from([1; 32])).unwrap();
+            assert!(refused(&mut metasino));
+            call_as(accounts.bob, 0);
+            metasino.cancel_duel(duel_id).unwrap();
+            metasino.withdraw_winnings().unwrap();
+
+            call_as(accounts.alice, 0);
+            let lottery_id = metasino.open_lottery(10, 1).unwrap();
+            assert!(refused(&mut metasino));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            metasino.draw_lottery(lottery_id).unwrap();
+
+            let market_id = metasino.open_market(2, 2).unwrap();
+            assert!(refused(&mut metasino));
+            let mut market = metasino.get_market(market_id).unwrap();
+            market.result = Some(0);
+            metasino.markets.insert(market_id, &market);
+
+            call_as(accounts.bob, 10);
+            let tournament_id = metasino.create_tournament(10, 1_000, 4, 10).unwrap();
+            assert!(refused(&mut metasino));
+            metasino.tournaments.remove(tournament_id);
+
+            call_as(accounts.bob, 10);
+            metasino.lock_creator_stake().unwrap();
+            assert!(refused(&mut metasino));
+            call_as(accounts.bob, 0);
+            metasino.unlock_creator_stake(10).unwrap();
+            metasino.withdraw_winnings().unwrap();
+
+            call_as(accounts.alice, 0);
+            metasino.set_withdrawal_limits(0, 0, 10, 0).unwrap();
+            metasino.credit_withdrawal(accounts.bob, 20).unwrap();
+            call_as(accounts.bob, 0);
+            metasino.withdraw_winnings().unwrap();
+            assert!(refused(&mut metasino));
+            metasino.confirm_withdrawal(accounts.bob).unwrap();
+
+            metasino.next_table_id = 1;
+            metasino.waitlists.insert(0, &vec![accounts.bob]);
+            assert!(refused(&mut metasino));
+            metasino.waitlists.insert(0, &Vec::<AccountId>::new());
+
+            metasino.held_games = 1;
+            assert!(refused(&mut metasino));
+            metasino.held_games = 0;
+            metasino.token_treasuries = 1;
+            assert!(refused(&mut metasino));
+            metasino.token_treasuries = 0;
+
+            ink_env::test::assert_contract_termination::<ink_env::DefaultEnvironment, _>(
+                move || {
+                    metasino.destroy_contract(accounts.alice).unwrap();
+                },
+                accounts.alice,
+                balance_of(contract),
+            );
+        }
+
+        #[ink::test]
+        fn escrowed_funds_are_owed_by_the_contract() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = revealed_table();
+            metasino.set_dispute_period(5).unwrap();
+            metasino.end_game(table_id, vec![accounts.bob], vec![100]).unwrap();
+            let report = metasino.check_invariants();
+            assert_eq!((report.tables, report.escrowed), (0, 300));
+            assert!(report.solvent);
+
+            call_as(accounts.bob, 50);
+            metasino.lock_creator_stake().unwrap();
+            let report = metasino.check_invariants();
+            assert_eq!(report.escrowed, 350);
+            assert!(!report.solvent);
+
+            call_as(accounts.charlie, 20);
+            let duel_id = metasino.create_duel(Hash::from([1; 32])).unwrap();
+            assert_eq!(metasino.check_invariants().escrowed, 370);
+            metasino.cancel_duel(duel_id).unwrap();
+            let report = metasino.check_invariants();
+            assert_eq!((report.claimable, report.escrowed), (20, 350));
+
+            for _ in 0..6 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            metasino.finalize_payouts(0).unwrap();
+            let report = metasino.check_invariants();
+            assert_eq!((report.claimable, report.escrowed), (320, 50));
+        }
+
+        #[ink::test]
+        fn insolvency_halts_the_contract() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, _) = staged_table();
+            assert_eq!(
+                metasino.check_invariants(),
+                InvariantReport {
+                    tables: 300,
+                    claimable: 0,
+                    treasury: 0,
+                    jackpot: 0,
+                    escrowed: 0,
+                    balance: 300,
+                    solvent: true,
+                }
+            );
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.halt_if_insolvent(), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            assert!(metasino.halt_if_insolvent().unwrap().solvent);
+            assert!(!metasino.paused);
+
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 250);
+            assert!(!metasino.halt_if_insolvent().unwrap().solvent);
+            assert!(metasino.paused);
+        }
+
+        #[ink::test]
+        fn history_is_pruned_by_the_owner() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            metasino.end_game(table_id, vec![accounts.bob], vec![100]).unwrap();
+            assert!(metasino.get_game(0).is_some());
+
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.prune_history(1), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.prune_history(5), Ok(1));
+            assert_eq!(metasino.get_game(0), None);
+            assert!(metasino.get_recent_games(10).is_empty());
+            assert_eq!(metasino.prune_history(5), Ok(0));
+        }
+
+        #[ink::test]
+        fn tables_are_opened_after_a_cooldown() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            call_as(accounts.eve, 0);
+            let mut metasino = Metasino::new();
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.set_table_cooldown(3), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.set_table_cooldown(3), Ok(()));
+            assert_eq!(metasino.get_config().table_cooldown_blocks, 3);
+
+            call_as(accounts.bob, 100);
+            assert!(metasino.create_table(100, 2, 4, None, GameKind::Poker, None, None).is_ok());
+            for _ in 0..2 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+                assert_eq!(
+                    metasino.create_table(100, 2, 4, None, GameKind::Poker, None, None),
+                    Err(MetasinoError::CooldownActive)
+                );
+            }
+            call_as(accounts.charlie, 100);
+            assert!(metasino.create_table(100, 2, 4, None, GameKind::Poker, None, None).is_ok());
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            call_as(accounts.bob, 100);
+            assert!(metasino.create_table(100, 2, 4, None, GameKind::Poker, None, None).is_ok());
+        }
+
+        #[ink::test]
+        fn roles_delegate_running_the_tables() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = revealed_table();
+            assert!(metasino.has_role(Role::Dealer, accounts.alice));
+            assert!(!metasino.has_role(Role::Operator, accounts.eve));
+            call_as(accounts.eve, 0);
+            assert_eq!(
+                metasino.grant_role(Role::Operator, accounts.eve),
+                Err(MetasinoError::NotAuthorized)
+            );
+            assert_eq!(metasino.pause(), Err(MetasinoError::NotAuthorized));
+            assert_eq!(metasino.claim_timeout(table_id), Err(MetasinoError::DeadlineNotReached));
+
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.grant_role(Role::Operator, accounts.eve), Ok(()));
+            assert_eq!(metasino.grant_role(Role::Dealer, accounts.frank), Ok(()));
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.claim_timeout(table_id), Ok(()));
+            assert!(metasino.get_betting_round(table_id).unwrap().folded[0]);
+            assert_eq!(metasino.pause(), Ok(()));
+            assert_eq!(metasino.unpause(), Ok(()));
+            assert_eq!(metasino.withdraw_treasury(accounts.eve), Err(MetasinoError::NotAuthorized));
+            assert_eq!(
+                metasino.end_game(table_id, vec![accounts.bob], vec![100]),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.frank, 0);
+            assert_eq!(metasino.end_game(table_id, vec![accounts.bob], vec![100]), Ok(()));
+
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.revoke_role(Role::Operator, accounts.eve), Ok(()));
+            assert_eq!(
+                metasino.revoke_role(Role::Operator, accounts.eve),
+                Err(MetasinoError::WrongState)
+            );
+            assert!(!metasino.has_role(Role::Operator, accounts.eve));
+            assert_eq!(metasino.grant_role(Role::Owner, accounts.django), Ok(()));
+            assert!(metasino.has_role(Role::Operator, accounts.django));
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.set_jackpot_cut(100), Ok(()));
+        }
+
+        #[ink::test]
+        fn ownership_is_handed_over_once_accepted() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.transfer_ownership(accounts.bob),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.transfer_ownership(accounts.charlie), Ok(()));
+            assert_eq!(metasino.transfer_ownership(accounts.bob), Ok(()));
+            assert_eq!(metasino.get_pending_owner(), Some(accounts.bob));
+            assert_eq!(metasino.get_owner(), accounts.alice);
+            call_as(accounts.charlie, 0);
+            assert_eq!(metasino.accept_ownership(), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.accept_ownership(), Ok(()));
+            assert_eq!(metasino.get_owner(), accounts.bob);
+            assert_eq!(metasino.get_pending_owner(), None);
+            assert_eq!(metasino.accept_ownership(), Err(MetasinoError::NotAuthorized));
+            assert_eq!(metasino.set_jackpot_cut(100), Ok(()));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_jackpot_cut(100), Err(MetasinoError::NotAuthorized));
+            let event = ink_env::test::recorded_events().last().unwrap();
+            let decoded = <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+            assert!(matches!(
+                decoded,
+                Event::OwnershipTransferred(OwnershipTransferred { from, to })
+                    if from == accounts.alice && to == accounts.bob
+            ));
+        }
+
+        #[ink::test]
+        fn upgrade_and_migrate_are_owner_only() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            assert_eq!(metasino.get_storage_version(), STORAGE_VERSION);
+            assert_eq!(metasino.migrate(1), Err(MetasinoError::WrongState));
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.upgrade(Hash::from([0x01; 32])),
+                Err(MetasinoError::NotAuthorized)
+            );
+            assert_eq!(metasino.migrate(1), Err(MetasinoError::NotAuthorized));
+        }
+
+        #[ink::test]
+        fn version_describes_the_build() {
+            let metasino = Metasino::new();
+            let build = metasino.get_version();
+            assert_eq!(build.version, env!("CARGO_PKG_VERSION"));
+            assert_eq!(build.storage_version, STORAGE_VERSION);
+            assert_eq!(build.games.len(), GAMES.len());
+            assert_eq!(build.games[0], "poker");
+            assert_eq!(build.chain_randomness, cfg!(feature = "chain-randomness"));
+        }
+
+        #[ink::test]
+        fn migrations_run_once_per_version_bump() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            // Storage written by the code of the previous version.
+            let mut old = v1::Root::new();
+            old.owner = accounts.alice;
+            write_v1(&old);
+            metasino.storage_version = STORAGE_VERSION - 1;
+            assert_eq!(metasino.migrate(1), Ok(()));
+            assert_eq!(metasino.get_storage_version(), STORAGE_VERSION);
+            let event = ink_env::test::recorded_events().last().unwrap();
+            let decoded = <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+            assert!(matches!(
+                decoded,
+                Event::Migrated(Migrated { from, to })
+                    if from == STORAGE_VERSION - 1 && to == STORAGE_VERSION
+            ));
+            assert_eq!(metasino.migrate(1), Err(MetasinoError::WrongState));
+        }
+
+        /// Write the root of version 1 to the storage, the entries of its
+        /// mappings being written as they are inserted.
+        fn write_v1(root: &v1::Root) {
+            ink_storage::traits::push_spread_root(root, &ink_primitives::Key::from([0x00; 32]));
+        }
+
+        #[ink::test]
+        fn storage_of_version_1_is_migrated() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            let mut old = v1::Root::new();
+            old.owner = accounts.alice;
+            old.rake_bps = 250;
+            old.rake_cap = 40;
+            old.treasury = 70;
+            old.storage_version = 1;
+            // The first table was closed, the second is playing its flop.
+            old.next_table_id = 2;
+            old.tables.insert(
+                1,
+                &v1::Table {
+                    initializer: accounts.alice,
+                    seated: 2,
+                    required_start_bet: 100,
+                    pot: 240,
+                    state: STATE::PLAYING,
+                    min_players: 2,
+                    max_players: 3,
+                    reveal_deadline: 5,
+                    revealed: 2,
+                    entropy: [0x03; 32],
+                    seed: Some([0x04; 32]),
+                    rake_bps: 250,
+                    rake_cap: 40,
+                    token: Some(accounts.django),
+                },
+            );
+            for (seat, player) in [accounts.bob, accounts.charlie].into_iter().enumerate() {
+                let seat = seat as u8;
+                old.seats.insert((1, seat), &player);
+                old.players.insert(
+                    (1, player),
+                    &v1::PlayerInfo {
+                        seat,
+                        revealed: true,
+                    },
+                );
+                old.commitments.insert((1, player), &Hash::from([seat; 32]));
+            }
+            old.rounds.insert(
+                1,
+                &v1::BettingRound {
+                    street: Street::Flop,
+                    bets: vec![0, 0],
+                    contributed: vec![20, 20],
+                    folded: vec![false, false],
+                    all_in: vec![false, false],
+                    acted: vec![true, false],
+                    active: 1,
+                    deadline: 9,
+                    highest_bet: 0,
+                    finished: false,
+                },
+            );
+            let game = GameRecord {
+                game_id: 0,
+                table_id: 0,
+                players: vec![accounts.bob, accounts.eve],
+                pot: 50,
+                winners: vec![accounts.eve],
+                payouts: vec![50],
+                ended_at_block: 3,
+            };
+            old.games.insert(0, &game);
+            old.next_game_id = 1;
+            let eve_stats = v1::PlayerStats {
+                games_played: 1,
+                games_won: 1,
+                total_wagered: 25,
+                total_won: 50,
+            };
+            old.stats.insert(accounts.eve, &eve_stats);
+            old.stats.insert(accounts.bob, &v1::PlayerStats::default());
+            old.leaderboard = vec![accounts.eve];
+            old.token_treasury.insert(accounts.django, &30);
+            write_v1(&old);
+            // The statistics of version 1 lie where this code keeps the block
+            // each account last opened a table in.
+            assert!(metasino.last_table_opened.get(accounts.bob).is_some());
+            metasino.storage_version = 1;
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            let block = ink_env::block_number::<ink_env::DefaultEnvironment>();
+
+            // A table at a time, the first table having been closed.
+            assert_eq!(metasino.migrate(1), Ok(()));
+            assert_eq!(metasino.migrate(1), Ok(()));
+            assert_eq!(metasino.get_storage_version(), 1);
+            assert_eq!(metasino.migration, Some((2, 0)));
+            assert!(metasino.tables.get(1).is_some());
+            assert_eq!(metasino.games.get(0), None);
+            assert_eq!(metasino.migrate(1), Ok(()));
+            assert_eq!(metasino.get_storage_version(), 2);
+            assert_eq!(metasino.migration, None);
+            let config = metasino.get_config();
+            assert_eq!((config.rake_bps, config.rake_cap), (250, 40));
+            assert_eq!((metasino.treasury, metasino.paused), (70, true));
+            assert_eq!((metasino.next_table_id, metasino.next_game_id), (2, 1));
+            assert_eq!(metasino.tables.get(0), None);
+            let table = metasino.table(1).unwrap();
+            assert_eq!((table.pot, table.state), (240, STATE::PLAYING));
+            assert_eq!(table.seed, Some([0x04; 32]));
+            assert_eq!((table.game, table.bond, table.staged_at), (GameKind::Poker, 0, block));
+            assert_eq!(metasino.hosted_tables.get(accounts.alice), Some(1));
+            assert_eq!(
+                metasino.get_players(1, 0, MAX_PLAYERS),
+                Ok(vec![accounts.bob, accounts.charlie])
+            );
+            assert_eq!(
+                metasino.get_player_info(1, accounts.charlie),
+                Some(PlayerInfo {
+                    seat: 1,
+                    revealed: true,
+                    registered_at: block,
+                    rebuys: 0,
+                    hand: 0,
+                    penalized: false,
+                })
+            );
+            assert_eq!(
+                metasino.commitments.get((1, accounts.charlie)),
+                Some(Hash::from([0x01; 32]))
+            );
+            let round = metasino.rounds.get(1).unwrap();
+            assert_eq!((round.street, round.active, round.pot), (Street::Flop, 1, 240));
+            assert_eq!(round.contributed, vec![20, 20]);
+            assert_eq!(metasino.games.get(0), Some(game));
+            assert_eq!(metasino.get_player_stats(accounts.eve).total_won, 50);
+            assert_eq!(metasino.get_player_stats(accounts.eve).reputation, 0);
+            assert_eq!(metasino.leaderboard.get(), Some(vec![accounts.eve]));
+            assert_eq!(metasino.result_signers.get(), Some(Vec::new()));
+            assert_eq!(metasino.token_treasury.get(accounts.django), Some(30));
+            assert_eq!(metasino.token_treasuries, 1);
+            // The entries are gone from the keys of version 1.
+            assert_eq!(old.tables.get(1), None);
+            assert_eq!(old.players.get((1, accounts.bob)), None);
+            assert_eq!(old.rounds.get(1), None);
+            assert_eq!(old.games.get(0), None);
+            assert_eq!(old.stats.get(accounts.bob), None);
+            assert_eq!(metasino.last_table_opened.get(accounts.bob), None);
+        }
+
+        #[ink::test]
+        fn leave_table_refunds_player() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            let bob_balance = metasino.get_claimable(accounts.bob);
+
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.leave_table(table_id), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 100);
+            assert_eq!(
+                metasino.get_players(table_id, 0, MAX_PLAYERS),
+                Ok(vec![accounts.alice, accounts.charlie])
+            );
+            assert_eq!(metasino.get_player_info(table_id, accounts.bob), None);
+            assert_eq!(
+                metasino.get_player_info(table_id, accounts.charlie),
+                Some(PlayerInfo {
+                    seat: 1,
+                    revealed: false,
+                    registered_at: 0,
+                    rebuys: 0,
+                    hand: 0,
+                    penalized: false,
+                })
+            );
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(200));
+            assert_eq!(metasino.leave_table(table_id), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.leave_table(table_id), Err(MetasinoError::NotAuthorized));
+        }
+
+        #[ink::test]
+        fn initializer_kicks_players_before_the_game() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.kick_player(table_id, accounts.charlie),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.kick_player(table_id, accounts.alice),
+                Err(MetasinoError::NotAuthorized)
+            );
+            assert_eq!(
+                metasino.kick_player(table_id, accounts.django),
+                Err(MetasinoError::NotAuthorized)
+            );
+            assert_eq!(metasino.kick_player(table_id, accounts.bob), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.bob), 100);
+            assert_eq!(metasino.get_player_info(table_id, accounts.bob), None);
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(200));
+            let event = ink_env::test::recorded_events().last().unwrap();
+            let decoded = <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap();
+            assert!(matches!(
+                decoded,
+                Event::PlayerKicked(PlayerKicked { refund: 100, player, .. })
+                    if player == accounts.bob
+            ));
+        }
+
+        #[ink::test]
+        fn leave_table_rejected_once_game_started() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.leave_table(table_id), Err(MetasinoError::WrongState));
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.kick_player(table_id, accounts.bob),
+                Err(MetasinoError::WrongState)
+            );
+            assert_eq!(metasino.get_players_count(table_id), Ok(3));
+        }
+
+        #[ink::test]
+        fn dealers_post_a_bond_to_deal_a_table() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            metasino.set_dealer_terms(30, 5).unwrap();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.set_dealer(table_id, Some(accounts.django)),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            metasino.set_dealer(table_id, Some(accounts.django)).unwrap();
+            call_as(accounts.eve, 30);
+            assert_eq!(metasino.accept_dealer(table_id), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.django, 10);
+            assert_eq!(metasino.accept_dealer(table_id), Err(MetasinoError::WrongBet));
+            call_as(accounts.django, 30);
+            assert_eq!(metasino.accept_dealer(table_id), Ok(()));
+            assert_eq!(metasino.get_table_info(table_id).unwrap().dealer, Some(accounts.django));
+            call_as(accounts.alice, 0);
+            metasino.set_dealer(table_id, None).unwrap();
+            assert_eq!(metasino.get_claimable(accounts.django), 30);
+            assert_eq!(metasino.start_game(table_id), Ok(()));
+        }
+
+        #[ink::test]
+        fn dealers_deal_their_table_until_they_stall() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            metasino.set_dealer_terms(30, 5).unwrap();
+            metasino.set_dealer(table_id, Some(accounts.django)).unwrap();
+            call_as(accounts.django, 30);
+            metasino.accept_dealer(table_id).unwrap();
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.start_game(table_id), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.start_game(table_id), Ok(()));
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+            // A hand outlasting the dealer timeout does not make the dealer late.
+            for player in [accounts.alice, accounts.bob] {
+                for _ in 0..5 {
+                    ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+                }
+                call_as(player, 0);
+                metasino.fold(table_id).unwrap();
+            }
+            for _ in 0..5 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            assert_eq!(metasino.settle_showdown(table_id), Err(MetasinoError::NotAuthorized));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            let before = metasino.get_claimable(accounts.bob);
+            assert_eq!(metasino.settle_showdown(table_id), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.bob) - before, 10);
+            assert_eq!(metasino.get_table_info(table_id).unwrap().dealer, None);
+        }
+
+        #[ink::test]
+        fn scheduled_game_is_started_by_anyone_on_time() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            assert_eq!(
+                metasino.create_table(100, 2, 10, None, GameKind::Poker, None, Some(0)),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, Some(3))
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            commit_as(&mut metasino, table_id, accounts.alice);
+            commit_as(&mut metasino, table_id, accounts.bob);
+            assert_eq!(metasino.get_table_info(table_id).unwrap().start_at, Some(3));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.start_game(table_id), Err(MetasinoError::DeadlineNotReached));
+            for _ in 0..3 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.start_game(table_id), Ok(()));
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::PLAYING));
+            assert_eq!(metasino.get_table_info(table_id).unwrap().start_at, None);
+        }
+
+        #[ink::test]
+        fn sit_and_go_starts_once_the_table_fills() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 3, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.set_sit_and_go(table_id, true), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_sit_and_go(table_id, true), Ok(()));
+            assert!(metasino.get_table_info(table_id).unwrap().sit_and_go);
+            commit_as(&mut metasino, table_id, accounts.alice);
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            commit_as(&mut metasino, table_id, accounts.bob);
+            call_as(accounts.charlie, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            // The table is full, but waits for the last commitment.
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::STAGING));
+
+            let emitted = ink_env::test::recorded_events().count();
+            commit_as(&mut metasino, table_id, accounts.charlie);
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::PLAYING));
+            let started = ink_env::test::recorded_events().skip(emitted).any(|event| {
+                matches!(
+                    <Event as scale::Decode>::decode(&mut &event.data[..]),
+                    Ok(Event::GameAutoStarted(GameAutoStarted { table_id: id })) if id == table_id
+                )
+            });
+            assert!(started);
+        }
+
+        #[ink::test]
+        fn only_players_can_start_game() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.start_game(table_id), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.start_game(table_id), Ok(()));
+        }
+
+        #[ink::test]
+        fn transfer_table_ownership_hands_over_initializer_rights() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.transfer_table_ownership(table_id, accounts.bob),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.transfer_table_ownership(table_id, accounts.bob), Ok(()));
+            assert_eq!(metasino.get_initializer(table_id), Ok(accounts.bob));
+            assert_eq!(metasino.set_private(table_id, true), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.terminate(table_id), Ok(()));
+        }
+
+        #[ink::test]
+        fn start_game_requires_every_commitment() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            call_as(accounts.django, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.start_game(table_id), Err(MetasinoError::MissingCommitment));
+            commit_as(&mut metasino, table_id, accounts.django);
+            assert_eq!(metasino.start_game(table_id), Ok(()));
+        }
+
+        #[ink::test]
+        fn reveals_derive_the_shuffle_seed() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.reveal_seed(table_id, seed_of(accounts.alice)),
+                Err(MetasinoError::InvalidReveal)
+            );
+            assert_eq!(metasino.reveal_seed(table_id, seed_of(accounts.bob)), Ok(()));
+            assert_eq!(
+                metasino.reveal_seed(table_id, seed_of(accounts.bob)),
+                Err(MetasinoError::AlreadyRevealed)
+            );
+            assert_eq!(metasino.get_shuffle_seed(table_id), Ok(None));
+
+            for player in [accounts.alice, accounts.charlie] {
+                call_as(player, 0);
+                assert_eq!(metasino.reveal_seed(table_id, seed_of(player)), Ok(()));
+            }
+            assert_eq!(metasino.get_shuffle_seed(table_id), Ok(Some([0x01 ^ 0x02 ^ 0x03; 32])));
+            assert_eq!(metasino.finalize_seed(table_id), Err(MetasinoError::WrongState));
+        }
+
+        #[cfg(feature = "chain-randomness")]
+        #[ink::test]
+        fn chain_randomness_is_mixed_into_the_seed() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                ink_env::test::register_chain_extension(MockedRandomness([0x10; 32]));
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+            // The seeds of the players cancel out, leaving the randomness of the chain.
+            assert_eq!(metasino.get_shuffle_seed(table_id), Ok(Some([0x10; 32])));
+        }
+
+        #[cfg(feature = "test-seed")]
+        #[ink::test]
+        fn fixed_seeds_replace_the_reveals() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            crate::test_seed::set(Some([0x20; 32]));
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+            crate::test_seed::set(None);
+            assert_eq!(metasino.get_shuffle_seed(table_id), Ok(Some([0x20; 32])));
+        }
+
+        #[ink::test]
+        fn non_revealers_are_slashed_after_deadline() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            for player in [accounts.alice, accounts.bob] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+            assert_eq!(metasino.finalize_seed(table_id), Err(MetasinoError::DeadlineNotReached));
+            for _ in 0..=REVEAL_PERIOD {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            call_as(accounts.charlie, 0);
+            assert_eq!(
+                metasino.reveal_seed(table_id, seed_of(accounts.charlie)),
+                Err(MetasinoError::DeadlinePassed)
+            );
+
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.finalize_seed(table_id), Ok(()));
+            assert_eq!(
+                metasino.get_players(table_id, 0, MAX_PLAYERS),
+                Ok(vec![accounts.alice, accounts.bob])
+            );
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(300));
+            assert_eq!(metasino.get_shuffle_seed(table_id), Ok(Some([0x01 ^ 0x02; 32])));
+        }
+
+        #[ink::test]
+        fn game_without_reveals_is_cancelled() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            for _ in 0..=REVEAL_PERIOD {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            assert_eq!(metasino.finalize_seed(table_id), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 100);
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::ENDED));
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(0));
+        }
+
+        #[ink::test]
+        fn betting_opens_once_seed_is_derived() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            assert_eq!(metasino.check(table_id), Err(MetasinoError::WrongState));
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+            assert_eq!(
+                metasino.get_betting_round(table_id),
+                Some(BettingRound {
+                    pot: 300,
+                    ..BettingRound::new(3, TURN_PERIOD)
+                })
+            );
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.check(table_id), Err(MetasinoError::NotAuthorized));
+        }
+
+        #[ink::test]
+        fn betting_actions_feed_the_pot() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = revealed_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.check(table_id), Err(MetasinoError::NotYourTurn));
+            call_as(accounts.alice, 40);
+            assert_eq!(metasino.raise(table_id, 50), Err(MetasinoError::WrongBet));
+            call_as(accounts.alice, 50);
+            assert_eq!(metasino.raise(table_id, 50), Ok(()));
+            call_as(accounts.bob, 50);
+            assert_eq!(metasino.call(table_id), Ok(()));
+            call_as(accounts.charlie, 0);
+            assert_eq!(metasino.fold(table_id), Ok(()));
+
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(400));
+            let round = metasino.get_betting_round(table_id).unwrap();
+            assert_eq!(round.street, crate::betting::Street::Flop);
+            assert_eq!(round.folded, vec![false, false, true]);
+        }
+
+        #[ink::test]
+        fn actions_are_played_through_a_single_message() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                let reveal = GameAction::RevealSeed(seed_of(player));
+                assert_eq!(metasino.play(table_id, reveal, None), Ok(()));
+            }
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.play(table_id, GameAction::Hit, None),
+                Err(MetasinoError::InvalidAction)
+            );
+            assert_eq!(
+                metasino.play(table_id, GameAction::RollDice, None),
+                Err(MetasinoError::InvalidAction)
+            );
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.play(table_id, GameAction::Check, None),
+                Err(MetasinoError::NotYourTurn)
+            );
+            call_as(accounts.alice, 50);
+            assert_eq!(metasino.play(table_id, GameAction::Raise(50), None), Ok(()));
+            call_as(accounts.bob, 50);
+            assert_eq!(metasino.play(table_id, GameAction::Call, None), Ok(()));
+            call_as(accounts.charlie, 0);
+            assert_eq!(metasino.play(table_id, GameAction::Fold, None), Ok(()));
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(400));
+            assert_eq!(
+                metasino.play(TableId::MAX, GameAction::Fold, None),
+                Err(MetasinoError::TableNotFound)
+            );
+
+            // The bets of the tables played against the house are actions too.
+            call_as(accounts.alice, 100);
+            let roulette = metasino
+                .create_table(100, 2, 10, None, GameKind::Roulette, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(roulette, None, None).unwrap();
+            call_as(accounts.bob, 10);
+            let bet = GameAction::Bet(RouletteBet::Red, 10);
+            assert_eq!(
+                metasino.play(table_id, bet.clone(), None),
+                Err(MetasinoError::InvalidAction)
+            );
+            assert_eq!(metasino.play(roulette, bet, None), Ok(()));
+            assert_eq!(
+                metasino.get_wagers(roulette, accounts.bob),
+                vec![Wager {
+                    bet: RouletteBet::Red,
+                    amount: 10,
+                }]
+            );
+        }
+
+        #[ink::test]
+        fn tables_in_intermission_are_refunded_while_paused() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            for (player, chips) in [(accounts.alice, 50), (accounts.bob, 150)] {
+                call_as(player, chips);
+                metasino.buy_chips(table_id, chips).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            metasino.end_game(table_id, vec![accounts.charlie], vec![100]).unwrap();
+            call_as(accounts.django, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::INTERMISSION));
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(100));
+
+            call_as(accounts.alice, 0);
+            metasino.pause().unwrap();
+            let players = [accounts.alice, accounts.bob, accounts.django];
+            let before = players.map(|player| metasino.get_claimable(player));
+            assert_eq!(metasino.emergency_refund_all(table_id), Ok(()));
+            let refunded: Vec<Balance> = players
+                .iter()
+                .zip(before)
+                .map(|(player, before)| metasino.get_claimable(*player) - before)
+                .collect();
+            assert_eq!(refunded, vec![50, 150, 100]);
+            assert_eq!(metasino.get_chips(table_id, accounts.bob), 0);
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::ENDED));
+            assert_eq!(metasino.get_treasury(), 0);
+        }
+
+        #[ink::test]
+        fn stuck_tables_are_refunded_while_paused() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = revealed_table();
+            call_as(accounts.alice, 50);
+            metasino.raise(table_id, 50).unwrap();
+            call_as(accounts.bob, 50);
+            metasino.call(table_id).unwrap();
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.emergency_refund_all(table_id), Err(MetasinoError::WrongState));
+            metasino.pause().unwrap();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.emergency_refund_all(table_id),
+                Err(MetasinoError::NotAuthorized)
+            );
+
+            call_as(accounts.alice, 0);
+            let claimable = |metasino: &Metasino| {
+                [accounts.alice, accounts.bob, accounts.charlie]
+                    .map(|player| metasino.get_claimable(player))
+            };
+            let before = claimable(&metasino);
+            assert_eq!(metasino.emergency_refund_all(table_id), Ok(()));
+            let after = claimable(&metasino);
+            assert_eq!(after[0] - before[0], 150);
+            assert_eq!(after[1] - before[1], 150);
+            assert_eq!(after[2] - before[2], 100);
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::ENDED));
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(0));
+            assert_eq!(metasino.get_treasury(), 0);
+            assert_eq!(metasino.emergency_refund_all(table_id), Err(MetasinoError::WrongState));
+        }
+
+        #[ink::test]
+        fn stalling_player_is_folded_after_turn_deadline() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = revealed_table();
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.claim_timeout(table_id), Err(MetasinoError::DeadlineNotReached));
+            for _ in 0..=TURN_PERIOD {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            assert_eq!(metasino.claim_timeout(table_id), Ok(()));
+            let round = metasino.get_betting_round(table_id).unwrap();
+            assert_eq!(round.folded, vec![true, false, false]);
+            assert_eq!(round.active, 1);
+            assert_eq!(metasino.claim_timeout(table_id), Err(MetasinoError::DeadlineNotReached));
+        }
+
+        #[ink::test]
+        fn non_revealers_time_out_during_reveal_phase() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            for player in [accounts.alice, accounts.bob] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.claim_timeout(table_id), Err(MetasinoError::DeadlineNotReached));
+            for _ in 0..=REVEAL_PERIOD {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            assert_eq!(metasino.claim_timeout(table_id), Ok(()));
+            assert_eq!(
+                metasino.get_players(table_id, 0, MAX_PLAYERS),
+                Ok(vec![accounts.alice, accounts.bob])
+            );
+            assert!(metasino.get_betting_round(table_id).is_some());
+        }
+
+        #[ink::test]
+        fn score_hand_ranks_poker_hands() {
+            use crate::cards::Suit::*;
+            let metasino = Metasino::new();
+            let straight = vec![
+                Card { rank: 9, suit: Spades },
+                Card { rank: 10, suit: Hearts },
+                Card { rank: 11, suit: Spades },
+                Card { rank: 12, suit: Clubs },
+                Card { rank: 13, suit: Spades },
+                Card { rank: 2, suit: Diamonds },
+            ];
+            let (category, score) = metasino.score_hand(straight.clone()).unwrap();
+            assert_eq!(category, HandCategory::Straight);
+            let (_, pair) = metasino
+                .score_hand(vec![straight[0], straight[1], straight[2], straight[3], straight[0]])
+                .unwrap();
+            assert!(score > pair);
+            assert_eq!(metasino.score_hand(straight[..4].to_vec()), None);
+        }
+
+        #[ink::test]
+        fn end_game_pays_side_pots_to_eligible_winners() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = revealed_table();
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(
+                AccountId::from([0x42; 32]),
+                540,
+            );
+            call_as(accounts.alice, 100);
+            metasino.raise(table_id, 100).unwrap();
+            call_as(accounts.bob, 40);
+            assert_eq!(metasino.all_in(table_id), Ok(()));
+            call_as(accounts.charlie, 100);
+            metasino.call(table_id).unwrap();
+            for _ in 0..3 {
+                for player in [accounts.alice, accounts.charlie] {
+                    call_as(player, 0);
+                    metasino.check(table_id).unwrap();
+                }
+            }
+            assert!(metasino.get_betting_round(table_id).unwrap().finished);
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            let charlie_balance = metasino.get_claimable(accounts.charlie);
+
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.end_game(table_id, vec![accounts.bob, accounts.charlie], vec![100, 0]),
+                Ok(())
+            );
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 420);
+            assert_eq!(metasino.get_claimable(accounts.charlie), charlie_balance + 120);
+        }
+
+        #[ink::test]
+        fn showdown_pays_the_best_hand_revealed() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let players = [accounts.alice, accounts.bob, accounts.charlie];
+            let (mut metasino, table_id) = staged_table();
+            for (index, player) in players.iter().enumerate() {
+                let first = 5 + 2 * index as u8;
+                let commitment =
+                    metasino.compute_hand_commitment(*player, vec![first, first + 1], [7; 32]);
+                call_as(*player, 0);
+                assert_eq!(metasino.commit_hand(table_id, commitment), Ok(()));
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            assert_eq!(
+                metasino.commit_hand(table_id, Hash::default()),
+                Err(MetasinoError::WrongState)
+            );
+            for player in players {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.reveal_hand(table_id, vec![5, 6], [7; 32]),
+                Err(MetasinoError::WrongState)
+            );
+            for _ in 0..4 {
+                for player in players {
+                    call_as(player, 0);
+                    metasino.check(table_id).unwrap();
+                }
+            }
+            assert!(metasino.get_betting_round(table_id).unwrap().finished);
+
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.reveal_hand(table_id, vec![5, 6], [8; 32]),
+                Err(MetasinoError::InvalidReveal)
+            );
+            assert_eq!(metasino.reveal_hand(table_id, vec![5, 6], [7; 32]), Ok(()));
+            assert_eq!(
+                metasino.reveal_hand(table_id, vec![5, 6], [7; 32]),
+                Err(MetasinoError::AlreadyRevealed)
+            );
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.reveal_hand(table_id, vec![7, 8], [7; 32]), Ok(()));
+            assert_eq!(metasino.settle_showdown(table_id), Err(MetasinoError::DeadlineNotReached));
+
+            // Charlie never shows down and forfeits the hand.
+            for _ in 0..=TURN_PERIOD {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            assert_eq!(metasino.get_last_result(table_id), None);
+            assert_eq!(metasino.settle_showdown(table_id), Ok(()));
+            let game = metasino.get_game(0).unwrap();
+            assert!(!game.winners.contains(&accounts.charlie));
+            assert_eq!(game.payouts.iter().sum::<Balance>(), 300);
+            let deck = cards::shuffled_deck(&[0x01 ^ 0x02 ^ 0x03; 32]);
+            let hole = |player| match player {
+                player if player == accounts.alice => vec![deck[5], deck[6]],
+                _ => vec![deck[7], deck[8]],
+            };
+            assert_eq!(
+                metasino.get_last_result(table_id),
+                Some(GameResult {
+                    winning_hands: game.winners.iter().map(|winner| hole(*winner)).collect(),
+                    winners: game.winners,
+                    pot_breakdown: game.payouts,
+                    rake_taken: 0,
+                })
+            );
+            assert_eq!(metasino.settle_showdown(table_id), Err(MetasinoError::WrongState));
+        }
+
+        #[ink::test]
+        fn omaha_hands_show_down_four_hole_cards() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let players = [accounts.alice, accounts.bob, accounts.charlie];
+            let (mut metasino, table_id) = staged_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.set_variant(table_id, Variant::Omaha),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_variant(table_id, Variant::Omaha), Ok(()));
+            let holes: Vec<Vec<u8>> = (0..3u8).map(|i| (5 + 4 * i..9 + 4 * i).collect()).collect();
+            for (player, positions) in players.iter().zip(holes.iter()) {
+                let commitment =
+                    metasino.compute_hand_commitment(*player, positions.clone(), [7; 32]);
+                call_as(*player, 0);
+                metasino.commit_hand(table_id, commitment).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            for player in players {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+            for _ in 0..4 {
+                for player in players {
+                    call_as(player, 0);
+                    metasino.check(table_id).unwrap();
+                }
+            }
+            for (player, positions) in players.iter().zip(holes.iter()) {
+                call_as(*player, 0);
+                assert_eq!(metasino.reveal_hand(table_id, positions.clone(), [7; 32]), Ok(()));
+            }
+            let seed = metasino.get_shuffle_seed(table_id).unwrap().unwrap();
+            assert_eq!(metasino.settle_showdown(table_id), Ok(()));
+
+            let deck = cards::shuffled_deck(&seed);
+            let scores: Vec<u32> = holes
+                .iter()
+                .map(|positions| {
+                    let hole: Vec<Card> = positions.iter().map(|p| deck[*p as usize]).collect();
+                    showdown::score(Variant::Omaha, &deck, &hole).unwrap()
+                })
+                .collect();
+            let best = scores.iter().max().unwrap();
+            let game = metasino.get_game(0).unwrap();
+            let expected: Vec<AccountId> = players
+                .iter()
+                .zip(scores.iter())
+                .filter(|(_, score)| *score == best)
+                .map(|(player, _)| *player)
+                .collect();
+            assert_eq!(game.winners, expected);
+            assert_eq!(game.payouts.iter().sum::<Balance>(), 300);
+        }
+
+        #[ink::test]
+        fn stud_hands_are_brought_in_and_led_by_the_up_cards() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let players = [accounts.alice, accounts.bob, accounts.charlie];
+            let (mut metasino, table_id) = staged_table();
+            assert_eq!(
+                metasino.set_variant(table_id, Variant::Stud),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 750);
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 3, 3, None, GameKind::Poker, None, None)
+                .unwrap();
+            for player in &players[1..] {
+                call_as(*player, 100);
+                metasino.register_player(table_id, None, None).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_variant(table_id, Variant::Stud), Ok(()));
+            metasino.set_blinds(table_id, 5, 10).unwrap();
+            metasino.set_betting_structure(table_id, BettingStructure::FixedLimit).unwrap();
+            let holes: Vec<Vec<u8>> =
+                (0..3u8).map(|i| (28 + 3 * i..31 + 3 * i).collect()).collect();
+            for (player, positions) in players.iter().zip(holes.iter()) {
+                commit_as(&mut metasino, table_id, *player);
+                let commitment =
+                    metasino.compute_hand_commitment(*player, positions.clone(), [7; 32]);
+                metasino.commit_hand(table_id, commitment).unwrap();
+                call_as(*player, 50);
+                metasino.buy_chips(table_id, 50).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.post_straddle(table_id), Err(MetasinoError::WrongState));
+            metasino.start_game(table_id).unwrap();
+            for player in players {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+
+            let seed = metasino.get_shuffle_seed(table_id).unwrap().unwrap();
+            let deck = cards::shuffled_deck(&seed);
+            let bring_in = stud::bring_in(&deck, 3);
+            let round = metasino.get_betting_round(table_id).unwrap();
+            assert_eq!(round.street, Street::Third);
+            assert_eq!(round.bets[bring_in as usize], 5);
+            assert_eq!(round.active, (bring_in + 1) % 3);
+            let view = metasino.get_spectator_view(table_id).unwrap();
+            assert!(view.board.is_empty());
+            assert_eq!(view.up_cards[bring_in as usize], vec![deck[bring_in as usize]]);
+
+            call_as(players[round.active as usize], 0);
+            assert_eq!(metasino.raise(table_id, 10), Err(MetasinoError::InvalidAction));
+            assert_eq!(metasino.raise(table_id, 5), Ok(()));
+            for _ in 0..2 {
+                let round = metasino.get_betting_round(table_id).unwrap();
+                call_as(players[round.active as usize], 0);
+                metasino.call(table_id).unwrap();
+            }
+            for street in [Street::Fourth, Street::Fifth, Street::Sixth, Street::Seventh] {
+                let round = metasino.get_betting_round(table_id).unwrap();
+                assert_eq!(round.street, street);
+                assert_eq!(round.active, stud::leader(&deck, &round));
+                for _ in 0..3 {
+                    let round = metasino.get_betting_round(table_id).unwrap();
+                    call_as(players[round.active as usize], 0);
+                    metasino.check(table_id).unwrap();
+                }
+            }
+            assert!(metasino.get_betting_round(table_id).unwrap().finished);
+            assert_eq!(metasino.run_it_twice(table_id), Err(MetasinoError::WrongState));
+
+            for (player, positions) in players.iter().zip(holes.iter()) {
+                call_as(*player, 0);
+                assert_eq!(metasino.reveal_hand(table_id, positions.clone(), [7; 32]), Ok(()));
+            }
+            assert_eq!(metasino.settle_showdown(table_id), Ok(()));
+            let scores: Vec<u32> = holes
+                .iter()
+                .enumerate()
+                .map(|(seat, positions)| {
+                    let mut cards = stud::up_cards(&deck, seat as u8, Street::Seventh);
+                    cards.extend(positions.iter().map(|p| deck[*p as usize]));
+                    crate::hand_rank::score(&cards).unwrap()
+                })
+                .collect();
+            let best = scores.iter().max().unwrap();
+            let expected: Vec<AccountId> = players
+                .iter()
+                .zip(scores.iter())
+                .filter(|(_, score)| *score == best)
+                .map(|(player, _)| *player)
+                .collect();
+            let game = metasino.get_game(0).unwrap();
+            assert_eq!(game.winners, expected);
+            assert_eq!(game.payouts.iter().sum::<Balance>(), 330);
+        }
+
+        #[ink::test]
+        fn all_in_hands_may_be_run_twice() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let players = [accounts.alice, accounts.bob, accounts.charlie];
+            let (mut metasino, table_id) = staged_table();
+            for (index, player) in players.iter().enumerate() {
+                let first = 5 + 2 * index as u8;
+                let commitment =
+                    metasino.compute_hand_commitment(*player, vec![first, first + 1], [7; 32]);
+                call_as(*player, 0);
+                metasino.commit_hand(table_id, commitment).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            for player in players {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+            call_as(accounts.alice, 50);
+            assert_eq!(metasino.run_it_twice(table_id), Err(MetasinoError::WrongState));
+            metasino.all_in(table_id).unwrap();
+            call_as(accounts.bob, 50);
+            metasino.all_in(table_id).unwrap();
+            call_as(accounts.charlie, 0);
+            metasino.fold(table_id).unwrap();
+
+            assert_eq!(metasino.run_it_twice(table_id), Err(MetasinoError::NotAuthorized));
+            for (player, positions) in [(accounts.alice, [5, 6]), (accounts.bob, [7, 8])] {
+                call_as(player, 0);
+                assert_eq!(metasino.run_it_twice(table_id), Ok(()));
+                assert_eq!(
+                    metasino.run_it_twice(table_id),
+                    Err(MetasinoError::AlreadyRegistered)
+                );
+                metasino.reveal_hand(table_id, positions.to_vec(), [7; 32]).unwrap();
+            }
+            assert_eq!(metasino.settle_showdown(table_id), Ok(()));
+            let boards: Vec<BoardResult> = ink_env::test::recorded_events()
+                .filter_map(|event| {
+                    match <Event as scale::Decode>::decode(&mut &event.data[..]).ok()? {
+                        Event::BoardResult(result) => Some(result),
+                        _ => None,
+                    }
+                })
+                .collect();
+            assert_eq!(boards.len(), 2);
+            assert_eq!(boards[0].payouts.iter().sum::<Balance>(), 200);
+            assert_eq!(boards[1].payouts.iter().sum::<Balance>(), 200);
+            assert_ne!(boards[0].cards[3..], boards[1].cards[3..]);
+            assert_eq!(metasino.get_game(0).unwrap().payouts.iter().sum::<Balance>(), 400);
+        }
+
+        #[ink::test]
+        fn disputed_payouts_wait_for_a_ruling() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = revealed_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.set_dispute_period(5), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_dispute_period(5), Ok(()));
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            assert_eq!(metasino.end_game(table_id, vec![accounts.bob], vec![100]), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance);
+            assert_eq!(metasino.get_pending_payout(0).unwrap().payouts, vec![300]);
+            assert_eq!(metasino.finalize_payouts(0), Err(MetasinoError::DeadlineNotReached));
+
+            let evidence = Hash::from([7; 32]);
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.raise_dispute(0, evidence), Err(MetasinoError::NotAuthorized));
+            assert_eq!(metasino.raise_dispute(1, evidence), Err(MetasinoError::GameNotFound));
+            call_as(accounts.charlie, 0);
+            assert_eq!(metasino.raise_dispute(0, evidence), Ok(()));
+            assert_eq!(metasino.raise_dispute(0, evidence), Err(MetasinoError::WrongState));
+            for _ in 0..6 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            assert_eq!(metasino.finalize_payouts(0), Err(MetasinoError::WrongState));
+            assert_eq!(
+                metasino.resolve_dispute(0, vec![0, 0, 300]),
+                Err(MetasinoError::NotAuthorized)
+            );
+
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.resolve_dispute(0, vec![0, 0, 301]),
+                Err(MetasinoError::InvalidPayout)
+            );
+            let charlie_balance = metasino.get_claimable(accounts.charlie);
+            assert_eq!(metasino.resolve_dispute(0, vec![0, 0, 300]), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.charlie), charlie_balance + 300);
+            assert_eq!(metasino.get_game(0).unwrap().winners, vec![accounts.charlie]);
+            assert_eq!(metasino.get_pending_payout(0), None);
+
+            // Undisputed payouts are paid out to the winners proposed.
+            let (mut metasino, table_id) = revealed_table();
+            metasino.set_dispute_period(5).unwrap();
+            metasino.end_game(table_id, vec![accounts.bob], vec![100]).unwrap();
+            for _ in 0..6 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            call_as(accounts.charlie, 0);
+            assert_eq!(metasino.raise_dispute(0, evidence), Err(MetasinoError::DeadlinePassed));
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            assert_eq!(metasino.finalize_payouts(0), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 300);
+            assert_eq!(metasino.finalize_payouts(0), Err(MetasinoError::WrongState));
+        }
+
+        #[ink::test]
+        fn large_pots_are_held_for_a_veto() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = revealed_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.set_large_pot_delay(200, 5), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_large_pot_delay(200, 5), Ok(()));
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            assert_eq!(metasino.end_game(table_id, vec![accounts.bob], vec![100]), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance);
+            assert_eq!(metasino.finalize_payouts(0), Err(MetasinoError::DeadlineNotReached));
+
+            let evidence = Hash::from([7; 32]);
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.veto_payout(0, evidence), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            metasino.grant_role(Role::Operator, accounts.django).unwrap();
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.veto_payout(0, evidence), Ok(()));
+            assert_eq!(metasino.veto_payout(0, evidence), Err(MetasinoError::WrongState));
+            for _ in 0..6 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            assert_eq!(metasino.finalize_payouts(0), Err(MetasinoError::WrongState));
+            let reputation = metasino.get_player_stats(accounts.django).reputation;
+            assert_eq!(metasino.resolve_dispute(0, vec![0, 300, 0]), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 300);
+            assert_eq!(metasino.get_player_stats(accounts.django).reputation, reputation);
+
+            // Pots up to the threshold are paid out at once.
+            call_as(accounts.alice, 0);
+            let (mut metasino, table_id) = revealed_table();
+            metasino.set_large_pot_delay(300, 5).unwrap();
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            metasino.end_game(table_id, vec![accounts.bob], vec![100]).unwrap();
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 300);
+            assert_eq!(metasino.get_pending_payout(0), None);
+
+            // A delay running past the last block holds the payouts for good.
+            let (mut metasino, table_id) = revealed_table();
+            metasino.set_large_pot_delay(200, BlockNumber::MAX).unwrap();
+            metasino.end_game(table_id, vec![accounts.bob], vec![100]).unwrap();
+            let pending = metasino.get_pending_payout(0).unwrap();
+            assert_eq!(pending.dispute_until, BlockNumber::MAX);
+        }
+
+        #[ink::test]
+        fn the_arbiter_of_the_table_rules_on_its_disputes() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.set_arbiter(table_id, Some(accounts.eve)),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_arbiter(table_id, Some(accounts.eve)), Ok(()));
+            metasino.set_dispute_period(5).unwrap();
+            metasino.start_game(table_id).unwrap();
+            assert_eq!(
+                metasino.set_arbiter(table_id, None),
+                Err(MetasinoError::WrongState)
+            );
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            metasino.end_game(table_id, vec![accounts.alice], vec![100]).unwrap();
+            assert_eq!(metasino.get_pending_payout(0).unwrap().arbiter, Some(accounts.eve));
+            call_as(accounts.bob, 0);
+            metasino.raise_dispute(0, Hash::from([7; 32])).unwrap();
+
+            // The operators no longer rule on the disputes of the table.
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.resolve_dispute(0, vec![0, 300, 0]),
+                Err(MetasinoError::NotAuthorized)
+            );
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.resolve_dispute(0, vec![0, 300, 0]), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 300);
+        }
+
+        #[ink::test]
+        fn end_game_takes_capped_rake_into_treasury() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 300);
+            call_as(accounts.eve, 0);
+            let mut metasino = Metasino::new();
+            assert_eq!(metasino.set_rake(MAX_BPS + 1, 20), Err(MetasinoError::InvalidRake));
+            assert_eq!(metasino.set_rake(1_000, 20), Ok(()));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_rake(0, 0), Err(MetasinoError::NotAuthorized));
+
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            commit_as(&mut metasino, table_id, accounts.alice);
+            commit_as(&mut metasino, table_id, accounts.bob);
+            metasino.start_game(table_id).unwrap();
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.end_game(table_id, vec![accounts.bob], vec![100]), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 180);
+            assert_eq!(metasino.get_treasury(), 20);
+
+            assert_eq!(
+                metasino.withdraw_treasury(accounts.alice),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.withdraw_treasury(accounts.frank), Ok(()));
+            assert_eq!(balance_of(accounts.frank), 20);
+            assert_eq!(metasino.get_treasury(), 0);
+        }
+
+        #[ink::test]
+        fn initializers_set_the_rake_of_their_tables() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            let set_rake = |metasino: &mut Metasino| {
+                metasino.set_table_rake(table_id, 1_000, 30, Some(accounts.frank))
+            };
+            assert_eq!(set_rake(&mut metasino), Err(MetasinoError::InvalidRake));
+            assert_eq!(
+                metasino.set_table_rake_limits(MAX_BPS, MAX_BPS + 1),
+                Err(MetasinoError::InvalidRake)
+            );
+            assert_eq!(metasino.set_table_rake_limits(1_000, 2_000), Ok(()));
+            call_as(accounts.bob, 0);
+            assert_eq!(set_rake(&mut metasino), Err(MetasinoError::NotAuthorized));
+            assert_eq!(
+                metasino.set_table_rake_limits(0, 0),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            assert_eq!(set_rake(&mut metasino), Ok(()));
+            let info = metasino.get_table_info(table_id).unwrap();
+            assert_eq!((info.rake_bps, info.rake_cap), (1_000, 30));
+            assert_eq!(info.rake_recipient, Some(accounts.frank));
+
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                commit_as(&mut metasino, table_id, player);
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            assert_eq!(set_rake(&mut metasino), Err(MetasinoError::WrongState));
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            assert_eq!(metasino.end_game(table_id, vec![accounts.bob], vec![100]), Ok(()));
+            // The pot of 300 is raked for the cap of 30, of which the house keeps 20%.
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 270);
+            assert_eq!(metasino.get_claimable(accounts.frank), 24);
+            assert_eq!(metasino.get_treasury(), 6);
+        }
+
+        #[ink::test]
+        fn treasury_is_parked_above_the_buffer() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            metasino.treasury = 100;
+            assert_eq!(metasino.park_treasury(50), Err(MetasinoError::WrongState));
+            assert_eq!(metasino.recall_treasury(50), Err(MetasinoError::WrongState));
+            assert_eq!(metasino.set_yield_strategy(Some(accounts.frank), 60), Ok(()));
+            assert_eq!(metasino.park_treasury(50), Err(MetasinoError::YieldBufferBreached));
+            assert_eq!(metasino.park_treasury(101), Err(MetasinoError::ArithmeticError));
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.set_yield_strategy(None, 0),
+                Err(MetasinoError::NotAuthorized)
+            );
+            assert_eq!(metasino.park_treasury(40), Err(MetasinoError::NotAuthorized));
+            assert_eq!(metasino.recall_treasury(40), Err(MetasinoError::NotAuthorized));
+
+            // Funds parked with a strategy keep it from being replaced.
+            call_as(accounts.alice, 0);
+            metasino.parked = 40;
+            assert_eq!(
+                metasino.set_yield_strategy(Some(accounts.eve), 60),
+                Err(MetasinoError::WrongState)
+            );
+            assert_eq!(metasino.set_yield_strategy(Some(accounts.frank), 0), Ok(()));
+            assert_eq!(metasino.get_parked(), 40);
+            assert_eq!(metasino.destroy_contract(accounts.alice), Err(MetasinoError::WrongState));
+        }
+
+        #[ink::test]
+        fn withdrawals_are_throttled_and_large_ones_queued() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.set_withdrawal_limits(0, 0, 0, 0),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_withdrawal_limits(150, 60, 0, 0), Ok(()));
+            metasino.terminate(table_id).unwrap();
+
+            // Alice is capped by the account, Bob by what is left of the block.
+            let alice_balance = balance_of(accounts.alice);
+            assert_eq!(metasino.withdraw_winnings(), Ok(()));
+            assert_eq!(balance_of(accounts.alice), alice_balance + 60);
+            assert_eq!(metasino.get_claimable(accounts.alice), 40);
+            assert_eq!(metasino.withdraw_winnings(), Err(MetasinoError::WithdrawalThrottled));
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.withdraw_winnings(), Ok(()));
+            call_as(accounts.charlie, 0);
+            assert_eq!(metasino.withdraw_winnings(), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.charlie), 70);
+            assert_eq!(metasino.withdraw_winnings(), Err(MetasinoError::WithdrawalThrottled));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            assert_eq!(metasino.withdraw_winnings(), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.charlie), 40);
+
+            // Above the ceiling the withdrawal waits for an operator.
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_withdrawal_limits(0, 0, 30, 5), Ok(()));
+            call_as(accounts.charlie, 0);
+            assert_eq!(metasino.withdraw_winnings(), Ok(()));
+            let release_at = ink_env::block_number::<ink_env::DefaultEnvironment>() + 5;
+            assert_eq!(metasino.get_queued_withdrawal(accounts.charlie), Some((40, release_at)));
+            assert_eq!(metasino.get_claimable(accounts.charlie), 0);
+            assert_eq!(
+                metasino.confirm_withdrawal(accounts.charlie),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.confirm_withdrawal(accounts.charlie),
+                Err(MetasinoError::DeadlineNotReached)
+            );
+
+            // A vetoed withdrawal is credited back and may be queued again.
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.cancel_withdrawal(accounts.charlie),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.cancel_withdrawal(accounts.charlie), Ok(()));
+            assert_eq!(metasino.get_queued_withdrawal(accounts.charlie), None);
+            assert_eq!(metasino.get_claimable(accounts.charlie), 40);
+            assert_eq!(
+                metasino.cancel_withdrawal(accounts.charlie),
+                Err(MetasinoError::WrongState)
+            );
+            call_as(accounts.charlie, 0);
+            assert_eq!(metasino.withdraw_winnings(), Ok(()));
+
+            call_as(accounts.alice, 0);
+            for _ in 0..5 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            let charlie_balance = balance_of(accounts.charlie);
+            assert_eq!(metasino.confirm_withdrawal(accounts.charlie), Ok(()));
+            assert_eq!(balance_of(accounts.charlie), charlie_balance + 40);
+            assert_eq!(metasino.get_queued_withdrawal(accounts.charlie), None);
+            assert_eq!(
+                metasino.confirm_withdrawal(accounts.charlie),
+                Err(MetasinoError::WrongState)
+            );
+
+            // The withdrawal confirmed counts against the cap of the block.
+            assert_eq!(metasino.set_withdrawal_limits(50, 0, 0, 0), Ok(()));
+            let alice_balance = balance_of(accounts.alice);
+            assert_eq!(metasino.withdraw_winnings(), Ok(()));
+            assert_eq!(balance_of(accounts.alice), alice_balance + 10);
+        }
+
+        #[ink::test]
+        fn referrers_earn_a_share_of_the_rake() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 200);
+            call_as(accounts.eve, 0);
+            let mut metasino = Metasino::new();
+            metasino.set_rake(1_000, 100).unwrap();
+            assert_eq!(metasino.set_referral_cut(MAX_BPS + 1), Err(MetasinoError::InvalidRake));
+            assert_eq!(metasino.set_referral_cut(5_000), Ok(()));
+
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            assert_eq!(
+                metasino.register_player(table_id, None, Some(accounts.bob)),
+                Err(MetasinoError::InvalidAction)
+            );
+            assert_eq!(metasino.register_player(table_id, None, Some(accounts.django)), Ok(()));
+            assert_eq!(metasino.get_referrer(accounts.bob), Some(accounts.django));
+            assert_eq!(metasino.get_referrer(accounts.alice), None);
+            commit_as(&mut metasino, table_id, accounts.alice);
+            commit_as(&mut metasino, table_id, accounts.bob);
+            metasino.start_game(table_id).unwrap();
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.end_game(table_id, vec![accounts.alice], vec![100]), Ok(()));
+            assert_eq!(metasino.get_referral_earnings(accounts.django), 5);
+            assert_eq!(metasino.get_claimable(accounts.django), 5);
+            assert_eq!(metasino.get_treasury(), 15);
+            let paid = ink_env::test::recorded_events()
+                .filter_map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).ok())
+                .any(|event| {
+                    matches!(event, Event::ReferralPaid(paid) if paid.player == accounts.bob)
+                });
+            assert!(paid);
+        }
+
+        #[ink::test]
+        fn loyalty_points_are_redeemed_out_of_the_treasury() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 400);
+            call_as(accounts.eve, 0);
+            let mut metasino = Metasino::new();
+            metasino.set_rake(MAX_BPS, 200).unwrap();
+
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            commit_as(&mut metasino, table_id, accounts.alice);
+            commit_as(&mut metasino, table_id, accounts.bob);
+            metasino.start_game(table_id).unwrap();
+            call_as(accounts.alice, 0);
+            metasino.end_game(table_id, vec![accounts.alice], vec![100]).unwrap();
+            assert_eq!(metasino.get_treasury(), 200);
+            assert_eq!(metasino.get_loyalty_points(accounts.bob), 100);
+            assert_eq!(
+                metasino.redeem_points(Redemption::Rakeback(10)),
+                Err(MetasinoError::WrongState)
+            );
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.set_loyalty_rate(2), Ok(()));
+
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.redeem_points(Redemption::Rakeback(20)), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.alice), 10);
+            assert_eq!(metasino.get_loyalty_points(accounts.alice), 80);
+            // Only the points worth a whole unit are taken.
+            assert_eq!(
+                metasino.redeem_points(Redemption::Rakeback(1)),
+                Err(MetasinoError::InsufficientChips)
+            );
+            assert_eq!(metasino.redeem_points(Redemption::Rakeback(15)), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.alice), 17);
+            assert_eq!(metasino.get_loyalty_points(accounts.alice), 66);
+            assert_eq!(
+                metasino.redeem_points(Redemption::Rakeback(67)),
+                Err(MetasinoError::InsufficientChips)
+            );
+
+            call_as(accounts.frank, 40);
+            let tournament_id = metasino.create_tournament(40, 1_000, 3, 5).unwrap();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.redeem_points(Redemption::TournamentTicket(tournament_id)),
+                Ok(())
+            );
+            assert_eq!(metasino.get_loyalty_points(accounts.bob), 20);
+            let tournament = metasino.get_tournament(tournament_id).unwrap();
+            assert_eq!(tournament.players, vec![accounts.frank, accounts.bob]);
+            assert_eq!(metasino.get_treasury(), 143);
+        }
+
+        #[ink::test]
+        fn freerolls_are_funded_by_the_treasury() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 200);
+            call_as(accounts.eve, 0);
+            let mut metasino = Metasino::new();
+            metasino.set_rake(MAX_BPS, 200).unwrap();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            commit_as(&mut metasino, table_id, accounts.alice);
+            commit_as(&mut metasino, table_id, accounts.bob);
+            metasino.start_game(table_id).unwrap();
+            call_as(accounts.alice, 0);
+            metasino.end_game(table_id, vec![accounts.alice], vec![100]).unwrap();
+            assert_eq!(metasino.get_treasury(), 200);
+
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.create_freeroll(50, 3), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.create_freeroll(50, 1), Err(MetasinoError::InvalidTableConfig));
+            assert_eq!(metasino.create_freeroll(201, 3), Err(MetasinoError::ArithmeticError));
+            let freeroll = metasino.create_freeroll(150, 3).unwrap();
+            assert_eq!(metasino.get_treasury(), 50);
+            let table = metasino.get_table(freeroll).unwrap();
+            assert!(table.freeroll);
+            assert_eq!((table.pot, table.required_start_bet, table.rake_bps), (150, 0, 0));
+
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.register_player(freeroll, None, None), Ok(()));
+            call_as(accounts.charlie, 0);
+            assert_eq!(metasino.register_player(freeroll, None, None), Ok(()));
+            call_as(accounts.eve, 0);
+            let second = metasino.create_freeroll(40, 3).unwrap();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.register_player(second, None, None),
+                Err(MetasinoError::FreerollLimitReached)
+            );
+            for _ in 0..FREEROLL_EPOCH {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            assert_eq!(metasino.register_player(second, None, None), Ok(()));
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.terminate(second), Ok(()));
+            assert_eq!(metasino.get_treasury(), 50);
+
+            commit_as(&mut metasino, freeroll, accounts.bob);
+            commit_as(&mut metasino, freeroll, accounts.charlie);
+            assert_eq!(metasino.start_game(freeroll), Ok(()));
+            call_as(accounts.eve, 0);
+            assert_eq!(
+                metasino.end_game(freeroll, vec![accounts.charlie], vec![100]),
+                Ok(())
+            );
+            assert_eq!(metasino.get_claimable(accounts.charlie), 150);
+            assert_eq!(metasino.get_treasury(), 50);
+        }
+
+        #[ink::test]
+        fn seasons_rank_players_and_award_prizes() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 200);
+            call_as(accounts.eve, 0);
+            let mut metasino = Metasino::new();
+            metasino.set_rake(1_000, 20).unwrap();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            commit_as(&mut metasino, table_id, accounts.alice);
+            commit_as(&mut metasino, table_id, accounts.bob);
+            metasino.start_game(table_id).unwrap();
+            call_as(accounts.alice, 0);
+            metasino.end_game(table_id, vec![accounts.alice], vec![100]).unwrap();
+            assert_eq!(metasino.get_treasury(), 20);
+            assert_eq!(metasino.get_season(), 0);
+            let leaderboard = metasino.get_season_leaderboard(0, 5);
+            assert_eq!(leaderboard.len(), 1);
+            assert_eq!(leaderboard[0].0, accounts.alice);
+            assert_eq!(leaderboard[0].1.total_won, 180);
+            assert_eq!(metasino.get_season_stats(0, accounts.bob).games_played, 1);
+
+            assert_eq!(metasino.close_season(), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.set_season_prizes(vec![1; 11]), Err(MetasinoError::InvalidPayout));
+            assert_eq!(metasino.set_season_prizes(vec![25, 10]), Ok(()));
+            assert_eq!(metasino.close_season(), Err(MetasinoError::ArithmeticError));
+            assert_eq!(metasino.set_season_prizes(vec![15, 10]), Ok(()));
+            assert_eq!(metasino.close_season(), Ok(()));
+            assert_eq!(metasino.get_season(), 1);
+            assert_eq!(metasino.get_treasury(), 5);
+            assert!(metasino.get_season_leaderboard(1, 5).is_empty());
+            assert_eq!(metasino.get_top_players(5)[0].0, accounts.alice);
 
-    /// The maximum players alowed in the game participaction.
-    const MAX_PLAYERS: u8 = 10;
-    /// The minimum player required to start the game.
-    const MIN_PLAYERS: u8 = 3;
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.claim_season_prize(0), Err(MetasinoError::InvalidPayout));
+            call_as(accounts.alice, 0);
+            let claimable = metasino.get_claimable(accounts.alice);
+            assert_eq!(metasino.get_season_prize(0, accounts.alice), 15);
+            assert_eq!(metasino.claim_season_prize(0), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.alice), claimable + 15);
+            assert_eq!(metasino.claim_season_prize(0), Err(MetasinoError::InvalidPayout));
 
-    #[derive(
-        Debug,
-        Copy,
-        Clone,
-        PartialEq,
-        Eq,
-        scale::Encode,
-        scale::Decode,
-        SpreadLayout,
-        PackedLayout,
-    )]
-    #[cfg_attr(
-        feature = "std",
-        derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
-    )]
-    pub enum STATE {
-        STAGING,
-        PLAYING,
-        ENDED
-    }
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.set_season_length(10), Ok(()));
+            for _ in 0..10 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            call_as(accounts.alice, 100);
+            metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            assert_eq!(metasino.get_season(), 2);
+            assert_eq!(metasino.get_season_stats(2, accounts.alice).games_played, 1);
+            assert_eq!(metasino.get_player_stats(accounts.alice).games_played, 2);
+        }
 
-    #[ink(event)]
-    pub struct NewTableOpened {
-        #[ink(topic)]
-        pub initiator: AccountId,
-        #[ink(topic)]
-        pub required_start_bet: Balance,
-    }
+        #[ink::test]
+        fn achievements_are_unlocked_by_the_games() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            assert_eq!(metasino.end_game(table_id, vec![accounts.bob], vec![100]), Ok(()));
+            let progress = metasino.get_progress(accounts.bob);
+            assert_eq!(progress.hands_played, 1);
+            assert_eq!(progress.unlocked, vec![Achievement::FirstWin]);
+            assert!(progress.minted.is_empty());
+            assert!(metasino.get_progress(accounts.alice).unlocked.is_empty());
+            let unlocked = ink_env::test::recorded_events()
+                .filter_map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).ok())
+                .filter(|event| matches!(event, Event::AchievementUnlocked(_)))
+                .count();
+            assert_eq!(unlocked, 1);
 
-    #[ink(event)]
-    pub struct MinimumPlayerReached {
-        #[ink(topic)]
-        pub account_id: AccountId,
-    }
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.mint_badges(), Err(MetasinoError::WrongState));
+            assert_eq!(
+                metasino.set_achievements_contract(Some(accounts.eve)),
+                Err(MetasinoError::NotAuthorized)
+            );
+        }
 
-    /// Defines the storage of your contract.
-    /// Add new fields to the below struct in order
-    /// to add new static storage fields to your contract.
-    /// StorageLayout is used to define the layout of the storage.
-    /// SpreadLayout is used to define the layout of the spread.
-    #[ink(storage)]
-    pub struct Metasino {
-        /// Account which Initialized the contract.
-        initializer: AccountId,
-        /// Store the current number of players ready for the game.
-        players: Vec<AccountId>,
-        /// Start betting value,
-        required_start_bet: Balance,
-        /// Accumulated value in the pot.
-        pot: Balance,
-        /// The current state of the game.
-        state: STATE,
-    }
+        #[ink::test]
+        fn end_game_pays_out_shares_and_empties_table() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            let charlie_balance = metasino.get_claimable(accounts.charlie);
 
-    impl Metasino {
-        /// Constructor that initializes the `bool` value to the given `init_value`.
-        #[ink(constructor)]
-        pub fn new(required_start_bet: Balance) -> Self {
-            ink_env::debug_print!("Metasino::new()");
-            if required_start_bet <= 0 {
-                panic!("Required start bet must be greater than 0");
+            assert_eq!(
+                metasino.end_game(table_id, vec![accounts.bob, accounts.charlie], vec![67, 33]),
+                Ok(())
+            );
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 201);
+            assert_eq!(metasino.get_claimable(accounts.charlie), charlie_balance + 99);
+            withdraw_all(&mut metasino);
+            assert_eq!(balance_of(AccountId::from([0x42; 32])), 0);
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::ENDED));
+            assert_eq!(metasino.get_players_count(table_id), Ok(0));
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(0));
+            assert_eq!(
+                metasino.get_game(0),
+                Some(GameRecord {
+                    game_id: 0,
+                    table_id,
+                    players: vec![accounts.alice, accounts.bob, accounts.charlie],
+                    pot: 300,
+                    winners: vec![accounts.bob, accounts.charlie],
+                    payouts: vec![201, 99],
+                    ended_at_block: 0,
+                })
+            );
+
+            call_as(accounts.django, 100);
+            assert_eq!(metasino.register_player(table_id, None, None), Ok(()));
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::STAGING));
+        }
+
+        #[ink::test]
+        fn recent_games_are_listed_latest_first() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            assert_eq!(metasino.get_recent_games(10), vec![]);
+            metasino.end_game(table_id, vec![accounts.bob], vec![100]).unwrap();
+
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(
+                AccountId::from([0x42; 32]),
+                300,
+            );
+            for player in [accounts.alice, accounts.charlie, accounts.django] {
+                call_as(player, 100);
+                metasino.register_player(table_id, None, None).unwrap();
+                commit_as(&mut metasino, table_id, player);
             }
-            let mut players: Vec<AccountId> = Vec::new();
-            players.push(Self::env().caller());
-            Self::env().emit_event(NewTableOpened {
-                initiator: Self::env().caller(),
-                required_start_bet,
-            });
+            metasino.start_game(table_id).unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(
+                AccountId::from([0x42; 32]),
+                400,
+            );
+            call_as(accounts.alice, 0);
+            metasino.end_game(table_id, vec![accounts.charlie], vec![100]).unwrap();
 
-            
-            Self {
-                initializer: Self::env().caller(),
-                required_start_bet,
-                players,
-                pot: required_start_bet,
-                state: STATE::STAGING,
+            let games = metasino.get_recent_games(10);
+            assert_eq!(games.len(), 2);
+            assert_eq!((games[0].game_id, games[0].ended_at_block), (1, 1));
+            assert_eq!(games[0].winners, vec![accounts.charlie]);
+            assert_eq!(games[1].game_id, 0);
+            assert_eq!(metasino.get_recent_games(1), vec![games[0].clone()]);
+            assert_eq!(metasino.get_game(2), None);
+        }
+
+        #[ink::test]
+        fn reputation_follows_conduct_and_gates_registration() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = revealed_table();
+            let active = metasino.get_betting_round(table_id).unwrap().active;
+            let stalling = metasino.get_table_info(table_id).unwrap().players[active as usize];
+            for _ in 0..=TURN_PERIOD {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
             }
+            metasino.claim_timeout(table_id).unwrap();
+            assert_eq!(metasino.get_player_stats(stalling).reputation, -TIMEOUT_PENALTY);
+            let winner = if stalling == accounts.bob { accounts.charlie } else { accounts.bob };
+            call_as(accounts.alice, 0);
+            metasino.end_game(table_id, vec![winner], vec![100]).unwrap();
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                let reputation = metasino.get_player_stats(player).reputation;
+                assert_eq!(reputation, if player == stalling { -TIMEOUT_PENALTY } else { 1 });
+            }
+
+            call_as(accounts.django, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.eve, 0);
+            assert_eq!(
+                metasino.set_min_reputation(table_id, Some(0)),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.set_min_reputation(table_id, Some(0)), Ok(()));
+            call_as(stalling, 100);
+            assert_eq!(
+                metasino.register_player(table_id, None, None),
+                Err(MetasinoError::ReputationTooLow)
+            );
+            call_as(accounts.eve, 100);
+            assert_eq!(metasino.register_player(table_id, None, None), Ok(()));
         }
 
-        #[ink(message)]
-        pub fn terminate(&mut self) {
-            self.table_status_guard();
-            if self.get_players().contains(&Self::env().caller()) {
-                panic!("Only the initializer can terminate the game");
+        #[ink::test]
+        fn hand_history_is_logged_for_indexers() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = revealed_table();
+            call_as(accounts.alice, 10);
+            metasino.raise(table_id, 10).unwrap();
+            let logged: Vec<(u8, LoggedAction, Balance, Street)> =
+                ink_env::test::recorded_events()
+                    .filter_map(|event| {
+                        match <Event as scale::Decode>::decode(&mut &event.data[..]).ok()? {
+                            Event::ActionLogged(entry) => {
+                                Some((entry.seat, entry.action, entry.amount, entry.street))
+                            }
+                            _ => None,
+                        }
+                    })
+                    .collect();
+            assert_eq!(
+                logged,
+                vec![
+                    (0, LoggedAction::Deal, 100, Street::PreFlop),
+                    (1, LoggedAction::Deal, 100, Street::PreFlop),
+                    (2, LoggedAction::Deal, 100, Street::PreFlop),
+                    (0, LoggedAction::Bet(Action::Raise(10)), 10, Street::PreFlop),
+                ]
+            );
+        }
+
+        #[ink::test]
+        fn spectators_follow_the_hand() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = revealed_table();
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.watch_table(table_id), Ok(()));
+            assert_eq!(metasino.watch_table(table_id), Err(MetasinoError::AlreadyRegistered));
+            assert_eq!(metasino.watch_table(table_id + 1), Err(MetasinoError::TableNotFound));
+            assert_eq!(metasino.get_spectators(table_id), vec![accounts.django]);
+            let view = metasino.get_spectator_view(table_id).unwrap();
+            assert_eq!(view.spectators, 1);
+            assert_eq!(view.street, Some(Street::PreFlop));
+            assert!(view.board.is_empty() && view.history.is_empty());
+
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                metasino.check(table_id).unwrap();
             }
-            self.players.clear();
+            let view = metasino.get_spectator_view(table_id).unwrap();
+            assert_eq!(view.street, Some(Street::Flop));
+            assert_eq!(view.board.len(), 3);
+            assert_eq!(view.history.len(), 3);
+            assert!(view.shown_hands.is_empty());
+
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.stop_watching(table_id), Ok(()));
+            assert_eq!(metasino.stop_watching(table_id), Err(MetasinoError::NotAuthorized));
+            assert!(metasino.get_spectators(table_id).is_empty());
         }
 
-        /// Register new player into the table.
-        /// error if the player is already registered.
-        /// error if the table is full.
-        /// error if new player places bet less or more than the required start bet.
-        #[ink(message)]
-        pub fn register_player(&mut self, start_bet: Balance) {
-            self.table_status_guard();
-            let caller = Self::env().caller();
-            if self.get_players_count() >= MAX_PLAYERS {
-                panic!("Max players reached");
+        #[ink::test]
+        fn stats_track_wagers_and_wins() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = revealed_table();
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(
+                AccountId::from([0x42; 32]),
+                450,
+            );
+            call_as(accounts.alice, 50);
+            metasino.raise(table_id, 50).unwrap();
+            for player in [accounts.bob, accounts.charlie] {
+                call_as(player, 50);
+                metasino.call(table_id).unwrap();
             }
+            call_as(accounts.alice, 0);
+            metasino
+                .end_game(table_id, vec![accounts.bob, accounts.charlie], vec![50, 50])
+                .unwrap();
 
-            if start_bet != self.required_start_bet {
-                panic!(
-                    "start Bet value requires at exact {}",
-                    self.required_start_bet
-                );
+            assert_eq!(
+                metasino.get_player_stats(accounts.alice),
+                PlayerStats {
+                    games_played: 1,
+                    games_won: 0,
+                    total_wagered: 150,
+                    total_won: 0,
+                    reputation: 1,
+                }
+            );
+            assert_eq!(
+                metasino.get_player_stats(accounts.bob),
+                PlayerStats {
+                    games_played: 1,
+                    games_won: 1,
+                    total_wagered: 150,
+                    total_won: 225,
+                    reputation: 1,
+                }
+            );
+            assert_eq!(
+                metasino.get_top_players(10),
+                vec![
+                    (accounts.bob, metasino.get_player_stats(accounts.bob)),
+                    (accounts.charlie, metasino.get_player_stats(accounts.charlie)),
+                ]
+            );
+            assert_eq!(metasino.get_top_players(1).len(), 1);
+            assert_eq!(metasino.get_player_stats(accounts.django), PlayerStats::default());
+        }
+
+        #[ink::test]
+        fn refunds_undo_registration_stats() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            call_as(accounts.bob, 0);
+            metasino.leave_table(table_id).unwrap();
+            assert_eq!(metasino.get_player_stats(accounts.bob), PlayerStats::default());
+            assert_eq!(metasino.get_player_stats(accounts.charlie).games_played, 1);
+        }
+
+        #[ink::test]
+        fn blackjack_tables_are_settled_against_the_house() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 200);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Blackjack, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            for player in [accounts.alice, accounts.bob] {
+                commit_as(&mut metasino, table_id, player);
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            for player in [accounts.alice, accounts.bob] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
             }
+            assert_eq!(metasino.check(table_id), Err(MetasinoError::WrongState));
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.end_game(table_id, vec![accounts.alice], vec![100]),
+                Err(MetasinoError::WrongState)
+            );
 
-            self.pot += start_bet;
-            if !self.players.contains(&caller) {
-                self.players.push(caller);
-            } else {
-                panic!("Player already registered");
+            while let Some(round) = metasino.get_blackjack_round(table_id) {
+                let player = metasino.get_players(table_id, round.active, 1).unwrap()[0];
+                call_as(player, 0);
+                metasino.stand(table_id).unwrap();
             }
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::ENDED));
+            let game = metasino.get_game(0).unwrap();
+            let paid: Balance = game.payouts.iter().sum();
+            assert_eq!(paid + metasino.get_treasury(), 200);
+            withdraw_all(&mut metasino);
+            assert_eq!(balance_of(contract), metasino.get_treasury());
         }
 
-        /// Start the game by extending the table to the game contract.
-        #[ink(message)]
-        pub fn start_game(&mut self) {
-            self.table_status_guard();
+        #[ink::test]
+        fn insurance_is_paid_on_a_dealer_blackjack() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 200);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Blackjack, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            for player in [accounts.alice, accounts.bob] {
+                commit_as(&mut metasino, table_id, player);
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            for player in [accounts.alice, accounts.bob] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+            let hand = |ranks: [u8; 2]| {
+                ranks
+                    .iter()
+                    .map(|rank| Card { rank: *rank, suit: crate::cards::Suit::Clubs })
+                    .collect::<Vec<_>>()
+            };
+            let mut round = BlackjackRound::deal(2, 100, &[0; 32], 100);
+            round.hands = vec![hand([10, 8]), hand([10, 7])];
+            round.dealer = hand([crate::cards::ACE, 13]);
+            round.done = vec![false, false];
+            round.active = 0;
+            round.finished = false;
+            round.offer_insurance(INSURANCE_PERIOD);
+            metasino.blackjack_rounds.insert(table_id, &round);
 
-            if self.get_players_count() < MIN_PLAYERS {
-                panic!("Minimum {} players required to start the game", MIN_PLAYERS);
+            call_as(accounts.bob, 60);
+            assert_eq!(metasino.insure(table_id, 60), Err(MetasinoError::WrongBet));
+            call_as(accounts.alice, 50);
+            assert_eq!(metasino.insure(table_id, 50), Ok(()));
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(250));
+            for player in [accounts.alice, accounts.bob] {
+                call_as(player, 0);
+                metasino.stand(table_id).unwrap();
             }
-            self.state = STATE::PLAYING;
+            assert_eq!(metasino.get_game(0).unwrap().payouts, vec![150]);
+            assert_eq!(metasino.get_game(0).unwrap().winners, vec![accounts.alice]);
         }
 
-        /// Guarding the contract from being executed in a wrong state.
-        #[inline]
-        fn table_status_guard(&self){
-            if self.state == STATE::PLAYING {
-                panic!("Game is ongoing!!");
+        #[ink::test]
+        fn overflowing_amounts_are_rejected() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, Balance::MAX);
+            let table_id = metasino
+                .create_table(Balance::MAX, 2, 10, None, GameKind::Roulette, None, None)
+                .unwrap();
+            call_as(accounts.bob, Balance::MAX);
+            assert_eq!(
+                metasino.register_player(table_id, None, None),
+                Err(MetasinoError::ArithmeticError)
+            );
+            assert_eq!(metasino.get_players_count(table_id), Ok(1));
+            call_as(accounts.alice, 1);
+            assert_eq!(
+                metasino.place_bet(table_id, RouletteBet::Red, 1),
+                Err(MetasinoError::ArithmeticError)
+            );
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(Balance::MAX));
+
+            let straight = Balance::MAX / 36 + 1;
+            call_as(accounts.bob, 1);
+            let table_id = metasino
+                .create_table(1, 2, 10, None, GameKind::Roulette, None, None)
+                .unwrap();
+            call_as(accounts.bob, straight);
+            assert_eq!(
+                metasino.place_bet(table_id, RouletteBet::Straight(7), straight),
+                Err(MetasinoError::ArithmeticError)
+            );
+            call_as(accounts.bob, straight - 1);
+            assert_eq!(
+                metasino.place_bet(table_id, RouletteBet::Straight(7), straight - 1),
+                Ok(())
+            );
+
+            let stake = Balance::MAX / 2 + 1;
+            call_as(accounts.charlie, stake);
+            assert_eq!(
+                metasino.create_duel(Hash::from([1; 32])),
+                Err(MetasinoError::ArithmeticError)
+            );
+            assert_eq!(metasino.get_duel(0), None);
+        }
+
+        #[ink::test]
+        fn roulette_pays_bets_after_the_spin() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 260);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Roulette, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            call_as(accounts.bob, 10);
+            assert_eq!(
+                metasino.place_bet(table_id, RouletteBet::Dozen(4), 10),
+                Err(MetasinoError::InvalidAction)
+            );
+            assert_eq!(
+                metasino.place_bet(table_id, RouletteBet::Red, 20),
+                Err(MetasinoError::WrongBet)
+            );
+            assert_eq!(metasino.place_bet(table_id, RouletteBet::Red, 10), Ok(()));
+            call_as(accounts.alice, 50);
+            assert_eq!(metasino.place_bet(table_id, RouletteBet::Black, 50), Ok(()));
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(260));
+            assert_eq!(
+                metasino.get_wagers(table_id, accounts.bob),
+                vec![Wager {
+                    bet: RouletteBet::Red,
+                    amount: 10,
+                }]
+            );
+
+            for player in [accounts.alice, accounts.bob] {
+                commit_as(&mut metasino, table_id, player);
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            call_as(accounts.charlie, 0);
+            assert_eq!(
+                metasino.place_bet(table_id, RouletteBet::Red, 0),
+                Err(MetasinoError::NotAuthorized)
+            );
+            for player in [accounts.alice, accounts.bob] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+
+            let pocket = roulette::spin(&[0x01 ^ 0x02; 32]);
+            let owed = 200
+                + RouletteBet::Red.payout(10, pocket).unwrap()
+                + RouletteBet::Black.payout(50, pocket).unwrap();
+            let game = metasino.get_game(0).unwrap();
+            assert_eq!(game.payouts.iter().sum::<Balance>(), owed.min(260));
+            withdraw_all(&mut metasino);
+            assert_eq!(balance_of(contract), metasino.get_treasury());
+            assert_eq!(metasino.get_wagers(table_id, accounts.bob), vec![]);
+        }
+
+        #[ink::test]
+        fn baccarat_pays_bets_after_the_coup() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 280);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Baccarat, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.place_baccarat_bet(table_id, BaccaratBet::Tie, 0),
+                Err(MetasinoError::WrongBet)
+            );
+            assert_eq!(
+                metasino.place_bet(table_id, RouletteBet::Red, 10),
+                Err(MetasinoError::WrongState)
+            );
+            call_as(accounts.bob, 30);
+            assert_eq!(metasino.place_baccarat_bet(table_id, BaccaratBet::Banker, 30), Ok(()));
+            call_as(accounts.alice, 50);
+            assert_eq!(metasino.place_baccarat_bet(table_id, BaccaratBet::Player, 50), Ok(()));
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(280));
+            assert_eq!(
+                metasino.get_baccarat_bets(table_id, accounts.bob),
+                vec![BaccaratWager {
+                    bet: BaccaratBet::Banker,
+                    amount: 30,
+                }]
+            );
+
+            for player in [accounts.alice, accounts.bob] {
+                commit_as(&mut metasino, table_id, player);
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            for player in [accounts.alice, accounts.bob] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+
+            let outcome = baccarat::deal(&[0x01 ^ 0x02; 32]).outcome();
+            let owed = 200
+                + BaccaratBet::Banker.payout(30, outcome).unwrap()
+                + BaccaratBet::Player.payout(50, outcome).unwrap();
+            let game = metasino.get_game(0).unwrap();
+            assert_eq!(game.payouts.iter().sum::<Balance>(), owed.min(280));
+            withdraw_all(&mut metasino);
+            assert_eq!(balance_of(contract), metasino.get_treasury());
+            assert_eq!(metasino.get_baccarat_bets(table_id, accounts.bob), vec![]);
+        }
+
+        #[ink::test]
+        fn dice_rolls_until_the_bets_resolve() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 200);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Dice, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.choose_dice_bet(table_id, DiceBet::DontPass), Ok(()));
+            assert_eq!(metasino.get_dice_bet(table_id, accounts.alice), DiceBet::Pass);
+            assert_eq!(metasino.roll_dice(table_id), Err(MetasinoError::WrongState));
+            for player in [accounts.alice, accounts.bob] {
+                commit_as(&mut metasino, table_id, player);
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            for player in [accounts.alice, accounts.bob] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
             }
 
-            if self.state == STATE::ENDED {
-                panic!("Game has already has ended");
+            call_as(accounts.django, 0);
+            let mut rolls = 0;
+            while metasino.get_dice_round(table_id).is_some() {
+                metasino.roll_dice(table_id).unwrap();
+                rolls += 1;
             }
+            assert_eq!(metasino.roll_dice(table_id), Err(MetasinoError::WrongState));
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::ENDED));
+            let events = ink_env::test::recorded_events()
+                .filter_map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).ok())
+                .filter(|event| matches!(event, Event::DiceRolled(_)))
+                .count();
+            assert_eq!(events, rolls);
+            let game = metasino.get_game(0).unwrap();
+            assert_eq!(game.payouts.iter().sum::<Balance>() + metasino.get_treasury(), 200);
         }
 
-        /// Get the current state of the table.
-        #[ink(message)]
-        pub fn get_table_state(&self) -> STATE {
-            self.state
+        #[ink::test]
+        fn duels_pay_the_winner_of_the_flip() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 200);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 0);
+            metasino.set_rake(1000, 5).unwrap();
+            let commitment = |player| randomness::commitment(&player, &seed_of(player));
+            call_as(accounts.alice, 100);
+            let duel_id = metasino.create_duel(commitment(accounts.alice)).unwrap();
+            call_as(accounts.alice, 100);
+            assert_eq!(
+                metasino.accept_duel(duel_id, commitment(accounts.alice)),
+                Err(MetasinoError::WrongState)
+            );
+            call_as(accounts.bob, 50);
+            assert_eq!(
+                metasino.accept_duel(duel_id, commitment(accounts.bob)),
+                Err(MetasinoError::WrongBet)
+            );
+            call_as(accounts.bob, 100);
+            metasino.accept_duel(duel_id, commitment(accounts.bob)).unwrap();
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.cancel_duel(duel_id), Err(MetasinoError::WrongState));
+            assert_eq!(metasino.claim_duel(duel_id), Err(MetasinoError::DeadlineNotReached));
+            metasino.reveal_duel(duel_id, seed_of(accounts.alice)).unwrap();
+
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            call_as(accounts.bob, 0);
+            metasino.reveal_duel(duel_id, seed_of(accounts.bob)).unwrap();
+            let duel = metasino.get_duel(duel_id).unwrap();
+            assert_eq!((duel.state, duel.winner), (DuelState::Settled, Some(accounts.bob)));
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 195);
+            assert_eq!(metasino.get_treasury(), 5);
+            assert_eq!(metasino.get_player_stats(accounts.bob).total_won, 195);
         }
 
-        /// check if the table is fully occupied.
-        #[ink(message)]
-        pub fn is_table_full(&self) -> bool {
-            self.get_players_count() >= MAX_PLAYERS
+        #[ink::test]
+        fn unrevealed_duels_go_to_the_revealer_or_are_refunded() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 400);
+            let mut metasino = Metasino::new();
+            let commitment = |player| randomness::commitment(&player, &seed_of(player));
+            let mut duels = Vec::new();
+            for _ in 0..2 {
+                call_as(accounts.alice, 100);
+                let duel_id = metasino.create_duel(commitment(accounts.alice)).unwrap();
+                call_as(accounts.bob, 100);
+                metasino.accept_duel(duel_id, commitment(accounts.bob)).unwrap();
+                duels.push(duel_id);
+            }
+            call_as(accounts.alice, 0);
+            metasino.reveal_duel(duels[0], seed_of(accounts.alice)).unwrap();
+            for _ in 0..=REVEAL_PERIOD {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+
+            let alice_balance = metasino.get_claimable(accounts.alice);
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            call_as(accounts.django, 0);
+            metasino.claim_duel(duels[0]).unwrap();
+            assert_eq!(metasino.get_duel(duels[0]).unwrap().winner, Some(accounts.alice));
+            assert_eq!(metasino.get_claimable(accounts.alice), alice_balance + 200);
+            metasino.claim_duel(duels[1]).unwrap();
+            assert_eq!(metasino.get_duel(duels[1]).unwrap().winner, None);
+            assert_eq!(metasino.get_claimable(accounts.alice), alice_balance + 300);
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 100);
+            assert_eq!(metasino.claim_duel(duels[1]), Err(MetasinoError::WrongState));
         }
 
+        #[ink::test]
+        fn lottery_pays_the_holder_of_the_drawn_ticket() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 30);
+            let mut metasino = Metasino::new();
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.open_lottery(10, 3), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            let lottery_id = metasino.open_lottery(10, 3).unwrap();
+            let holders = [accounts.alice, accounts.bob, accounts.charlie];
+            for (ticket, player) in holders.iter().enumerate() {
+                call_as(*player, 10);
+                let commitment = randomness::commitment(player, &seed_of(*player));
+                assert_eq!(metasino.buy_ticket(lottery_id, commitment), Ok(ticket as u32));
+            }
+            call_as(accounts.django, 5);
+            assert_eq!(
+                metasino.buy_ticket(lottery_id, Hash::default()),
+                Err(MetasinoError::WrongBet)
+            );
+            assert_eq!(metasino.draw_lottery(lottery_id), Err(MetasinoError::DeadlineNotReached));
+            for _ in 0..3 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            call_as(accounts.django, 10);
+            assert_eq!(
+                metasino.buy_ticket(lottery_id, Hash::default()),
+                Err(MetasinoError::WrongState)
+            );
 
-        /// Get the current number of players in the table.
-        #[ink(message)]
-        pub fn get_players_count(&self) -> u8 {
-            self.players.len() as u8
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.draw_lottery(lottery_id), Ok(()));
+            let ticket = metasino.get_lottery(lottery_id).unwrap().winner.unwrap();
+            let winner = metasino.get_ticket_holder(lottery_id, ticket).unwrap();
+            assert_eq!(metasino.get_player_stats(winner).total_won, 30);
+            assert_eq!(metasino.draw_lottery(lottery_id), Err(MetasinoError::WrongState));
+            let drawn = ink_env::test::recorded_events()
+                .filter_map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).ok())
+                .any(|event| {
+                    matches!(event, Event::LotteryDrawn(drawn) if drawn.winner == Some(winner))
+                });
+            assert!(drawn);
         }
 
-        /// Get the current accumulated pot value.
-        #[ink(message)]
-        pub fn get_accumulated_pot(&self) -> Balance {
-            self.pot
+        #[ink::test]
+        fn markets_share_the_losing_stakes_between_the_winners() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 0);
+            metasino.set_rake(500, 1_000).unwrap();
+            metasino.add_result_signer(accounts.eve).unwrap();
+            assert_eq!(metasino.open_market(1, 3), Err(MetasinoError::InvalidTableConfig));
+            let market_id = metasino.open_market(2, 3).unwrap();
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.open_market(2, 3), Err(MetasinoError::NotAuthorized));
+            for (player, outcome, amount) in [
+                (accounts.bob, 0, 100),
+                (accounts.charlie, 0, 300),
+                (accounts.django, 1, 200),
+            ] {
+                call_as(player, amount);
+                assert_eq!(metasino.stake_on_market(market_id, outcome), Ok(()));
+            }
+            assert_eq!(metasino.get_market_stakes(market_id, accounts.bob), vec![100, 0]);
+            call_as(accounts.eve, 0);
+            assert_eq!(
+                metasino.report_market(market_id, 0),
+                Err(MetasinoError::DeadlineNotReached)
+            );
+            for _ in 0..3 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            call_as(accounts.bob, 100);
+            assert_eq!(metasino.stake_on_market(market_id, 1), Err(MetasinoError::WrongState));
+            assert_eq!(metasino.report_market(market_id, 1), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.report_market(market_id, 0), Ok(()));
+            assert_eq!(metasino.get_treasury(), 10);
+
+            for player in [accounts.bob, accounts.charlie, accounts.django] {
+                call_as(player, 0);
+                assert_eq!(metasino.claim_market(market_id), Ok(()));
+            }
+            assert_eq!(metasino.get_claimable(accounts.bob), 147);
+            assert_eq!(metasino.get_claimable(accounts.charlie), 442);
+            assert_eq!(metasino.get_claimable(accounts.django), 0);
+            assert_eq!(metasino.claim_market(market_id), Err(MetasinoError::NotAuthorized));
+            assert_eq!(metasino.claim_market(1), Err(MetasinoError::MarketNotFound));
         }
 
-        /// Get address of the player in the table.
-        #[ink(message)]
-        pub fn get_players(&self) -> Vec<AccountId> {
-            self.players.clone()
+        #[ink::test]
+        fn pots_feed_the_jackpot_won_by_the_spade_blackjack() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = started_table();
+            assert_eq!(metasino.set_jackpot_cut(MAX_BPS + 1), Err(MetasinoError::InvalidRake));
+            assert_eq!(metasino.set_jackpot_cut(500), Ok(()));
+            assert_eq!(metasino.end_game(table_id, vec![accounts.bob], vec![100]), Ok(()));
+            assert_eq!(metasino.get_jackpot(), 15);
+            assert_eq!(metasino.get_game(0).unwrap().payouts, vec![285]);
+
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Blackjack, None, None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            let table = metasino.table(table_id).unwrap();
+            let spades = |ranks: [u8; 2]| {
+                ranks
+                    .iter()
+                    .map(|rank| Card { rank: *rank, suit: crate::cards::Suit::Spades })
+                    .collect::<Vec<_>>()
+            };
+            let mut round = BlackjackRound::deal(2, 100, &[0; 32], 0);
+            round.hands = vec![spades([10, 9]), spades([11, crate::cards::ACE])];
+            let bob_balance = metasino.get_claimable(accounts.bob);
+            assert_eq!(metasino.award_jackpot(table_id, &table, &round), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.bob), bob_balance + 15);
+            assert_eq!(metasino.get_jackpot(), 0);
+            assert_eq!(metasino.get_player_stats(accounts.bob).total_won, 300);
         }
 
-        /// Get the required start bet value.
-        #[ink(message)]
-        pub fn get_required_start_bet(&self) -> Balance {
-            self.required_start_bet
+        #[ink::test]
+        fn bounties_are_paid_on_every_knockout() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let tournament_id = metasino.create_tournament(100, 1_000, 3, 5).unwrap();
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.set_bounty(tournament_id, 101),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            assert_eq!(metasino.set_bounty(tournament_id, 40), Ok(()));
+            for player in [accounts.bob, accounts.charlie] {
+                call_as(player, 100);
+                metasino.join_tournament(tournament_id).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_tournament(tournament_id).unwrap();
+            assert_eq!(metasino.set_bounty(tournament_id, 0), Err(MetasinoError::WrongState));
+            metasino.report_hand(tournament_id, 1, vec![20, 20, 1_000]).unwrap();
+            assert_eq!(metasino.get_claimable(accounts.bob), 40);
+            metasino.report_hand(tournament_id, 1, vec![980, 20, 0]).unwrap();
+
+            assert_eq!(metasino.get_claimable(accounts.bob), 120 + 90);
+            assert_eq!(metasino.get_claimable(accounts.alice), 54);
+            assert_eq!(metasino.get_claimable(accounts.charlie), 36);
+            let collected = ink_env::test::recorded_events()
+                .filter_map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).ok())
+                .filter(|event| matches!(event, Event::BountyCollected(_)))
+                .count();
+            assert_eq!(collected, 3);
         }
-    }
 
-    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
-    /// module and test functions are marked with a `#[test]` attribute.
-    /// The below code is technically just normal Rust code.
-    #[cfg(test)]
-    mod tests {
-        /// Imports all the definitions from the outer scope so we can use them here.
-        use super::*;
+        #[ink::test]
+        fn satellites_are_set_up_by_the_director() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let main = metasino.create_tournament(100, 1_000, 3, 5).unwrap();
+            call_as(accounts.bob, 10);
+            let satellite = metasino.create_tournament(10, 1_000, 10, 5).unwrap();
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.register_for_tournament(tournament::ticket_id(main, 0)),
+                Err(MetasinoError::WrongState)
+            );
+            assert_eq!(
+                metasino.set_satellite(satellite, Some(main)),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            assert_eq!(
+                metasino.set_tournament_ticket(main, Some(accounts.eve)),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_tournament_ticket(main, Some(accounts.eve)), Ok(()));
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.set_satellite(satellite, Some(satellite)),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            assert_eq!(metasino.set_satellite(satellite, Some(main)), Ok(()));
+            let tournament = metasino.get_tournament(satellite).unwrap();
+            assert_eq!(tournament.satellite_for, Some(main));
+            assert_eq!(
+                metasino.register_for_tournament(tournament::ticket_id(main, 0)),
+                Err(MetasinoError::InvalidAction)
+            );
+        }
 
-        /// Imports `ink_lang` so we can use `#[ink::test]`.
-        use ink_lang as ink;
+        #[ink::test]
+        fn tournament_pays_the_last_standing() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 300);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let tournament_id = metasino.create_tournament(100, 1_000, 3, 5).unwrap();
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.start_tournament(tournament_id),
+                Err(MetasinoError::NotEnoughPlayers)
+            );
+            for player in [accounts.bob, accounts.charlie] {
+                call_as(player, 100);
+                metasino.join_tournament(tournament_id).unwrap();
+            }
+            call_as(accounts.django, 100);
+            assert_eq!(metasino.join_tournament(tournament_id), Err(MetasinoError::TableFull));
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.start_tournament(tournament_id), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            metasino.start_tournament(tournament_id).unwrap();
+            for _ in 0..5 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            assert_eq!(metasino.get_blind(tournament_id), Ok(40));
+            assert_eq!(
+                metasino.report_hand(tournament_id, 1, vec![20, 20, 20]),
+                Err(MetasinoError::InvalidAction)
+            );
+            metasino.report_hand(tournament_id, 1, vec![40, 40, 1_000]).unwrap();
+            let balances = [
+                metasino.get_claimable(accounts.alice),
+                metasino.get_claimable(accounts.bob),
+            ];
+            metasino.report_hand(tournament_id, 1, vec![960, 40, 0]).unwrap();
+
+            let tournament = metasino.get_tournament(tournament_id).unwrap();
+            assert_eq!(tournament.chips, vec![0, 3_000, 0]);
+            assert_eq!(metasino.get_claimable(accounts.bob), balances[1] + 150);
+            assert_eq!(metasino.get_claimable(accounts.alice), balances[0] + 90);
+            assert_eq!(metasino.get_player_stats(accounts.charlie).total_won, 60);
+            assert_eq!(
+                metasino.report_hand(tournament_id, 1, vec![0, 40, 0]),
+                Err(MetasinoError::WrongState)
+            );
+        }
 
-        /// Test constructor works as per expected.
-        /// - Test the required start bet value is as per the initialized.
-        /// - Test the initializer is the caller who initialized the contract.
-        /// - Test the total number of player is 1 when initialized.
-        /// - Test the total accumulated pot value is as start bet value put by the initializer caller.
-        /// - Test the state is 0 when initialized.
         #[ink::test]
-        fn initialize_with_player_count_equal_one() {
+        fn bets_are_taken_out_of_the_chips() {
             let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
-            let metasino = Metasino::new(100);
-            assert_eq!(true, metasino.get_required_start_bet().eq(&100));
-            assert_eq!(accounts.alice, metasino.initializer);
-            assert_eq!(metasino.get_players_count(), 1);
-            assert_eq!(metasino.get_accumulated_pot(), 100);
-            assert_eq!(metasino.get_table_state(), STATE::STAGING);
+            let (mut metasino, table_id) = revealed_table();
+            call_as(accounts.django, 50);
+            assert_eq!(metasino.buy_chips(table_id, 50), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 40);
+            assert_eq!(metasino.buy_chips(table_id, 50), Err(MetasinoError::WrongBet));
+            call_as(accounts.alice, 50);
+            metasino.buy_chips(table_id, 50).unwrap();
+            assert_eq!(metasino.get_chips(table_id, accounts.alice), 50);
+
+            call_as(accounts.alice, 0);
+            metasino.raise(table_id, 30).unwrap();
+            assert_eq!(metasino.get_chips(table_id, accounts.alice), 20);
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(330));
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.call(table_id), Err(MetasinoError::WrongBet));
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.cash_out(table_id), Err(MetasinoError::InsufficientChips));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.cash_out(table_id), Err(MetasinoError::WrongState));
+
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(
+                AccountId::from([0x42; 32]),
+                350,
+            );
+            metasino.end_game(table_id, vec![accounts.alice], vec![100]).unwrap();
+            assert_eq!(metasino.get_chips(table_id, accounts.alice), 350);
+            let balance = metasino.get_claimable(accounts.alice);
+            assert_eq!(metasino.cash_out(table_id), Ok(()));
+            assert_eq!(metasino.get_claimable(accounts.alice), balance + 350);
+            assert_eq!(metasino.get_chips(table_id, accounts.alice), 0);
         }
 
         #[ink::test]
-        #[should_panic = "Player already registered"]
-        fn register_same_player_will_fail() {
+        fn players_on_chips_play_the_next_hand() {
             let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
-            let mut metasino = Metasino::new(100);
-            metasino.register_player(100);
+            let (mut metasino, table_id) = staged_table();
+            for (player, chips) in [(accounts.alice, 50), (accounts.bob, 150)] {
+                call_as(player, chips);
+                metasino.buy_chips(table_id, chips).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            assert_eq!(metasino.next_hand(table_id), Err(MetasinoError::WrongState));
+            metasino.end_game(table_id, vec![accounts.charlie], vec![100]).unwrap();
+
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::INTERMISSION));
+            assert_eq!(metasino.get_table_info(table_id).unwrap().hand_number, 1);
+            assert_eq!(
+                metasino.get_players(table_id, 0, MAX_PLAYERS),
+                Ok(vec![accounts.alice, accounts.bob])
+            );
+            assert_eq!(metasino.commitments.get((table_id, accounts.alice)), None);
+            call_as(accounts.django, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::INTERMISSION));
+
+            call_as(accounts.bob, 0);
+            metasino.next_hand(table_id).unwrap();
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::STAGING));
+            assert_eq!(
+                metasino.get_players(table_id, 0, MAX_PLAYERS),
+                Ok(vec![accounts.django, accounts.bob])
+            );
+            assert_eq!(metasino.get_chips(table_id, accounts.bob), 50);
+            assert_eq!(metasino.get_chips(table_id, accounts.alice), 50);
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(200));
         }
 
         #[ink::test]
-        fn adding_new_player() {
+        fn rebuys_are_limited_in_number_and_time() {
             let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
-            let mut metasino = Metasino::new(100);
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
-            metasino.register_player(100);
-            assert_eq!(metasino.get_players_count(), 2);
-            assert_eq!(metasino.get_accumulated_pot(), 200);
-            assert_eq!(metasino.get_players()[0], accounts.alice);
-            assert_eq!(metasino.get_players()[1], accounts.bob);
-            assert_eq!(metasino.get_table_state(), STATE::STAGING);
-            assert_eq!(metasino.get_required_start_bet(), 100);
+            let (mut metasino, table_id) = staged_table();
+            call_as(accounts.bob, 50);
+            assert_eq!(metasino.rebuy(table_id, 50), Err(MetasinoError::WrongState));
+            assert_eq!(
+                metasino.set_rebuy_limits(table_id, 2, 3),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            metasino.set_rebuy_limits(table_id, 2, 3).unwrap();
+
+            call_as(accounts.bob, 150);
+            assert_eq!(metasino.rebuy(table_id, 150), Err(MetasinoError::WrongBet));
+            for _ in 0..2 {
+                call_as(accounts.bob, 50);
+                metasino.rebuy(table_id, 50).unwrap();
+            }
+            assert_eq!(metasino.get_chips(table_id, accounts.bob), 100);
+            assert_eq!(metasino.get_player_info(table_id, accounts.bob).unwrap().rebuys, 2);
+            assert_eq!(metasino.rebuy(table_id, 50), Err(MetasinoError::WrongState));
+            for _ in 0..4 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            }
+            call_as(accounts.charlie, 50);
+            assert_eq!(metasino.rebuy(table_id, 50), Err(MetasinoError::WrongState));
+
+            // A window running past the last block never closes.
+            call_as(accounts.alice, 0);
+            metasino.set_rebuy_limits(table_id, 1, BlockNumber::MAX).unwrap();
+            call_as(accounts.charlie, 50);
+            assert_eq!(metasino.rebuy(table_id, 50), Ok(()));
         }
 
         #[ink::test]
-        #[should_panic = "Required start bet must be greater than 0"]
-        fn initialize_with_zero_start_bet() {
-            Metasino::new(0);
+        fn blinds_are_posted_from_the_chips_after_the_button() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let (mut metasino, table_id) = staged_table();
+            assert_eq!(
+                metasino.set_blinds(table_id, 10, 5),
+                Err(MetasinoError::InvalidTableConfig)
+            );
+            metasino.set_blinds(table_id, 5, 10).unwrap();
+            for player in [accounts.bob, accounts.charlie] {
+                call_as(player, 50);
+                metasino.buy_chips(table_id, 50).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            assert_eq!(metasino.set_blinds(table_id, 5, 10), Err(MetasinoError::WrongState));
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+
+            assert_eq!(metasino.get_chips(table_id, accounts.bob), 45);
+            assert_eq!(metasino.get_chips(table_id, accounts.charlie), 40);
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(315));
+            let round = metasino.get_betting_round(table_id).unwrap();
+            assert_eq!((round.bets, round.active), (vec![0, 5, 10], 0));
+            assert_eq!(metasino.get_button_position(table_id), Ok(0));
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(
+                AccountId::from([0x42; 32]),
+                400,
+            );
+            call_as(accounts.alice, 0);
+            metasino.end_game(table_id, vec![accounts.charlie], vec![100]).unwrap();
+            assert_eq!(metasino.get_button_position(table_id), Ok(1));
         }
 
         #[ink::test]
-        #[should_panic = "Minimum 3 players required to start the game"]
-        fn less_than_minimum_player_unable_to_start_game(){
+        fn antes_are_put_in_before_the_blinds() {
             let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
-            let mut metasino = Metasino::new(100);
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
-            metasino.register_player(100);
-            metasino.start_game();
+            let (mut metasino, table_id) = staged_table();
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.set_ante(table_id, 5), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_ante(table_id, 5), Ok(()));
+            metasino.set_blinds(table_id, 5, 10).unwrap();
+            assert_eq!(metasino.get_table_info(table_id).unwrap().ante, 5);
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 50);
+                metasino.buy_chips(table_id, 50).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            assert_eq!(metasino.set_ante(table_id, 0), Err(MetasinoError::WrongState));
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+
+            assert_eq!(metasino.get_chips(table_id, accounts.alice), 45);
+            assert_eq!(metasino.get_chips(table_id, accounts.bob), 40);
+            assert_eq!(metasino.get_chips(table_id, accounts.charlie), 35);
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(330));
+            let round = metasino.get_betting_round(table_id).unwrap();
+            assert_eq!(round.bets, vec![0, 5, 10]);
+            assert!(round.all_in.iter().all(|all_in| !all_in));
         }
 
         #[ink::test]
-        fn able_to_start_game(){
+        fn pot_limit_tables_cap_raises_at_the_pot() {
             let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
-            let mut metasino = Metasino::new(100);
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
-            metasino.register_player(100);
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.charlie);
-            metasino.register_player(100);
-            metasino.start_game();
-            assert_eq!(metasino.get_table_state(), STATE::PLAYING);
+            let (mut metasino, table_id) = staged_table();
+            let pot_limit = BettingStructure::PotLimit;
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.set_betting_structure(table_id, pot_limit),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.set_betting_structure(table_id, pot_limit), Ok(()));
+            assert_eq!(metasino.get_table_info(table_id).unwrap().structure, pot_limit);
+            metasino.set_blinds(table_id, 5, 10).unwrap();
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 50);
+                metasino.buy_chips(table_id, 50).unwrap();
+            }
+            call_as(accounts.alice, 0);
+            metasino.start_game(table_id).unwrap();
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
+
+            let round = metasino.get_betting_round(table_id).unwrap();
+            assert_eq!((round.pot, round.active), (315, 0));
+            call_as(accounts.alice, 0);
+            // Calling 10 makes the pot 325, the most the raise adds.
+            assert_eq!(metasino.raise(table_id, 326), Err(MetasinoError::InvalidAction));
+            assert_eq!(metasino.raise(table_id, 30), Ok(()));
+            assert_eq!(metasino.get_betting_round(table_id).unwrap().pot, 355);
+            assert_eq!(metasino.get_chips(table_id, accounts.alice), 10);
         }
 
         #[ink::test]
-        #[should_panic = "Game is ongoing!!"]
-        fn fail_to_add_player_when_game_status_started(){
+        fn the_seat_left_of_the_big_blind_may_straddle() {
             let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
-            let mut metasino = Metasino::new(100);
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
-            metasino.register_player(100);
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.charlie);
-            metasino.register_player(100);
-            metasino.start_game();
+            let (mut metasino, table_id) = staged_table();
+            assert_eq!(metasino.post_straddle(table_id), Err(MetasinoError::WrongState));
+            metasino.set_blinds(table_id, 5, 10).unwrap();
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 50);
+                metasino.buy_chips(table_id, 50).unwrap();
+            }
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.post_straddle(table_id), Err(MetasinoError::NotAuthorized));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.post_straddle(table_id), Ok(()));
+            assert_eq!(metasino.post_straddle(table_id), Err(MetasinoError::WrongState));
+            metasino.start_game(table_id).unwrap();
+            for player in [accounts.alice, accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                metasino.reveal_seed(table_id, seed_of(player)).unwrap();
+            }
 
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.django);
-            metasino.register_player(100);
+            assert_eq!(metasino.get_chips(table_id, accounts.alice), 30);
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(335));
+            let round = metasino.get_betting_round(table_id).unwrap();
+            assert_eq!((round.bets, round.highest_bet, round.active), (vec![20, 5, 10], 20, 1));
+            call_as(accounts.alice, 0);
+            assert_eq!(metasino.post_straddle(table_id), Err(MetasinoError::WrongState));
         }
 
         #[ink::test]
-        #[should_panic = "Game is ongoing!!"]
-        fn should_not_allow_termination_if_table_game_in_started_state(){
+        fn private_tables_only_seat_invited_accounts() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.bob, 0);
+            assert_eq!(metasino.set_private(table_id, true), Err(MetasinoError::NotAuthorized));
+            assert_eq!(
+                metasino.invite(table_id, accounts.bob),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.alice, 0);
+            metasino.set_private(table_id, true).unwrap();
+            metasino.invite(table_id, accounts.bob).unwrap();
+            metasino.invite(table_id, accounts.charlie).unwrap();
+            metasino.revoke_invite(table_id, accounts.charlie).unwrap();
+            assert!(metasino.is_invited(table_id, accounts.bob));
+
+            call_as(accounts.charlie, 100);
+            assert_eq!(
+                metasino.register_player(table_id, None, None),
+                Err(MetasinoError::NotAuthorized)
+            );
+            call_as(accounts.bob, 100);
+            assert_eq!(metasino.register_player(table_id, None, None), Ok(()));
+            call_as(accounts.alice, 0);
+            metasino.set_private(table_id, false).unwrap();
+            call_as(accounts.charlie, 100);
+            assert_eq!(metasino.register_player(table_id, None, None), Ok(()));
+        }
+
+        #[ink::test]
+        fn join_code_gates_the_registration() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            let join_code_hash = metasino.compute_join_code_hash(b"friends".to_vec());
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, Some(join_code_hash), None)
+                .unwrap();
+            call_as(accounts.bob, 100);
+            assert_eq!(
+                metasino.register_player(table_id, None, None),
+                Err(MetasinoError::InvalidJoinCode)
+            );
+            assert_eq!(
+                metasino.register_player(table_id, Some(b"strangers".to_vec()), None),
+                Err(MetasinoError::InvalidJoinCode)
+            );
+            assert_eq!(metasino.register_player(table_id, Some(b"friends".to_vec()), None), Ok(()));
+            assert_eq!(metasino.get_players_count(table_id), Ok(2));
+        }
+
+        #[ink::test]
+        fn banned_players_cannot_register() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut metasino = Metasino::new();
+            call_as(accounts.eve, 100);
+            let table_id = metasino
+                .create_table(100, 2, 10, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.ban_player(None, accounts.bob), Err(MetasinoError::NotAuthorized));
+            metasino.ban_player(Some(table_id), accounts.bob).unwrap();
+            call_as(accounts.alice, 0);
+            assert_eq!(
+                metasino.ban_player(Some(table_id), accounts.charlie),
+                Err(MetasinoError::NotAuthorized)
+            );
+            metasino.ban_player(None, accounts.charlie).unwrap();
+            assert!(metasino.is_banned(None, accounts.charlie));
+            assert!(!metasino.is_banned(None, accounts.bob));
+
+            for player in [accounts.bob, accounts.charlie] {
+                call_as(player, 100);
+                assert_eq!(
+                    metasino.register_player(table_id, None, None),
+                    Err(MetasinoError::Banned)
+                );
+            }
+            call_as(accounts.alice, 0);
+            metasino.unban_player(None, accounts.charlie).unwrap();
+            call_as(accounts.charlie, 100);
+            assert_eq!(metasino.register_player(table_id, None, None), Ok(()));
+            let banned = ink_env::test::recorded_events()
+                .filter_map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).ok())
+                .filter(|event| matches!(event, Event::PlayerBanned(_)))
+                .count();
+            assert_eq!(banned, 2);
+        }
+
+        #[ink::test]
+        fn waitlisted_players_take_freed_seats() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 400);
+            let mut metasino = Metasino::new();
+            call_as(accounts.alice, 100);
+            let table_id = metasino
+                .create_table(100, 2, 2, None, GameKind::Poker, None, None)
+                .unwrap();
+            call_as(accounts.charlie, 100);
+            assert_eq!(metasino.join_waitlist(table_id, None), Err(MetasinoError::WrongState));
+            call_as(accounts.bob, 100);
+            metasino.register_player(table_id, None, None).unwrap();
+            for player in [accounts.charlie, accounts.django] {
+                call_as(player, 100);
+                metasino.join_waitlist(table_id, None).unwrap();
+            }
+            call_as(accounts.charlie, 100);
+            assert_eq!(
+                metasino.join_waitlist(table_id, None),
+                Err(MetasinoError::AlreadyRegistered)
+            );
+
+            let django_balance = metasino.get_claimable(accounts.django);
+            call_as(accounts.django, 0);
+            metasino.leave_waitlist(table_id).unwrap();
+            assert_eq!(metasino.get_claimable(accounts.django), django_balance + 100);
+            assert_eq!(metasino.leave_waitlist(table_id), Err(MetasinoError::NotAuthorized));
+
+            call_as(accounts.bob, 0);
+            metasino.leave_table(table_id).unwrap();
+            assert_eq!(
+                metasino.get_players(table_id, 0, MAX_PLAYERS),
+                Ok(vec![accounts.alice, accounts.charlie])
+            );
+            assert_eq!(metasino.get_waitlist(table_id), vec![]);
+            assert_eq!(metasino.get_accumulated_pot(table_id), Ok(200));
+            let seated = ink_env::test::recorded_events()
+                .filter_map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).ok())
+                .any(|event| matches!(event, Event::SeatedFromWaitlist(_)));
+            assert!(seated);
+        }
+
+        #[ink::test]
+        fn waitlists_are_capped_and_give_back_freeroll_entries() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let contract = AccountId::from([0x42; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 100);
+            let mut metasino = Metasino::new();
+            metasino.treasury = 100;
+            let freeroll = metasino.create_freeroll(50, 2).unwrap();
+            for player in [accounts.bob, accounts.charlie] {
+                call_as(player, 0);
+                metasino.register_player(freeroll, None, None).unwrap();
+            }
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.join_waitlist(freeroll, None), Ok(()));
+            assert_eq!(metasino.leave_waitlist(freeroll), Ok(()));
+            call_as(accounts.alice, 0);
+            let second = metasino.create_freeroll(50, 2).unwrap();
+            call_as(accounts.django, 0);
+            assert_eq!(metasino.register_player(second, None, None), Ok(()));
+            call_as(accounts.charlie, 0);
+            assert_eq!(
+                metasino.register_player(second, None, None),
+                Err(MetasinoError::FreerollLimitReached)
+            );
+            assert_eq!(metasino.leave_table(freeroll), Ok(()));
+            assert_eq!(metasino.register_player(second, None, None), Ok(()));
+
+            for seed in 0..MAX_WAITLIST as u8 {
+                call_as(AccountId::from([0x80 + seed; 32]), 0);
+                metasino.join_waitlist(second, None).unwrap();
+            }
+            call_as(accounts.eve, 0);
+            assert_eq!(metasino.join_waitlist(second, None), Err(MetasinoError::TableFull));
+            assert_eq!(metasino.get_waitlist(second).len(), MAX_WAITLIST);
+        }
+
+        #[ink::test]
+        fn messages_load_little_of_the_storage() {
+            use ink_storage::traits::{
+                pull_spread_root,
+                push_spread_root,
+            };
+            let contract = AccountId::from([0x42; 32]);
+            let cells = || {
+                ink_env::test::get_contract_storage_rw::<ink_env::DefaultEnvironment>(&contract)
+            };
+            let (mut metasino, table_id) = revealed_table();
+            let round_size = |metasino: &Metasino| {
+                scale::Encode::encoded_size(&metasino.rounds.get(table_id).unwrap())
+            };
+            let dealt = round_size(&metasino);
+
+            // Every message loads the root and stores it back: a cell for each
+            // of its 27 plain fields, the `Option`s being `None`, and two for
+            // the pair of `block_withdrawals`. The mappings and lazy fields keep
+            // their values in cells of their own, loaded only when asked for.
+            let root = ink_primitives::Key::from([0; 32]);
+            let (_, written) = cells();
+            push_spread_root(&metasino, &root);
+            let (read, stored) = cells();
+            let _: Metasino = pull_spread_root(&root);
+            assert_eq!((stored - written, cells().0 - read), (29, 29));
+
+            // The betting round does not grow with the actions played.
+            for _ in 0..9 {
+                let seat = metasino.rounds.get(table_id).unwrap().active;
+                call_as(metasino.seats.get((table_id, seat)).unwrap(), 0);
+                metasino.check(table_id).unwrap();
+            }
+            assert_eq!(round_size(&metasino), dealt);
+            assert_eq!(metasino.get_spectator_view(table_id).unwrap().history.len(), 9);
+        }
+
+        #[ink::test]
+        fn end_game_rejects_invalid_payouts() {
             let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
-            let mut metasino = Metasino::new(100);
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
-            metasino.register_player(100);
-            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.charlie);
-            metasino.register_player(100);
-            metasino.start_game();
-            metasino.terminate();
+            let (mut metasino, table_id) = started_table();
+            assert_eq!(
+                metasino.end_game(table_id, vec![accounts.bob], vec![50]),
+                Err(MetasinoError::InvalidPayout)
+            );
+            assert_eq!(
+                metasino.end_game(table_id, vec![accounts.django], vec![100]),
+                Err(MetasinoError::InvalidPayout)
+            );
+            assert_eq!(
+                metasino.end_game(table_id, vec![accounts.bob, accounts.bob], vec![50, 50]),
+                Err(MetasinoError::InvalidPayout)
+            );
+            call_as(accounts.bob, 0);
+            assert_eq!(
+                metasino.end_game(table_id, vec![accounts.bob], vec![100]),
+                Err(MetasinoError::NotAuthorized)
+            );
+            assert_eq!(metasino.get_table_state(table_id), Ok(STATE::PLAYING));
         }
     }
 }
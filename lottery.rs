@@ -0,0 +1,146 @@
+//! Lotteries drawn once a block height is reached.
+//!
+//! Anyone buys numbered tickets at the price of the lottery, committing to a
+//! seed with each ticket. Every commitment is hashed into the entropy of the
+//! lottery, and the winning ticket is drawn out of it once the draw block is
+//! reached, so that no single buyer decides the outcome.
+
+use crate::metasino::{
+    MetasinoError,
+    Result,
+};
+use ink_env::{
+    hash::{
+        Blake2x256,
+        HashOutput,
+    },
+    DefaultEnvironment,
+    Environment,
+};
+use ink_storage::traits::{
+    PackedLayout,
+    SpreadLayout,
+};
+
+type Balance = <DefaultEnvironment as Environment>::Balance;
+type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+type Hash = <DefaultEnvironment as Environment>::Hash;
+
+/// A lottery and the tickets sold for it.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub struct Lottery {
+    /// The price of a ticket.
+    pub ticket_price: Balance,
+    /// The first block in which the lottery may be drawn.
+    pub draw_at: BlockNumber,
+    /// The number of tickets sold, numbered from 0.
+    pub tickets: u32,
+    /// The commitments of the tickets hashed together.
+    pub entropy: [u8; 32],
+    /// Whether the lottery was drawn.
+    pub drawn: bool,
+    /// The winning ticket, once drawn, if any was sold.
+    pub winner: Option<u32>,
+}
+
+impl Lottery {
+    /// Open a lottery selling tickets at `ticket_price` until `draw_at`.
+    pub fn new(ticket_price: Balance, draw_at: BlockNumber) -> Self {
+        Self {
+            ticket_price,
+            draw_at,
+            tickets: 0,
+            entropy: [0; 32],
+            drawn: false,
+            winner: None,
+        }
+    }
+
+    /// Sell the next ticket, mixing its commitment into the entropy.
+    /// Returns the number of the ticket.
+    pub fn add_ticket(&mut self, commitment: &Hash) -> u32 {
+        let mut output = <Blake2x256 as HashOutput>::Type::default();
+        ink_env::hash_encoded::<Blake2x256, _>(&(self.entropy, commitment), &mut output);
+        self.entropy = output;
+        self.tickets += 1;
+        self.tickets - 1
+    }
+
+    /// The prize pool made of every ticket sold.
+    /// error if the pool overflows.
+    pub fn pool(&self) -> Result<Balance> {
+        self.ticket_price
+            .checked_mul(self.tickets as Balance)
+            .ok_or(MetasinoError::ArithmeticError)
+    }
+
+    /// Draw the winning ticket out of the entropy.
+    /// error if the lottery was already drawn.
+    /// error if the draw block is not reached.
+    pub fn draw(&mut self, now: BlockNumber) -> Result<Option<u32>> {
+        if self.drawn {
+            return Err(MetasinoError::WrongState);
+        }
+        if now < self.draw_at {
+            return Err(MetasinoError::DeadlineNotReached);
+        }
+        self.drawn = true;
+        if self.tickets > 0 {
+            let draw = u32::from_le_bytes([
+                self.entropy[0],
+                self.entropy[1],
+                self.entropy[2],
+                self.entropy[3],
+            ]);
+            self.winner = Some(draw % self.tickets);
+        }
+        Ok(self.winner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tickets_are_numbered_and_change_the_entropy() {
+        let mut lottery = Lottery::new(10, 5);
+        assert_eq!(lottery.add_ticket(&Hash::from([1; 32])), 0);
+        let entropy = lottery.entropy;
+        assert_eq!(lottery.add_ticket(&Hash::from([2; 32])), 1);
+        assert_ne!(lottery.entropy, entropy);
+        assert_eq!(lottery.pool(), Ok(20));
+
+        let mut expensive = Lottery::new(Balance::MAX, 5);
+        expensive.add_ticket(&Hash::from([1; 32]));
+        assert_eq!(expensive.pool(), Ok(Balance::MAX));
+        expensive.add_ticket(&Hash::from([2; 32]));
+        assert_eq!(expensive.pool(), Err(MetasinoError::ArithmeticError));
+    }
+
+    #[test]
+    fn draw_waits_for_the_draw_block_and_happens_once() {
+        let mut lottery = Lottery::new(10, 5);
+        for byte in 0..3 {
+            lottery.add_ticket(&Hash::from([byte; 32]));
+        }
+        assert_eq!(lottery.draw(4), Err(MetasinoError::DeadlineNotReached));
+        let winner = lottery.draw(5).unwrap();
+        assert!(winner.is_some_and(|ticket| ticket < 3));
+        assert_eq!(lottery.draw(6), Err(MetasinoError::WrongState));
+        assert_eq!(Lottery::new(10, 0).draw(0), Ok(None));
+    }
+}
@@ -0,0 +1,278 @@
+//! The layouts of the storage written by the earlier versions of the code.
+//!
+//! The root of the contract is laid out field after field, each `Mapping`
+//! keying its entries by the cell it sits in, so a field added or moved
+//! shifts the cells and the entries of all the fields after it. The storage
+//! of an earlier version cannot be read through the fields of this code,
+//! it is read through the layout of its version instead, by the migration
+//! step of [`Metasino::migrate`](crate::metasino::Metasino::migrate) upgrading
+//! it to the next version.
+//!
+//! The root itself is loaded before any message runs though, through the
+//! fields of the code running it, and the cells of the root of version 1 do
+//! not decode as those of this code: on chain, a contract of version 1
+//! upgraded to this code fails every message before `migrate` gets to run.
+//! Upgrading a deployed contract of version 1 takes an intermediate code
+//! whose root is still that of version 1, running the steps below before
+//! switching to this code, which this crate does not build. The steps are
+//! run by the tests, on storage laid out as version 1 left it.
+
+/// The layout of version 1, in which the root held the rake of the house
+/// along with the tables, the players, the bets, the games and the
+/// statistics, and none of what was added since.
+pub mod v1 {
+    use crate::betting::{
+        self,
+        BettingStructure,
+        Street,
+    };
+    use crate::cards::Variant;
+    use crate::metasino::{
+        self,
+        GameId,
+        GameKind,
+        GameRecord,
+        TableId,
+        STATE,
+    };
+    use ink_env::{
+        DefaultEnvironment,
+        Environment,
+    };
+    use ink_prelude::vec::Vec;
+    use ink_primitives::Key;
+    use ink_storage::{
+        traits::{
+            PackedLayout,
+            SpreadAllocate,
+            SpreadLayout,
+        },
+        Mapping,
+    };
+
+    type AccountId = <DefaultEnvironment as Environment>::AccountId;
+    type Balance = <DefaultEnvironment as Environment>::Balance;
+    type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+    type Hash = <DefaultEnvironment as Environment>::Hash;
+
+    /// The root of the contract.
+    #[derive(SpreadLayout, SpreadAllocate)]
+    pub struct Root {
+        pub owner: AccountId,
+        pub rake_bps: u16,
+        pub rake_cap: Balance,
+        pub treasury: Balance,
+        pub paused: bool,
+        pub storage_version: u16,
+        pub token_treasury: Mapping<AccountId, Balance>,
+        pub tables: Mapping<TableId, Table>,
+        pub next_table_id: TableId,
+        pub players: Mapping<(TableId, AccountId), PlayerInfo>,
+        pub seats: Mapping<(TableId, u8), AccountId>,
+        pub commitments: Mapping<(TableId, AccountId), Hash>,
+        pub rounds: Mapping<TableId, BettingRound>,
+        pub games: Mapping<GameId, GameRecord>,
+        pub next_game_id: GameId,
+        pub stats: Mapping<AccountId, PlayerStats>,
+        pub leaderboard: Vec<AccountId>,
+    }
+
+    impl Root {
+        /// Load the root, the cells of which the code of version 1 always wrote.
+        pub fn load() -> Self {
+            ink_storage::traits::pull_spread_root(&Key::from([0x00; 32]))
+        }
+
+        /// A root with every field empty, its mappings reading and writing
+        /// the entries of version 1, loading nothing.
+        pub fn new() -> Self {
+            SpreadAllocate::allocate_spread(&mut Key::from([0x00; 32]).into())
+        }
+    }
+
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    pub struct Table {
+        pub initializer: AccountId,
+        pub seated: u8,
+        pub required_start_bet: Balance,
+        pub pot: Balance,
+        pub state: STATE,
+        pub min_players: u8,
+        pub max_players: u8,
+        pub reveal_deadline: BlockNumber,
+        pub revealed: u8,
+        pub entropy: [u8; 32],
+        pub seed: Option<[u8; 32]>,
+        pub rake_bps: u16,
+        pub rake_cap: Balance,
+        pub token: Option<AccountId>,
+    }
+
+    impl Table {
+        /// The poker table it is, staged anew in `block` if staging, without
+        /// any of the options added since nor a bond, which version 1 did
+        /// not take.
+        pub fn upgrade(self, block: BlockNumber) -> metasino::Table {
+            metasino::Table {
+                initializer: self.initializer,
+                seated: self.seated,
+                required_start_bet: self.required_start_bet,
+                pot: self.pot,
+                state: self.state,
+                min_players: self.min_players,
+                max_players: self.max_players,
+                reveal_deadline: self.reveal_deadline,
+                revealed: self.revealed,
+                entropy: self.entropy,
+                seed: self.seed,
+                rake_bps: self.rake_bps,
+                rake_cap: self.rake_cap,
+                rake_recipient: None,
+                token: self.token,
+                game: GameKind::Poker,
+                max_rebuys: 0,
+                rebuy_window: 0,
+                button: 0,
+                small_blind: 0,
+                big_blind: 0,
+                ante: 0,
+                straddler: None,
+                hand_number: 0,
+                variant: Variant::Holdem,
+                structure: BettingStructure::NoLimit,
+                is_private: false,
+                join_code_hash: None,
+                start_at: None,
+                sit_and_go: false,
+                freeroll: false,
+                staged_at: block,
+                arbiter: None,
+                min_reputation: None,
+                bond: 0,
+                dealer_nominee: None,
+                dealer: None,
+                dealer_bond: 0,
+                dealer_deadline: None,
+            }
+        }
+    }
+
+    #[derive(
+        Debug,
+        Copy,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    pub struct PlayerInfo {
+        pub seat: u8,
+        pub revealed: bool,
+    }
+
+    impl PlayerInfo {
+        /// The player, registered in `block` for the first hand of the table.
+        pub fn upgrade(self, block: BlockNumber) -> metasino::PlayerInfo {
+            metasino::PlayerInfo {
+                seat: self.seat,
+                revealed: self.revealed,
+                registered_at: block,
+                rebuys: 0,
+                hand: 0,
+                penalized: false,
+            }
+        }
+    }
+
+    #[derive(
+        Debug,
+        Default,
+        Copy,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    pub struct PlayerStats {
+        pub games_played: u32,
+        pub games_won: u32,
+        pub total_wagered: Balance,
+        pub total_won: Balance,
+    }
+
+    impl PlayerStats {
+        /// The statistics, with a neutral reputation.
+        pub fn upgrade(self) -> metasino::PlayerStats {
+            metasino::PlayerStats {
+                games_played: self.games_played,
+                games_won: self.games_won,
+                total_wagered: self.total_wagered,
+                total_won: self.total_won,
+                reputation: 0,
+            }
+        }
+    }
+
+    #[derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        SpreadLayout,
+        PackedLayout,
+    )]
+    pub struct BettingRound {
+        pub street: Street,
+        pub bets: Vec<Balance>,
+        pub contributed: Vec<Balance>,
+        pub folded: Vec<bool>,
+        pub all_in: Vec<bool>,
+        pub acted: Vec<bool>,
+        pub active: u8,
+        pub deadline: BlockNumber,
+        pub highest_bet: Balance,
+        pub finished: bool,
+    }
+
+    impl BettingRound {
+        /// The no-limit betting of the hand, whose whole pot is `pot`, the
+        /// streets after the pre-flop starting from the first seat as they
+        /// did in version 1.
+        pub fn upgrade(self, pot: Balance) -> betting::BettingRound {
+            betting::BettingRound {
+                street: self.street,
+                bets: self.bets,
+                contributed: self.contributed,
+                folded: self.folded,
+                all_in: self.all_in,
+                acted: self.acted,
+                active: self.active,
+                first: 0,
+                deadline: self.deadline,
+                highest_bet: self.highest_bet,
+                finished: self.finished,
+                plays: 0,
+                structure: BettingStructure::NoLimit,
+                small_bet: 0,
+                pot,
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Parimutuel markets on the outcome of events outside the contract.
+//!
+//! An operator opens a market with a number of outcomes and a lock block.
+//! Until the lock block anyone stakes on an outcome, and once it is reached a
+//! result signer reports the outcome that happened. The stakes on the other
+//! outcomes, minus the rake of the house, are then shared between the stakes
+//! on the winning outcome in proportion to their size. A market nobody staked
+//! the winning outcome on gives every stake back.
+
+use crate::metasino::{
+    MetasinoError,
+    Result,
+};
+use ink_env::{
+    DefaultEnvironment,
+    Environment,
+};
+use ink_prelude::vec::Vec;
+use ink_storage::traits::{
+    PackedLayout,
+    SpreadLayout,
+};
+
+type Balance = <DefaultEnvironment as Environment>::Balance;
+type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+
+/// The basis points making up the whole of the losing stakes.
+const MAX_BPS: Balance = 10_000;
+
+/// A market and the stakes placed on each of its outcomes.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub struct Market {
+    /// The first block in which no stake is taken and the result may be reported.
+    pub lock_at: BlockNumber,
+    /// The total staked on each outcome.
+    pub stakes: Vec<Balance>,
+    /// The outcome reported, once the market is resolved.
+    pub result: Option<u8>,
+    /// The rake taken by the house out of the losing stakes.
+    pub rake: Balance,
+    /// The payouts claimed by the bettors so far.
+    pub claimed: Balance,
+}
+
+impl Market {
+    /// Open a market on `outcomes` outcomes taking stakes until `lock_at`.
+    pub fn new(outcomes: u8, lock_at: BlockNumber) -> Self {
+        Self {
+            lock_at,
+            stakes: ink_prelude::vec![0; outcomes as usize],
+            result: None,
+            rake: 0,
+            claimed: 0,
+        }
+    }
+
+    /// Add `amount` to the stakes on the outcome in block `now`.
+    /// error if the market is locked.
+    /// error if the outcome does not exist or nothing is staked.
+    pub fn stake(&mut self, outcome: u8, amount: Balance, now: BlockNumber) -> Result<()> {
+        if self.result.is_some() || now >= self.lock_at {
+            return Err(MetasinoError::WrongState);
+        }
+        if outcome as usize >= self.stakes.len() {
+            return Err(MetasinoError::InvalidAction);
+        }
+        if amount == 0 {
+            return Err(MetasinoError::WrongBet);
+        }
+        let total = &mut self.stakes[outcome as usize];
+        *total = total.checked_add(amount).ok_or(MetasinoError::ArithmeticError)?;
+        Ok(())
+    }
+
+    /// Resolve the market with the outcome reported in block `now`, taking
+    /// `rake_bps` basis points of the losing stakes, up to `rake_cap`.
+    /// Returns the rake.
+    /// error if the market is resolved or not locked yet.
+    /// error if the outcome does not exist.
+    pub fn resolve(
+        &mut self,
+        outcome: u8,
+        now: BlockNumber,
+        rake_bps: u16,
+        rake_cap: Balance,
+    ) -> Result<Balance> {
+        if self.result.is_some() {
+            return Err(MetasinoError::WrongState);
+        }
+        if now < self.lock_at {
+            return Err(MetasinoError::DeadlineNotReached);
+        }
+        let winning = *self
+            .stakes
+            .get(outcome as usize)
+            .ok_or(MetasinoError::InvalidAction)?;
+        if winning > 0 {
+            let losing = self.pool()? - winning;
+            self.rake = mul_div(losing, rake_bps as Balance, MAX_BPS)?.min(rake_cap);
+        }
+        self.result = Some(outcome);
+        Ok(self.rake)
+    }
+
+    /// What a bettor gets back out of the resolved market for their stakes
+    /// on each outcome: their winning stake along with its share of the
+    /// losing stakes minus the rake, or every stake if nobody won.
+    /// error if the market is not resolved.
+    pub fn payout(&self, stakes: &[Balance]) -> Result<Balance> {
+        let outcome = self.result.ok_or(MetasinoError::WrongState)? as usize;
+        let winning = self.stakes[outcome];
+        if winning == 0 {
+            return stakes
+                .iter()
+                .try_fold(0, |total: Balance, stake| total.checked_add(*stake))
+                .ok_or(MetasinoError::ArithmeticError)
+        }
+        let stake = stakes.get(outcome).copied().unwrap_or_default();
+        let losing = self.pool()? - winning - self.rake;
+        stake
+            .checked_add(mul_div(losing, stake, winning)?)
+            .ok_or(MetasinoError::ArithmeticError)
+    }
+
+    /// Pay out the stakes of a bettor on each outcome as by [`Market::payout`],
+    /// counting the payout as claimed.
+    /// error if the market is not resolved.
+    pub fn claim(&mut self, stakes: &[Balance]) -> Result<Balance> {
+        let payout = self.payout(stakes)?;
+        self.claimed = self
+            .claimed
+            .checked_add(payout)
+            .ok_or(MetasinoError::ArithmeticError)?;
+        Ok(payout)
+    }
+
+    /// What the market still owes its bettors: every stake until resolved,
+    /// then the pool less the rake and the payouts claimed.
+    /// error if the pool overflows.
+    pub fn unclaimed(&self) -> Result<Balance> {
+        Ok(self.pool()?.saturating_sub(self.rake).saturating_sub(self.claimed))
+    }
+
+    /// Everything staked on the market.
+    /// error if the pool overflows.
+    pub fn pool(&self) -> Result<Balance> {
+        self.stakes
+            .iter()
+            .try_fold(0, |total: Balance, stake| total.checked_add(*stake))
+            .ok_or(MetasinoError::ArithmeticError)
+    }
+}
+
+/// `amount * numerator / denominator`, rounded down.
+fn mul_div(amount: Balance, numerator: Balance, denominator: Balance) -> Result<Balance> {
+    amount
+        .checked_mul(numerator)
+        .and_then(|product| product.checked_div(denominator))
+        .ok_or(MetasinoError::ArithmeticError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stakes_are_taken_until_the_lock_block() {
+        let mut market = Market::new(3, 10);
+        assert_eq!(market.stake(1, 50, 9), Ok(()));
+        assert_eq!(market.stake(1, 25, 0), Ok(()));
+        assert_eq!(market.stakes, vec![0, 75, 0]);
+        assert_eq!(market.stake(3, 50, 0), Err(MetasinoError::InvalidAction));
+        assert_eq!(market.stake(0, 0, 0), Err(MetasinoError::WrongBet));
+        assert_eq!(market.stake(0, 50, 10), Err(MetasinoError::WrongState));
+        assert_eq!(market.payout(&[0, 75, 0]), Err(MetasinoError::WrongState));
+    }
+
+    #[test]
+    fn losing_stakes_go_to_the_winners_pro_rata() {
+        let mut market = Market::new(2, 10);
+        market.stake(0, 100, 0).unwrap();
+        market.stake(0, 300, 0).unwrap();
+        market.stake(1, 200, 0).unwrap();
+        assert_eq!(market.resolve(0, 9, 500, 50), Err(MetasinoError::DeadlineNotReached));
+        assert_eq!(market.resolve(2, 10, 500, 50), Err(MetasinoError::InvalidAction));
+        assert_eq!(market.resolve(0, 10, 500, 50), Ok(10));
+        assert_eq!(market.resolve(1, 10, 500, 50), Err(MetasinoError::WrongState));
+        assert_eq!(market.payout(&[100, 0]), Ok(147));
+        assert_eq!(market.payout(&[300, 0]), Ok(442));
+        assert_eq!(market.payout(&[0, 200]), Ok(0));
+    }
+
+    #[test]
+    fn claims_are_taken_out_of_what_is_owed() {
+        let mut market = Market::new(2, 10);
+        market.stake(0, 100, 0).unwrap();
+        market.stake(0, 300, 0).unwrap();
+        market.stake(1, 200, 0).unwrap();
+        assert_eq!(market.unclaimed(), Ok(600));
+        market.resolve(0, 10, 500, 50).unwrap();
+        assert_eq!(market.unclaimed(), Ok(590));
+        assert_eq!(market.claim(&[100, 0]), Ok(147));
+        assert_eq!(market.claim(&[0, 200]), Ok(0));
+        assert_eq!(market.unclaimed(), Ok(443));
+        assert_eq!(market.claim(&[300, 0]), Ok(442));
+        assert_eq!(market.unclaimed(), Ok(1));
+    }
+
+    #[test]
+    fn stakes_are_given_back_when_nobody_won() {
+        let mut market = Market::new(3, 10);
+        market.stake(0, 100, 0).unwrap();
+        market.stake(1, 200, 0).unwrap();
+        assert_eq!(market.resolve(2, 10, 500, 50), Ok(0));
+        assert_eq!(market.payout(&[100, 0, 0]), Ok(100));
+        assert_eq!(market.payout(&[40, 60, 0]), Ok(100));
+    }
+}
@@ -0,0 +1,82 @@
+//! Cross-contract calls to PSP22 fungible token contracts.
+//!
+//! Tables may take their buy-ins in a PSP22 token instead of the native
+//! balance. The calls are built by hand with the selectors of the standard
+//! so that any compliant token contract can be used.
+
+use crate::metasino::{
+    MetasinoError,
+    Result,
+};
+use ink_env::{
+    call::{
+        build_call,
+        Call,
+        ExecutionInput,
+        Selector,
+    },
+    DefaultEnvironment,
+    Environment,
+};
+use ink_prelude::{
+    string::String,
+    vec::Vec,
+};
+
+type AccountId = <DefaultEnvironment as Environment>::AccountId;
+type Balance = <DefaultEnvironment as Environment>::Balance;
+
+/// The selector of `PSP22::transfer`.
+const TRANSFER: [u8; 4] = [0xdb, 0x20, 0xf9, 0xf5];
+/// The selector of `PSP22::transfer_from`.
+const TRANSFER_FROM: [u8; 4] = [0x54, 0xb3, 0xc7, 0x6e];
+
+/// The errors returned by a PSP22 token contract.
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+pub enum PSP22Error {
+    Custom(String),
+    InsufficientBalance,
+    InsufficientAllowance,
+    ZeroRecipientAddress,
+    ZeroSenderAddress,
+    SafeTransferCheckFailed(String),
+}
+
+/// Send `value` of the token held by the contract to `to`.
+pub fn transfer(token: AccountId, to: AccountId, value: Balance) -> Result<()> {
+    build_call::<DefaultEnvironment>()
+        .call_type(Call::new().callee(token))
+        .exec_input(
+            ExecutionInput::new(Selector::new(TRANSFER))
+                .push_arg(to)
+                .push_arg(value)
+                .push_arg(Vec::<u8>::new()),
+        )
+        .returns::<core::result::Result<(), PSP22Error>>()
+        .fire()
+        .map_err(|_| MetasinoError::TransferFailed)?
+        .map_err(|_| MetasinoError::TransferFailed)
+}
+
+/// Move `value` of the token from `from` to `to`, spending the allowance
+/// `from` gave to the contract.
+pub fn transfer_from(
+    token: AccountId,
+    from: AccountId,
+    to: AccountId,
+    value: Balance,
+) -> Result<()> {
+    build_call::<DefaultEnvironment>()
+        .call_type(Call::new().callee(token))
+        .exec_input(
+            ExecutionInput::new(Selector::new(TRANSFER_FROM))
+                .push_arg(from)
+                .push_arg(to)
+                .push_arg(value)
+                .push_arg(Vec::<u8>::new()),
+        )
+        .returns::<core::result::Result<(), PSP22Error>>()
+        .fire()
+        .map_err(|_| MetasinoError::TransferFailed)?
+        .map_err(|_| MetasinoError::TransferFailed)
+}
@@ -0,0 +1,96 @@
+//! Cross-contract calls to PSP34 non-fungible token contracts.
+//!
+//! The achievements of the players are minted as badges of a PSP34 contract
+//! configured by the owner, which grants Metasino the right to mint and is
+//! expected to refuse transfers so that the badges stay soulbound. The calls
+//! are built by hand with the selectors of the standard and its mintable
+//! extension. The tickets entering tournaments are PSP34 tokens as well.
+
+use crate::metasino::{
+    MetasinoError,
+    Result,
+};
+use ink_env::{
+    call::{
+        build_call,
+        Call,
+        ExecutionInput,
+        Selector,
+    },
+    DefaultEnvironment,
+    Environment,
+};
+use ink_prelude::{
+    string::String,
+    vec::Vec,
+};
+
+type AccountId = <DefaultEnvironment as Environment>::AccountId;
+
+/// The selector of `PSP34::owner_of`.
+const OWNER_OF: [u8; 4] = ink_lang::selector_bytes!("PSP34::owner_of");
+/// The selector of `PSP34::transfer`.
+const TRANSFER: [u8; 4] = ink_lang::selector_bytes!("PSP34::transfer");
+/// The selector of `PSP34Mintable::mint`.
+const MINT: [u8; 4] = ink_lang::selector_bytes!("PSP34Mintable::mint");
+
+/// The id of a token of a PSP34 contract.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+pub enum Id {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Bytes(Vec<u8>),
+}
+
+/// The errors returned by a PSP34 token contract.
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+pub enum PSP34Error {
+    Custom(String),
+    SelfApprove,
+    NotApproved,
+    TokenExists,
+    TokenNotExists,
+    SafeTransferCheckFailed(String),
+}
+
+/// Mint the token `id` of the contract to `to`.
+pub fn mint(contract: AccountId, to: AccountId, id: Id) -> Result<()> {
+    build_call::<DefaultEnvironment>()
+        .call_type(Call::new().callee(contract))
+        .exec_input(ExecutionInput::new(Selector::new(MINT)).push_arg(to).push_arg(id))
+        .returns::<core::result::Result<(), PSP34Error>>()
+        .fire()
+        .map_err(|_| MetasinoError::TransferFailed)?
+        .map_err(|_| MetasinoError::TransferFailed)
+}
+
+/// The owner of the token `id` of the contract, if it exists.
+pub fn owner_of(contract: AccountId, id: Id) -> Result<Option<AccountId>> {
+    build_call::<DefaultEnvironment>()
+        .call_type(Call::new().callee(contract))
+        .exec_input(ExecutionInput::new(Selector::new(OWNER_OF)).push_arg(id))
+        .returns::<Option<AccountId>>()
+        .fire()
+        .map_err(|_| MetasinoError::TransferFailed)
+}
+
+/// Send the token `id` of the contract to `to`, the contract being allowed
+/// to by its owner.
+pub fn transfer(contract: AccountId, to: AccountId, id: Id) -> Result<()> {
+    build_call::<DefaultEnvironment>()
+        .call_type(Call::new().callee(contract))
+        .exec_input(
+            ExecutionInput::new(Selector::new(TRANSFER))
+                .push_arg(to)
+                .push_arg(id)
+                .push_arg(Vec::<u8>::new()),
+        )
+        .returns::<core::result::Result<(), PSP34Error>>()
+        .fire()
+        .map_err(|_| MetasinoError::TransferFailed)?
+        .map_err(|_| MetasinoError::TransferFailed)
+}
@@ -0,0 +1,191 @@
+//! European roulette, a single zero wheel of 37 pockets.
+//!
+//! Players place typed bets before the spin, the pocket is drawn from the seed
+//! of the table and each winning bet is paid at the standard odds on top of
+//! getting the amount bet back.
+
+use crate::metasino::{
+    MetasinoError,
+    Result,
+};
+use ink_env::{
+    DefaultEnvironment,
+    Environment,
+};
+use ink_storage::traits::{
+    PackedLayout,
+    SpreadLayout,
+};
+
+type Balance = <DefaultEnvironment as Environment>::Balance;
+
+/// The number of pockets of the wheel, from 0 to 36.
+const POCKETS: u32 = 37;
+
+/// The red numbers of the wheel, every other number but zero being black.
+const RED: [u8; 18] = [1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36];
+
+/// A bet on the outcome of the spin.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub enum RouletteBet {
+    /// A single number from 0 to 36, paying 35:1.
+    Straight(u8),
+    /// Any red number, paying 1:1.
+    Red,
+    /// Any black number, paying 1:1.
+    Black,
+    /// Any odd number, paying 1:1.
+    Odd,
+    /// Any even number but zero, paying 1:1.
+    Even,
+    /// The first, second or third dozen of numbers, paying 2:1.
+    Dozen(u8),
+}
+
+impl RouletteBet {
+    /// Whether the bet designates existing pockets.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            RouletteBet::Straight(number) => *number < POCKETS as u8,
+            RouletteBet::Dozen(dozen) => (1..=3).contains(dozen),
+            _ => true,
+        }
+    }
+
+    /// Whether the bet wins when the ball lands in the pocket.
+    pub fn wins(&self, pocket: u8) -> bool {
+        match self {
+            RouletteBet::Straight(number) => *number == pocket,
+            _ if pocket == 0 => false,
+            RouletteBet::Red => RED.contains(&pocket),
+            RouletteBet::Black => !RED.contains(&pocket),
+            RouletteBet::Odd => pocket % 2 == 1,
+            RouletteBet::Even => pocket.is_multiple_of(2),
+            RouletteBet::Dozen(dozen) => (pocket - 1) / 12 + 1 == *dozen,
+        }
+    }
+
+    /// The odds paid by the bet.
+    pub fn odds(&self) -> Balance {
+        match self {
+            RouletteBet::Straight(_) => 35,
+            RouletteBet::Dozen(_) => 2,
+            _ => 1,
+        }
+    }
+
+    /// What the bet of `amount` returns when the ball lands in the pocket,
+    /// the amount bet included.
+    /// error if the payout overflows.
+    pub fn payout(&self, amount: Balance, pocket: u8) -> Result<Balance> {
+        if !self.wins(pocket) {
+            return Ok(0)
+        }
+        amount
+            .checked_mul(self.odds() + 1)
+            .ok_or(MetasinoError::ArithmeticError)
+    }
+}
+
+/// A bet placed by a player along with its amount.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub struct Wager {
+    /// What the bet is on.
+    pub bet: RouletteBet,
+    /// The amount bet.
+    pub amount: Balance,
+}
+
+/// The pocket the ball lands in for the given seed.
+pub fn spin(seed: &[u8; 32]) -> u8 {
+    let draw = u32::from_le_bytes([seed[0], seed[1], seed[2], seed[3]]);
+    (draw % POCKETS) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bets_win_on_their_pockets() {
+        assert!(RouletteBet::Straight(17).wins(17));
+        assert!(!RouletteBet::Straight(17).wins(18));
+        assert!(RouletteBet::Straight(0).wins(0));
+        assert!(RouletteBet::Red.wins(1) && !RouletteBet::Red.wins(2));
+        assert!(RouletteBet::Black.wins(2) && !RouletteBet::Black.wins(36));
+        assert!(RouletteBet::Odd.wins(35) && RouletteBet::Even.wins(36));
+        assert!(RouletteBet::Dozen(1).wins(12) && RouletteBet::Dozen(2).wins(13));
+        assert!(RouletteBet::Dozen(3).wins(36));
+        for bet in [
+            RouletteBet::Red,
+            RouletteBet::Black,
+            RouletteBet::Odd,
+            RouletteBet::Even,
+            RouletteBet::Dozen(1),
+        ] {
+            assert!(!bet.wins(0));
+        }
+    }
+
+    #[test]
+    fn payouts_follow_the_odds() {
+        assert_eq!(RouletteBet::Straight(7).payout(10, 7), Ok(360));
+        assert_eq!(RouletteBet::Red.payout(10, 7), Ok(20));
+        assert_eq!(RouletteBet::Dozen(1).payout(10, 7), Ok(30));
+        assert_eq!(RouletteBet::Dozen(2).payout(10, 7), Ok(0));
+    }
+
+    #[test]
+    fn overflowing_payouts_are_rejected() {
+        let max = Balance::MAX / 36;
+        assert_eq!(RouletteBet::Straight(7).payout(max, 7), Ok(max * 36));
+        assert_eq!(
+            RouletteBet::Straight(7).payout(max + 1, 7),
+            Err(MetasinoError::ArithmeticError)
+        );
+        assert_eq!(RouletteBet::Straight(7).payout(Balance::MAX, 8), Ok(0));
+    }
+
+    #[test]
+    fn invalid_bets_are_detected() {
+        assert!(RouletteBet::Straight(36).is_valid());
+        assert!(!RouletteBet::Straight(37).is_valid());
+        assert!(!RouletteBet::Dozen(0).is_valid());
+        assert!(!RouletteBet::Dozen(4).is_valid());
+    }
+
+    #[test]
+    fn spin_lands_on_the_wheel() {
+        assert_eq!(spin(&[0; 32]), 0);
+        assert_eq!(spin(&[36; 32]), (u32::from_le_bytes([36; 4]) % 37) as u8);
+        assert!((0..=255).all(|byte| spin(&[byte; 32]) < 37));
+    }
+}
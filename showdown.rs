@@ -0,0 +1,294 @@
+//! Showdown of poker hands dealt as commitments.
+//!
+//! The shuffle seed of a table orders the deck: its first five cards are the
+//! board and the rest are the hole cards, dealt to the players by positions
+//! in the deck agreed off-chain. Before the seed is derived each player
+//! commits to the positions of their hole cards along with a salt, so
+//! that nobody else learns them and nobody picks them once the deck is
+//! known. At the showdown the players still in the hand reveal the positions
+//! and the salt, and every pot goes to the best hand revealed among the
+//! seats eligible to it. A player who does not reveal forfeits the hand.
+//! The variant of the table decides the deck, the number of hole cards and
+//! how the hands are scored. Seven-card stud deals no board: its hole cards
+//! are the down cards, dealt past the up cards, which each player plays in
+//! place of the board.
+//!
+//! A hand whose betting ended with everyone all-in before the river may be
+//! run twice: the second board keeps the cards dealt before the all-in and
+//! completes them with the last cards of the deck no player showed down, and
+//! each board wins half of every pot.
+
+use crate::betting::{
+    BettingRound,
+    SidePot,
+    Street,
+};
+use crate::cards::{
+    Card,
+    Variant,
+};
+use crate::hand_rank;
+use crate::stud;
+use crate::metasino::{
+    MetasinoError,
+    Result,
+};
+use ink_env::{
+    hash::{
+        Blake2x256,
+        HashOutput,
+    },
+    DefaultEnvironment,
+    Environment,
+};
+use ink_prelude::vec::Vec;
+
+type AccountId = <DefaultEnvironment as Environment>::AccountId;
+type Balance = <DefaultEnvironment as Environment>::Balance;
+type Hash = <DefaultEnvironment as Environment>::Hash;
+
+/// The number of cards of the board, dealt first out of the deck.
+pub const BOARD_CARDS: usize = 5;
+
+/// Compute the commitment of a player to the positions of their hole cards.
+/// The player is part of the preimage so that a commitment cannot be copied.
+pub fn commitment(player: &AccountId, positions: &[u8], salt: &[u8; 32]) -> Hash {
+    let mut output = <Blake2x256 as HashOutput>::Type::default();
+    ink_env::hash_encoded::<Blake2x256, _>(&(player, positions, salt), &mut output);
+    Hash::from(output)
+}
+
+/// The hole cards at the given positions of the deck of the variant.
+/// error if the variant deals another number of hole cards.
+/// error if a position is dealt face up or off the deck, or repeated.
+pub fn hole_cards(deck: &[Card], positions: &[u8], variant: Variant) -> Result<Vec<Card>> {
+    let face_up = match variant {
+        Variant::Stud => stud::UP_CARDS,
+        _ => BOARD_CARDS,
+    };
+    let dealt = face_up..deck.len();
+    let repeated = |(i, position): (usize, &u8)| positions[..i].contains(position);
+    if positions.len() != variant.hole_cards()
+        || positions.iter().any(|position| !dealt.contains(&(*position as usize)))
+        || positions.iter().enumerate().any(repeated)
+    {
+        return Err(MetasinoError::InvalidReveal);
+    }
+    Ok(positions.iter().map(|position| deck[*position as usize]).collect())
+}
+
+/// The score of the hole cards along with the board, made of the first
+/// cards given, such as the deck, or with the up cards of a stud hand.
+pub fn score(variant: Variant, board: &[Card], hole: &[Card]) -> Option<u32> {
+    let board = &board[..BOARD_CARDS.min(board.len())];
+    hand_rank::score_variant(variant, hole, board)
+}
+
+/// The cards of the board dealt face up so far in the hand: none before the
+/// flop, three on the flop, four on the turn and the whole board on the river
+/// or once the remaining streets are run out between the seats still in.
+pub fn shown_board(deck: &[Card], round: &BettingRound) -> Vec<Card> {
+    let shown = match round.street {
+        street if street.is_stud() => 0,
+        _ if round.finished && round.remaining() > 1 => BOARD_CARDS,
+        street => street_cards(street),
+    };
+    deck.iter().take(shown).copied().collect()
+}
+
+/// The number of cards of the board dealt face up once the street is bet on.
+fn street_cards(street: Street) -> usize {
+    match street {
+        Street::PreFlop => 0,
+        Street::Flop => 3,
+        Street::Turn => 4,
+        Street::River => BOARD_CARDS,
+        Street::Third | Street::Fourth | Street::Fifth | Street::Sixth | Street::Seventh => 0,
+    }
+}
+
+/// Whether the betting of the hand ended before the river with more than
+/// one seat still in, the rest of the board being run out, so that the
+/// hand may be run twice. Stud hands have no board to run out.
+pub fn runs_out(round: &BettingRound) -> bool {
+    round.finished
+        && round.remaining() > 1
+        && matches!(round.street, Street::PreFlop | Street::Flop | Street::Turn)
+}
+
+/// The second board of a hand run twice: the cards dealt before the betting
+/// ended, completed from the end of the deck with the cards at none of the
+/// positions shown down.
+pub fn second_board(deck: &[Card], round: &BettingRound, shown: &[Vec<u8>]) -> Vec<Card> {
+    let dealt = street_cards(round.street);
+    let mut board: Vec<Card> = deck.iter().take(dealt).copied().collect();
+    let runout = (BOARD_CARDS..deck.len())
+        .rev()
+        .filter(|position| !shown.iter().any(|hole| hole.contains(&(*position as u8))))
+        .take(BOARD_CARDS - dealt)
+        .map(|position| deck[position]);
+    board.extend(runout);
+    board
+}
+
+/// Split every pot into two halves, the first one getting the odd unit.
+pub fn halves(pots: &[SidePot]) -> (Vec<SidePot>, Vec<SidePot>) {
+    let half = |pot: &SidePot, amount: Balance| SidePot {
+        amount,
+        eligible: pot.eligible.clone(),
+    };
+    pots.iter()
+        .map(|pot| {
+            let second = pot.amount / 2;
+            (half(pot, pot.amount - second), half(pot, second))
+        })
+        .unzip()
+}
+
+/// Split each pot between the best hands revealed among the seats eligible
+/// to it, given the score of each seat if it revealed. A pot none of its
+/// seats revealed for goes to the best hands revealed at the table. Tied
+/// hands split the pot evenly, the remainder going to the first of them.
+/// Returns the amount won by each seat.
+/// error if no seat revealed.
+pub fn award(pots: &[SidePot], scores: &[Option<u32>]) -> Result<Vec<Balance>> {
+    let everyone: Vec<u8> = (0..scores.len() as u8).collect();
+    let mut won: Vec<Balance> = ink_prelude::vec![0; scores.len()];
+    for pot in pots {
+        let winners = match best(&pot.eligible, scores) {
+            winners if winners.is_empty() => best(&everyone, scores),
+            winners => winners,
+        };
+        let first = *winners.first().ok_or(MetasinoError::InvalidPayout)?;
+        let share = pot.amount / winners.len() as Balance;
+        for seat in winners.iter() {
+            won[*seat as usize] += share;
+        }
+        won[first as usize] += pot.amount % winners.len() as Balance;
+    }
+    Ok(won)
+}
+
+/// The seats holding the best score among the given ones.
+fn best(seats: &[u8], scores: &[Option<u32>]) -> Vec<u8> {
+    let top = seats.iter().filter_map(|seat| scores[*seat as usize]).max();
+    seats
+        .iter()
+        .copied()
+        .filter(|seat| top.is_some() && scores[*seat as usize] == top)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{
+        shuffled_deck,
+        variant_deck,
+        Suit,
+    };
+
+    fn pot(amount: Balance, eligible: &[u8]) -> SidePot {
+        SidePot {
+            amount,
+            eligible: eligible.to_vec(),
+        }
+    }
+
+    #[test]
+    fn hole_cards_are_dealt_off_the_board() {
+        let deck = shuffled_deck(&[3; 32]);
+        let holdem = |positions: &[u8]| hole_cards(&deck, positions, Variant::Holdem);
+        assert_eq!(holdem(&[5, 51]), Ok(vec![deck[5], deck[51]]));
+        assert_eq!(holdem(&[4, 6]), Err(MetasinoError::InvalidReveal));
+        assert_eq!(holdem(&[6, 52]), Err(MetasinoError::InvalidReveal));
+        assert_eq!(holdem(&[6, 6]), Err(MetasinoError::InvalidReveal));
+        assert_eq!(holdem(&[6, 7, 8, 9]), Err(MetasinoError::InvalidReveal));
+        let omaha = hole_cards(&deck, &[6, 7, 8, 9], Variant::Omaha);
+        assert_eq!(omaha, Ok(deck[6..10].to_vec()));
+
+        let stud = hole_cards(&deck, &[27, 28, 29], Variant::Stud);
+        assert_eq!(stud, Err(MetasinoError::InvalidReveal));
+        assert!(hole_cards(&deck, &[28, 29, 51], Variant::Stud).is_ok());
+
+        let short = variant_deck(&[3; 32], Variant::ShortDeck);
+        assert!(hole_cards(&short, &[6, 35], Variant::ShortDeck).is_ok());
+        assert_eq!(
+            hole_cards(&short, &[6, 36], Variant::ShortDeck),
+            Err(MetasinoError::InvalidReveal)
+        );
+    }
+
+    #[test]
+    fn commitments_bind_the_player_and_the_cards() {
+        let alice = AccountId::from([1; 32]);
+        let bob = AccountId::from([2; 32]);
+        let committed = commitment(&alice, &[5, 6], &[9; 32]);
+        assert_eq!(commitment(&alice, &[5, 6], &[9; 32]), committed);
+        assert_ne!(commitment(&bob, &[5, 6], &[9; 32]), committed);
+        assert_ne!(commitment(&alice, &[5, 7], &[9; 32]), committed);
+        assert_ne!(commitment(&alice, &[5, 6], &[8; 32]), committed);
+    }
+
+    #[test]
+    fn hole_cards_play_with_the_board() {
+        let deck = shuffled_deck(&[3; 32]);
+        let hole = [deck[5], deck[6]];
+        let mut cards = deck[..BOARD_CARDS].to_vec();
+        cards.extend_from_slice(&hole);
+        assert_eq!(score(Variant::Holdem, &deck, &hole), hand_rank::score(&cards));
+        let aces = [
+            Card { rank: 14, suit: Suit::Spades },
+            Card { rank: 14, suit: Suit::Hearts },
+        ];
+        assert!(score(Variant::Holdem, &deck, &aces).is_some());
+        assert_eq!(score(Variant::Omaha, &deck, &aces), None);
+    }
+
+    #[test]
+    fn board_is_shown_street_by_street() {
+        let deck = shuffled_deck(&[3; 32]);
+        let mut round = BettingRound::new(2, 0);
+        assert!(shown_board(&deck, &round).is_empty());
+        round.street = Street::Turn;
+        assert_eq!(shown_board(&deck, &round), deck[..4].to_vec());
+        round.finished = true;
+        assert_eq!(shown_board(&deck, &round), deck[..BOARD_CARDS].to_vec());
+        round.folded[0] = true;
+        assert_eq!(shown_board(&deck, &round), deck[..4].to_vec());
+    }
+
+    #[test]
+    fn second_board_keeps_the_cards_dealt_before_the_all_in() {
+        let deck = shuffled_deck(&[3; 32]);
+        let mut round = BettingRound::new(2, 0);
+        round.street = Street::Flop;
+        round.finished = true;
+        assert!(runs_out(&round));
+        let board = second_board(&deck, &round, &[vec![51, 6], vec![7, 49]]);
+        assert_eq!(board, vec![deck[0], deck[1], deck[2], deck[50], deck[48]]);
+        round.street = Street::River;
+        assert!(!runs_out(&round));
+
+        let (first, second) = halves(&[pot(101, &[0, 1]), pot(10, &[1])]);
+        assert_eq!(first, vec![pot(51, &[0, 1]), pot(5, &[1])]);
+        assert_eq!(second, vec![pot(50, &[0, 1]), pot(5, &[1])]);
+    }
+
+    #[test]
+    fn pots_go_to_the_best_hands_revealed() {
+        let scores = [Some(10), Some(30), None, Some(30)];
+        assert_eq!(award(&[pot(100, &[0, 1, 2, 3])], &scores), Ok(vec![0, 50, 0, 50]));
+        assert_eq!(award(&[pot(101, &[1, 3])], &scores), Ok(vec![0, 51, 0, 50]));
+        assert_eq!(
+            award(&[pot(90, &[0, 2]), pot(10, &[1])], &scores),
+            Ok(vec![90, 10, 0, 0])
+        );
+        // Nobody eligible revealed, the best hands of the table take the pot.
+        assert_eq!(award(&[pot(60, &[2])], &scores), Ok(vec![0, 30, 0, 30]));
+        assert_eq!(
+            award(&[pot(60, &[0, 1])], &[None, None]),
+            Err(MetasinoError::InvalidPayout)
+        );
+    }
+}
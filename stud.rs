@@ -0,0 +1,154 @@
+//! Seven-card stud, dealt without community cards.
+//!
+//! Every player gets two down cards and an up card on third street, an up
+//! card on each of fourth to sixth street and a last down card on seventh
+//! street, making their best hand out of their own seven cards. The up cards
+//! are dealt face up from the top of the deck, street after street, so that
+//! anyone reads them off the shuffle seed, while the three down cards are
+//! hole cards committed to by their positions past the up cards, as in the
+//! showdown of hold'em. The lowest up card on third street brings the
+//! betting in and the best hand showing opens every later street.
+
+use crate::betting::{
+    BettingRound,
+    Street,
+};
+use crate::cards::Card;
+use ink_prelude::vec::Vec;
+
+/// The most players a stud table deals to, the deck holding seven cards
+/// for each of them.
+pub const MAX_SEATS: u8 = 7;
+/// The number of cards on top of the deck kept for the up cards, the down
+/// cards being dealt past them.
+pub const UP_CARDS: usize = 4 * MAX_SEATS as usize;
+
+/// The number of up cards each seat shows once the street is dealt.
+fn dealt(street: Street) -> usize {
+    match street {
+        Street::Third => 1,
+        Street::Fourth => 2,
+        Street::Fifth => 3,
+        Street::Sixth | Street::Seventh => 4,
+        Street::PreFlop | Street::Flop | Street::Turn | Street::River => 0,
+    }
+}
+
+/// The up cards the seat shows once the street is dealt, the up card of
+/// each street being dealt to the seats in order.
+pub fn up_cards(deck: &[Card], seat: u8, street: Street) -> Vec<Card> {
+    (0..dealt(street))
+        .filter_map(|card| deck.get(card * MAX_SEATS as usize + seat as usize))
+        .copied()
+        .collect()
+}
+
+/// The seat bringing the betting in on third street, showing the lowest up
+/// card, ties going to the lowest suit from clubs to spades.
+pub fn bring_in(deck: &[Card], seats: u8) -> u8 {
+    (0..seats)
+        .min_by_key(|seat| {
+            let card = deck[*seat as usize];
+            (card.rank, card.suit as u8)
+        })
+        .unwrap_or_default()
+}
+
+/// The seat opening the current street of the round, showing the best hand
+/// among the seats still in, ties going to the first of them.
+pub fn leader(deck: &[Card], round: &BettingRound) -> u8 {
+    let seats = round.folded.len() as u8;
+    (0..seats)
+        .filter(|seat| !round.folded[*seat as usize])
+        .map(|seat| (showing(&up_cards(deck, seat, round.street)), seat))
+        .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+        .map(|(_, seat)| seat)
+        .unwrap_or_default()
+}
+
+/// Score the up cards shown by a seat, where only pairs, trips and quads
+/// count: the ranks grouped by how often they appear, most frequent and
+/// highest first.
+fn showing(up: &[Card]) -> u32 {
+    let mut groups: Vec<(u8, u8)> = Vec::new();
+    for card in up {
+        match groups.iter_mut().find(|(_, rank)| *rank == card.rank) {
+            Some(group) => group.0 += 1,
+            None => groups.push((1, card.rank)),
+        }
+    }
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+    groups
+        .iter()
+        .enumerate()
+        .map(|(i, (count, rank))| ((*count as u32) << 4 | *rank as u32) << (8 * (3 - i as u32)))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{
+        Suit,
+        ACE,
+    };
+
+    fn card(rank: u8, suit: Suit) -> Card {
+        Card { rank, suit }
+    }
+
+    /// A deck whose up cards are given by street, then seat.
+    fn deck(up: &[&[Card]]) -> Vec<Card> {
+        let mut deck = vec![card(2, Suit::Clubs); UP_CARDS];
+        for (street, cards) in up.iter().enumerate() {
+            for (seat, card) in cards.iter().enumerate() {
+                deck[street * MAX_SEATS as usize + seat] = *card;
+            }
+        }
+        deck
+    }
+
+    #[test]
+    fn up_cards_are_dealt_street_by_street() {
+        let deck = deck(&[
+            &[card(9, Suit::Clubs), card(3, Suit::Spades)],
+            &[card(ACE, Suit::Hearts), card(4, Suit::Clubs)],
+        ]);
+        assert_eq!(up_cards(&deck, 1, Street::Third), vec![card(3, Suit::Spades)]);
+        assert_eq!(
+            up_cards(&deck, 0, Street::Fourth),
+            vec![card(9, Suit::Clubs), card(ACE, Suit::Hearts)]
+        );
+        assert_eq!(up_cards(&deck, 0, Street::Seventh).len(), 4);
+        assert!(up_cards(&deck, 0, Street::River).is_empty());
+    }
+
+    #[test]
+    fn lowest_up_card_brings_in() {
+        let deck = deck(&[&[
+            card(9, Suit::Clubs),
+            card(3, Suit::Spades),
+            card(3, Suit::Diamonds),
+            card(ACE, Suit::Clubs),
+        ]]);
+        assert_eq!(bring_in(&deck, 4), 2);
+        assert_eq!(bring_in(&deck, 2), 1);
+    }
+
+    #[test]
+    fn best_hand_showing_opens_the_street() {
+        let deck = deck(&[
+            &[card(ACE, Suit::Clubs), card(9, Suit::Spades), card(9, Suit::Hearts)],
+            &[card(13, Suit::Hearts), card(9, Suit::Clubs), card(9, Suit::Diamonds)],
+        ]);
+        let mut round = BettingRound::stud(3, 0);
+        round.street = Street::Fourth;
+        // A pair of nines shows better than ace high, and ties go to the first seat.
+        assert_eq!(leader(&deck, &round), 1);
+        round.folded[1] = true;
+        assert_eq!(leader(&deck, &round), 2);
+        round.street = Street::Third;
+        round.folded[1] = false;
+        assert_eq!(leader(&deck, &round), 0);
+    }
+}
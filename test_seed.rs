@@ -0,0 +1,24 @@
+//! Fixed shuffle seeds for reproducible tests.
+//!
+//! With the `test-seed` feature a test fixes the shuffle seed of the hands
+//! dealt on its thread with [`set`], so that the cards, spins and coups it
+//! asserts on do not depend on the seeds revealed by the players. Without a
+//! fixed seed, and always without the feature, the seed is derived from the
+//! commit-reveal entropy of the table.
+
+use std::cell::Cell;
+
+thread_local! {
+    static SEED: Cell<Option<[u8; 32]>> = const { Cell::new(None) };
+}
+
+/// Fix the shuffle seed of the hands dealt on this thread, or go back to
+/// the revealed entropy with `None`.
+pub fn set(seed: Option<[u8; 32]>) {
+    SEED.with(|fixed| fixed.set(seed));
+}
+
+/// The shuffle seed fixed on this thread, if any.
+pub fn fixed() -> Option<[u8; 32]> {
+    SEED.with(|fixed| fixed.get())
+}
@@ -0,0 +1,396 @@
+//! Freezeout tournaments played for chips instead of the balance.
+//!
+//! Every player pays the same buy-in and gets the same stack of chips. The
+//! director of the tournament reports the result of each hand, every player
+//! still in having to put at least the blind of the current level in. The
+//! blind doubles every level, so the stacks keep shrinking until a single
+//! player holds every chip. Players are eliminated as they run out of chips
+//! and the prize pool is paid to the last standing, 50/30/20.
+//!
+//! In a bounty tournament part of every buy-in is the bounty on the head of
+//! the player, paid to whoever eliminates them, the last standing keeping
+//! their own. The rest of the buy-ins makes up the prize pool.
+//!
+//! A tournament may also be entered with a ticket, a PSP34 token minted by
+//! Metasino to the top places of a satellite tournament. A satellite pays
+//! its places in tickets to its target tournament, each worth the buy-in of
+//! the target, the pool left once every ticket is paid going to the next
+//! place in cash.
+
+use crate::metasino::{
+    MetasinoError,
+    Result,
+    TournamentId,
+};
+use ink_env::{
+    DefaultEnvironment,
+    Environment,
+};
+use ink_prelude::vec::Vec;
+use ink_storage::traits::{
+    PackedLayout,
+    SpreadLayout,
+};
+
+type AccountId = <DefaultEnvironment as Environment>::AccountId;
+type Balance = <DefaultEnvironment as Environment>::Balance;
+type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+
+/// An amount of tournament chips.
+pub type Chips = u64;
+
+/// The share of the prize pool, in percent, paid to each place.
+const PRIZES: [Balance; 3] = [50, 30, 20];
+/// The blind of the first level is this fraction of the starting stack.
+const BLIND_DIVISOR: Chips = 50;
+
+/// The id of the ticket numbered `serial` among the tickets minted for the
+/// tournament, which it is made out of.
+pub fn ticket_id(tournament_id: TournamentId, serial: u32) -> u128 {
+    ((tournament_id as u128) << 32) | serial as u128
+}
+
+/// The tournament the ticket enters and its number among the tickets minted
+/// for it.
+pub fn ticket_of(ticket_id: u128) -> (TournamentId, u32) {
+    ((ticket_id >> 32) as TournamentId, ticket_id as u32)
+}
+
+/// A tournament and the stacks of its players.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    scale::Encode,
+    scale::Decode,
+    SpreadLayout,
+    PackedLayout,
+)]
+#[cfg_attr(
+    feature = "std",
+    derive(::scale_info::TypeInfo, ::ink_storage::traits::StorageLayout)
+)]
+pub struct Tournament {
+    /// The account reporting the hands, who opened the tournament.
+    pub director: AccountId,
+    /// The balance every player pays to enter.
+    pub buy_in: Balance,
+    /// The chips every player starts with.
+    pub starting_chips: Chips,
+    /// The most players that may enter.
+    pub max_players: u8,
+    /// The number of blocks each blind level lasts.
+    pub blind_interval: BlockNumber,
+    /// The block the tournament started in.
+    pub started_at: Option<BlockNumber>,
+    /// The players, by seat.
+    pub players: Vec<AccountId>,
+    /// The chips of each seat.
+    pub chips: Vec<Chips>,
+    /// The seats in the order they were eliminated.
+    pub eliminated: Vec<u8>,
+    /// The part of the buy-in paid to whoever eliminates the player.
+    pub bounty: Balance,
+    /// The PSP34 contract of the tickets entering the tournament, if any.
+    pub ticket: Option<AccountId>,
+    /// The number of tickets minted for the tournament.
+    pub tickets_issued: u32,
+    /// The number of tickets the tournament was entered with.
+    pub tickets_redeemed: u32,
+    /// The tournament whose tickets the places of this satellite are paid in.
+    pub satellite_for: Option<TournamentId>,
+}
+
+impl Tournament {
+    /// Open a tournament seating nobody yet.
+    pub fn new(
+        director: AccountId,
+        buy_in: Balance,
+        starting_chips: Chips,
+        max_players: u8,
+        blind_interval: BlockNumber,
+    ) -> Self {
+        Self {
+            director,
+            buy_in,
+            starting_chips,
+            max_players,
+            blind_interval,
+            started_at: None,
+            players: Vec::new(),
+            chips: Vec::new(),
+            eliminated: Vec::new(),
+            bounty: 0,
+            ticket: None,
+            tickets_issued: 0,
+            tickets_redeemed: 0,
+            satellite_for: None,
+        }
+    }
+
+    /// Seat the player with the starting stack.
+    /// error if the tournament started.
+    /// error if the player already entered or the tournament is full.
+    pub fn join(&mut self, player: AccountId) -> Result<()> {
+        if self.started_at.is_some() {
+            return Err(MetasinoError::WrongState);
+        }
+        if self.players.contains(&player) {
+            return Err(MetasinoError::AlreadyRegistered);
+        }
+        if self.players.len() >= self.max_players as usize {
+            return Err(MetasinoError::TableFull);
+        }
+        self.players.push(player);
+        self.chips.push(self.starting_chips);
+        Ok(())
+    }
+
+    /// Start the first blind level.
+    /// error if the tournament already started.
+    /// error if fewer than two players entered.
+    pub fn start(&mut self, now: BlockNumber) -> Result<()> {
+        if self.started_at.is_some() {
+            return Err(MetasinoError::WrongState);
+        }
+        if self.players.len() < 2 {
+            return Err(MetasinoError::NotEnoughPlayers);
+        }
+        self.started_at = Some(now);
+        Ok(())
+    }
+
+    /// The blind level reached at block `now`, counted from 0.
+    pub fn level(&self, now: BlockNumber) -> u32 {
+        match self.started_at {
+            Some(started_at) => now.saturating_sub(started_at) / self.blind_interval.max(1),
+            None => 0,
+        }
+    }
+
+    /// The least every player still in has to put into a hand at block `now`.
+    pub fn blind(&self, now: BlockNumber) -> Chips {
+        let base = (self.starting_chips / BLIND_DIVISOR).max(1);
+        base.saturating_mul(1u64.checked_shl(self.level(now)).unwrap_or(Chips::MAX))
+    }
+
+    /// The prize pool made of the buy-in of every player, less their bounty.
+    /// error if the pool overflows.
+    pub fn pool(&self) -> Result<Balance> {
+        (self.buy_in - self.bounty)
+            .checked_mul(self.players.len() as Balance)
+            .ok_or(MetasinoError::ArithmeticError)
+    }
+
+    /// What the tournament holds until it is paid out: every buy-in along
+    /// with the buy-in backing each ticket still to be entered with, less
+    /// the bounties paid. A ticket is worth nothing once the tournament
+    /// starts.
+    /// error if the buy-ins overflow.
+    pub fn escrow(&self) -> Result<Balance> {
+        if self.is_finished() {
+            return Ok(0)
+        }
+        let tickets = match self.started_at {
+            Some(_) => 0,
+            None => self.tickets_issued.saturating_sub(self.tickets_redeemed),
+        };
+        let entries = self.players.len() as Balance + tickets as Balance;
+        let bounties = self.bounty.saturating_mul(self.eliminated.len() as Balance);
+        self.buy_in
+            .checked_mul(entries)
+            .map(|buy_ins| buy_ins.saturating_sub(bounties))
+            .ok_or(MetasinoError::ArithmeticError)
+    }
+
+    /// Whether a single player holds every chip.
+    pub fn is_finished(&self) -> bool {
+        self.started_at.is_some() && self.eliminated.len() + 1 == self.players.len()
+    }
+
+    /// Give the chips each seat put into the hand to the seat that won it.
+    /// Returns the seats eliminated by the hand.
+    /// error if the tournament is not running.
+    /// error if the winner is out or a seat put in less than the blind, more
+    /// than its stack, or anything once out.
+    pub fn play_hand(
+        &mut self,
+        winner: u8,
+        wagers: &[Chips],
+        now: BlockNumber,
+    ) -> Result<Vec<u8>> {
+        if self.started_at.is_none() || self.is_finished() {
+            return Err(MetasinoError::WrongState);
+        }
+        let blind = self.blind(now);
+        let winner = winner as usize;
+        if wagers.len() != self.chips.len() || self.chips.get(winner).is_none_or(|c| *c == 0) {
+            return Err(MetasinoError::InvalidAction);
+        }
+        let valid = wagers
+            .iter()
+            .zip(self.chips.iter())
+            .all(|(wager, chips)| *wager <= *chips && *wager >= blind.min(*chips));
+        if !valid {
+            return Err(MetasinoError::InvalidAction);
+        }
+        let mut out = Vec::new();
+        for (seat, wager) in wagers.iter().enumerate() {
+            if seat == winner || self.chips[seat] == 0 {
+                continue
+            }
+            self.chips[seat] -= wager;
+            self.chips[winner] += wager;
+            if self.chips[seat] == 0 {
+                out.push(seat as u8);
+            }
+        }
+        self.eliminated.extend_from_slice(&out);
+        Ok(out)
+    }
+
+    /// The seats by finishing place, the last one standing first.
+    pub fn standings(&self) -> Vec<u8> {
+        let mut standings: Vec<u8> = (0..self.players.len() as u8)
+            .filter(|seat| !self.eliminated.contains(seat))
+            .collect();
+        standings.extend(self.eliminated.iter().rev());
+        standings
+    }
+
+    /// The number of tickets at `price` paid out of the prize pool of a
+    /// satellite, at most one per player, and the pool left once they are.
+    pub fn tickets(&self, pool: Balance, price: Balance) -> (u32, Balance) {
+        let tickets = match price {
+            0 => 0,
+            price => (pool / price).min(self.players.len() as Balance),
+        };
+        (tickets as u32, pool - tickets * price)
+    }
+
+    /// Split the prize pool between the paid places, by finishing place.
+    /// With fewer players than paid places the shares of the places left are
+    /// scaled up, and the remainder of the division goes to the winner.
+    /// Shares are taken out of the pool divided first so that no pool
+    /// overflows.
+    pub fn prizes(&self, pool: Balance) -> Vec<Balance> {
+        let paid = PRIZES.len().min(self.players.len());
+        let total: Balance = PRIZES[..paid].iter().sum();
+        let mut prizes: Vec<Balance> = PRIZES[..paid]
+            .iter()
+            .map(|share| pool / total * share + pool % total * share / total)
+            .collect();
+        let remainder = pool - prizes.iter().sum::<Balance>();
+        if let Some(first) = prizes.first_mut() {
+            *first += remainder;
+        }
+        prizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn running(players: u8) -> Tournament {
+        let mut tournament = Tournament::new(AccountId::from([0; 32]), 100, 1_000, 10, 10);
+        for player in 1..=players {
+            tournament.join(AccountId::from([player; 32])).unwrap();
+        }
+        tournament.start(0).unwrap();
+        tournament
+    }
+
+    #[test]
+    fn blinds_double_every_level() {
+        let tournament = running(2);
+        assert_eq!(tournament.blind(0), 20);
+        assert_eq!(tournament.blind(9), 20);
+        assert_eq!(tournament.blind(10), 40);
+        assert_eq!(tournament.blind(35), 160);
+        assert_eq!(tournament.blind(10_000), Chips::MAX);
+    }
+
+    #[test]
+    fn hands_move_chips_and_eliminate_busted_players() {
+        let mut tournament = running(3);
+        assert_eq!(tournament.play_hand(0, &[20, 10, 20], 0), Err(MetasinoError::InvalidAction));
+        assert_eq!(tournament.play_hand(0, &[20, 20], 0), Err(MetasinoError::InvalidAction));
+        assert_eq!(tournament.play_hand(0, &[20, 1_000, 20], 0), Ok(vec![1]));
+        assert_eq!(tournament.chips, vec![2_020, 0, 980]);
+        assert_eq!(tournament.play_hand(1, &[20, 0, 20], 0), Err(MetasinoError::InvalidAction));
+        assert_eq!(tournament.play_hand(2, &[20, 1, 20], 0), Err(MetasinoError::InvalidAction));
+        assert_eq!(tournament.play_hand(0, &[20, 0, 980], 0), Ok(vec![2]));
+        assert!(tournament.is_finished());
+        assert_eq!(tournament.standings(), vec![0, 2, 1]);
+        assert_eq!(tournament.play_hand(0, &[0, 0, 0], 0), Err(MetasinoError::WrongState));
+    }
+
+    #[test]
+    fn short_stacks_are_all_in_on_the_blind() {
+        let mut tournament = running(2);
+        tournament.chips = vec![1_990, 10];
+        assert_eq!(tournament.play_hand(0, &[20, 10], 0), Ok(vec![1]));
+    }
+
+    #[test]
+    fn prizes_pay_the_top_three() {
+        assert_eq!(running(5).prizes(1_000), vec![500, 300, 200]);
+        assert_eq!(running(2).prizes(1_000), vec![625, 375]);
+        assert_eq!(running(3).prizes(99), vec![51, 29, 19]);
+        let prizes = running(3).prizes(Balance::MAX);
+        assert_eq!(prizes.iter().sum::<Balance>(), Balance::MAX);
+        assert_eq!(prizes[1], Balance::MAX / 10 * 3 + Balance::MAX % 10 * 3 / 10);
+    }
+
+    #[test]
+    fn satellites_pay_as_many_tickets_as_the_pool_buys() {
+        assert_eq!(running(5).tickets(1_000, 300), (3, 100));
+        assert_eq!(running(2).tickets(1_000, 300), (2, 400));
+        assert_eq!(running(2).tickets(1_000, 0), (0, 1_000));
+    }
+
+    #[test]
+    fn tickets_are_made_out_of_their_tournament() {
+        assert_eq!(ticket_of(ticket_id(7, 3)), (7, 3));
+        assert_ne!(ticket_id(7, 3), ticket_id(3, 7));
+        let last = ticket_id(TournamentId::MAX, u32::MAX);
+        assert_eq!(ticket_of(last), (TournamentId::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn bounties_come_out_of_the_pool() {
+        let mut tournament = running(3);
+        tournament.bounty = 40;
+        assert_eq!(tournament.pool(), Ok(180));
+    }
+
+    #[test]
+    fn escrow_is_held_until_the_pool_is_paid() {
+        let mut tournament = Tournament::new(AccountId::from([0; 32]), 100, 1_000, 10, 10);
+        tournament.join(AccountId::from([1; 32])).unwrap();
+        tournament.tickets_issued = 2;
+        assert_eq!(tournament.escrow(), Ok(300));
+        tournament.join(AccountId::from([2; 32])).unwrap();
+        tournament.tickets_redeemed = 1;
+        assert_eq!(tournament.escrow(), Ok(300));
+        tournament.bounty = 40;
+        tournament.start(0).unwrap();
+        assert_eq!(tournament.escrow(), Ok(200));
+        tournament.chips = vec![1_990, 10];
+        tournament.play_hand(0, &[20, 10], 0).unwrap();
+        assert_eq!(tournament.escrow(), Ok(0));
+    }
+
+    #[test]
+    fn pool_is_every_buy_in() {
+        let tournament = running(3);
+        assert_eq!(tournament.pool(), Ok(300));
+        let mut expensive = Tournament::new(AccountId::from([0; 32]), Balance::MAX, 1, 2, 1);
+        expensive.join(AccountId::from([1; 32])).unwrap();
+        assert_eq!(expensive.pool(), Ok(Balance::MAX));
+        expensive.join(AccountId::from([2; 32])).unwrap();
+        assert_eq!(expensive.pool(), Err(MetasinoError::ArithmeticError));
+    }
+}
@@ -0,0 +1,83 @@
+//! Cross-contract calls to a yield strategy parking the idle treasury.
+//!
+//! The owner may point the contract at a strategy contract, such as an
+//! adapter staking or lending the native balance, and park the part of the
+//! treasury above a liquid buffer with it. The strategy keeps the funds
+//! deposited by Metasino along with whatever they earn, and sends them back
+//! when they are withdrawn. The calls are built by hand with the selectors
+//! of [`YieldStrategy`].
+//!
+//! Only the treasury is parked. The jackpot stays liquid, since the hand
+//! winning it pays it out at once, and a strategy failing to send funds back
+//! must not keep a hand from settling.
+
+use crate::metasino::{
+    MetasinoError,
+    Result,
+};
+use ink_env::{
+    call::{
+        build_call,
+        Call,
+        ExecutionInput,
+        Selector,
+    },
+    DefaultEnvironment,
+    Environment,
+};
+
+type AccountId = <DefaultEnvironment as Environment>::AccountId;
+type Balance = <DefaultEnvironment as Environment>::Balance;
+
+/// The selector of `YieldStrategy::deposit`.
+const DEPOSIT: [u8; 4] = ink_lang::selector_bytes!("YieldStrategy::deposit");
+/// The selector of `YieldStrategy::withdraw`.
+const WITHDRAW: [u8; 4] = ink_lang::selector_bytes!("YieldStrategy::withdraw");
+/// The selector of `YieldStrategy::balance`.
+const BALANCE: [u8; 4] = ink_lang::selector_bytes!("YieldStrategy::balance");
+
+/// The messages a yield strategy contract implements for Metasino.
+#[ink_lang::trait_definition]
+pub trait YieldStrategy {
+    /// Put the value transferred to work on behalf of the caller.
+    #[ink(message, payable)]
+    fn deposit(&mut self) -> Result<()>;
+
+    /// Send `amount` of the funds of the caller back to it.
+    #[ink(message)]
+    fn withdraw(&mut self, amount: Balance) -> Result<()>;
+
+    /// The funds of the account held by the strategy, earnings included.
+    #[ink(message)]
+    fn balance(&self, account: AccountId) -> Balance;
+}
+
+/// Park `amount` of the native balance with the strategy.
+pub fn deposit(strategy: AccountId, amount: Balance) -> Result<()> {
+    build_call::<DefaultEnvironment>()
+        .call_type(Call::new().callee(strategy).transferred_value(amount))
+        .exec_input(ExecutionInput::new(Selector::new(DEPOSIT)))
+        .returns::<Result<()>>()
+        .fire()
+        .map_err(|_| MetasinoError::StrategyFailed)?
+}
+
+/// Have the strategy send `amount` parked with it back.
+pub fn withdraw(strategy: AccountId, amount: Balance) -> Result<()> {
+    build_call::<DefaultEnvironment>()
+        .call_type(Call::new().callee(strategy))
+        .exec_input(ExecutionInput::new(Selector::new(WITHDRAW)).push_arg(amount))
+        .returns::<Result<()>>()
+        .fire()
+        .map_err(|_| MetasinoError::StrategyFailed)?
+}
+
+/// The funds of the account parked with the strategy, earnings included.
+pub fn balance(strategy: AccountId, account: AccountId) -> Result<Balance> {
+    build_call::<DefaultEnvironment>()
+        .call_type(Call::new().callee(strategy))
+        .exec_input(ExecutionInput::new(Selector::new(BALANCE)).push_arg(account))
+        .returns::<Balance>()
+        .fire()
+        .map_err(|_| MetasinoError::StrategyFailed)
+}